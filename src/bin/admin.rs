@@ -0,0 +1,137 @@
+use clap::{Parser, Subcommand};
+use relation_server::{
+    config::{redacted, C},
+    error::{Error, Result},
+    storage::store,
+    tigergraph::migration,
+    upstream::{fetch_all, prefetch, trim_name, Platform, Target},
+    util::make_http_client,
+};
+use serde_json::json;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+
+/// Maintenance tasks on the identity graph, run against the configured store.
+#[derive(Parser)]
+#[command(name = "relation-admin")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fetch a target from upstreams again and save what is found.
+    Refetch {
+        platform: Platform,
+        identity: String,
+        /// Rounds of neighbors to fetch.
+        #[arg(long, default_value_t = 3)]
+        depth: u16,
+    },
+    /// Print an identity vertex and its identity graph as JSON.
+    Inspect {
+        platform: Platform,
+        identity: String,
+    },
+    /// Remove connections inside the identity graph an identity belongs to.
+    DeleteGraph {
+        platform: Platform,
+        identity: String,
+    },
+    /// Remove the domain search cache of one name, or of all names with `--all`.
+    DeleteDomainCache {
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        name: Option<String>,
+        #[arg(long)]
+        all: bool,
+    },
+    /// Run the SybilList prefetch.
+    Prefetch,
+    /// Print the effective config with secrets redacted.
+    Config,
+    /// Apply pending TigerGraph schema changes and query files.
+    Migrate {
+        /// Record schema versions up to this one as applied without running them,
+        /// for a TigerGraph migrated by hand.
+        #[arg(long, default_value_t = 0)]
+        baseline: u32,
+        /// Only print pending migrations.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let log_subscriber = tracing_subscriber::FmtSubscriber::builder()
+        .with_env_filter(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::INFO.into())
+                .from_env_lossy()
+                .add_directive("hyper=info".parse().unwrap())
+                .add_directive("tokio=info".parse().unwrap()),
+        )
+        .with_writer(std::io::stderr)
+        .finish();
+
+    tracing::subscriber::set_global_default(log_subscriber)
+        .expect("Setting default subscriber failed");
+
+    match Cli::parse().command {
+        Command::Refetch {
+            platform,
+            identity,
+            depth,
+        } => {
+            fetch_all(vec![Target::Identity(platform, identity)], Some(depth)).await?;
+        }
+        Command::Inspect { platform, identity } => {
+            let vertex = store().find_identity(&platform, &identity).await?;
+            let graph = store()
+                .find_identity_graph(&platform, &identity, None, None)
+                .await?;
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json!({"identity": vertex, "identityGraph": graph}))?
+            );
+        }
+        Command::DeleteGraph { platform, identity } => {
+            let record = store()
+                .find_identity(&platform, &identity)
+                .await?
+                .ok_or(Error::NoResult)?;
+            store()
+                .delete_graph_inner_connection(record.v_id.clone())
+                .await?;
+            tracing::info!(v_id = record.v_id, "Identity graph deleted");
+        }
+        Command::DeleteDomainCache { name, all } => {
+            if all {
+                let removed = store().clear_domain_search_cache().await?;
+                tracing::info!(removed, "Domain search cache cleared");
+            } else if let Some(name) = name {
+                // Collections are keyed by name without extension, e.g. `foo` of `foo.eth`.
+                let name = trim_name(&name);
+                store().delete_domain_collection(&name).await?;
+                tracing::info!(name, "Domain collection deleted");
+            }
+        }
+        Command::Prefetch => prefetch().await?,
+        Command::Config => {
+            println!("{}", serde_json::to_string_pretty(&redacted(&C)?)?);
+        }
+        Command::Migrate { baseline, dry_run } => {
+            let client = make_http_client();
+            if dry_run {
+                for name in migration::pending(&client, &C.tdb).await? {
+                    println!("{}", name);
+                }
+            } else {
+                let ran = migration::migrate(&client, &C.tdb, baseline).await?;
+                tracing::info!(?ran, "Migrations applied");
+                migration::verify(&client, &C.tdb).await?;
+            }
+        }
+    }
+    Ok(())
+}
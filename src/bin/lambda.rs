@@ -1,36 +1,42 @@
-use lambda_http::Error as LambdaError;
+use lambda_http::{service_fn, Error as LambdaError};
+use relation_server::{
+    config::{StoreBackend, C},
+    controller::{lambda::entrypoint, tigergraphql::schema},
+    jobs::{queue, FetchJobHandler},
+    tigergraph::migration,
+    util::make_http_client,
+};
+use std::sync::Arc;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
+/// Job workers only make progress while an invocation is running, as the instance
+/// is frozen in between. `/var/task` is read-only, so set `[jobs] path` to `":memory:"`
+/// or a file under `/tmp`: jobs left queued are lost when the instance is recycled.
 #[tokio::main]
 async fn main() -> Result<(), LambdaError> {
-    //     let _ = env_logger::try_init();
+    let log_subscriber = tracing_subscriber::FmtSubscriber::builder()
+        .with_env_filter(
+            EnvFilter::builder()
+                .with_default_directive(LevelFilter::INFO.into())
+                .from_env_lossy()
+                .add_directive("hyper=info".parse().unwrap())
+                .add_directive("tokio=info".parse().unwrap()),
+        )
+        // CloudWatch shows no colors, and adds its own timestamps.
+        .with_ansi(false)
+        .without_time()
+        .finish();
 
-    //     lambda_http::run(service_fn(entrypoint)).await?;
-    Ok(())
-}
+    tracing::subscriber::set_global_default(log_subscriber)
+        .expect("Setting default subscriber failed");
 
-// fn entrypoint (req: LambdaRequest) -> Result<impl IntoResponse, LambdaError> {
-//     todo!()
-// }
+    if C.store.backend == StoreBackend::TigerGraph {
+        migration::verify(&make_http_client(), &C.tdb).await?;
+    }
 
-// /// Translate between `lambda_http` `Body` and our `Body`.
-// async fn parse<F>(req: LambdaRequest, controller: fn(OurRequest) -> F) -> LambdaResponse<LambdaBody>
-// where
-//     F: Future<Output = Result<OurResponse, Error>>,
-// {
-//     let (parts, old_body) = req.into_parts();
-//     let body: OurBody = crate::controller::LambdaBody(old_body).into();
-//     let new_req: OurRequest = http::Request::from_parts(parts, body);
+    // Stopped with the instance.
+    let _workers = queue()?.start(Arc::new(FetchJobHandler), C.jobs.workers);
 
-//     match controller(new_req).await {
-//         Ok(resp) => {
-//             let (parts, our_resp) = resp.into_parts();
-//             let resp = lambda_http::Body::Text(our_resp);
-//             LambdaResponse::from_parts(parts, resp)
-//         }
-//         Err(err) => {
-//             let (parts, our_resp) = error_response(err).into_parts();
-//             let resp = lambda_http::Body::Text(our_resp);
-//             LambdaResponse::from_parts(parts, resp)
-//         }
-//     }
-// }
+    let schema = schema();
+    lambda_http::run(service_fn(move |req| entrypoint(schema.clone(), req))).await
+}
@@ -1,18 +1,20 @@
-use async_graphql::{
-    http::{playground_source, GraphQLPlaygroundConfig},
-    EmptyMutation, EmptySubscription, Schema,
-};
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_warp::{GraphQLBadRequest, GraphQLResponse};
-use dataloader::non_cached::Loader;
 use http::StatusCode;
 use relation_server::{
-    config::C,
-    controller::tigergraphql::Query,
+    config::{StoreBackend, C},
+    controller::{
+        healthz,
+        tigergraphql::{schema, RelationSchema},
+    },
+    curation::authenticate,
     error::Result,
-    tigergraph::vertex::{ContractLoadFn, IdentityLoadFn, OwnerLoadFn},
+    jobs::{queue, FetchJobHandler, JobKind},
+    metrics,
+    tigergraph::migration,
     util::make_http_client,
 };
-use std::{convert::Infallible, net::SocketAddr};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
 use tracing::{info, warn};
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 use warp::{http::Response as HttpResponse, Filter, Rejection};
@@ -32,43 +34,29 @@ async fn main() -> Result<()> {
     tracing::subscriber::set_global_default(log_subscriber)
         .expect("Setting default subscriber failed");
 
+    if C.store.backend == StoreBackend::TigerGraph {
+        migration::verify(&make_http_client(), &C.tdb).await?;
+    }
+
     let middleware_cors = warp::cors()
         .allow_any_origin() // : maybe more strict CORS in production?
         .allow_methods(vec!["GET", "POST"])
-        .allow_headers(vec!["Accept", "Content-Type", "Length"]);
-
-    let client = make_http_client();
-    let contract_loader_fn = ContractLoadFn {
-        client: client.to_owned(),
-    };
-    let identity_loader_fn = IdentityLoadFn {
-        client: client.to_owned(),
-    };
-    let owner_loader_fn = OwnerLoadFn {
-        client: client.to_owned(),
-    };
-    let contract_loader = Loader::new(contract_loader_fn)
-        .with_max_batch_size(500)
-        .with_yield_count(100);
-    let identity_loader = Loader::new(identity_loader_fn)
-        .with_max_batch_size(500)
-        .with_yield_count(100);
-    let owner_loader = Loader::new(owner_loader_fn)
-        .with_max_batch_size(500)
-        .with_yield_count(100);
-
-    let schema = Schema::build(Query::default(), EmptyMutation, EmptySubscription)
-        .data(contract_loader)
-        .data(identity_loader)
-        .data(owner_loader)
-        .finish();
+        .allow_headers(vec!["Accept", "Content-Type", "Length", "Authorization"]);
+
+    let schema = schema();
+
+    // `identityGraphUpdates` over WebSocket (`graphql-ws` / `graphql-transport-ws`).
+    let graphql_subscription = async_graphql_warp::graphql_subscription(schema.clone());
 
     let graphql_post = async_graphql_warp::graphql(schema)
+        .and(warp::header::optional::<String>("authorization"))
         .and_then(
-            |(schema, request): (
-                Schema<Query, EmptyMutation, EmptySubscription>,
-                async_graphql::Request,
-            )| async move {
+            |(schema, request): (RelationSchema, async_graphql::Request),
+             authorization: Option<String>| async move {
+                let request = match authenticate(authorization.as_deref()) {
+                    Some(operator) => request.data(operator),
+                    None => request,
+                };
                 Ok::<_, Infallible>(GraphQLResponse::from(schema.execute(request).await))
             },
         )
@@ -77,10 +65,48 @@ async fn main() -> Result<()> {
     let playground = warp::path::end().and(warp::get()).map(|| {
         HttpResponse::builder()
             .header("content-type", "text/html")
-            .body(playground_source(GraphQLPlaygroundConfig::new("/")))
+            .body(playground_source(
+                GraphQLPlaygroundConfig::new("/").subscription_endpoint("/"),
+            ))
+    });
+
+    let healthz = warp::path!("api" / "healthz")
+        .and(warp::get())
+        .and_then(|| async {
+            healthz::controller(Default::default())
+                .await
+                .map_err(warp::reject::custom)
+        });
+
+    let healthz_live = warp::path!("healthz" / "live")
+        .and(warp::get())
+        .and_then(|| async {
+            healthz::live(Default::default())
+                .await
+                .map_err(warp::reject::custom)
+        });
+
+    let healthz_ready = warp::path!("healthz" / "ready")
+        .and(warp::get())
+        .and_then(|| async {
+            healthz::ready(Default::default())
+                .await
+                .map_err(warp::reject::custom)
+        });
+
+    let metrics = warp::path!("metrics").and(warp::get()).and_then(|| async {
+        metrics::render()
+            .await
+            .map(|body| warp::reply::with_header(body, "content-type", "text/plain; version=0.0.4"))
+            .map_err(warp::reject::custom)
     });
 
-    let routes = playground
+    let routes = graphql_subscription
+        .or(playground)
+        .or(healthz)
+        .or(healthz_live)
+        .or(healthz_ready)
+        .or(metrics)
         .or(graphql_post)
         .recover(|err: Rejection| async move {
             if let Some(GraphQLBadRequest(err)) = err.find() {
@@ -107,8 +133,21 @@ async fn main() -> Result<()> {
     let address = SocketAddr::new(C.web.listen.parse().unwrap(), C.web.port);
     info!("Playground: http://{}", address);
 
-    warp::serve(routes).run(address).await;
+    let queue = queue()?;
+    let workers = queue.start(Arc::new(FetchJobHandler), C.jobs.workers);
+    if C.jobs.rank_interval_seconds > 0 {
+        queue.repeat(
+            JobKind::IdentityRank,
+            Duration::from_secs(C.jobs.rank_interval_seconds),
+        );
+    }
+
+    let (_, server) = warp::serve(routes).bind_with_graceful_shutdown(address, async {
+        tokio::signal::ctrl_c().await.ok();
+    });
+    server.await;
 
     println!("Shutting down...");
+    workers.shutdown().await;
     Ok(())
 }
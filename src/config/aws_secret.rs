@@ -0,0 +1,189 @@
+use crate::error::Error;
+use hmac::{Hmac, Mac};
+use http::StatusCode;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+const SERVICE: &str = "secretsmanager";
+const TARGET: &str = "secretsmanager.GetSecretValue";
+const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+/// Where the config secret lives, and the credentials to read it with.
+#[derive(Clone, Debug)]
+pub struct AwsSecretSource {
+    /// Secret name or ARN.
+    pub name: String,
+    pub region: String,
+    /// `https://secretsmanager.{region}.amazonaws.com` unless overridden,
+    /// e.g. to a local mock server.
+    pub endpoint: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Given to temporary credentials, e.g. in Lambda.
+    pub session_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GetSecretValueResponse {
+    #[serde(rename = "SecretString")]
+    secret_string: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(rename = "__type", default)]
+    error_type: String,
+    #[serde(alias = "Message", default)]
+    message: String,
+}
+
+impl AwsSecretSource {
+    /// Read from `AWS_SECRET_NAME`, `AWS_SECRET_REGION` (or `AWS_REGION`),
+    /// `AWS_SECRET_ENDPOINT` (optional), and the standard
+    /// `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Result<Self, Error> {
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        let required = |key: &str| var(key).ok_or_else(|| Error::ParamMissing(key.to_string()));
+        let region = var("AWS_SECRET_REGION")
+            .or_else(|| var("AWS_REGION"))
+            .ok_or_else(|| Error::ParamMissing("AWS_SECRET_REGION".to_string()))?;
+        Ok(Self {
+            name: required("AWS_SECRET_NAME")?,
+            endpoint: var("AWS_SECRET_ENDPOINT")
+                .unwrap_or_else(|| format!("https://{}.{}.amazonaws.com", SERVICE, region)),
+            region,
+            access_key_id: required("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: required("AWS_SECRET_ACCESS_KEY")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// `SecretString` of the secret, by `GetSecretValue`.
+    /// Runs on its own thread, as config may be first read inside an async runtime.
+    pub fn fetch(&self) -> Result<String, Error> {
+        let source = self.clone();
+        std::thread::spawn(move || source.fetch_blocking())
+            .join()
+            .map_err(|_| {
+                Error::General(
+                    "AWS Secrets Manager | fetch thread panicked".to_string(),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+            })?
+    }
+
+    fn fetch_blocking(&self) -> Result<String, Error> {
+        let url = url::Url::parse(&self.endpoint)
+            .map_err(|err| Error::ParamError(format!("AWS_SECRET_ENDPOINT: {}", err)))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(Error::ParamError(format!(
+                    "AWS_SECRET_ENDPOINT: no host in {}",
+                    self.endpoint
+                )))
+            }
+        };
+        let body = serde_json::json!({ "SecretId": self.name }).to_string();
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut headers = vec![
+            ("content-type", CONTENT_TYPE.to_string()),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+            ("x-amz-target", TARGET.to_string()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.sort_by_key(|(name, _)| *name);
+        let authorization = self.authorization(&amz_date, &headers, &body);
+
+        let client = reqwest::blocking::Client::new();
+        let mut req = client.post(url).body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            req = req.header(*name, value);
+        }
+        let resp = req
+            .header("authorization", authorization)
+            .send()
+            .map_err(|err| {
+                Error::ManualHttpClientError(format!("AWS Secrets Manager | {}", err))
+            })?;
+        let status = StatusCode::from_u16(resp.status().as_u16())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let text = resp.text().map_err(|err| {
+            Error::ManualHttpClientError(format!("AWS Secrets Manager | {}", err))
+        })?;
+        if !status.is_success() {
+            let err: ErrorResponse = serde_json::from_str(&text).unwrap_or(ErrorResponse {
+                error_type: String::new(),
+                message: text,
+            });
+            return Err(Error::General(
+                format!(
+                    "AWS Secrets Manager | {} {}: {}",
+                    status, err.error_type, err.message
+                ),
+                status,
+            ));
+        }
+        let resp: GetSecretValueResponse = serde_json::from_str(&text)?;
+        resp.secret_string.ok_or_else(|| {
+            Error::ParamMissing(format!(
+                "SecretString of {} (binary secrets unsupported)",
+                self.name
+            ))
+        })
+    }
+
+    /// AWS Signature Version 4 of a `POST /` request. `headers` must be sorted by name.
+    fn authorization(&self, amz_date: &str, headers: &[(&str, String)], body: &str) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, SERVICE);
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(body.as_bytes()))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_access_key, date, &self.region, SERVICE);
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id,
+            scope,
+            signed_headers,
+            hex::encode(hmac(&key, string_to_sign.as_bytes()))
+        )
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// SigV4 signing key of `date` (`YYYYMMDD`).
+pub(crate) fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let key = hmac(&key, region.as_bytes());
+    let key = hmac(&key, service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
@@ -1,8 +1,11 @@
+pub mod aws_secret;
 mod env;
+mod tests;
 
-use crate::error::Error;
-use config::Config;
-use serde::Deserialize;
+use crate::{error::Error, upstream::DataSource};
+use config::{builder::DefaultState, Config, ConfigBuilder, FileFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use self::env::ENV;
 
@@ -22,14 +25,32 @@ lazy_static! {
     };
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct KVConfig {
+    #[serde(default)]
     pub tdb: ConfigTigerGraph,
+    #[serde(default)]
     pub web: ConfigWeb,
     pub upstream: Upstream,
+    #[serde(default)]
+    pub store: ConfigStore,
+    #[serde(default)]
+    pub jobs: ConfigJobs,
+    #[serde(default)]
+    pub lease: ConfigLease,
+    #[serde(default)]
+    pub rate_limit: ConfigRateLimit,
+    #[serde(default)]
+    pub circuit_breaker: ConfigCircuitBreaker,
+    #[serde(default)]
+    pub upstream_registry: ConfigUpstreamRegistry,
+    #[serde(default)]
+    pub curation: ConfigCuration,
+    #[serde(default)]
+    pub graphql: ConfigGraphQL,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct Upstream {
     pub proof_service: ConfigProofService,
     pub aggregation_service: ConfigAggregationService,
@@ -49,56 +70,343 @@ pub struct Upstream {
     pub solana_rpc: ConfigSolanaRPC,
     pub genome_api: ConfigGenomeAPI,
     pub clusters_api: ConfigClustersAPI,
+    #[serde(default)]
+    pub evm_rpc: ConfigEvmRPC,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigTigerGraph {
     pub host: String,
     pub username: String,
     pub password: String,
     pub identity_graph_token: String,
     pub social_graph_token: String,
+    /// GSQL server migrations are applied through, e.g. `http://localhost:14240`.
+    #[serde(default)]
+    pub gsql_host: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct ConfigStore {
+    /// Which graph storage backend to persist into.
+    #[serde(default)]
+    pub backend: StoreBackend,
+    /// Database file used by `backend = "sqlite"`.
+    #[serde(default = "default_store_path")]
+    pub path: String,
+}
+
+fn default_store_path() -> String {
+    "relation_server.sqlite".to_string()
+}
+
+/// Graph storage backends supported by RelationService.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreBackend {
+    /// Remote TigerGraph server configured in `[tdb]`.
+    #[default]
+    TigerGraph,
+    /// Process-local in-memory graph. Nothing survives a restart.
+    Memory,
+    /// Embedded SQLite file at `[store] path`. For development and small
+    /// single-node deployments: the whole graph is held in memory.
+    Sqlite,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigJobs {
+    /// SQLite file holding queued background jobs.
+    #[serde(default = "default_jobs_path")]
+    pub path: String,
+    /// How many jobs run at the same time.
+    #[serde(default = "default_jobs_workers")]
+    pub workers: usize,
+    /// A job is marked as failed after this many attempts.
+    #[serde(default = "default_jobs_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry. Doubled on each further retry.
+    #[serde(default = "default_jobs_backoff_seconds")]
+    pub backoff_seconds: u64,
+    /// How often IdentityRank scores the whole graph. `0` disables it.
+    #[serde(default = "default_jobs_rank_interval_seconds")]
+    pub rank_interval_seconds: u64,
+}
+
+impl Default for ConfigJobs {
+    fn default() -> Self {
+        Self {
+            path: default_jobs_path(),
+            workers: default_jobs_workers(),
+            max_attempts: default_jobs_max_attempts(),
+            backoff_seconds: default_jobs_backoff_seconds(),
+            rank_interval_seconds: default_jobs_rank_interval_seconds(),
+        }
+    }
+}
+
+fn default_jobs_path() -> String {
+    "relation_jobs.sqlite".to_string()
+}
+
+fn default_jobs_workers() -> usize {
+    4
+}
+
+fn default_jobs_max_attempts() -> u32 {
+    5
+}
+
+fn default_jobs_backoff_seconds() -> u64 {
+    10
+}
+
+fn default_jobs_rank_interval_seconds() -> u64 {
+    86400
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigLease {
+    /// Where fetch leases are kept.
+    #[serde(default)]
+    pub backend: LeaseBackend,
+    /// Server used by `backend = "redis"`.
+    #[serde(default)]
+    pub url: String,
+    /// A lease expires after this long if its holder never releases it.
+    #[serde(default = "default_lease_ttl_seconds")]
+    pub ttl_seconds: u64,
+    /// Name of this replica in lease records. Defaults to `hostname:pid`.
+    #[serde(default)]
+    pub holder: Option<String>,
+}
+
+impl Default for ConfigLease {
+    fn default() -> Self {
+        Self {
+            backend: LeaseBackend::default(),
+            url: String::default(),
+            ttl_seconds: default_lease_ttl_seconds(),
+            holder: None,
+        }
+    }
+}
+
+fn default_lease_ttl_seconds() -> u64 {
+    300
+}
+
+/// Lease backends supported by RelationService.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaseBackend {
+    /// Process-local. Only dedups fetches inside one replica.
+    #[default]
+    Memory,
+    /// Redis-compatible server at `[lease] url`, shared by all replicas.
+    Redis,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigRateLimit {
+    /// How many targets `fetch_many` fetches at the same time.
+    #[serde(default = "default_rate_limit_concurrent_targets")]
+    pub concurrent_targets: usize,
+    /// How long an upstream call may wait for its limit before being deferred.
+    #[serde(default = "default_rate_limit_max_wait_seconds")]
+    pub max_wait_seconds: u64,
+    /// Deferred upstream calls run in background queue after this delay.
+    #[serde(default = "default_rate_limit_defer_seconds")]
+    pub defer_seconds: u64,
+    /// Limits of each upstream, keyed by `DataSource` name. Unlisted ones are unlimited.
+    #[serde(default)]
+    pub sources: HashMap<DataSource, ConfigSourceLimit>,
+}
+
+impl Default for ConfigRateLimit {
+    fn default() -> Self {
+        Self {
+            concurrent_targets: default_rate_limit_concurrent_targets(),
+            max_wait_seconds: default_rate_limit_max_wait_seconds(),
+            defer_seconds: default_rate_limit_defer_seconds(),
+            sources: HashMap::new(),
+        }
+    }
+}
+
+fn default_rate_limit_concurrent_targets() -> usize {
+    5
+}
+
+fn default_rate_limit_max_wait_seconds() -> u64 {
+    5
+}
+
+fn default_rate_limit_defer_seconds() -> u64 {
+    60
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ConfigSourceLimit {
+    /// Token bucket refill rate. `None` means no rate limit.
+    pub rate_per_second: Option<f64>,
+    /// Token bucket capacity. Defaults to one second of `rate_per_second`.
+    pub burst: Option<u32>,
+    /// Calls running at the same time. `None` means no limit.
+    pub max_in_flight: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigCircuitBreaker {
+    /// An upstream is skipped after failing this many times in a row.
+    #[serde(default = "default_circuit_breaker_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long it is skipped before a probe call is let through.
+    #[serde(default = "default_circuit_breaker_open_seconds")]
+    pub open_seconds: u64,
+}
+
+impl Default for ConfigCircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_circuit_breaker_failure_threshold(),
+            open_seconds: default_circuit_breaker_open_seconds(),
+        }
+    }
+}
+
+fn default_circuit_breaker_failure_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_breaker_open_seconds() -> u64 {
+    60
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+pub struct ConfigUpstreamRegistry {
+    /// Timeout of every upstream call, in seconds. Defaults to 30.
+    pub timeout_seconds: Option<u64>,
+    /// Overrides of each upstream, keyed by upstream name (e.g. `the_graph`, `knn3`).
+    #[serde(default)]
+    pub upstreams: HashMap<String, ConfigUpstreamEntry>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ConfigUpstreamEntry {
+    pub enabled: Option<bool>,
+    /// Higher ones are called and merged first. Defaults to 0.
+    pub priority: Option<i32>,
+    pub timeout_seconds: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigCuration {
+    /// Bearer token of each operator allowed to curate links, keyed by operator name.
+    /// Mutations are refused when empty.
+    #[serde(default)]
+    pub operators: HashMap<String, String>,
+    /// SQLite file recording every curation with its operator and note.
+    #[serde(default = "default_curation_audit_path")]
+    pub audit_path: String,
+}
+
+impl Default for ConfigCuration {
+    fn default() -> Self {
+        Self {
+            operators: HashMap::new(),
+            audit_path: default_curation_audit_path(),
+        }
+    }
+}
+
+fn default_curation_audit_path() -> String {
+    "relation_curation.sqlite".to_string()
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigGraphQL {
+    /// Queries nested deeper than this are rejected.
+    #[serde(default = "default_graphql_max_depth")]
+    pub max_depth: usize,
+    /// Queries costing more than this are rejected. A field costs 1 unless priced below.
+    #[serde(default = "default_graphql_max_complexity")]
+    pub max_complexity: usize,
+    /// Cost of each hop of a traversal, paid again for every field asked of the neighbors.
+    #[serde(default = "default_graphql_depth_cost")]
+    pub depth_cost: usize,
+    /// Cost of a field which may fetch from upstreams.
+    #[serde(default = "default_graphql_fetch_cost")]
+    pub fetch_cost: usize,
+}
+
+impl Default for ConfigGraphQL {
+    fn default() -> Self {
+        Self {
+            max_depth: default_graphql_max_depth(),
+            max_complexity: default_graphql_max_complexity(),
+            depth_cost: default_graphql_depth_cost(),
+            fetch_cost: default_graphql_fetch_cost(),
+        }
+    }
+}
+
+fn default_graphql_max_depth() -> usize {
+    15
+}
+
+fn default_graphql_max_complexity() -> usize {
+    1000
+}
+
+fn default_graphql_depth_cost() -> usize {
+    10
+}
+
+fn default_graphql_fetch_cost() -> usize {
+    20
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigWeb {
     pub listen: String,
     pub port: u16,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigProofService {
     pub url: String,
+    #[serde(default)]
     pub api_key: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigKeybaseService {
     pub url: String,
     pub stable_url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigAggregationService {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigSybilService {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigKnn3Service {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigRss3Service {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigUpstreamTheGraph {
     pub ens: String,
     pub subgraph0: Option<String>,
@@ -108,66 +416,100 @@ pub struct ConfigUpstreamTheGraph {
     pub subgraph4: Option<String>,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigENSReverse {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigDotbitService {
     pub url: String,
     pub register_api: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigLensAPI {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigUnstoppableDomainsAPI {
     pub url: String,
+    #[serde(default)]
     pub token: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigDataMgrAPI {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigWarpcastAPI {
     pub url: String,
+    #[serde(default)]
     pub token: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigSpaceIdAPI {
     pub url: String,
     pub graphql: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigCrossbellAPI {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigSolanaRPC {
     pub rpc_url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+/// JSON-RPC node read by `evm_transfer` upstream.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConfigEvmRPC {
+    /// `evm_transfer` fetches nothing if empty.
+    #[serde(default)]
+    pub rpc_url: String,
+    /// Transfer logs are read from this block on.
+    #[serde(default)]
+    pub from_block: u64,
+    /// Only read transfers of these token contracts. Any token if empty.
+    #[serde(default)]
+    pub contracts: Vec<String>,
+    /// Blocks covered by one `eth_getLogs` call. Nodes reject too wide ranges.
+    #[serde(default = "default_evm_rpc_block_range")]
+    pub block_range: u64,
+}
+
+impl Default for ConfigEvmRPC {
+    fn default() -> Self {
+        Self {
+            rpc_url: String::default(),
+            from_block: 0,
+            contracts: vec![],
+            block_range: default_evm_rpc_block_range(),
+        }
+    }
+}
+
+fn default_evm_rpc_block_range() -> u64 {
+    2_000
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigGenomeAPI {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Clone, Serialize, Deserialize, Default)]
 pub struct ConfigClustersAPI {
     pub url: String,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum ConfigCategory {
     File,
     AWSSecret,
@@ -189,9 +531,9 @@ pub fn app_env() -> ENV {
         .into()
 }
 
-/// Parse config from local file or ENV.
-pub fn parse() -> Result<KVConfig, Error> {
-    let s = Config::builder()
+/// Config file and `KV__` ENV sources, in ascending priority.
+fn builder() -> ConfigBuilder<DefaultState> {
+    Config::builder()
         // Default
         .add_source(config::File::with_name(CONFIG_FILE_PATH).required(false))
         // app-env-based config
@@ -205,12 +547,81 @@ pub fn parse() -> Result<KVConfig, Error> {
                 .separator("__")
                 .ignore_empty(true),
         )
-        .build()?;
+}
+
+/// Parse config from local file or ENV.
+pub fn parse() -> Result<KVConfig, Error> {
+    let s = builder().build()?;
 
     s.try_deserialize().map_err(|e| e.into())
 }
 
 /// `AWS_SECRET_NAME` and `AWS_SECRET_REGION` is needed.
+/// See `aws_secret::AwsSecretSource::from_env()` for the rest.
 pub fn from_aws_secret() -> Result<KVConfig, Error> {
-    todo!()
+    let secret = aws_secret::AwsSecretSource::from_env()?.fetch()?;
+    merge_secret(&secret)
+}
+
+/// Config `secret` (TOML, or JSON if it is an object) merged over file and ENV sources.
+pub fn merge_secret(secret: &str) -> Result<KVConfig, Error> {
+    let format = if secret.trim_start().starts_with('{') {
+        FileFormat::Json
+    } else {
+        FileFormat::Toml
+    };
+    let s = builder()
+        .add_source(config::File::from_str(secret, format))
+        .build()?;
+
+    s.try_deserialize().map_err(|e| e.into())
+}
+
+/// Config field names holding credentials.
+/// EVM RPC endpoints usually carry the provider API key in their path.
+const SECRET_FIELDS: [&str; 5] = ["password", "token", "secret", "api_key", "rpc_url"];
+
+/// `url` with its userinfo (`user:password@`) replaced by `[redacted]`,
+/// or `None` if it is not a URL or has no userinfo.
+fn redact_userinfo(url: &str) -> Option<String> {
+    let mut url = url::Url::parse(url).ok()?;
+    if url.username().is_empty() && url.password().is_none() {
+        return None;
+    }
+    url.set_username("").ok()?;
+    url.set_password(None).ok()?;
+    Some(url.to_string().replacen("://", "://[redacted]@", 1))
+}
+
+/// `config` as JSON with credentials replaced by `"[redacted]"`, safe to print.
+/// Those are fields named after `SECRET_FIELDS`, every `curation.operators` token
+/// and the userinfo of any URL, e.g. `redis://:password@host`.
+pub fn redacted(config: &KVConfig) -> Result<serde_json::Value, Error> {
+    fn redact(value: &mut serde_json::Value, secret: bool) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let secret = secret || SECRET_FIELDS.iter().any(|field| key.contains(field));
+                    redact(value, secret);
+                }
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(|v| redact(v, secret)),
+            serde_json::Value::String(s) if secret && !s.is_empty() => {
+                *s = "[redacted]".to_string()
+            }
+            serde_json::Value::String(s) => {
+                if let Some(url) = redact_userinfo(s) {
+                    *s = url
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(config)?;
+    redact(&mut value, false);
+    if let Some(operators) = value.pointer_mut("/curation/operators") {
+        redact(operators, true);
+    }
+    Ok(value)
 }
@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{
+            aws_secret::{signing_key, AwsSecretSource},
+            merge_secret, redacted,
+        },
+        error::Error,
+    };
+    use http::{Request, Response, StatusCode};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Server,
+    };
+    use serde_json::{json, Value};
+    use std::convert::Infallible;
+
+    /// Mock Secrets Manager holding `secret` under the name `relation`.
+    fn spawn_secrets_manager(secret: &'static str) -> String {
+        let make_service = make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| async move {
+                let header = |name: &str| {
+                    req.headers()
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let signed = header("authorization")
+                    .starts_with("AWS4-HMAC-SHA256 Credential=AKID/")
+                    && header("authorization").contains("x-amz-security-token");
+                let target = header("x-amz-target");
+                let body = crate::tests::replay::read_body(req.into_body())
+                    .await
+                    .unwrap();
+                let body: Value = serde_json::from_slice(&body).unwrap_or_default();
+                let (status, resp) = match (signed, target.as_str(), body["SecretId"].as_str()) {
+                    (false, _, _) => (
+                        StatusCode::FORBIDDEN,
+                        json!({"__type": "UnrecognizedClientException", "message": "Unsigned"}),
+                    ),
+                    (true, "secretsmanager.GetSecretValue", Some("relation")) => (
+                        StatusCode::OK,
+                        json!({"Name": "relation", "SecretString": secret}),
+                    ),
+                    _ => (
+                        StatusCode::BAD_REQUEST,
+                        json!({"__type": "ResourceNotFoundException", "message": "Not found"}),
+                    ),
+                };
+                Ok::<_, Infallible>(
+                    Response::builder()
+                        .status(status)
+                        .body(Body::from(resp.to_string()))
+                        .unwrap(),
+                )
+            }))
+        });
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    fn source(name: &str, endpoint: String) -> AwsSecretSource {
+        AwsSecretSource {
+            name: name.to_string(),
+            region: "us-east-1".to_string(),
+            endpoint,
+            access_key_id: "AKID".to_string(),
+            secret_access_key: "SECRET".to_string(),
+            session_token: Some("TOKEN".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // Example of AWS Signature Version 4 docs.
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_merge_secret() -> Result<(), Error> {
+        let config = merge_secret("[web]\nlisten = \"0.0.0.0\"\nport = 8001\n")?;
+        assert_eq!(config.web.port, 8001);
+        // Still read from `config/testing.toml`.
+        assert_eq!(config.tdb.host, "http://127.0.0.1:9000");
+
+        let config = merge_secret(r#"{"tdb": {"host": "http://tigergraph:9000"}}"#)?;
+        assert_eq!(config.tdb.host, "http://tigergraph:9000");
+        assert_eq!(config.tdb.username, "tigergraph");
+        Ok(())
+    }
+
+    #[test]
+    fn test_redacted() -> Result<(), Error> {
+        let config = merge_secret(
+            r#"{"tdb": {"password": "hunter2"}, "curation": {"operators": {"alice": "t0ken"}},
+                "lease": {"url": "redis://:hunter3@redis:6379/0"},
+                "upstream": {"evm_rpc": {"rpc_url": "https://mainnet.infura.io/v3/k3y"}}}"#,
+        )?;
+        let value = redacted(&config)?;
+        assert_eq!(value["tdb"]["password"], "[redacted]");
+        assert_eq!(value["tdb"]["host"], "http://127.0.0.1:9000");
+        assert_eq!(value["curation"]["operators"]["alice"], "[redacted]");
+        assert!(!value.to_string().contains("hunter2"));
+        assert!(!value.to_string().contains("t0ken"));
+        assert_eq!(value["lease"]["url"], "redis://[redacted]@redis:6379/0");
+        assert_eq!(value["upstream"]["evm_rpc"]["rpc_url"], "[redacted]");
+        assert!(!value.to_string().contains("hunter3"));
+        assert!(!value.to_string().contains("k3y"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_secret() -> Result<(), Error> {
+        let endpoint = spawn_secrets_manager(r#"{"web": {"listen": "0.0.0.0", "port": 8002}}"#);
+
+        let found = source("relation", endpoint.clone());
+        let secret = tokio::task::spawn_blocking(move || found.fetch())
+            .await
+            .unwrap()?;
+        assert_eq!(merge_secret(&secret)?.web.port, 8002);
+
+        let missing = source("missing", endpoint);
+        let err = tokio::task::spawn_blocking(move || missing.fetch())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("ResourceNotFoundException"));
+        Ok(())
+    }
+}
@@ -1,15 +1,32 @@
 use crate::{
+    config::{StoreBackend, C},
     controller::{json_response, Request, Response},
     error::Error,
+    tigergraph::{ping, ping_id_allocation, Graph},
+    upstream::{breakers, UpstreamStatus},
+    util::make_http_client,
 };
 use http::StatusCode;
 use serde::Serialize;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// A dependency not answering within this long is down.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const IDENTITY_GRAPH: &str = "tigergraph_identity_graph";
+const SOCIAL_GRAPH: &str = "tigergraph_social_graph";
+const ID_ALLOCATION: &str = "id_allocation";
 
 #[derive(Serialize)]
 struct HealthzResponse {
     pub hello: String,
     pub built_at: String,
     pub revision: String,
+    /// Circuit breaker state of upstreams.
+    pub upstreams: Vec<UpstreamStatus>,
 }
 
 pub async fn controller(_req: Request) -> Result<Response, Error> {
@@ -23,6 +40,145 @@ pub async fn controller(_req: Request) -> Result<Response, Error> {
             revision: option_env!("RELATION_SERVER_REVISION")
                 .unwrap_or("UNKNOWN")
                 .to_string(),
+            upstreams: breakers().statuses(),
+        },
+    )
+}
+
+#[derive(Serialize)]
+struct LiveResponse {
+    pub status: String,
+    pub built_at: String,
+    pub revision: String,
+}
+
+/// Liveness: the process is serving. Nothing else is checked.
+pub async fn live(_req: Request) -> Result<Response, Error> {
+    json_response(
+        StatusCode::OK,
+        &LiveResponse {
+            status: "ok".to_string(),
+            built_at: option_env!("RELATION_SERVER_BUILT_AT")
+                .unwrap_or("UNKNOWN")
+                .to_string(),
+            revision: option_env!("RELATION_SERVER_REVISION")
+                .unwrap_or("UNKNOWN")
+                .to_string(),
+        },
+    )
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyState {
+    Up,
+    Down,
+    /// Not used by the configured `[store] backend`.
+    Skipped,
+}
+
+#[derive(Serialize)]
+struct DependencyStatus {
+    pub name: String,
+    pub status: DependencyState,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ReadyResponse {
+    pub ready: bool,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+/// Run `probe` for at most `PROBE_TIMEOUT`.
+async fn probe<F>(name: &str, probe: F) -> DependencyStatus
+where
+    F: Future<Output = Result<(), Error>>,
+{
+    let started = Instant::now();
+    let result = match tokio::time::timeout(PROBE_TIMEOUT, probe).await {
+        Ok(result) => result,
+        Err(_) => Err(Error::General(
+            format!("No answer in {:?}", PROBE_TIMEOUT),
+            StatusCode::GATEWAY_TIMEOUT,
+        )),
+    };
+    DependencyStatus {
+        name: name.to_string(),
+        status: match result {
+            Ok(()) => DependencyState::Up,
+            Err(_) => DependencyState::Down,
+        },
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err().map(|err| err.to_string()),
+    }
+}
+
+fn skipped(name: &str) -> DependencyStatus {
+    DependencyStatus {
+        name: name.to_string(),
+        status: DependencyState::Skipped,
+        latency_ms: 0,
+        error: None,
+    }
+}
+
+/// Config entries the `[store] backend` cannot work without.
+fn check_config() -> Result<(), Error> {
+    let required: Vec<(&str, &str)> = match C.store.backend {
+        StoreBackend::TigerGraph => vec![
+            ("tdb.host", &C.tdb.host),
+            ("tdb.identity_graph_token", &C.tdb.identity_graph_token),
+            ("tdb.social_graph_token", &C.tdb.social_graph_token),
+        ],
+        StoreBackend::Sqlite => vec![("store.path", &C.store.path)],
+        StoreBackend::Memory => vec![],
+    };
+    let missing: Vec<&str> = required
+        .into_iter()
+        .filter(|(_, value)| value.trim().is_empty())
+        .map(|(key, _)| key)
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ConfigError(config::ConfigError::Message(format!(
+            "missing {}",
+            missing.join(", ")
+        ))))
+    }
+}
+
+/// Readiness: every dependency of the configured backend answers.
+/// Gives `503` with the failing ones marked `down` otherwise.
+pub async fn ready(_req: Request) -> Result<Response, Error> {
+    let mut dependencies = vec![probe("config", async { check_config() }).await];
+    if C.store.backend == StoreBackend::TigerGraph {
+        let client = make_http_client();
+        let (identity_graph, social_graph, id_allocation) = tokio::join!(
+            probe(IDENTITY_GRAPH, ping(&client, Graph::IdentityGraph)),
+            probe(SOCIAL_GRAPH, ping(&client, Graph::SocialGraph)),
+            probe(ID_ALLOCATION, ping_id_allocation(&client)),
+        );
+        dependencies.extend([identity_graph, social_graph, id_allocation]);
+    } else {
+        dependencies.extend([IDENTITY_GRAPH, SOCIAL_GRAPH, ID_ALLOCATION].map(skipped));
+    }
+
+    let ready = dependencies
+        .iter()
+        .all(|dependency| dependency.status != DependencyState::Down);
+    json_response(
+        if ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        },
+        &ReadyResponse {
+            ready,
+            dependencies,
         },
     )
 }
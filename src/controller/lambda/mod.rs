@@ -1,17 +1,27 @@
+mod tests;
+
 use crate::controller::{
-    error_response, healthz, Body as OurBody, Request as OurRequest, Response as OurResponse,
+    error_response, healthz, json_response, tigergraphql::RelationSchema, Body as OurBody,
+    Request as OurRequest, Response as OurResponse,
+};
+use crate::{curation::authenticate, error::Error};
+use async_graphql::{
+    http::{parse_query_string, playground_source, GraphQLPlaygroundConfig},
+    BatchRequest,
 };
-use crate::error::Error;
 use http::{Method, StatusCode};
 use lambda_http::{
-    Body as LambdaBody, Error as LambdaError, IntoResponse, Request as LambdaRequest,
-    Response as LambdaResponse,
+    request::RequestContext, Body as LambdaBody, Error as LambdaError, Request as LambdaRequest,
+    RequestExt, Response as LambdaResponse,
 };
 use std::future::Future;
 use tracing::info;
 
 /// Translate between `lambda_http` `Body` and our `Body`.
-async fn parse<F>(req: LambdaRequest, controller: fn(OurRequest) -> F) -> LambdaResponse<LambdaBody>
+async fn parse<F>(
+    req: LambdaRequest,
+    controller: impl FnOnce(OurRequest) -> F,
+) -> LambdaResponse<LambdaBody>
 where
     F: Future<Output = Result<OurResponse, Error>>,
 {
@@ -33,16 +43,90 @@ where
     }
 }
 
+/// Run a GraphQL request, given as POST JSON body (single or batch) or GET `?query=`.
+/// Mutations see the curation operator of `Authorization` header, if any.
+async fn graphql(schema: RelationSchema, req: OurRequest) -> Result<OurResponse, Error> {
+    let request: BatchRequest = if req.method() == Method::GET {
+        parse_query_string(req.uri().query().unwrap_or_default())
+            .map_err(|err| Error::ParamError(err.to_string()))?
+            .into()
+    } else {
+        serde_json::from_str(req.body())?
+    };
+    let authorization = req
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok());
+    let request = match authenticate(authorization) {
+        Some(operator) => request.data(operator),
+        None => request,
+    };
+    let mut resp = json_response(StatusCode::OK, &schema.execute_batch(request).await)?;
+    resp.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+    Ok(resp)
+}
+
+/// Playground sending queries to `endpoint`, i.e. where it is served.
+fn playground(endpoint: &str) -> LambdaResponse<LambdaBody> {
+    LambdaResponse::builder()
+        .status(StatusCode::OK)
+        .header("content-type", "text/html")
+        .body(playground_source(GraphQLPlaygroundConfig::new(endpoint)).into())
+        .expect("Failed to render response")
+}
+
+/// CORS preflight.
+fn preflight() -> LambdaResponse<LambdaBody> {
+    let (parts, _) = json_response(StatusCode::NO_CONTENT, &())
+        .expect("Failed to render response")
+        .into_parts();
+    LambdaResponse::from_parts(parts, LambdaBody::Empty)
+}
+
+/// Request path with API Gateway stage (e.g. `/prod`) removed.
+fn route_path(req: &LambdaRequest) -> &str {
+    let path = req.uri().path();
+    let stage = match req.request_context_ref() {
+        Some(RequestContext::ApiGatewayV1(ctx)) => ctx.stage.as_deref(),
+        Some(RequestContext::ApiGatewayV2(ctx)) => ctx.stage.as_deref(),
+        _ => None,
+    };
+    match stage.filter(|stage| *stage != "$default") {
+        Some(stage) => match path.strip_prefix('/').and_then(|p| p.strip_prefix(stage)) {
+            Some("") => "/",
+            Some(rest) if rest.starts_with('/') => rest,
+            _ => path,
+        },
+        None => path,
+    }
+}
+
 /// Main entrypoint for `lambda_http`.
-pub async fn entrypoint(req: LambdaRequest) -> Result<impl IntoResponse, LambdaError> {
+pub async fn entrypoint(
+    schema: RelationSchema,
+    req: LambdaRequest,
+) -> Result<LambdaResponse<LambdaBody>, LambdaError> {
     info!(
         "{} {}",
         req.method().to_string(),
         req.uri().path().to_string()
     );
 
-    Ok(match (req.method(), req.uri().path()) {
+    let has_query = req
+        .uri()
+        .query()
+        .is_some_and(|q| q.split('&').any(|p| p.starts_with("query=")));
+    let path = route_path(&req).to_string();
+    Ok(match (req.method(), path.as_str()) {
         (&Method::GET, "/api/healthz") => parse(req, healthz::controller).await,
+        (&Method::GET, "/healthz/live") => parse(req, healthz::live).await,
+        (&Method::GET, "/healthz/ready") => parse(req, healthz::ready).await,
+        (&Method::GET, "/") if !has_query => playground(req.uri().path()),
+        (&Method::GET, "/") | (&Method::POST, "/") => parse(req, |req| graphql(schema, req)).await,
+        (&Method::OPTIONS, _) => preflight(),
         _ => LambdaResponse::builder()
             .status(StatusCode::NOT_FOUND)
             .body("Not Found".into())
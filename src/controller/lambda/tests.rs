@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use crate::controller::{lambda::entrypoint, tigergraphql::schema};
+    use http::StatusCode;
+    use lambda_http::{request::from_str, Body as LambdaBody, Response as LambdaResponse};
+    use serde_json::{json, Value};
+
+    /// API Gateway HTTP API (payload v2) event on `$default` stage.
+    fn http_api_event(method: &str, path: &str, query: &str, body: Option<Value>) -> String {
+        json!({
+            "version": "2.0",
+            "routeKey": "$default",
+            "rawPath": path,
+            "rawQueryString": query,
+            "headers": {
+                "content-type": "application/json",
+                "host": "xxx.execute-api.us-east-1.amazonaws.com",
+                "x-forwarded-proto": "https",
+            },
+            "requestContext": {
+                "accountId": "123456789012",
+                "apiId": "xxx",
+                "domainName": "xxx.execute-api.us-east-1.amazonaws.com",
+                "domainPrefix": "xxx",
+                "http": {
+                    "method": method,
+                    "path": path,
+                    "protocol": "HTTP/1.1",
+                    "sourceIp": "127.0.0.1",
+                    "userAgent": "test",
+                },
+                "requestId": "test",
+                "routeKey": "$default",
+                "stage": "$default",
+                "time": "06/May/2020:22:36:55 +0000",
+                "timeEpoch": 1588804615616_u64,
+            },
+            "body": body.map(|b| b.to_string()),
+            "isBase64Encoded": false,
+        })
+        .to_string()
+    }
+
+    /// API Gateway REST API (payload v1) event on stage `prod`.
+    fn rest_api_event(method: &str, path: &str, body: Option<Value>) -> String {
+        json!({
+            "resource": "/{proxy+}",
+            "path": path,
+            "httpMethod": method,
+            "headers": {
+                "Content-Type": "application/json",
+                "Host": "xxx.execute-api.us-east-1.amazonaws.com",
+            },
+            "multiValueHeaders": {},
+            "queryStringParameters": null,
+            "multiValueQueryStringParameters": null,
+            "pathParameters": {"proxy": path.trim_start_matches('/')},
+            "stageVariables": null,
+            "requestContext": {
+                "accountId": "123456789012",
+                "resourceId": "xxx",
+                "stage": "prod",
+                "requestId": "test",
+                "identity": {"sourceIp": "127.0.0.1"},
+                "resourcePath": "/{proxy+}",
+                "httpMethod": method,
+                "apiId": "xxx",
+                "path": format!("/prod{}", path),
+            },
+            "body": body.map(|b| b.to_string()),
+            "isBase64Encoded": false,
+        })
+        .to_string()
+    }
+
+    async fn invoke(event: String) -> (StatusCode, String) {
+        let req = from_str(&event).expect("synthetic event should parse");
+        let resp: LambdaResponse<LambdaBody> = entrypoint(schema(), req).await.unwrap();
+        let status = resp.status();
+        let body = match resp.into_body() {
+            LambdaBody::Empty => String::new(),
+            LambdaBody::Text(text) => text,
+            LambdaBody::Binary(bytes) => String::from_utf8(bytes).unwrap(),
+        };
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn test_graphql() {
+        let query = json!({"query": "{ ping apiVersion }"});
+        let (status, body) = invoke(http_api_event("POST", "/", "", Some(query.clone()))).await;
+        assert_eq!(status, StatusCode::OK);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["data"]["ping"], "Pong!");
+
+        // Batch request.
+        let batch = json!([query, {"query": "{ apiVersion }"}]);
+        let (status, body) = invoke(http_api_event("POST", "/", "", Some(batch))).await;
+        assert_eq!(status, StatusCode::OK);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body.as_array().map(|b| b.len()), Some(2));
+
+        let (status, body) =
+            invoke(http_api_event("GET", "/", "query=%7B%20ping%20%7D", None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("Pong!"));
+
+        let (status, _) = invoke(http_api_event("POST", "/", "", Some(json!("nope")))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_routes() {
+        let (status, body) = invoke(http_api_event("GET", "/api/healthz", "", None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("\"hello\""));
+
+        let (status, body) = invoke(http_api_event("GET", "/", "", None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("GraphQL Playground"));
+
+        let (status, _) = invoke(http_api_event("OPTIONS", "/", "", None)).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _) = invoke(http_api_event("GET", "/nowhere", "", None)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_healthz() {
+        let (status, body) = invoke(http_api_event("GET", "/healthz/live", "", None)).await;
+        assert_eq!(status, StatusCode::OK);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["status"], "ok");
+
+        let (status, body) = invoke(http_api_event("GET", "/healthz/ready", "", None)).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["ready"], true);
+        let dependencies = body["dependencies"].as_array().unwrap();
+        let names: Vec<&str> = dependencies
+            .iter()
+            .map(|d| d["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "config",
+                "tigergraph_identity_graph",
+                "tigergraph_social_graph",
+                "id_allocation"
+            ]
+        );
+        assert!(dependencies
+            .iter()
+            .all(|d| d["status"] == "up" && d["latency_ms"].is_u64()));
+    }
+
+    #[tokio::test]
+    async fn test_rest_api_stage() {
+        let (status, body) = invoke(rest_api_event(
+            "POST",
+            "/",
+            Some(json!({"query": "{ ping }"})),
+        ))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("Pong!"));
+
+        let (status, _) = invoke(rest_api_event("GET", "/api/healthz", None)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Queries are sent back to the stage it is served under.
+        let (status, body) = invoke(rest_api_event("GET", "/", None)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("/prod/"));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{
+    curation::{self, identity, Link, Operator},
+    error::{Error, FieldResult, Result},
+    upstream::{DomainNameSystem, Platform, ProofLevel},
+};
+
+use async_graphql::{Context, Object};
+
+/// Manually curated links, tagged `manually_added`.
+/// Needs `Authorization: Bearer <token>` of an operator in `[curation] operators`.
+#[derive(Default)]
+pub struct CurationMutation;
+
+fn operator<'a>(ctx: &'a Context<'_>) -> Result<&'a Operator> {
+    ctx.data_opt::<Operator>()
+        .ok_or_else(|| Error::Unauthorized("curation operator token required".to_string()))
+}
+
+#[Object]
+impl CurationMutation {
+    /// Link two identities with a `Proof_Forward` / `Proof_Backward` pair.
+    #[allow(clippy::too_many_arguments)]
+    async fn add_proof(
+        &self,
+        ctx: &Context<'_>,
+        from_platform: Platform,
+        from_identity: String,
+        to_platform: Platform,
+        to_identity: String,
+        #[graphql(desc = "Defaults to `VeryConfident`.")] level: Option<ProofLevel>,
+        #[graphql(desc = "Why this link is curated. Kept in audit log.")] note: String,
+    ) -> FieldResult<bool> {
+        let operator = operator(ctx)?;
+        let link = Link::Proof {
+            from: identity(from_platform, &from_identity).await?,
+            to: identity(to_platform, &to_identity).await?,
+            level: level.unwrap_or(ProofLevel::VeryConfident),
+        };
+        curation::add(operator, &link, &note).await?;
+        Ok(true)
+    }
+
+    /// Remove a manually added `Proof_Forward` / `Proof_Backward` pair.
+    async fn remove_proof(
+        &self,
+        ctx: &Context<'_>,
+        from_platform: Platform,
+        from_identity: String,
+        to_platform: Platform,
+        to_identity: String,
+        note: String,
+    ) -> FieldResult<bool> {
+        let operator = operator(ctx)?;
+        let link = Link::Proof {
+            from: identity(from_platform, &from_identity).await?,
+            to: identity(to_platform, &to_identity).await?,
+            level: ProofLevel::default(),
+        };
+        curation::remove(operator, &link, &note).await?;
+        Ok(true)
+    }
+
+    /// Record `from` holding identity `to`.
+    async fn add_hold(
+        &self,
+        ctx: &Context<'_>,
+        from_platform: Platform,
+        from_identity: String,
+        to_platform: Platform,
+        to_identity: String,
+        note: String,
+    ) -> FieldResult<bool> {
+        let operator = operator(ctx)?;
+        let link = Link::Hold {
+            from: identity(from_platform, &from_identity).await?,
+            to: identity(to_platform, &to_identity).await?,
+        };
+        curation::add(operator, &link, &note).await?;
+        Ok(true)
+    }
+
+    /// Remove a manually added `Hold` record.
+    async fn remove_hold(
+        &self,
+        ctx: &Context<'_>,
+        from_platform: Platform,
+        from_identity: String,
+        to_platform: Platform,
+        to_identity: String,
+        note: String,
+    ) -> FieldResult<bool> {
+        let operator = operator(ctx)?;
+        let link = Link::Hold {
+            from: identity(from_platform, &from_identity).await?,
+            to: identity(to_platform, &to_identity).await?,
+        };
+        curation::remove(operator, &link, &note).await?;
+        Ok(true)
+    }
+
+    /// Record domain `from` resolving to `to` in `system`.
+    #[allow(clippy::too_many_arguments)]
+    async fn add_resolve(
+        &self,
+        ctx: &Context<'_>,
+        system: DomainNameSystem,
+        from_platform: Platform,
+        from_identity: String,
+        to_platform: Platform,
+        to_identity: String,
+        note: String,
+    ) -> FieldResult<bool> {
+        let operator = operator(ctx)?;
+        let link = Link::Resolve {
+            from: identity(from_platform, &from_identity).await?,
+            to: identity(to_platform, &to_identity).await?,
+            system,
+        };
+        curation::add(operator, &link, &note).await?;
+        Ok(true)
+    }
+
+    /// Remove a manually added `Resolve` record.
+    #[allow(clippy::too_many_arguments)]
+    async fn remove_resolve(
+        &self,
+        ctx: &Context<'_>,
+        system: DomainNameSystem,
+        from_platform: Platform,
+        from_identity: String,
+        to_platform: Platform,
+        to_identity: String,
+        note: String,
+    ) -> FieldResult<bool> {
+        let operator = operator(ctx)?;
+        let link = Link::Resolve {
+            from: identity(from_platform, &from_identity).await?,
+            to: identity(to_platform, &to_identity).await?,
+            system,
+        };
+        curation::remove(operator, &link, &note).await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    error::{Error, FieldResult},
+    tigergraph::{
+        edge::FollowRecord,
+        vertex::{IdentityLoadFn, IdentityRecord},
+    },
+    upstream::DataSource,
+};
+use async_graphql::{Context, Object};
+use dataloader::non_cached::Loader;
+
+#[Object]
+impl FollowRecord {
+    /// Who reports this follow.
+    async fn source(&self) -> DataSource {
+        self.source
+    }
+
+    /// When this follow is updated by us.
+    /// Second-based unix timestamp.
+    async fn updated_at(&self) -> i64 {
+        self.updated_at.and_utc().timestamp()
+    }
+
+    /// `graph_id` of the identity graph the follower belongs to.
+    async fn from_graph_id(&self) -> String {
+        self.from_id.clone()
+    }
+
+    /// `graph_id` of the identity graph being followed.
+    async fn to_graph_id(&self) -> String {
+        self.to_id.clone()
+    }
+
+    /// The follower.
+    async fn from(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
+        let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.original_from.clone()).await {
+            Some(value) => Ok(value),
+            None => Err(Error::GraphQLError("record from no found.".to_string()).into()),
+        }
+    }
+
+    /// The identity being followed.
+    async fn to(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
+        let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.original_to.clone()).await {
+            Some(value) => Ok(value),
+            None => Err(Error::GraphQLError("record to no found.".to_string()).into()),
+        }
+    }
+}
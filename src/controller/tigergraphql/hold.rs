@@ -1,18 +1,19 @@
+use super::limit::fetch_cost;
 use crate::{
-    error::{Error, Result},
+    error::{Error, FieldResult},
+    jobs::{enqueue_or_warn, JobKind},
+    storage::store,
     tigergraph::{
-        delete_graph_inner_connection,
-        edge::{Hold, HoldRecord},
+        edge::HoldRecord,
         vertex::{ContractLoadFn, ContractRecord, IdentityLoadFn, IdentityRecord},
     },
     upstream::{fetch_all, Chain, ContractCategory, DataFetcher, DataSource, Target},
-    util::make_http_client,
 };
 
 use async_graphql::{Context, Object};
 use dataloader::non_cached::Loader;
 use strum::IntoEnumIterator;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 use uuid::Uuid;
 
 #[Object]
@@ -60,54 +61,54 @@ impl HoldRecord {
     }
 
     /// NFT Category. See `availableNftCategories` for all values available.
-    async fn category(&self, ctx: &Context<'_>) -> Result<ContractCategory> {
+    async fn category(&self, ctx: &Context<'_>) -> FieldResult<ContractCategory> {
         let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.to_id.clone()).await {
             Some(contract) => Ok(contract.category),
-            None => Err(Error::GraphQLError("contract no found.".to_string())),
+            None => Err(Error::GraphQLError("contract no found.".to_string()).into()),
         }
     }
 
     /// On which chain?
     /// See `availableChains` for all chains supported by RelationService.
-    async fn chain(&self, ctx: &Context<'_>) -> Result<Chain> {
+    async fn chain(&self, ctx: &Context<'_>) -> FieldResult<Chain> {
         let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.to_id.clone()).await {
             Some(contract) => Ok(contract.chain),
-            None => Err(Error::GraphQLError("contract no found.".to_string())),
+            None => Err(Error::GraphQLError("contract no found.".to_string()).into()),
             // None => Ok(Chain::Unknown),
         }
     }
 
     /// Contract address of this Contract. Usually `0xHEX_STRING`.
-    async fn address(&self, ctx: &Context<'_>) -> Result<String> {
+    async fn address(&self, ctx: &Context<'_>) -> FieldResult<String> {
         let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.to_id.clone()).await {
             Some(contract) => Ok(contract.address.clone()),
-            None => Err(Error::GraphQLError("contract no found.".to_string())),
+            None => Err(Error::GraphQLError("contract no found.".to_string()).into()),
         }
     }
 
     /// Token symbol (if any).
-    async fn symbol(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+    async fn symbol(&self, ctx: &Context<'_>) -> FieldResult<Option<String>> {
         let loader: &Loader<String, Option<ContractRecord>, ContractLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.to_id.clone()).await {
             Some(contract) => Ok(contract.symbol.clone()),
-            None => Err(Error::GraphQLError("contract no found.".to_string())),
+            None => Err(Error::GraphQLError("contract no found.".to_string()).into()),
         }
     }
 
     /// Which `Identity` does this NFT belong to.
-    async fn owner(&self, ctx: &Context<'_>) -> Result<IdentityRecord> {
+    async fn owner(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
         let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.from_id.clone()).await {
             Some(identity) => Ok(identity),
-            None => Err(Error::GraphQLError("record no found.".to_string())),
+            None => Err(Error::GraphQLError("record no found.".to_string()).into()),
         }
     }
 
@@ -118,22 +119,22 @@ impl HoldRecord {
     }
 
     /// Which `IdentityRecord` does this connection starts at.
-    async fn from(&self, ctx: &Context<'_>) -> Result<IdentityRecord> {
+    async fn from(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
         let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.from_id.clone()).await {
             Some(value) => Ok(value),
-            None => Err(Error::GraphQLError("record from no found.".to_string())),
+            None => Err(Error::GraphQLError("record from no found.".to_string()).into()),
         }
     }
 
     /// Which `IdentityRecord` does this connection ends at.
-    async fn to(&self, ctx: &Context<'_>) -> Result<IdentityRecord> {
+    async fn to(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
         let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.to_id.clone()).await {
             Some(value) => Ok(value),
-            None => Err(Error::GraphQLError("record to no found.".to_string())),
+            None => Err(Error::GraphQLError("record to no found.".to_string()).into()),
         }
     }
 }
@@ -155,6 +156,7 @@ impl HoldQuery {
 
     /// Search an NFT.
     #[tracing::instrument(level = "trace", skip(self, _ctx))]
+    #[graphql(complexity = "fetch_cost(1) + child_complexity")]
     async fn nft(
         &self,
         _ctx: &Context<'_>,
@@ -174,30 +176,26 @@ impl HoldQuery {
             desc = "Contract address of this NFT. Usually `0xHEX_STRING`. For `category: \"ENS\"`, this can be omitted."
         )]
         address: Option<String>,
-    ) -> Result<Option<HoldRecord>> {
-        let client = make_http_client();
+    ) -> FieldResult<Option<HoldRecord>> {
+        let store = store();
         let contract_address = address
             .or(category.default_contract_address())
             .ok_or(Error::GraphQLError("Contract address is required.".into()))?;
         let target = Target::NFT(chain, category, contract_address.clone(), id.clone());
-        match Hold::find_by_id_chain_address(&client, &id, &chain, &contract_address).await? {
+        match store.find_hold_nft(&id, &chain, &contract_address).await? {
             Some(hold) => {
                 let v_id = hold.from_id.clone();
                 if hold.is_outdated() {
-                    tokio::spawn(async move {
-                        // Delete and Refetch in the background
-                        sleep(Duration::from_secs(10)).await;
-                        delete_graph_inner_connection(&client, v_id).await?;
-                        fetch_all(vec![target], Some(3)).await?;
-                        Ok::<_, Error>(())
-                    });
+                    // Delete and Refetch in the background
+                    enqueue_or_warn(JobKind::Refetch { v_id, target }, Duration::from_secs(10))
+                        .await;
                 }
                 Ok(Some(hold))
             }
 
             None => {
                 let _ = fetch_all(vec![target], Some(3)).await;
-                Hold::find_by_id_chain_address(&client, &id, &chain, &contract_address).await
+                Ok(store.find_hold_nft(&id, &chain, &contract_address).await?)
             }
         }
     }
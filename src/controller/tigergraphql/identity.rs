@@ -1,20 +1,25 @@
+use super::{
+    limit::{fetch_cost, traversal_cost},
+    page::paginate,
+};
 use crate::{
-    error::{Error, Result},
+    error::{Error, FieldResult, Result},
+    jobs::{enqueue_or_warn, JobKind},
+    storage::store,
     tigergraph::{
-        delete_graph_inner_connection,
         edge::{resolve::ResolveReverse, EdgeUnion, HoldRecord},
         vertex::{
             ExpandIdentityRecord, IdentityGraph, IdentityRecord, IdentityWithSource, OwnerLoadFn,
+            RankLoadFn,
         },
     },
-    upstream::{fetch_all, Chain, ContractCategory, DataSource, Platform, Target},
-    util::make_http_client,
+    upstream::{fetch_all, Chain, ContractCategory, DataSource, Platform, ProofLevel, Target},
 };
 
-use async_graphql::{Context, Object};
+use async_graphql::{connection::Connection, Context, Object};
 use dataloader::non_cached::Loader;
 use strum::IntoEnumIterator;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 use tracing::{event, Level};
 use uuid::Uuid;
 
@@ -37,6 +42,36 @@ pub enum DataStatus {
     Fetching,
 }
 
+/// Where to start fetching `platform, identity`. ENS domains are fetched as NFTs.
+pub(super) fn fetch_target(platform: Platform, identity: &str) -> Target {
+    match platform {
+        Platform::ENS => Target::NFT(
+            Chain::Ethereum,
+            ContractCategory::ENS,
+            ContractCategory::ENS.default_contract_address().unwrap(),
+            identity.to_string(),
+        ),
+        _ => Target::Identity(platform, identity.to_string()),
+    }
+}
+
+/// `category` filter of NFTs, given in lowercase or not.
+pub(super) fn parse_categories(
+    category: Option<Vec<String>>,
+) -> Result<Option<Vec<ContractCategory>>> {
+    category
+        .map(|v| {
+            v.into_iter()
+                .map(|s| {
+                    s.to_lowercase()
+                        .parse::<ContractCategory>()
+                        .map_err(Error::from)
+                })
+                .collect::<Result<Vec<ContractCategory>>>()
+        })
+        .transpose()
+}
+
 #[Object]
 impl IdentityWithSource {
     async fn sources(&self) -> Vec<DataSource> {
@@ -141,6 +176,7 @@ impl IdentityRecord {
     }
 
     /// Neighbor identity from current. Flattened.
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
     async fn neighbor(
         &self,
         _ctx: &Context<'_>,
@@ -156,23 +192,74 @@ impl IdentityRecord {
         When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true."
         )]
         reverse: Option<bool>,
-    ) -> Result<Vec<IdentityWithSource>> {
-        let client = make_http_client();
-        self.neighbors(&client, depth.unwrap_or(1), reverse).await
+        #[graphql(desc = "Proofs below this level are not walked through. All levels if omitted")]
+        min_level: Option<ProofLevel>,
+    ) -> FieldResult<Vec<IdentityWithSource>> {
+        Ok(store()
+            .neighbors(self, depth.unwrap_or(1), reverse, min_level)
+            .await?)
+    }
+
+    /// `neighbor`, ordered by id and paged by `first` / `after` or `last` / `before`.
+    #[allow(clippy::too_many_arguments)]
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
+    async fn neighbor_connection(
+        &self,
+        #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
+        #[graphql(
+            desc = "This reverse flag can be used as a filtering for Identity which type is domain system .
+        If `reverse=None` if omitted, there is no need to filter anything.
+        When `reverse=true`, just return `primary domain` related identities.
+        When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true."
+        )]
+        reverse: Option<bool>,
+        #[graphql(desc = "Proofs below this level are not walked through. All levels if omitted")]
+        min_level: Option<ProofLevel>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, IdentityWithSource>> {
+        paginate(after, before, first, last, |skip, limit| async move {
+            store()
+                .neighbors_page(self, depth.unwrap_or(1), reverse, min_level, limit, skip)
+                .await
+        })
+        .await
     }
 
     /// Neighbor identity from current. The entire topology can be restored by return records.
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
     async fn neighbor_with_traversal(
         &self,
         _ctx: &Context<'_>,
         #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
-    ) -> Result<Vec<EdgeUnion>> {
-        let client = make_http_client();
-        self.neighbors_with_traversal(&client, depth.unwrap_or(1))
-            .await
+    ) -> FieldResult<Vec<EdgeUnion>> {
+        Ok(store()
+            .neighbors_with_traversal(self, depth.unwrap_or(1))
+            .await?)
+    }
+
+    /// `neighborWithTraversal`, ordered by edge type, from and to, and paged by `first` / `after` or `last` / `before`.
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
+    async fn neighbor_with_traversal_connection(
+        &self,
+        #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, EdgeUnion>> {
+        paginate(after, before, first, last, |skip, limit| async move {
+            store()
+                .neighbors_with_traversal_page(self, depth.unwrap_or(1), limit, skip)
+                .await
+        })
+        .await
     }
 
     /// Identity graph from current.
+    #[graphql(complexity = "fetch_cost(1) + child_complexity")]
     async fn identity_graph(
         &self,
         _ctx: &Context<'_>,
@@ -183,26 +270,17 @@ impl IdentityRecord {
     When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true."
         )]
         reverse: Option<bool>,
-    ) -> Result<Option<IdentityGraph>> {
-        let client = make_http_client();
-        match IdentityGraph::find_graph_by_platform_identity(
-            &client,
-            &self.platform,
-            &self.identity,
-            reverse,
-        )
-        .await?
+        #[graphql(
+            desc = "Proofs below this level and identities only connected by them are left out. All levels if omitted"
+        )]
+        min_level: Option<ProofLevel>,
+    ) -> FieldResult<Option<IdentityGraph>> {
+        match store()
+            .find_identity_graph(&self.platform, &self.identity, reverse, min_level)
+            .await?
         {
             None => {
-                let target = match self.platform {
-                    Platform::ENS => Target::NFT(
-                        Chain::Ethereum,
-                        ContractCategory::ENS,
-                        ContractCategory::ENS.default_contract_address().unwrap(),
-                        self.identity.clone(),
-                    ),
-                    _ => Target::Identity(self.platform.clone(), self.identity.clone()),
-                };
+                let target = fetch_target(self.platform, &self.identity);
                 let fetch_result = fetch_all(vec![target], Some(3)).await;
                 if fetch_result.is_err() {
                     event!(
@@ -213,22 +291,17 @@ impl IdentityRecord {
                         "Failed to fetch_all"
                     );
                 }
-                Ok(IdentityGraph::find_graph_by_platform_identity(
-                    &client,
-                    &self.platform,
-                    &self.identity,
-                    reverse,
-                )
-                .await?)
+                Ok(store()
+                    .find_identity_graph(&self.platform, &self.identity, reverse, min_level)
+                    .await?)
             }
             Some(identity_graph) => Ok(Some(identity_graph)),
         }
     }
 
     /// Return primary domain names where they would typically only show addresses.
-    async fn reverse_records(&self, _ctx: &Context<'_>) -> Result<Vec<ResolveReverse>> {
-        let client = make_http_client();
-        self.resolve_reverse_domains(&client).await
+    async fn reverse_records(&self, _ctx: &Context<'_>) -> FieldResult<Vec<ResolveReverse>> {
+        Ok(store().resolve_reverse_domains(self).await?)
     }
 
     /// The expiry date for the domain, from either the registration, or the wrapped domain if PCC is burned
@@ -246,11 +319,19 @@ impl IdentityRecord {
         self.expired_at.map(|dt| dt.and_utc().timestamp())
     }
 
+    /// IdentityRank: how much this identity looks like a real person, in `[0, 1]`.
+    /// `null` until it is scored by the ranking job.
+    async fn identity_rank(&self, ctx: &Context<'_>) -> FieldResult<Option<f64>> {
+        let loader: &Loader<String, Option<f64>, RankLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        Ok(loader.load(self.v_id.clone()).await)
+    }
+
     /// reverse flag can be used as a filtering for Identity which type is domain system.
     /// If `reverse=None` if omitted, there is no need to filter anything.
     /// When `reverse=true`, just return `primary domain` related identities.
     /// When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true.
-    async fn reverse(&self) -> Result<Option<bool>> {
+    async fn reverse(&self) -> FieldResult<Option<bool>> {
         if !vec![
             Platform::Lens,
             Platform::Dotbit,
@@ -272,7 +353,7 @@ impl IdentityRecord {
     }
 
     /// there's only `platform: lens, dotbit, unstoppabledomains, farcaster, space_id` identity `ownedBy` is not null
-    async fn owned_by(&self, ctx: &Context<'_>) -> Result<Option<IdentityRecord>> {
+    async fn owned_by(&self, ctx: &Context<'_>) -> FieldResult<Option<IdentityRecord>> {
         if !vec![
             Platform::Lens,
             Platform::Dotbit,
@@ -297,7 +378,7 @@ impl IdentityRecord {
             Ok(value) => Ok(value),
             Err(e) => match e.kind() {
                 std::io::ErrorKind::NotFound => Ok(None), // Not found, so return Ok(None)
-                _ => Err(Error::GraphQLError(e.to_string())), // For all other errors, propagate the error
+                _ => Err(Error::GraphQLError(e.to_string()).into()), // For all other errors, propagate the error
             },
         }
     }
@@ -320,21 +401,32 @@ impl IdentityRecord {
             desc = "`offset` determines the starting position from which the records are retrieved in query. It defaults to 0."
         )]
         offset: Option<u16>,
-    ) -> Result<Vec<HoldRecord>> {
-        let client = make_http_client();
-        let category = category
-            .map(|v| {
-                v.into_iter()
-                    .map(|s| {
-                        s.to_lowercase()
-                            .parse::<ContractCategory>()
-                            .map_err(Error::from)
-                    })
-                    .collect::<Result<Vec<ContractCategory>>>()
-            })
-            .transpose()?;
-        self.nfts(&client, category, limit.unwrap_or(100), offset.unwrap_or(0))
-            .await
+    ) -> FieldResult<Vec<HoldRecord>> {
+        let category = parse_categories(category)?;
+        let limit = limit.unwrap_or(100) as usize;
+        Ok(store()
+            .nfts(self, category, limit, offset.unwrap_or(0) as usize * limit)
+            .await?)
+    }
+
+    /// NFTs owned by this identity, ordered by contract and token id.
+    /// Paged by `first` / `after` or `last` / `before`, 100 records if neither `first` nor `last` is given.
+    async fn nft_connection(
+        &self,
+        #[graphql(
+            desc = "Filter condition for ContractCategory. If not provided or empty array, all category NFTs will be returned."
+        )]
+        category: Option<Vec<String>>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, HoldRecord>> {
+        let category = parse_categories(category)?;
+        paginate(after, before, first, last, |skip, limit| async move {
+            store().nfts(self, category, limit, skip).await
+        })
+        .await
     }
 }
 
@@ -344,39 +436,32 @@ pub struct IdentityQuery;
 #[Object]
 impl IdentityQuery {
     /// Returns a list of all platforms supported by RelationService.
-    async fn available_platforms(&self) -> Result<Vec<Platform>> {
+    async fn available_platforms(&self) -> FieldResult<Vec<Platform>> {
         Ok(Platform::iter().collect())
     }
 
     /// Returns a list of all upstreams (data sources) supported by RelationService.
-    async fn available_upstreams(&self) -> Result<Vec<DataSource>> {
+    async fn available_upstreams(&self) -> FieldResult<Vec<DataSource>> {
         Ok(DataSource::iter().collect())
     }
 
     /// Query an `identity` by given `platform` and `identity`.
     #[tracing::instrument(level = "trace", skip(self, _ctx))]
+    #[graphql(complexity = "fetch_cost(1) + child_complexity")]
     async fn identity(
         &self,
         _ctx: &Context<'_>,
         #[graphql(desc = "Platform to query")] platform: String,
         #[graphql(desc = "Identity on target Platform")] identity: String,
-    ) -> Result<Option<ExpandIdentityRecord>> {
-        let client = make_http_client();
+    ) -> FieldResult<Option<ExpandIdentityRecord>> {
+        let store = store();
 
         let platform: Platform = platform.to_lowercase().parse()?;
 
-        let target = match platform {
-            Platform::ENS => Target::NFT(
-                Chain::Ethereum,
-                ContractCategory::ENS,
-                ContractCategory::ENS.default_contract_address().unwrap(),
-                identity.clone(),
-            ),
-            _ => Target::Identity(platform, identity.clone()),
-        };
+        let target = fetch_target(platform, &identity);
         // FIXME: Still kinda dirty. Should be in an background queue/worker-like shape.
 
-        match IdentityGraph::find_expand_identity(&client, &platform, &identity).await? {
+        match store.find_expand_identity(&platform, &identity).await? {
             None => {
                 let fetch_result = fetch_all(vec![target], Some(3)).await;
                 if fetch_result.is_err() {
@@ -388,7 +473,7 @@ impl IdentityQuery {
                         "Failed to fetch"
                     );
                 }
-                Ok(IdentityGraph::find_expand_identity(&client, &platform, &identity).await?)
+                Ok(store.find_expand_identity(&platform, &identity).await?)
             }
             Some(found) => {
                 if found.is_outdated() {
@@ -399,13 +484,9 @@ impl IdentityQuery {
                         "Outdated. Delete and Refetching."
                     );
                     let v_id = found.v_id.clone();
-                    tokio::spawn(async move {
-                        // Delete and Refetch in the background
-                        sleep(Duration::from_secs(10)).await;
-                        delete_graph_inner_connection(&client, v_id).await?;
-                        fetch_all(vec![target], Some(3)).await?;
-                        Ok::<_, Error>(())
-                    });
+                    // Delete and Refetch in the background
+                    enqueue_or_warn(JobKind::Refetch { v_id, target }, Duration::from_secs(10))
+                        .await;
                 }
                 Ok(Some(found))
             }
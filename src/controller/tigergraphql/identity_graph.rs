@@ -1,17 +1,26 @@
+use super::{
+    identity::parse_categories,
+    limit::{fetch_cost, traversal_cost},
+    page::{paginate, paginate_vec},
+};
 use crate::{
     controller::tigergraphql::identity::DataStatus,
-    error::{Error, Result},
+    error::{Error, FieldResult},
+    storage::store,
     tigergraph::{
-        edge::{EdgeUnion, HoldRecord},
+        edge::{EdgeUnion, FollowRecord, HoldRecord},
         vertex::{
             Address, ExpandIdentityRecord, IdentityConnection, IdentityGraph, IdentityRecord,
-            IdentityWithSource, OwnerLoadFn,
+            IdentityWithSource, OwnerLoadFn, RankLoadFn,
         },
     },
-    upstream::{fetch_all, Chain, ContractCategory, DataSource, Platform, Target},
-    util::{make_http_client, naive_now},
+    upstream::{fetch_all, Chain, ContractCategory, DataSource, Platform, ProofLevel, Target},
+    util::naive_now,
+};
+use async_graphql::{
+    connection::{Connection, ConnectionNameType, Edge, EdgeNameType, EmptyFields},
+    Context, Object, OutputType,
 };
-use async_graphql::{Context, Object};
 use chrono::Duration;
 use dataloader::non_cached::Loader;
 use tracing::{event, Level};
@@ -20,6 +29,32 @@ use uuid::Uuid;
 #[derive(Default)]
 pub struct IdentityGraphQuery;
 
+/// `IdentityConnection` is taken by edges of identity graphs, so their pages are named after it.
+pub struct IdentityConnectionPageName;
+
+impl ConnectionNameType for IdentityConnectionPageName {
+    fn type_name<T: OutputType>() -> String {
+        "IdentityConnectionPage".to_string()
+    }
+}
+
+pub struct IdentityConnectionEdgeName;
+
+impl EdgeNameType for IdentityConnectionEdgeName {
+    fn type_name<T: OutputType>() -> String {
+        "IdentityConnectionPageEdge".to_string()
+    }
+}
+
+type IdentityConnectionPage = Connection<
+    usize,
+    IdentityConnection,
+    EmptyFields,
+    EmptyFields,
+    IdentityConnectionPageName,
+    IdentityConnectionEdgeName,
+>;
+
 #[Object]
 impl IdentityGraph {
     /// Connecting a person’s different identifiers together, form an identity graph
@@ -36,6 +71,69 @@ impl IdentityGraph {
     async fn edges(&self) -> &Vec<IdentityConnection> {
         &self.edges
     }
+
+    /// `vertices`, ordered by id and paged by `first` / `after` or `last` / `before`.
+    async fn vertex_connection(
+        &self,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, ExpandIdentityRecord>> {
+        paginate_vec(self.vertices.clone(), after, before, first, last).await
+    }
+
+    /// `edges`, ordered by source, target and type, and paged by `first` / `after` or `last` / `before`.
+    async fn edge_connection(
+        &self,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<IdentityConnectionPage> {
+        let page = paginate_vec(self.edges.clone(), after, before, first, last).await?;
+        let mut connection = Connection::new(page.has_previous_page, page.has_next_page);
+        connection.edges = page
+            .edges
+            .into_iter()
+            .map(|edge| Edge::new(edge.cursor, edge.node))
+            .collect();
+        Ok(connection)
+    }
+
+    /// Who follows identities in this identity graph, across social platforms.
+    async fn followers(
+        &self,
+        #[graphql(
+            desc = "`limit` used to control the maximum number of records returned by query. It defaults to 100"
+        )]
+        limit: Option<u16>,
+        #[graphql(
+            desc = "`offset` determines the starting position from which the records are retrieved in query. It defaults to 0."
+        )]
+        offset: Option<u16>,
+    ) -> FieldResult<Vec<FollowRecord>> {
+        Ok(store()
+            .followers(&self.graph_id, limit.unwrap_or(100), offset.unwrap_or(0))
+            .await?)
+    }
+
+    /// Whom identities in this identity graph follow, across social platforms.
+    async fn following(
+        &self,
+        #[graphql(
+            desc = "`limit` used to control the maximum number of records returned by query. It defaults to 100"
+        )]
+        limit: Option<u16>,
+        #[graphql(
+            desc = "`offset` determines the starting position from which the records are retrieved in query. It defaults to 0."
+        )]
+        offset: Option<u16>,
+    ) -> FieldResult<Vec<FollowRecord>> {
+        Ok(store()
+            .following(&self.graph_id, limit.unwrap_or(100), offset.unwrap_or(0))
+            .await?)
+    }
 }
 
 #[Object]
@@ -154,11 +252,19 @@ impl ExpandIdentityRecord {
         self.expired_at.map(|dt| dt.and_utc().timestamp())
     }
 
+    /// IdentityRank: how much this identity looks like a real person, in `[0, 1]`.
+    /// `null` until it is scored by the ranking job.
+    async fn identity_rank(&self, ctx: &Context<'_>) -> FieldResult<Option<f64>> {
+        let loader: &Loader<String, Option<f64>, RankLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        Ok(loader.load(self.v_id.clone()).await)
+    }
+
     /// reverse flag can be used as a filtering for Identity which type is domain system.
     /// If `reverse=None` if omitted, there is no need to filter anything.
     /// When `reverse=true`, just return `primary domain` related identities.
     /// When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true.
-    async fn reverse(&self) -> Result<Option<bool>> {
+    async fn reverse(&self) -> FieldResult<Option<bool>> {
         if !vec![
             Platform::Lens,
             Platform::Dotbit,
@@ -180,7 +286,7 @@ impl ExpandIdentityRecord {
     }
 
     /// there's only `platform: lens, dotbit, unstoppabledomains, farcaster, space_id` identity `ownedBy` is not null
-    async fn owned_by(&self, ctx: &Context<'_>) -> Result<Option<IdentityRecord>> {
+    async fn owned_by(&self, ctx: &Context<'_>) -> FieldResult<Option<IdentityRecord>> {
         if !vec![
             Platform::Lens,
             Platform::Dotbit,
@@ -205,7 +311,7 @@ impl ExpandIdentityRecord {
             Ok(value) => Ok(value),
             Err(e) => match e.kind() {
                 std::io::ErrorKind::NotFound => Ok(None), // Not found, so return Ok(None)
-                _ => Err(Error::GraphQLError(e.to_string())), // For all other errors, propagate the error
+                _ => Err(Error::GraphQLError(e.to_string()).into()), // For all other errors, propagate the error
             },
         }
     }
@@ -229,25 +335,38 @@ impl ExpandIdentityRecord {
             desc = "`offset` determines the starting position from which the records are retrieved in query. It defaults to 0."
         )]
         offset: Option<u16>,
-    ) -> Result<Vec<HoldRecord>> {
-        let client = make_http_client();
-        let parsed_category: Option<Vec<ContractCategory>> = category
-            .map(|v| {
-                v.into_iter()
-                    .map(|s| {
-                        s.to_lowercase()
-                            .parse::<ContractCategory>()
-                            .map_err(Error::from)
-                    })
-                    .collect::<Result<Vec<ContractCategory>>>()
-            })
-            .transpose()?;
-        self.nfts(
-            &client,
-            parsed_category,
-            limit.unwrap_or(100),
-            offset.unwrap_or(0),
-        )
+    ) -> FieldResult<Vec<HoldRecord>> {
+        let parsed_category = parse_categories(category)?;
+        let limit = limit.unwrap_or(100) as usize;
+        Ok(store()
+            .nfts(
+                &self.record,
+                parsed_category,
+                limit,
+                offset.unwrap_or(0) as usize * limit,
+            )
+            .await?)
+    }
+
+    /// NFTs owned by this identity, ordered by contract and token id.
+    /// Paged by `first` / `after` or `last` / `before`, 100 records if neither `first` nor `last` is given.
+    async fn nft_connection(
+        &self,
+        #[graphql(
+            desc = "Filter condition for ContractCategory. If missing or empty, all category NFTs will be returned."
+        )]
+        category: Option<Vec<String>>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, HoldRecord>> {
+        let parsed_category = parse_categories(category)?;
+        paginate(after, before, first, last, |skip, limit| async move {
+            store()
+                .nfts(&self.record, parsed_category, limit, skip)
+                .await
+        })
         .await
     }
 
@@ -260,6 +379,7 @@ impl ExpandIdentityRecord {
     }
 
     /// Neighbor identity from current. Flattened.
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
     async fn neighbor(
         &self,
         _ctx: &Context<'_>,
@@ -275,23 +395,81 @@ impl ExpandIdentityRecord {
         When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true."
         )]
         reverse: Option<bool>,
-    ) -> Result<Vec<IdentityWithSource>> {
-        let client = make_http_client();
-        self.neighbors(&client, depth.unwrap_or(1), reverse).await
+        #[graphql(desc = "Proofs below this level are not walked through. All levels if omitted")]
+        min_level: Option<ProofLevel>,
+    ) -> FieldResult<Vec<IdentityWithSource>> {
+        Ok(store()
+            .neighbors(&self.record, depth.unwrap_or(1), reverse, min_level)
+            .await?)
+    }
+
+    /// `neighbor`, ordered by id and paged by `first` / `after` or `last` / `before`.
+    #[allow(clippy::too_many_arguments)]
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
+    async fn neighbor_connection(
+        &self,
+        #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
+        #[graphql(
+            desc = "This reverse flag can be used as a filtering for Identity which type is domain system .
+        If `reverse=None` if omitted, there is no need to filter anything.
+        When `reverse=true`, just return `primary domain` related identities.
+        When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true."
+        )]
+        reverse: Option<bool>,
+        #[graphql(desc = "Proofs below this level are not walked through. All levels if omitted")]
+        min_level: Option<ProofLevel>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, IdentityWithSource>> {
+        paginate(after, before, first, last, |skip, limit| async move {
+            store()
+                .neighbors_page(
+                    &self.record,
+                    depth.unwrap_or(1),
+                    reverse,
+                    min_level,
+                    limit,
+                    skip,
+                )
+                .await
+        })
+        .await
     }
 
     /// Neighbor identity from current. The entire topology can be restored by return records.
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
     async fn neighbor_with_traversal(
         &self,
         _ctx: &Context<'_>,
         #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
-    ) -> Result<Vec<EdgeUnion>> {
-        let client = make_http_client();
-        self.neighbors_with_traversal(&client, depth.unwrap_or(1))
-            .await
+    ) -> FieldResult<Vec<EdgeUnion>> {
+        Ok(store()
+            .neighbors_with_traversal(&self.record, depth.unwrap_or(1))
+            .await?)
+    }
+
+    /// `neighborWithTraversal`, ordered by edge type, from and to, and paged by `first` / `after` or `last` / `before`.
+    #[graphql(complexity = "traversal_cost(depth, child_complexity)")]
+    async fn neighbor_with_traversal_connection(
+        &self,
+        #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<usize, EdgeUnion>> {
+        paginate(after, before, first, last, |skip, limit| async move {
+            store()
+                .neighbors_with_traversal_page(&self.record, depth.unwrap_or(1), limit, skip)
+                .await
+        })
+        .await
     }
 
     /// Identity graph from current.
+    #[graphql(complexity = "fetch_cost(1) + child_complexity")]
     async fn identity_graph(
         &self,
         _ctx: &Context<'_>,
@@ -302,15 +480,15 @@ impl ExpandIdentityRecord {
     When `reverse=false`, Only `non-primary domain` will be returned, which is the inverse set of reverse=true."
         )]
         reverse: Option<bool>,
-    ) -> Result<Option<IdentityGraph>> {
-        let client = make_http_client();
-        match IdentityGraph::find_graph_by_platform_identity(
-            &client,
-            &self.platform,
-            &self.identity,
-            reverse,
-        )
-        .await?
+        #[graphql(
+            desc = "Proofs below this level and identities only connected by them are left out. All levels if omitted"
+        )]
+        min_level: Option<ProofLevel>,
+    ) -> FieldResult<Option<IdentityGraph>> {
+        let store = store();
+        match store
+            .find_identity_graph(&self.platform, &self.identity, reverse, min_level)
+            .await?
         {
             None => {
                 let target = match self.platform {
@@ -332,13 +510,9 @@ impl ExpandIdentityRecord {
                         "Failed to fetch_all"
                     );
                 }
-                Ok(IdentityGraph::find_graph_by_platform_identity(
-                    &client,
-                    &self.platform,
-                    &self.identity,
-                    reverse,
-                )
-                .await?)
+                Ok(store
+                    .find_identity_graph(&self.platform, &self.identity, reverse, min_level)
+                    .await?)
             }
             Some(identity_graph) => {
                 // filter out dataSource == "basenames" edges
@@ -413,13 +587,14 @@ impl ExpandIdentityRecord {
                                         "Failed to fetch_all"
                                     );
                                 }
-                                return Ok(IdentityGraph::find_graph_by_platform_identity(
-                                    &client,
-                                    &self.platform,
-                                    &self.identity,
-                                    reverse,
-                                )
-                                .await?);
+                                return Ok(store
+                                    .find_identity_graph(
+                                        &self.platform,
+                                        &self.identity,
+                                        reverse,
+                                        min_level,
+                                    )
+                                    .await?);
                             }
                         }
                     }
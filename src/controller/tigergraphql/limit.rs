@@ -0,0 +1,73 @@
+use crate::config::C;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation},
+    ErrorExtensionValues, ServerError, ValidationResult,
+};
+use std::sync::Arc;
+
+/// `extensions.code` of a query nested deeper than `max_depth`.
+pub const QUERY_TOO_DEEP: &str = "QUERY_TOO_DEEP";
+/// `extensions.code` of a query costing more than `max_complexity`.
+pub const QUERY_TOO_COMPLEX: &str = "QUERY_TOO_COMPLEX";
+
+/// Rejects queries deeper or costlier than allowed, before anything is resolved.
+/// Costs of fields are given by their `complexity`.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryLimit {
+    pub max_depth: usize,
+    pub max_complexity: usize,
+}
+
+impl ExtensionFactory for QueryLimit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(*self)
+    }
+}
+
+/// Cost of a traversal `depth` hops deep (1 if omitted), asking `child_complexity` of the neighbors.
+pub fn traversal_cost(depth: Option<u16>, child_complexity: usize) -> usize {
+    depth.unwrap_or(1).max(1) as usize * (C.graphql.depth_cost + child_complexity)
+}
+
+/// Cost of a field which may fetch `targets` from upstreams.
+pub fn fetch_cost(targets: usize) -> usize {
+    targets * C.graphql.fetch_cost
+}
+
+fn rejected(code: &str, message: String) -> Vec<ServerError> {
+    let mut error = ServerError::new(message, None);
+    let mut extensions = ErrorExtensionValues::default();
+    extensions.set("code", code);
+    error.extensions = Some(extensions);
+    vec![error]
+}
+
+#[async_trait::async_trait]
+impl Extension for QueryLimit {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        if result.depth > self.max_depth {
+            return Err(rejected(
+                QUERY_TOO_DEEP,
+                format!(
+                    "Query is nested {} levels deep, more than {} allowed.",
+                    result.depth, self.max_depth
+                ),
+            ));
+        }
+        if result.complexity > self.max_complexity {
+            return Err(rejected(
+                QUERY_TOO_COMPLEX,
+                format!(
+                    "Query costs {}, more than {} allowed. Ask for less depth or fewer fields.",
+                    result.complexity, self.max_complexity
+                ),
+            ));
+        }
+        Ok(result)
+    }
+}
@@ -1,15 +1,68 @@
 mod contract;
+mod curation;
+mod follow;
 mod hold;
 mod identity;
 mod identity_graph;
+mod limit;
+mod page;
+mod path;
 mod proof;
 mod relation;
+mod report;
 mod resolve;
+mod subscription;
+mod tests;
+mod timing;
+mod upstream;
 
-use self::{hold::HoldQuery, identity::IdentityQuery, proof::ProofQuery, resolve::ResolveQuery};
-use async_graphql::{MergedObject, Object};
+use self::{
+    curation::CurationMutation, hold::HoldQuery, identity::IdentityQuery, limit::QueryLimit,
+    path::PathQuery, proof::ProofQuery, relation::RelationQuery, report::FetchReport,
+    resolve::ResolveQuery, subscription::Subscription, timing::OperationTiming,
+    upstream::UpstreamQuery,
+};
+use crate::{
+    config::C,
+    storage::store,
+    tigergraph::vertex::{ContractLoadFn, IdentityLoadFn, OwnerLoadFn, RankLoadFn},
+};
+use async_graphql::{MergedObject, Object, Schema};
+use dataloader::non_cached::Loader;
 const API_VERSION: &str = "0.1";
 
+/// GraphQL schema served by both `standalone` and `lambda`.
+pub type RelationSchema = Schema<Query, Mutation, Subscription>;
+
+/// Build the schema, with dataloaders batching vertex lookups on the global store.
+pub fn schema() -> RelationSchema {
+    let contract_loader = Loader::new(ContractLoadFn { store: store() })
+        .with_max_batch_size(500)
+        .with_yield_count(100);
+    let identity_loader = Loader::new(IdentityLoadFn { store: store() })
+        .with_max_batch_size(500)
+        .with_yield_count(100);
+    let owner_loader = Loader::new(OwnerLoadFn { store: store() })
+        .with_max_batch_size(500)
+        .with_yield_count(100);
+    let rank_loader = Loader::new(RankLoadFn { store: store() })
+        .with_max_batch_size(500)
+        .with_yield_count(100);
+
+    Schema::build(Query::default(), Mutation::default(), Subscription)
+        .data(contract_loader)
+        .data(identity_loader)
+        .data(owner_loader)
+        .data(rank_loader)
+        .extension(QueryLimit {
+            max_depth: C.graphql.max_depth,
+            max_complexity: C.graphql.max_complexity,
+        })
+        .extension(FetchReport)
+        .extension(OperationTiming)
+        .finish()
+}
+
 /// Base struct of GraphQL query request.
 #[derive(MergedObject, Default)]
 pub struct Query(
@@ -18,8 +71,15 @@ pub struct Query(
     ResolveQuery,
     ProofQuery,
     HoldQuery,
+    RelationQuery,
+    PathQuery,
+    UpstreamQuery,
 );
 
+/// Base struct of GraphQL mutation request.
+#[derive(MergedObject, Default)]
+pub struct Mutation(CurationMutation);
+
 #[derive(Default)]
 pub struct GeneralQuery;
 
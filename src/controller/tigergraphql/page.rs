@@ -0,0 +1,93 @@
+use crate::error::{Error, FieldResult, Result};
+use async_graphql::{
+    connection::{Connection, CursorType, Edge},
+    OutputType,
+};
+use std::future::Future;
+
+/// Records in a page if neither `first` nor `last` is given.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// Most records `first` / `last` can ask for.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Relay-style page over records in a stable order.
+/// Cursors are positions in that order. `last` needs `before` or `first`,
+/// so no more than a page of records is ever fetched.
+/// `fetch(skip, limit)` gives at most `limit` records after the first `skip` ones.
+pub async fn paginate<T, F, R>(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    fetch: F,
+) -> FieldResult<Connection<usize, T>>
+where
+    T: OutputType,
+    F: FnOnce(usize, usize) -> R,
+    R: Future<Output = Result<Vec<T>>>,
+{
+    let after = after.as_deref().map(cursor).transpose()?;
+    let before = before.as_deref().map(cursor).transpose()?;
+    let first = first.map(|first| page_size("first", first)).transpose()?;
+    let last = last.map(|last| page_size("last", last)).transpose()?;
+    let first = match (first, last) {
+        (None, None) => Some(DEFAULT_PAGE_SIZE),
+        _ => first,
+    };
+    let mut start = after.map_or(0, |after| after + 1);
+    // `last` counts back from the end of the page, which is only known from `before` or `first`.
+    let mut end = match (before, first) {
+        (Some(before), _) => before.max(start),
+        (None, Some(first)) => start + first,
+        (None, None) => {
+            return Err(Error::ParamError("`last` needs `before` or `first`".to_string()).into())
+        }
+    };
+    if let Some(first) = first {
+        end = end.min(start + first);
+    }
+    if let Some(last) = last {
+        start = start.max(end.saturating_sub(last));
+    }
+    // One more record tells if there is a next page.
+    let mut records = fetch(start, end - start + 1).await?;
+    let has_next_page = records.len() > end - start;
+    records.truncate(end - start);
+    let mut connection = Connection::new(start > 0, has_next_page);
+    connection.edges.extend(
+        records
+            .into_iter()
+            .enumerate()
+            .map(|(idx, record)| Edge::new(start + idx, record)),
+    );
+    Ok(connection)
+}
+
+fn cursor(cursor: &str) -> Result<usize> {
+    usize::decode_cursor(cursor)
+        .map_err(|_| Error::ParamError(format!("invalid cursor `{}`", cursor)))
+}
+
+fn page_size(name: &str, size: i32) -> Result<usize> {
+    match usize::try_from(size) {
+        Ok(size) if size <= MAX_PAGE_SIZE => Ok(size),
+        _ => Err(Error::ParamError(format!(
+            "`{}` should be between 0 and {}",
+            name, MAX_PAGE_SIZE
+        ))),
+    }
+}
+
+/// `paginate` over records already in memory.
+pub async fn paginate_vec<T: OutputType>(
+    records: Vec<T>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> FieldResult<Connection<usize, T>> {
+    paginate(after, before, first, last, |skip, limit| async move {
+        Ok(records.into_iter().skip(skip).take(limit).collect())
+    })
+    .await
+}
@@ -0,0 +1,122 @@
+use super::{
+    limit::{fetch_cost, traversal_cost},
+    relation::find_or_fetch,
+};
+use crate::{
+    error::{Error, FieldResult},
+    storage::store,
+    tigergraph::{
+        edge::{IdentityPath, PathHop},
+        vertex::{IdentityLoadFn, IdentityRecord},
+    },
+    upstream::{DataSource, Platform, ProofLevel},
+};
+use async_graphql::{Context, InputObject, Object};
+use dataloader::non_cached::Loader;
+
+/// Hops walked at most if `maxDepth` is omitted.
+const DEFAULT_MAX_DEPTH: u16 = 5;
+
+/// One end of a path.
+#[derive(InputObject, Debug)]
+pub struct IdentityInput {
+    /// Platform of the identity.
+    platform: String,
+    /// Identity on `platform`.
+    identity: String,
+}
+
+#[Object]
+impl PathHop {
+    /// Type of the edge: `Proof_Forward`, `Hold_Identity`, `Resolve` or `Reverse_Resolve`.
+    async fn edge_type(&self) -> String {
+        self.e_type.clone()
+    }
+
+    /// Data source (upstream) which provides this connection info.
+    async fn source(&self) -> DataSource {
+        self.source
+    }
+
+    /// Confidence of this connection. Only given by `Proof`s.
+    async fn level(&self) -> Option<ProofLevel> {
+        self.level
+    }
+
+    /// When this connection is fetched by us RelationService.
+    async fn updated_at(&self) -> i64 {
+        self.updated_at.and_utc().timestamp()
+    }
+
+    /// Which `IdentityRecord` does this connection starts at.
+    /// It may be the end closer to `to` of the path.
+    async fn from(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
+        let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.from_id.clone()).await {
+            Some(value) => Ok(value),
+            None => Err(Error::GraphQLError("record from no found.".to_string()).into()),
+        }
+    }
+
+    /// Which `IdentityRecord` does this connection ends at.
+    async fn to(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
+        let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.to_id.clone()).await {
+            Some(value) => Ok(value),
+            None => Err(Error::GraphQLError("record to no found.".to_string()).into()),
+        }
+    }
+}
+
+#[Object]
+impl IdentityPath {
+    /// Hops from `from` to `to`, in walking order.
+    async fn hops(&self) -> Vec<PathHop> {
+        self.hops.clone()
+    }
+
+    /// Number of hops.
+    async fn length(&self) -> usize {
+        self.hops.len()
+    }
+}
+
+/// Query entrypoint for `IdentityPath`
+#[derive(Default)]
+pub struct PathQuery;
+
+#[Object]
+impl PathQuery {
+    /// Shortest chains of `Proof`, `Hold` and `Resolve` between two identities.
+    #[tracing::instrument(level = "trace", skip(self, _ctx))]
+    #[graphql(
+        complexity = "fetch_cost(2) + traversal_cost(Some(max_depth.unwrap_or(DEFAULT_MAX_DEPTH)), child_complexity)"
+    )]
+    async fn path(
+        &self,
+        _ctx: &Context<'_>,
+        #[graphql(desc = "Identity to start from")] from: IdentityInput,
+        #[graphql(desc = "Identity to end at")] to: IdentityInput,
+        #[graphql(desc = "Hops walked at most. 5 if omitted")] max_depth: Option<u16>,
+        #[graphql(desc = "Proofs below this level are not walked through. All levels if omitted")]
+        min_level: Option<ProofLevel>,
+    ) -> FieldResult<Vec<IdentityPath>> {
+        let from_platform: Platform = from.platform.to_lowercase().parse()?;
+        let to_platform: Platform = to.platform.to_lowercase().parse()?;
+        let source = find_or_fetch(from_platform, &from.identity).await?;
+        let target = find_or_fetch(to_platform, &to.identity).await?;
+        match (source, target) {
+            (Some(source), Some(target)) => Ok(store()
+                .path(
+                    &source,
+                    &target,
+                    max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+                    min_level,
+                )
+                .await?),
+            _ => Ok(vec![]),
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use crate::{
-    error::{Error, Result},
+    error::{Error, FieldResult},
     tigergraph::{
         edge::{Edge, ProofRecord},
         vertex::{IdentityLoadFn, IdentityRecord},
     },
-    upstream::{DataFetcher, DataSource},
+    upstream::{DataFetcher, DataSource, ProofLevel},
     util::make_http_client,
 };
 
@@ -26,6 +26,11 @@ impl ProofRecord {
         self.source
     }
 
+    /// Confidence of this connection, by how `source` verifies it.
+    async fn level(&self) -> ProofLevel {
+        self.level
+    }
+
     /// ID of this connection in upstream platform to locate (if any).
     async fn record_id(&self) -> Option<String> {
         self.record_id.clone()
@@ -48,22 +53,22 @@ impl ProofRecord {
     }
 
     /// Which `IdentityRecord` does this connection starts at.
-    async fn from(&self, ctx: &Context<'_>) -> Result<IdentityRecord> {
+    async fn from(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
         let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.from_id.clone()).await {
             Some(value) => Ok(value),
-            None => Err(Error::GraphQLError("record from no found.".to_string())),
+            None => Err(Error::GraphQLError("record from no found.".to_string()).into()),
         }
     }
 
     /// Which `IdentityRecord` does this connection ends at.
-    async fn to(&self, ctx: &Context<'_>) -> Result<IdentityRecord> {
+    async fn to(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
         let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
             ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
         match loader.load(self.to_id.clone()).await {
             Some(value) => Ok(value),
-            None => Err(Error::GraphQLError("record to no found.".to_string())),
+            None => Err(Error::GraphQLError("record to no found.".to_string()).into()),
         }
     }
 }
@@ -78,7 +83,7 @@ impl ProofQuery {
         &self,
         _ctx: &Context<'_>,
         #[graphql(desc = "UUID of this proof")] uuid: Option<String>,
-    ) -> Result<Option<ProofRecord>> {
+    ) -> FieldResult<Option<ProofRecord>> {
         let client = make_http_client();
 
         if uuid.is_none() {
@@ -91,7 +96,7 @@ impl ProofQuery {
     }
 
     /// Prefetch proofs which are prefetchable, e.g. SybilList.
-    async fn prefetch_proof(&self) -> Result<String> {
+    async fn prefetch_proof(&self) -> FieldResult<String> {
         tokio::spawn(async move {
             let _ = crate::upstream::prefetch().await;
         });
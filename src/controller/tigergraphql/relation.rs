@@ -1,44 +1,111 @@
-use std::vec;
-
+use super::{
+    identity::fetch_target,
+    limit::{fetch_cost, traversal_cost},
+};
 use crate::{
-    error::{Error, Result},
+    error::{Error, FieldResult},
+    jobs::{enqueue_or_warn, JobKind},
+    storage::store,
     tigergraph::{
-        delete_graph_inner_connection,
-        edge::{RelationUniqueTX, RelationUniqueTXRecord},
-        vertex::{Identity, IdentityRecord},
+        edge::RelationUniqueTXRecord,
+        vertex::{IdentityLoadFn, IdentityRecord},
     },
-    upstream::{fetch_all, Platform, Target},
-    util::make_http_client,
+    upstream::{fetch_all, Platform},
 };
 use async_graphql::{Context, Object};
-use tokio::time::{sleep, Duration};
+use dataloader::non_cached::Loader;
+use tokio::time::Duration;
 use tracing::{event, Level};
 
 #[Object]
 impl RelationUniqueTXRecord {
-    async fn count(&self) -> u32 {
-        self.count.clone()
+    /// Token contract transferred.
+    async fn contract_address(&self) -> String {
+        self.contract_address.clone()
+    }
+
+    /// Number of transactions from `from` to `to`.
+    async fn count(&self) -> i64 {
+        self.count
     }
 
-    async fn sum(&self) -> u32 {
+    /// Total value transferred from `from` to `to`, in the smallest unit of the token, as decimal.
+    async fn sum(&self) -> String {
         self.sum.clone()
     }
 
-    async fn max(&self) -> u32 {
+    /// Largest value transferred in one transaction, as decimal.
+    async fn max(&self) -> String {
         self.max.clone()
     }
 
-    async fn min(&self) -> u32 {
+    /// Smallest value transferred in one transaction, as decimal.
+    async fn min(&self) -> String {
         self.min.clone()
     }
 
-    async fn from(&self, _ctx: &Context<'_>) -> Result<IdentityRecord> {
-        todo!()
+    /// When this aggregation is updated by us.
+    /// Second-based unix timestamp.
+    async fn updated_at(&self) -> i64 {
+        self.updated_at.and_utc().timestamp()
+    }
+
+    /// Which `IdentityRecord` does this connection starts at.
+    async fn from(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
+        let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.from_id.clone()).await {
+            Some(value) => Ok(value),
+            None => Err(Error::GraphQLError("record from no found.".to_string()).into()),
+        }
     }
 
     /// Which `IdentityRecord` does this connection ends at.
-    async fn to(&self, _ctx: &Context<'_>) -> Result<IdentityRecord> {
-        todo!()
+    async fn to(&self, ctx: &Context<'_>) -> FieldResult<IdentityRecord> {
+        let loader: &Loader<String, Option<IdentityRecord>, IdentityLoadFn> =
+            ctx.data().map_err(|err| Error::GraphQLError(err.message))?;
+        match loader.load(self.to_id.clone()).await {
+            Some(value) => Ok(value),
+            None => Err(Error::GraphQLError("record to no found.".to_string()).into()),
+        }
+    }
+}
+
+/// Find `platform, identity` in store. Fetch it first if not found,
+/// or refetch it in the background if outdated.
+pub(super) async fn find_or_fetch(
+    platform: Platform,
+    identity: &str,
+) -> FieldResult<Option<IdentityRecord>> {
+    let target = fetch_target(platform, identity);
+    match store().find_identity(&platform, identity).await? {
+        None => {
+            let fetch_result = fetch_all(vec![target], Some(3)).await;
+            if let Err(err) = &fetch_result {
+                event!(
+                    Level::WARN,
+                    ?platform,
+                    identity,
+                    err = err.to_string(),
+                    "Failed to fetch"
+                );
+            }
+            Ok(store().find_identity(&platform, identity).await?)
+        }
+        Some(found) => {
+            if found.is_outdated() {
+                event!(
+                    Level::DEBUG,
+                    ?platform,
+                    identity,
+                    "Outdated. Delete and Refetching."
+                );
+                let v_id = found.v_id.clone();
+                // Delete and Refetch in the background
+                enqueue_or_warn(JobKind::Refetch { v_id, target }, Duration::from_secs(10)).await;
+            }
+            Ok(Some(found))
+        }
     }
 }
 
@@ -48,7 +115,9 @@ pub struct RelationQuery;
 
 #[Object]
 impl RelationQuery {
+    /// Transaction relations on the paths between two identities.
     #[tracing::instrument(level = "trace", skip(self, _ctx))]
+    #[graphql(complexity = "fetch_cost(2) + traversal_cost(depth, child_complexity)")]
     async fn relation(
         &self,
         _ctx: &Context<'_>,
@@ -57,156 +126,32 @@ impl RelationQuery {
         #[graphql(desc = "Target Platform")] target_platform: String,
         #[graphql(desc = "Target Identity")] target_identity: String,
         #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
-    ) -> Result<Vec<RelationUniqueTXRecord>> {
-        let client = make_http_client();
-        let source_platform: Platform = source_platform.parse()?;
-        let target_platform: Platform = target_platform.parse()?;
-        let source_fetch = Target::Identity(source_platform, source_identity.clone());
-        let target_fetch = Target::Identity(target_platform, target_identity.clone());
-        let source =
-            match Identity::find_by_platform_identity(&client, &source_platform, &source_identity)
-                .await?
-            {
-                None => {
-                    let fetch_result = fetch_all(vec![source_fetch], Some(3)).await;
-                    if fetch_result.is_err() {
-                        event!(
-                            Level::WARN,
-                            ?source_platform,
-                            source_identity,
-                            err = fetch_result.unwrap_err().to_string(),
-                            "Failed to fetch"
-                        );
-                    }
-                    Identity::find_by_platform_identity(&client, &source_platform, &source_identity)
-                        .await?
-                }
-                Some(found) => {
-                    if found.is_outdated() {
-                        event!(
-                            Level::DEBUG,
-                            ?source_platform,
-                            source_identity,
-                            "Outdated. Delete and Refetching."
-                        );
-                        let v_id = found.v_id.clone();
-                        tokio::spawn(async move {
-                            // Delete and Refetch in the background
-                            sleep(Duration::from_secs(10)).await;
-                            delete_graph_inner_connection(&client, v_id).await?;
-                            fetch_all(vec![source_fetch], Some(3)).await?;
-                            Ok::<_, Error>(())
-                        });
-                    }
-                    Some(found)
-                }
-            };
-
-        let client = make_http_client();
-        let target =
-            match Identity::find_by_platform_identity(&client, &target_platform, &target_identity)
-                .await?
-            {
-                None => {
-                    let fetch_result = fetch_all(vec![target_fetch], Some(3)).await;
-                    if fetch_result.is_err() {
-                        event!(
-                            Level::WARN,
-                            ?target_platform,
-                            target_identity,
-                            err = fetch_result.unwrap_err().to_string(),
-                            "Failed to fetch"
-                        );
-                    }
-                    Identity::find_by_platform_identity(&client, &target_platform, &target_identity)
-                        .await?
-                }
-                Some(found) => {
-                    if found.is_outdated() {
-                        event!(
-                            Level::DEBUG,
-                            ?target_platform,
-                            target_identity,
-                            "Outdated. Delete and Refetching."
-                        );
-                        let v_id = found.v_id.clone();
-                        tokio::spawn(async move {
-                            // Delete and Refetch in the background
-                            sleep(Duration::from_secs(10)).await;
-                            delete_graph_inner_connection(&client, v_id).await?;
-                            fetch_all(vec![target_fetch], Some(3)).await?;
-                            Ok::<_, Error>(())
-                        });
-                    }
-                    Some(found)
-                }
-            };
-        if source.is_none() || target.is_none() {
-            return Ok(vec![]);
+    ) -> FieldResult<Vec<RelationUniqueTXRecord>> {
+        let source_platform: Platform = source_platform.to_lowercase().parse()?;
+        let target_platform: Platform = target_platform.to_lowercase().parse()?;
+        let source = find_or_fetch(source_platform, &source_identity).await?;
+        let target = find_or_fetch(target_platform, &target_identity).await?;
+        match (source, target) {
+            (Some(source), Some(target)) => Ok(store()
+                .relation(&source, &target, depth.unwrap_or(1))
+                .await?),
+            _ => Ok(vec![]),
         }
-        let client = make_http_client();
-        let relation = RelationUniqueTX::relation(
-            &client,
-            &source.unwrap(),
-            &target.unwrap(),
-            depth.unwrap_or(1),
-        )
-        .await?;
-        Ok(relation)
     }
 
+    /// Transaction relations around an identity.
+    #[graphql(complexity = "fetch_cost(1) + traversal_cost(depth, child_complexity)")]
     async fn expand(
         &self,
         _ctx: &Context<'_>,
         #[graphql(desc = "Platform to query")] platform: String,
         #[graphql(desc = "Identity on target Platform")] identity: String,
         #[graphql(desc = "Depth of traversal. 1 if omitted")] depth: Option<u16>,
-    ) -> Result<Vec<RelationUniqueTXRecord>> {
-        let client = make_http_client();
-
-        let platform: Platform = platform.parse()?;
-        let target = Target::Identity(platform, identity.clone());
-        let source =
-            match Identity::find_by_platform_identity(&client, &platform, &identity).await? {
-                None => {
-                    let fetch_result = fetch_all(vec![target], Some(3)).await;
-                    if fetch_result.is_err() {
-                        event!(
-                            Level::WARN,
-                            ?platform,
-                            identity,
-                            err = fetch_result.unwrap_err().to_string(),
-                            "Failed to fetch"
-                        );
-                    }
-                    Identity::find_by_platform_identity(&client, &platform, &identity).await?
-                }
-                Some(found) => {
-                    if found.is_outdated() {
-                        event!(
-                            Level::DEBUG,
-                            ?platform,
-                            identity,
-                            "Outdated. Delete and Refetching."
-                        );
-                        let v_id = found.v_id.clone();
-                        tokio::spawn(async move {
-                            // Delete and Refetch in the background
-                            sleep(Duration::from_secs(10)).await;
-                            delete_graph_inner_connection(&client, v_id).await?;
-                            fetch_all(vec![target], Some(3)).await?;
-                            Ok::<_, Error>(())
-                        });
-                    }
-                    Some(found)
-                }
-            };
-        if source.is_none() {
-            return Ok(vec![]);
+    ) -> FieldResult<Vec<RelationUniqueTXRecord>> {
+        let platform: Platform = platform.to_lowercase().parse()?;
+        match find_or_fetch(platform, &identity).await? {
+            Some(source) => Ok(store().expand_relation(&source, depth.unwrap_or(1)).await?),
+            None => Ok(vec![]),
         }
-        let client = make_http_client();
-        let expand =
-            RelationUniqueTX::expand(&client, &source.unwrap(), depth.unwrap_or(1)).await?;
-        Ok(expand)
     }
 }
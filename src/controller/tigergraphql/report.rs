@@ -0,0 +1,37 @@
+use crate::upstream::reported;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextRequest},
+    to_value, Response,
+};
+use std::sync::Arc;
+
+/// Key in response `extensions` of upstream outcomes.
+pub const FETCH_REPORT: &str = "fetchReport";
+
+/// Tells in response `extensions.fetchReport` which upstreams succeeded, failed, timed out
+/// or were skipped for each target fetched while resolving, so partial data can be noticed.
+/// Left out if nothing was fetched.
+#[derive(Debug, Clone, Copy)]
+pub struct FetchReport;
+
+impl ExtensionFactory for FetchReport {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(*self)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for FetchReport {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        let (mut response, report) = reported(next.run(ctx)).await;
+        if !report.is_empty() {
+            match to_value(report) {
+                Ok(report) => {
+                    response.extensions.insert(FETCH_REPORT.to_string(), report);
+                }
+                Err(err) => tracing::warn!(%err, "Failed to serialize fetch report"),
+            }
+        }
+        response
+    }
+}
@@ -1,19 +1,21 @@
+use super::limit::fetch_cost;
 use crate::{
-    error::{Error, Result},
+    error::{Error, FieldResult},
+    jobs::{enqueue_or_warn, JobKind},
+    storage::store,
     tigergraph::{
-        delete_domain_collection, delete_graph_inner_connection,
-        edge::{resolve::ResolveReverse, AvailableDomain, Resolve, ResolveEdge},
-        vertex::{DomainCollection, IdentityRecord},
+        edge::{resolve::ResolveReverse, AvailableDomain, ResolveEdge},
+        vertex::IdentityRecord,
     },
     upstream::{
         fetch_all, fetch_domains, trim_name, Chain, ContractCategory, DataFetcher, DataSource,
         DomainNameSystem, DomainStatus, Platform, Target,
     },
-    util::{make_http_client, naive_now},
+    util::naive_now,
 };
 use async_graphql::{Context, Object};
 use strum::IntoEnumIterator;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 use tracing::{event, Level};
 use uuid::Uuid;
 
@@ -127,9 +129,9 @@ impl ResolveEdge {
     }
 
     /// `owner`: Return ENS name or .bit owned by wallet address.
-    async fn owner(&self) -> Result<IdentityRecord> {
+    async fn owner(&self) -> FieldResult<IdentityRecord> {
         match self.owner.clone() {
-            None => Err(Error::GraphQLError("owner no found.".to_string())),
+            None => Err(Error::GraphQLError("owner no found.".to_string()).into()),
             Some(owner) => Ok(owner),
         }
     }
@@ -155,11 +157,12 @@ pub struct ResolveQuery {}
 
 #[Object]
 impl ResolveQuery {
-    async fn available_name_system(&self) -> Result<Vec<DomainNameSystem>> {
+    async fn available_name_system(&self) -> FieldResult<Vec<DomainNameSystem>> {
         Ok(DomainNameSystem::iter().collect())
     }
 
     #[tracing::instrument(level = "trace", skip(self, _ctx))]
+    #[graphql(complexity = "fetch_cost(1) + child_complexity")]
     async fn domain_available_search(
         &self,
         _ctx: &Context<'_>,
@@ -167,11 +170,11 @@ impl ResolveQuery {
             desc = "name, providing name to query the registration of each domain system. See `availableNameSystem` for all domain name system supported by RelationService."
         )]
         name: String,
-    ) -> Result<Option<Vec<AvailableDomain>>> {
+    ) -> FieldResult<Option<Vec<AvailableDomain>>> {
         let process_name = trim_name(&name);
-        let client = make_http_client();
+        let store = store();
         // Check name if exists in storage
-        match DomainCollection::domain_available_search(&client, &process_name).await? {
+        match store.domain_available_search(&process_name).await? {
             None => {
                 let fetch_result = fetch_domains(&process_name).await;
                 if fetch_result.is_err() {
@@ -182,7 +185,7 @@ impl ResolveQuery {
                         "Failed to fetch_domains"
                     );
                 }
-                match DomainCollection::domain_available_search(&client, &process_name).await? {
+                match store.domain_available_search(&process_name).await? {
                     None => Ok(None),
                     Some(result) => Ok(Some(result.domains)),
                 }
@@ -208,11 +211,9 @@ impl ResolveQuery {
                             process_name,
                             "Outdated. Delete and Refetching all available domains."
                         );
-                        delete_domain_collection(&client, &process_name).await?;
+                        store.delete_domain_collection(&process_name).await?;
                         fetch_domains(&name).await?;
-                        match DomainCollection::domain_available_search(&client, &process_name)
-                            .await?
-                        {
+                        match store.domain_available_search(&process_name).await? {
                             None => return Ok(None),
                             Some(result) => return Ok(Some(result.domains)),
                         }
@@ -224,14 +225,9 @@ impl ResolveQuery {
                             process_name,
                             "Outdated. Delete and Refetching all available domains."
                         );
-                        let client_clone = client.clone();
-                        tokio::spawn(async move {
-                            // Delete and Refetch in the background
-                            sleep(Duration::from_secs(10)).await;
-                            delete_domain_collection(&client_clone, &process_name).await?;
-                            fetch_domains(&name).await?;
-                            Ok::<_, Error>(())
-                        });
+                        // Delete and Refetch in the background
+                        enqueue_or_warn(JobKind::RefetchDomains(name), Duration::from_secs(10))
+                            .await;
                     }
                 }
 
@@ -241,6 +237,7 @@ impl ResolveQuery {
     }
 
     #[tracing::instrument(level = "trace", skip(self, _ctx))]
+    #[graphql(complexity = "fetch_cost(1) + child_complexity")]
     async fn domain(
         &self,
         _ctx: &Context<'_>,
@@ -252,8 +249,8 @@ impl ResolveQuery {
             desc = "Name of domain. For example the name is (name: \"abc.eth\") or (name: \"abc.bit\") or (name: \"abc.bnb\")"
         )]
         name: String,
-    ) -> Result<Option<ResolveEdge>> {
-        let client = make_http_client();
+    ) -> FieldResult<Option<ResolveEdge>> {
+        let store = store();
         match domain_system {
             DomainNameSystem::ENS => {
                 let target = Target::NFT(
@@ -262,10 +259,10 @@ impl ResolveQuery {
                     ContractCategory::ENS.default_contract_address().unwrap(),
                     name.clone(),
                 );
-                match Resolve::find_by_name_system(&client, &name, &domain_system).await? {
+                match store.find_resolve(&name, &domain_system).await? {
                     None => {
                         let _ = fetch_all(vec![target], Some(3)).await;
-                        Resolve::find_by_name_system(&client, &name, &domain_system).await
+                        Ok(store.find_resolve(&name, &domain_system).await?)
                     }
                     Some(resolve) => {
                         if resolve.is_outdated() {
@@ -274,13 +271,12 @@ impl ResolveQuery {
                                 .owner
                                 .and_then(|f| Some(f.v_id.clone()))
                                 .unwrap_or("".to_string());
-                            tokio::spawn(async move {
-                                // Delete and Refetch in the background
-                                sleep(Duration::from_secs(10)).await;
-                                delete_graph_inner_connection(&client, v_id).await?;
-                                fetch_all(vec![target], Some(3)).await?;
-                                Ok::<_, Error>(())
-                            });
+                            // Delete and Refetch in the background
+                            enqueue_or_warn(
+                                JobKind::Refetch { v_id, target },
+                                Duration::from_secs(10),
+                            )
+                            .await;
                         }
                         Ok(Some(resolve))
                     }
@@ -292,10 +288,10 @@ impl ResolveQuery {
             | DomainNameSystem::SpaceId => {
                 let platform = domain_system.into();
                 let target = Target::Identity(platform, name.clone());
-                match Resolve::find_by_name_system(&client, &name, &domain_system).await? {
+                match store.find_resolve(&name, &domain_system).await? {
                     None => {
                         let _ = fetch_all(vec![target], Some(3)).await;
-                        Resolve::find_by_name_system(&client, &name, &domain_system).await
+                        Ok(store.find_resolve(&name, &domain_system).await?)
                     }
                     Some(resolve) => {
                         if resolve.is_outdated() {
@@ -304,13 +300,12 @@ impl ResolveQuery {
                                 .owner
                                 .and_then(|f| Some(f.v_id.clone()))
                                 .unwrap_or("".to_string());
-                            tokio::spawn(async move {
-                                // Delete and Refetch in the background
-                                sleep(Duration::from_secs(10)).await;
-                                delete_graph_inner_connection(&client, v_id).await?;
-                                fetch_all(vec![target], Some(3)).await?;
-                                Ok::<_, Error>(())
-                            });
+                            // Delete and Refetch in the background
+                            enqueue_or_warn(
+                                JobKind::Refetch { v_id, target },
+                                Duration::from_secs(10),
+                            )
+                            .await;
                         }
                         Ok(Some(resolve))
                     }
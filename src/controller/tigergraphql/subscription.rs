@@ -0,0 +1,199 @@
+use super::{
+    identity::fetch_target,
+    limit::{fetch_cost, traversal_cost},
+};
+use crate::{
+    error::FieldResult,
+    storage::store,
+    tigergraph::{
+        vertex::{identity_graph::VERTEX_NAME as IDENTITIES_GRAPH, IdentityGraph},
+        EdgeWrapperEnum,
+    },
+    upstream::{fetch_all_with_progress, FetchRound, Platform},
+};
+
+use async_graphql::{SimpleObject, Subscription};
+use futures::{Stream, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::sync::{
+    atomic::{AtomicU16, Ordering},
+    Arc,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+/// Rounds fetched by `identityGraphUpdates` if `depth` is omitted.
+pub const DEFAULT_DEPTH: u16 = 3;
+/// Most rounds a subscriber can ask for. The rest is queued as background jobs.
+pub const MAX_DEPTH: u16 = 5;
+
+/// Rounds actually fetched for a requested `depth`.
+fn rounds(depth: Option<u16>) -> u16 {
+    depth.unwrap_or(DEFAULT_DEPTH).clamp(1, MAX_DEPTH)
+}
+
+/// A vertex found while fetching. Not upserted yet, so it has no graph nor `id`.
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredVertex {
+    /// Vertex type, e.g. `Identities`, `Contracts`.
+    pub v_type: String,
+    /// `platform,identity` of an identity, `chain,address` of a contract.
+    pub key: String,
+}
+
+/// An edge found while fetching, between two `DiscoveredVertex.key`s.
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredEdge {
+    /// Edge type, e.g. `Proof_Forward`, `Hold_Identity`.
+    pub e_type: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// What an `identityGraphUpdates` round discovered.
+#[derive(SimpleObject, Clone, Debug, Default)]
+pub struct IdentityGraphUpdate {
+    /// Fetch round starting from 1. The completion event repeats the last one.
+    pub round: u16,
+    /// Vertices not seen in previous rounds.
+    pub vertices: Vec<DiscoveredVertex>,
+    pub edges: Vec<DiscoveredEdge>,
+    /// `true` on the last event, sent after everything is saved.
+    /// The identity graph can be queried from then on.
+    pub completed: bool,
+    /// Why fetching stopped, on the completion event.
+    pub error: Option<String>,
+}
+
+impl IdentityGraphUpdate {
+    /// `IdentitiesGraph` placeholders are left out, as graph ids are only allocated on upsert.
+    pub(super) fn from_round(fetched: &FetchRound, seen: &mut HashSet<String>) -> Self {
+        let mut update = IdentityGraphUpdate {
+            round: fetched.round,
+            ..Default::default()
+        };
+        for edge in fetched.edges.iter() {
+            if matches!(edge, EdgeWrapperEnum::PartOfIdentitiesGraph(_)) {
+                continue;
+            }
+            for vertex in [edge.source(), edge.target()] {
+                if vertex.vertex_type() != IDENTITIES_GRAPH && seen.insert(vertex.primary_key()) {
+                    update.vertices.push(DiscoveredVertex {
+                        v_type: vertex.vertex_type(),
+                        key: vertex.primary_key(),
+                    });
+                }
+            }
+            update.edges.push(DiscoveredEdge {
+                e_type: edge.e_type().to_string(),
+                from: edge.source().primary_key(),
+                to: edge.target().primary_key(),
+            });
+        }
+        update
+    }
+
+    /// What is already stored, as round 0.
+    pub(super) fn from_graph(graph: &IdentityGraph) -> Self {
+        let keys: HashMap<&str, String> = graph
+            .vertices
+            .iter()
+            .map(|v| (v.v_id.as_str(), format!("{},{}", v.platform, v.identity)))
+            .collect();
+        let key = |v_id: &str| keys.get(v_id).cloned().unwrap_or_else(|| v_id.to_string());
+        IdentityGraphUpdate {
+            vertices: graph
+                .vertices
+                .iter()
+                .map(|v| DiscoveredVertex {
+                    v_type: v.v_type.clone(),
+                    key: key(&v.v_id),
+                })
+                .collect(),
+            edges: graph
+                .edges
+                .iter()
+                .map(|e| DiscoveredEdge {
+                    e_type: e.edge_type.clone(),
+                    from: key(&e.source),
+                    to: key(&e.target),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Aborts the fetch once the subscription stream is dropped.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[derive(Default)]
+pub struct Subscription;
+
+#[Subscription]
+impl Subscription {
+    /// Fetch from `platform, identity` for `depth` rounds, streaming what each round
+    /// discovers, then a `completed` event. If nothing was fetched, e.g. the target is
+    /// being fetched elsewhere, the stored identity graph is sent as round 0 first.
+    #[graphql(complexity = "fetch_cost(1) + traversal_cost(Some(rounds(depth)), child_complexity)")]
+    async fn identity_graph_updates(
+        &self,
+        #[graphql(desc = "Platform to query")] platform: String,
+        #[graphql(desc = "Identity on target Platform")] identity: String,
+        #[graphql(
+            desc = "Stop after this many rounds, 3 if omitted and 5 at most. The rest is queued as background jobs."
+        )]
+        depth: Option<u16>,
+    ) -> FieldResult<impl Stream<Item = IdentityGraphUpdate>> {
+        let platform: Platform = platform.to_lowercase().parse()?;
+        let target = fetch_target(platform, &identity);
+        let depth = rounds(depth);
+        let (progress, rounds) = mpsc::unbounded_channel();
+        let (done, completion) = tokio::sync::oneshot::channel();
+        let fetching = AbortOnDrop(tokio::spawn(async move {
+            let result = fetch_all_with_progress(vec![target], Some(depth), Some(progress)).await;
+            let _ = done.send(result.err().map(|err| err.to_string()));
+        }));
+
+        let mut seen: HashSet<String> = HashSet::new();
+        let last_round = Arc::new(AtomicU16::new(0));
+        let updates = UnboundedReceiverStream::new(rounds).map({
+            let last_round = last_round.clone();
+            move |fetched: FetchRound| {
+                last_round.store(fetched.round, Ordering::Relaxed);
+                IdentityGraphUpdate::from_round(&fetched, &mut seen)
+            }
+        });
+        let completed = futures::stream::once(async move {
+            let _fetching = fetching;
+            let error = completion.await.unwrap_or_default();
+            let round = last_round.load(Ordering::Relaxed);
+            let mut events = vec![];
+            if round == 0 {
+                match store()
+                    .find_identity_graph(&platform, &identity, None, None)
+                    .await
+                {
+                    Ok(Some(graph)) => events.push(IdentityGraphUpdate::from_graph(&graph)),
+                    Ok(None) => {}
+                    Err(err) => tracing::warn!(%err, "Failed to find stored identity graph"),
+                }
+            }
+            events.push(IdentityGraphUpdate {
+                round,
+                completed: true,
+                error,
+                ..Default::default()
+            });
+            futures::stream::iter(events)
+        })
+        .flatten();
+        Ok(updates.chain(completed))
+    }
+}
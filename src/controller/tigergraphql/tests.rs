@@ -0,0 +1,464 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        controller::tigergraphql::{
+            limit::{QUERY_TOO_COMPLEX, QUERY_TOO_DEEP},
+            report::FETCH_REPORT,
+            schema,
+            subscription::IdentityGraphUpdate,
+        },
+        lease::leases,
+        metrics::render,
+        rank::rank_all,
+        storage::store,
+        tigergraph::{
+            edge::{
+                Hold, HyperEdge, Proof, Wrapper, HOLD_CONTRACT, HYPER_EDGE, PROOF_EDGE,
+                PROOF_REVERSE_EDGE,
+            },
+            vertex::{Contract, IdentitiesGraph, Identity},
+            EdgeWrapperEnum,
+        },
+        upstream::{Chain, DataSource, FetchRound, Platform, Target},
+    };
+    use async_graphql::{Request, Value};
+    use futures::StreamExt;
+    use std::{collections::HashSet, time::Duration};
+    use uuid::Uuid;
+
+    fn identity(platform: Platform, name: &str) -> Identity {
+        Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn proof_round(round: u16, from: &Identity, to: &Identity) -> FetchRound {
+        let proof = Proof::default();
+        let hv = IdentitiesGraph::default();
+        FetchRound {
+            round,
+            edges: vec![
+                EdgeWrapperEnum::new_proof_forward(proof.wrapper(from, to, PROOF_EDGE)),
+                EdgeWrapperEnum::new_proof_backward(proof.wrapper(to, from, PROOF_REVERSE_EDGE)),
+                EdgeWrapperEnum::new_hyper_edge(HyperEdge {}.wrapper(&hv, from, HYPER_EDGE)),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_update_from_round() {
+        let eth = identity(Platform::Ethereum, "0xa");
+        let github = identity(Platform::Github, "alice");
+        let twitter = identity(Platform::Twitter, "alice");
+        let mut seen = HashSet::new();
+
+        let first = IdentityGraphUpdate::from_round(&proof_round(1, &eth, &github), &mut seen);
+        assert_eq!(first.round, 1);
+        let keys: Vec<&str> = first.vertices.iter().map(|v| v.key.as_str()).collect();
+        assert_eq!(keys, vec!["ethereum,0xa", "github,alice"]);
+        assert_eq!(first.edges.len(), 2);
+        assert_eq!(first.edges[0].e_type, "Proof_Forward");
+        assert_eq!(first.edges[1].from, "github,alice");
+
+        let second = IdentityGraphUpdate::from_round(&proof_round(2, &github, &twitter), &mut seen);
+        let keys: Vec<&str> = second.vertices.iter().map(|v| v.key.as_str()).collect();
+        assert_eq!(keys, vec!["twitter,alice"]);
+        assert!(!second.completed);
+    }
+
+    #[tokio::test]
+    async fn test_identity_graph_updates() {
+        let query = format!(
+            r#"subscription {{ identityGraphUpdates(platform: "github", identity: "{}", depth: 1) {{
+                round vertices {{ key }} completed error }} }}"#,
+            Uuid::new_v4().simple()
+        );
+        let events: Vec<_> = schema().execute_stream(query).collect().await;
+        let last = events.last().expect("completion event should be sent");
+        assert!(last.errors.is_empty(), "{:?}", last.errors);
+        let data = last.data.clone().into_json().unwrap();
+        assert_eq!(data["identityGraphUpdates"]["completed"], true);
+        assert_eq!(data["identityGraphUpdates"]["round"], 1);
+        assert!(events[..events.len() - 1].iter().all(|e| {
+            e.data.clone().into_json().unwrap()["identityGraphUpdates"]["completed"] == false
+        }));
+
+        let events: Vec<_> = schema()
+            .execute_stream(r#"subscription { identityGraphUpdates(platform: "nowhere", identity: "a") { completed } }"#)
+            .collect()
+            .await;
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_identity_graph_updates_held_elsewhere() {
+        let github = identity(Platform::Github, &Uuid::new_v4().simple().to_string());
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let mut edges = proof_round(1, &github, &eth).edges;
+        edges.push(EdgeWrapperEnum::new_hyper_edge(HyperEdge {}.wrapper(
+            &IdentitiesGraph::default(),
+            &eth,
+            HYPER_EDGE,
+        )));
+        store().batch_upsert(edges).await.unwrap();
+        let target = Target::Identity(Platform::Github, github.identity.clone());
+        leases()
+            .acquire(
+                &target.to_string(),
+                "elsewhere",
+                "elsewhere",
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        let query = format!(
+            r#"subscription {{ identityGraphUpdates(platform: "github", identity: "{}") {{
+                round vertices {{ key }} completed }} }}"#,
+            github.identity
+        );
+        let events: Vec<_> = schema().execute_stream(query).collect().await;
+        assert_eq!(events.len(), 2);
+        let stored = events[0].data.clone().into_json().unwrap();
+        assert_eq!(stored["identityGraphUpdates"]["round"], 0);
+        assert_eq!(stored["identityGraphUpdates"]["completed"], false);
+        let keys = stored["identityGraphUpdates"]["vertices"].to_string();
+        assert!(keys.contains(&format!("github,{}", github.identity)));
+        assert!(keys.contains(&format!("ethereum,{}", eth.identity)));
+        let last = events[1].data.clone().into_json().unwrap();
+        assert_eq!(last["identityGraphUpdates"]["completed"], true);
+    }
+
+    #[tokio::test]
+    async fn test_path() {
+        let github = identity(Platform::Github, &Uuid::new_v4().simple().to_string());
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let twitter = identity(Platform::Twitter, &Uuid::new_v4().simple().to_string());
+        let edges = [
+            proof_round(1, &github, &eth),
+            proof_round(1, &eth, &twitter),
+        ]
+        .into_iter()
+        .flat_map(|round| round.edges)
+        .collect();
+        store().batch_upsert(edges).await.unwrap();
+
+        let query = format!(
+            r#"{{ path(from: {{ platform: "github", identity: "{}" }}, to: {{ platform: "twitter", identity: "{}" }}) {{
+                length hops {{ edgeType source level updatedAt from {{ identity }} to {{ identity }} }} }} }}"#,
+            github.identity, twitter.identity
+        );
+        let resp = schema().execute(Request::new(query)).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let data = resp.data.into_json().unwrap();
+        let paths = data["path"].as_array().unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0]["length"], 2);
+        let hops = paths[0]["hops"].as_array().unwrap();
+        assert_eq!(hops[0]["edgeType"], "Proof_Forward");
+        assert_eq!(hops[0]["level"], "INSECURE");
+        assert_eq!(hops[0]["from"]["identity"], github.identity.as_str());
+        assert_eq!(hops[1]["to"]["identity"], twitter.identity.as_str());
+
+        let query = format!(
+            r#"{{ path(from: {{ platform: "github", identity: "{}" }}, to: {{ platform: "twitter", identity: "{}" }}, maxDepth: 1) {{ length }} }}"#,
+            github.identity, twitter.identity
+        );
+        let resp = schema().execute(Request::new(query)).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        assert_eq!(
+            resp.data.into_json().unwrap()["path"],
+            serde_json::json!([])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_identity_rank() {
+        let github = identity(Platform::Github, &Uuid::new_v4().simple().to_string());
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let mut edges = proof_round(1, &github, &eth).edges;
+        let hv = IdentitiesGraph::default();
+        edges.push(EdgeWrapperEnum::new_hyper_edge(
+            HyperEdge {}.wrapper(&hv, &eth, HYPER_EDGE),
+        ));
+        store().batch_upsert(edges).await.unwrap();
+        let query = format!(
+            r#"{{ identity(platform: "github", identity: "{}") {{ identityRank }} }}"#,
+            github.identity
+        );
+        let resp = schema().execute(Request::new(query.clone())).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        assert!(resp.data.into_json().unwrap()["identity"]["identityRank"].is_null());
+
+        assert!(rank_all(store().as_ref()).await.unwrap() > 0);
+        let resp = schema().execute(Request::new(query)).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let rank = resp.data.into_json().unwrap()["identity"]["identityRank"]
+            .as_f64()
+            .expect("identity should be ranked");
+        assert!(rank > 0.0 && rank <= 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_min_level() {
+        let twitter = identity(Platform::Twitter, &Uuid::new_v4().simple().to_string());
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let github = identity(Platform::Github, &Uuid::new_v4().simple().to_string());
+        let proof = Proof {
+            source: DataSource::NextID,
+            level: DataSource::NextID.proof_level(),
+            ..Default::default()
+        };
+        let hv = IdentitiesGraph::default();
+        let mut edges = vec![
+            EdgeWrapperEnum::new_proof_forward(proof.wrapper(&twitter, &eth, PROOF_EDGE)),
+            EdgeWrapperEnum::new_proof_backward(proof.wrapper(&eth, &twitter, PROOF_REVERSE_EDGE)),
+            EdgeWrapperEnum::new_hyper_edge(HyperEdge {}.wrapper(&hv, &eth, HYPER_EDGE)),
+        ];
+        edges.extend(proof_round(1, &twitter, &github).edges);
+        store().batch_upsert(edges).await.unwrap();
+
+        let query = format!(
+            r#"{{ identity(platform: "twitter", identity: "{}") {{
+                all: neighbor {{ identity {{ identity }} }}
+                confident: neighbor(minLevel: CONFIDENT) {{ identity {{ identity }} }}
+                identityGraph(minLevel: CONFIDENT) {{ vertices {{ identity }} }}
+                neighborWithTraversal {{ ... on ProofRecord {{ level }} }} }} }}"#,
+            twitter.identity
+        );
+        let resp = schema().execute(Request::new(query)).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let data = resp.data.into_json().unwrap();
+        assert_eq!(data["identity"]["all"].as_array().unwrap().len(), 2);
+        let confident = data["identity"]["confident"].as_array().unwrap();
+        assert_eq!(confident.len(), 1);
+        assert_eq!(confident[0]["identity"]["identity"], eth.identity.as_str());
+        let vertices = data["identity"]["identityGraph"]["vertices"]
+            .as_array()
+            .unwrap();
+        assert_eq!(vertices.len(), 2);
+        let levels: HashSet<&str> = data["identity"]["neighborWithTraversal"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|proof| proof["level"].as_str())
+            .collect();
+        assert_eq!(levels, HashSet::from(["VERY_CONFIDENT", "INSECURE"]));
+    }
+
+    #[tokio::test]
+    async fn test_connection() {
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let twitter = identity(Platform::Twitter, &Uuid::new_v4().simple().to_string());
+        let github = identity(Platform::Github, &Uuid::new_v4().simple().to_string());
+        let contract = Contract {
+            chain: Chain::Ethereum,
+            address: format!("0x{}", Uuid::new_v4().simple()),
+            ..Default::default()
+        };
+        let hv = IdentitiesGraph::default();
+        let mut edges = proof_round(1, &eth, &twitter).edges;
+        edges.extend(proof_round(1, &eth, &github).edges);
+        for member in [&twitter, &github] {
+            edges.push(EdgeWrapperEnum::new_hyper_edge(
+                HyperEdge {}.wrapper(&hv, member, HYPER_EDGE),
+            ));
+        }
+        for id in ["3", "1", "2"] {
+            let hold = Hold {
+                id: id.to_string(),
+                ..Default::default()
+            };
+            edges.push(EdgeWrapperEnum::new_hold_contract(hold.wrapper(
+                &eth,
+                &contract,
+                HOLD_CONTRACT,
+            )));
+        }
+        store().batch_upsert(edges).await.unwrap();
+
+        let query = format!(
+            r#"{{ identity(platform: "ethereum", identity: "{}") {{
+                head: nftConnection(first: 2) {{
+                    edges {{ cursor node {{ id }} }}
+                    pageInfo {{ hasPreviousPage hasNextPage endCursor }} }}
+                tail: nftConnection(after: "1") {{
+                    edges {{ node {{ id }} }}
+                    pageInfo {{ hasPreviousPage hasNextPage }} }}
+                latest: nftConnection(last: 1, before: "3") {{ edges {{ cursor node {{ id }} }} }}
+                neighborConnection(first: 1) {{
+                    edges {{ node {{ identity {{ id }} }} }}
+                    pageInfo {{ hasNextPage }} }}
+                identityGraph {{
+                    vertexConnection(first: 2, after: "0") {{
+                        edges {{ cursor }}
+                        pageInfo {{ hasPreviousPage hasNextPage }} }}
+                    edgeConnection(before: "1") {{ edges {{ node {{ edgeType }} }} }} }} }} }}"#,
+            eth.identity
+        );
+        let resp = schema().execute(Request::new(query)).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let data = resp.data.into_json().unwrap();
+        let identity = &data["identity"];
+
+        let ids = |page: &serde_json::Value| -> Vec<String> {
+            page["edges"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|edge| edge["node"]["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(ids(&identity["head"]), vec!["1", "2"]);
+        assert_eq!(identity["head"]["edges"][0]["cursor"], "0");
+        assert_eq!(identity["head"]["pageInfo"]["hasPreviousPage"], false);
+        assert_eq!(identity["head"]["pageInfo"]["hasNextPage"], true);
+        assert_eq!(identity["head"]["pageInfo"]["endCursor"], "1");
+        assert_eq!(ids(&identity["tail"]), vec!["3"]);
+        assert_eq!(identity["tail"]["pageInfo"]["hasPreviousPage"], true);
+        assert_eq!(identity["tail"]["pageInfo"]["hasNextPage"], false);
+        assert_eq!(ids(&identity["latest"]), vec!["3"]);
+        assert_eq!(identity["latest"]["edges"][0]["cursor"], "2");
+
+        let neighbors = &identity["neighborConnection"];
+        let first = neighbors["edges"][0]["node"]["identity"]["id"].clone();
+        assert_eq!(first, format!("github,{}", github.identity).as_str());
+        assert_eq!(neighbors["pageInfo"]["hasNextPage"], true);
+
+        let vertices = &identity["identityGraph"]["vertexConnection"];
+        assert_eq!(vertices["edges"].as_array().unwrap().len(), 2);
+        assert_eq!(vertices["edges"][0]["cursor"], "1");
+        assert_eq!(vertices["pageInfo"]["hasPreviousPage"], true);
+        assert_eq!(vertices["pageInfo"]["hasNextPage"], false);
+        let graph_edges = &identity["identityGraph"]["edgeConnection"]["edges"];
+        assert_eq!(graph_edges.as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_limit() {
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let twitter = identity(Platform::Twitter, &Uuid::new_v4().simple().to_string());
+        store()
+            .batch_upsert(proof_round(1, &eth, &twitter).edges)
+            .await
+            .unwrap();
+        let code = |query: String| async move {
+            let resp = schema().execute(Request::new(query)).await;
+            resp.errors
+                .first()
+                .and_then(|err| err.extensions.as_ref())
+                .and_then(|extensions| extensions.get("code").cloned())
+        };
+
+        let neighbor = |depth: u16| {
+            format!(
+                r#"{{ identity(platform: "ethereum", identity: "{}") {{
+                    neighbor(depth: {}) {{ identity {{ id }} }} }} }}"#,
+                eth.identity, depth
+            )
+        };
+        assert_eq!(code(neighbor(3)).await, None);
+        assert_eq!(
+            code(neighbor(200)).await,
+            Some(Value::from(QUERY_TOO_COMPLEX))
+        );
+
+        let mut nested = "id".to_string();
+        for _ in 0..8 {
+            nested = format!("identityGraph {{ vertices {{ {} }} }}", nested);
+        }
+        let deep = format!(
+            r#"{{ identity(platform: "ethereum", identity: "{}") {{ {} }} }}"#,
+            eth.identity, nested
+        );
+        assert_eq!(code(deep).await, Some(Value::from(QUERY_TOO_DEEP)));
+    }
+
+    #[tokio::test]
+    async fn test_error_code() {
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let twitter = identity(Platform::Twitter, &Uuid::new_v4().simple().to_string());
+        store()
+            .batch_upsert(proof_round(1, &eth, &twitter).edges)
+            .await
+            .unwrap();
+        let query = |first: u32| {
+            format!(
+                r#"{{ identity(platform: "ethereum", identity: "{}") {{
+                    neighborConnection(first: {}) {{ nodes {{ identity {{ id }} }} }} }} }}"#,
+                eth.identity, first
+            )
+        };
+
+        let resp = schema().execute(Request::new(query(10))).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        // Nothing was fetched from upstreams.
+        assert!(!resp.extensions.contains_key(FETCH_REPORT));
+
+        let resp = schema().execute(Request::new(query(5000))).await;
+        let extensions = resp.errors[0].extensions.as_ref().unwrap();
+        assert_eq!(extensions.get("code"), Some(&Value::from("PARAM_ERROR")));
+        assert_eq!(extensions.get("status"), Some(&Value::from(400)));
+
+        // `last` alone would have to read every record to find the end.
+        let last = format!(
+            r#"{{ identity(platform: "ethereum", identity: "{}") {{
+                neighborConnection(last: 1) {{ nodes {{ identity {{ id }} }} }} }} }}"#,
+            eth.identity
+        );
+        let resp = schema().execute(Request::new(last)).await;
+        let extensions = resp.errors[0].extensions.as_ref().unwrap();
+        assert_eq!(extensions.get("code"), Some(&Value::from("PARAM_ERROR")));
+    }
+
+    #[tokio::test]
+    async fn test_operation_timing_label() {
+        let eth = identity(
+            Platform::Ethereum,
+            &format!("0x{}", Uuid::new_v4().simple()),
+        );
+        let twitter = identity(Platform::Twitter, &Uuid::new_v4().simple().to_string());
+        store()
+            .batch_upsert(proof_round(1, &eth, &twitter).edges)
+            .await
+            .unwrap();
+        let name = format!("Op{}", Uuid::new_v4().simple());
+        let query = format!(
+            r#"query {} {{ identity(platform: "ethereum", identity: "{}") {{ uuid }} }}"#,
+            name, eth.identity
+        );
+        let resp = schema().execute(Request::new(query)).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+
+        let text = render().await.unwrap();
+        assert!(text.contains(
+            r#"relation_graphql_operation_duration_seconds_count{operation="query identity"}"#
+        ));
+        assert!(!text.contains(&name));
+    }
+}
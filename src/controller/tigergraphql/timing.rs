@@ -0,0 +1,95 @@
+use crate::metrics::observe_graphql;
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextExecute, NextParseQuery},
+    parser::types::{ExecutableDocument, OperationDefinition, Selection},
+    Response, ServerResult, Variables,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+/// Records how long each operation takes to execute, by operation type and
+/// root field, e.g. `query identity`. Operations selecting several root
+/// fields are recorded by type alone.
+/// The client-supplied operation name is never used as a label: it is
+/// unbounded and would grow the metric without limit.
+#[derive(Debug, Clone, Copy)]
+pub struct OperationTiming;
+
+impl ExtensionFactory for OperationTiming {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(Timing::default())
+    }
+}
+
+/// Per-request state: labels of the operations in the parsed document,
+/// by operation name.
+#[derive(Default)]
+struct Timing {
+    labels: Mutex<Vec<(Option<String>, String)>>,
+}
+
+/// Label of `operation`. Fields are only known to exist once the document
+/// is validated, so this is only used for executed operations.
+fn label(operation: &OperationDefinition) -> String {
+    let mut fields =
+        operation
+            .selection_set
+            .node
+            .items
+            .iter()
+            .map(|selection| match &selection.node {
+                Selection::Field(field) => Some(field.node.name.node.as_str()),
+                _ => None,
+            });
+    match (fields.next(), fields.next()) {
+        (Some(Some(field)), None) => format!("{} {}", operation.ty, field),
+        _ => operation.ty.to_string(),
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for Timing {
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        *self.labels.lock().unwrap() = document
+            .operations
+            .iter()
+            .map(|(name, operation)| (name.map(|name| name.to_string()), label(&operation.node)))
+            .collect();
+        Ok(document)
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let started = Instant::now();
+        let response = next.run(ctx, operation_name).await;
+        let label = {
+            let labels = self.labels.lock().unwrap();
+            match operation_name {
+                Some(name) => labels
+                    .iter()
+                    .find(|(operation, _)| operation.as_deref() == Some(name))
+                    .map(|(_, label)| label.clone()),
+                None if labels.len() == 1 => Some(labels[0].1.clone()),
+                None => None,
+            }
+        };
+        // Unknown operations fail before running and are not recorded.
+        if let Some(label) = label {
+            observe_graphql(&label, started.elapsed());
+        }
+        response
+    }
+}
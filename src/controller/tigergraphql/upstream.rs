@@ -0,0 +1,53 @@
+use crate::upstream::{breakers, registry, BreakerState, DataSource, UpstreamStatus};
+use async_graphql::{Object, SimpleObject};
+
+/// An upstream RelationService is fetching from.
+#[derive(SimpleObject)]
+pub struct AvailableUpstream {
+    /// Upstream name, e.g. `the_graph`.
+    name: String,
+    /// Records from this upstream are rate limited as this `DataSource`.
+    data_source: DataSource,
+    /// Used when fetching identities.
+    fetch: bool,
+    /// Used when searching domains.
+    domain_search: bool,
+    /// Higher ones are called first.
+    priority: i32,
+    timeout_seconds: u64,
+    /// Circuit breaker state. Upstreams in `open` state are skipped for now.
+    state: BreakerState,
+}
+
+/// Query entrypoint for upstream health.
+#[derive(Default)]
+pub struct UpstreamQuery;
+
+#[Object]
+impl UpstreamQuery {
+    /// Circuit breaker state of each upstream contacted since startup.
+    async fn upstream_status(&self) -> Vec<UpstreamStatus> {
+        breakers().statuses()
+    }
+
+    /// Enabled upstreams, ordered by priority.
+    async fn available_upstreams(&self) -> Vec<AvailableUpstream> {
+        let statuses = breakers().statuses();
+        registry()
+            .active()
+            .map(|upstream| AvailableUpstream {
+                name: upstream.name.to_string(),
+                data_source: upstream.source,
+                fetch: upstream.fetcher.is_some(),
+                domain_search: upstream.domain_search.is_some(),
+                priority: upstream.priority,
+                timeout_seconds: upstream.timeout.as_secs(),
+                state: statuses
+                    .iter()
+                    .find(|s| s.name == upstream.name)
+                    .map(|s| s.state)
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+}
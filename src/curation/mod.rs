@@ -0,0 +1,389 @@
+mod tests;
+
+use crate::{
+    config::C,
+    error::Error,
+    storage::store,
+    tigergraph::{
+        edge::{
+            Hold, HyperEdge, Proof, Resolve, Wrapper, HOLD_IDENTITY, HYPER_EDGE, PROOF_EDGE,
+            PROOF_REVERSE_EDGE, RESOLVE,
+        },
+        vertex::{IdentitiesGraph, Identity, IdentityGraph, Vertex},
+        EdgeList, EdgeWrapperEnum,
+    },
+    upstream::{DataFetcher, DataSource, DomainNameSystem, Platform, ProofLevel},
+    util::naive_now,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use strum_macros::{Display, EnumString};
+use tracing::info;
+use uuid::Uuid;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    operator TEXT NOT NULL,
+    action TEXT NOT NULL,
+    e_type TEXT NOT NULL,
+    from_id TEXT NOT NULL,
+    to_id TEXT NOT NULL,
+    note TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);";
+
+lazy_static! {
+    /// Audit log configured by `[curation]`.
+    static ref AUDIT: Arc<AuditLog> =
+        Arc::new(AuditLog::open(&C.curation.audit_path).expect("Failed to open audit log"));
+}
+
+/// Get the global curation audit log.
+pub fn audit_log() -> Arc<AuditLog> {
+    AUDIT.clone()
+}
+
+/// Someone listed in `[curation] operators`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operator {
+    pub name: String,
+}
+
+/// Find the operator of an `Authorization: Bearer <token>` header value.
+pub fn authenticate(authorization: Option<&str>) -> Option<Operator> {
+    authenticate_with(&C.curation.operators, authorization)
+}
+
+fn authenticate_with(
+    operators: &HashMap<String, String>,
+    authorization: Option<&str>,
+) -> Option<Operator> {
+    let token = authorization?.trim().strip_prefix("Bearer ")?.trim();
+    if token.is_empty() {
+        return None;
+    }
+    operators
+        .iter()
+        .find(|(_, expected)| constant_time_eq(expected.as_bytes(), token.as_bytes()))
+        .map(|(name, _)| Operator { name: name.clone() })
+}
+
+/// Compare without leaking where the first mismatch is.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Add,
+    Remove,
+}
+
+/// A manually curated link between two identities.
+#[derive(Debug, Clone)]
+pub enum Link {
+    /// `from` and `to` prove each other, as `Proof_Forward` and `Proof_Backward`.
+    Proof {
+        from: Identity,
+        to: Identity,
+        level: ProofLevel,
+    },
+    /// `from` holds `to`.
+    Hold { from: Identity, to: Identity },
+    /// Domain `from` resolves to `to`.
+    Resolve {
+        from: Identity,
+        to: Identity,
+        system: DomainNameSystem,
+    },
+}
+
+impl Link {
+    pub fn ends(&self) -> (&Identity, &Identity) {
+        match self {
+            Link::Proof { from, to, .. }
+            | Link::Hold { from, to }
+            | Link::Resolve { from, to, .. } => (from, to),
+        }
+    }
+
+    /// Edges of this link, all tagged `ManuallyAdded`.
+    pub fn edges(&self) -> EdgeList {
+        match self {
+            Link::Proof { from, to, level } => {
+                let proof = Proof {
+                    uuid: Uuid::new_v4(),
+                    source: DataSource::ManuallyAdded,
+                    level: *level,
+                    record_id: None,
+                    created_at: Some(naive_now()),
+                    updated_at: naive_now(),
+                    fetcher: DataFetcher::RelationService,
+                };
+                vec![
+                    EdgeWrapperEnum::new_proof_forward(proof.wrapper(from, to, PROOF_EDGE)),
+                    EdgeWrapperEnum::new_proof_backward(proof.wrapper(
+                        to,
+                        from,
+                        PROOF_REVERSE_EDGE,
+                    )),
+                ]
+            }
+            Link::Hold { from, to } => {
+                let hold = Hold {
+                    uuid: Uuid::new_v4(),
+                    source: DataSource::ManuallyAdded,
+                    transaction: None,
+                    id: to.identity.clone(),
+                    created_at: Some(naive_now()),
+                    updated_at: naive_now(),
+                    fetcher: DataFetcher::RelationService,
+                    expired_at: None,
+                };
+                vec![EdgeWrapperEnum::new_hold_identity(hold.wrapper(
+                    from,
+                    to,
+                    HOLD_IDENTITY,
+                ))]
+            }
+            Link::Resolve { from, to, system } => {
+                let resolve = Resolve {
+                    uuid: Uuid::new_v4(),
+                    source: DataSource::ManuallyAdded,
+                    system: *system,
+                    name: from.identity.clone(),
+                    fetcher: DataFetcher::RelationService,
+                    updated_at: naive_now(),
+                };
+                vec![EdgeWrapperEnum::new_resolve(
+                    resolve.wrapper(from, to, RESOLVE),
+                )]
+            }
+        }
+    }
+}
+
+/// Identity as stored, or a new one if not seen yet.
+pub async fn identity(platform: Platform, identity: &str) -> Result<Identity, Error> {
+    if identity.is_empty() {
+        return Err(Error::ParamMissing("identity".to_string()));
+    }
+    Ok(match store().find_identity(&platform, identity).await? {
+        Some(found) => found.attributes.clone(),
+        None => Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: identity.to_string(),
+            display_name: Some(identity.to_string()),
+            ..Default::default()
+        },
+    })
+}
+
+/// Upsert `link` through `batch_upsert`, so both ends join (or merge) an `IdentitiesGraph`.
+pub async fn add(operator: &Operator, link: &Link, note: &str) -> Result<(), Error> {
+    let note = check_note(note)?;
+    let (from, to) = link.ends();
+    let hv = IdentitiesGraph::default();
+    let mut edges = link.edges();
+    edges.push(EdgeWrapperEnum::new_hyper_edge(
+        HyperEdge {}.wrapper(&hv, from, HYPER_EDGE),
+    ));
+    edges.push(EdgeWrapperEnum::new_hyper_edge(
+        HyperEdge {}.wrapper(&hv, to, HYPER_EDGE),
+    ));
+    store().batch_upsert(edges).await?;
+    audit_log().record(operator, Action::Add, link, note)
+}
+
+/// Delete the `ManuallyAdded` edges of `link`. Edges of other sources are kept.
+/// If `to` is no longer connected to `from`, its side gets an `IdentitiesGraph` of its own.
+pub async fn remove(operator: &Operator, link: &Link, note: &str) -> Result<(), Error> {
+    let note = check_note(note)?;
+    store().delete_edges(link.edges()).await?;
+    split(link).await?;
+    audit_log().record(operator, Action::Remove, link, note)
+}
+
+/// Move identities reachable from `to` but not from `from` into a new `IdentitiesGraph`.
+async fn split(link: &Link) -> Result<(), Error> {
+    let (from, to) = link.ends();
+    let store = store();
+    let (kept, orphaned) = match (
+        store
+            .find_identity_graph(&from.platform, &from.identity, None, None)
+            .await?,
+        store
+            .find_identity_graph(&to.platform, &to.identity, None, None)
+            .await?,
+    ) {
+        (Some(kept), Some(orphaned)) if kept.graph_id == orphaned.graph_id => (kept, orphaned),
+        _ => return Ok(()),
+    };
+    if connected(&kept, &from.primary_key()).contains(&to.primary_key()) {
+        return Ok(());
+    }
+    let reached = connected(&orphaned, &to.primary_key());
+    let identities: Vec<Identity> = orphaned
+        .vertices
+        .iter()
+        .filter(|v| reached.contains(&v.v_id))
+        .map(|v| v.attributes.clone())
+        .collect();
+    if identities.is_empty() {
+        return Ok(());
+    }
+    let graph_id = store
+        .split_identity_graph(&orphaned.graph_id, identities)
+        .await?;
+    info!(
+        from = from.primary_key(),
+        to = to.primary_key(),
+        graph_id,
+        "Identity graph split"
+    );
+    Ok(())
+}
+
+/// v_ids of `graph` reachable from `v_id` through its edges, in either direction.
+fn connected(graph: &IdentityGraph, v_id: &str) -> HashSet<String> {
+    let mut reached = HashSet::from([v_id.to_string()]);
+    let mut queue = vec![v_id.to_string()];
+    while let Some(current) = queue.pop() {
+        for edge in graph.edges.iter() {
+            let next = if edge.source == current {
+                &edge.target
+            } else if edge.target == current {
+                &edge.source
+            } else {
+                continue;
+            };
+            if reached.insert(next.clone()) {
+                queue.push(next.clone());
+            }
+        }
+    }
+    reached
+}
+
+fn check_note(note: &str) -> Result<&str, Error> {
+    match note.trim() {
+        "" => Err(Error::ParamMissing("note".to_string())),
+        note => Ok(note),
+    }
+}
+
+/// One curated edge recorded in audit log.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub operator: String,
+    pub action: Action,
+    pub e_type: String,
+    /// `primary_key` of the source vertex.
+    pub from: String,
+    /// `primary_key` of the target vertex.
+    pub to: String,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Who curated which edge and why, persisted in SQLite.
+pub struct AuditLog {
+    conn: Mutex<Connection>,
+}
+
+impl AuditLog {
+    /// Open (or create) an audit log file at `path`.
+    pub fn open(path: &str) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// A throwaway audit log living in memory. For tests.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record every edge of `link`.
+    pub fn record(
+        &self,
+        operator: &Operator,
+        action: Action,
+        link: &Link,
+        note: &str,
+    ) -> Result<(), Error> {
+        let now = Utc::now().timestamp_millis();
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        for edge in link.edges() {
+            tx.execute(
+                "INSERT INTO audit (operator, action, e_type, from_id, to_id, note, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    operator.name,
+                    action.to_string(),
+                    edge.e_type(),
+                    edge.source().primary_key(),
+                    edge.target().primary_key(),
+                    note,
+                    now,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        info!(operator = operator.name, %action, note, "Curated {:?}", link.ends());
+        Ok(())
+    }
+
+    /// All recorded curations, oldest first.
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, Error> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, operator, action, e_type, from_id, to_id, note, created_at
+             FROM audit ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, i64>(7)?,
+            ))
+        })?;
+        let mut entries = vec![];
+        for row in rows {
+            let (id, operator, action, e_type, from, to, note, created_at) = row?;
+            entries.push(AuditEntry {
+                id,
+                operator,
+                action: action.parse()?,
+                e_type,
+                from,
+                to,
+                note,
+                created_at: DateTime::from_timestamp_millis(created_at)
+                    .unwrap_or_default()
+                    .naive_utc(),
+            });
+        }
+        Ok(entries)
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        controller::tigergraphql::schema,
+        curation::{
+            self, audit_log, authenticate, authenticate_with, identity, Action, AuditLog, Link,
+            Operator,
+        },
+        error::Error,
+        storage::store,
+        upstream::{DataSource, DomainNameSystem, Platform, ProofLevel},
+    };
+    use async_graphql::Request;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn tester() -> Operator {
+        Operator {
+            name: "tester".to_string(),
+        }
+    }
+
+    /// Name unique to this run, as the TigerGraph stand-in is shared by all tests.
+    fn unique(prefix: &str) -> String {
+        format!("{}{}", prefix, Uuid::new_v4().simple())
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let operators = HashMap::from([("alice".to_string(), "s3cret".to_string())]);
+        assert_eq!(
+            authenticate_with(&operators, Some("Bearer s3cret")),
+            Some(Operator {
+                name: "alice".to_string()
+            })
+        );
+        assert_eq!(authenticate_with(&operators, Some("Bearer s3cre")), None);
+        assert_eq!(authenticate_with(&operators, Some("s3cret")), None);
+        assert_eq!(authenticate_with(&operators, Some("Bearer ")), None);
+        assert_eq!(authenticate_with(&operators, None), None);
+        assert_eq!(authenticate_with(&HashMap::new(), Some("Bearer ")), None);
+        // `[curation]` in `config/testing.toml`.
+        assert_eq!(authenticate(Some("Bearer test-token")), Some(tester()));
+    }
+
+    #[tokio::test]
+    async fn test_audit_log() -> Result<(), Error> {
+        let audit = AuditLog::open_in_memory()?;
+        let link = Link::Proof {
+            from: identity(Platform::Ethereum, "0xa").await?,
+            to: identity(Platform::Github, "alice").await?,
+            level: ProofLevel::VeryConfident,
+        };
+        audit.record(&tester(), Action::Add, &link, "met in person")?;
+        let entries = audit.entries()?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, Action::Add);
+        assert_eq!(entries[0].operator, "tester");
+        assert_eq!(entries[0].e_type, "Proof_Forward");
+        assert_eq!(entries[0].from, "ethereum,0xa");
+        assert_eq!(entries[1].e_type, "Proof_Backward");
+        assert_eq!(entries[1].from, "github,alice");
+        assert_eq!(entries[1].note, "met in person");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_add_remove() -> Result<(), Error> {
+        let eth = unique("0x");
+        let lens = format!("{}.lens", unique("a"));
+        let hold = Link::Hold {
+            from: identity(Platform::Ethereum, &eth).await?,
+            to: identity(Platform::Lens, &lens).await?,
+        };
+        let resolve = Link::Resolve {
+            from: identity(Platform::Lens, &lens).await?,
+            to: identity(Platform::Ethereum, &eth).await?,
+            system: DomainNameSystem::Lens,
+        };
+        assert!(curation::add(&tester(), &hold, " ").await.is_err());
+
+        curation::add(&tester(), &hold, "ticket 41").await?;
+        curation::add(&tester(), &resolve, "ticket 42").await?;
+        let found = store()
+            .find_resolve(&lens, &DomainNameSystem::Lens)
+            .await?
+            .expect("domain should be added");
+        assert_eq!(found.source, DataSource::ManuallyAdded);
+        assert!(found.resolved.is_some_and(|r| r.identity == eth));
+        let graph = store()
+            .find_identity_graph(&Platform::Ethereum, &eth, None, None)
+            .await?
+            .expect("both ends should join a graph");
+        assert_eq!(graph.vertices.len(), 2);
+
+        curation::remove(&tester(), &resolve, "ticket 43").await?;
+        let found = store()
+            .find_resolve(&lens, &DomainNameSystem::Lens)
+            .await?
+            .expect("hold should be kept");
+        assert!(found.resolved.is_none());
+        assert!(found.owner.is_some_and(|o| o.identity == eth));
+
+        curation::remove(&tester(), &hold, "ticket 44").await?;
+        assert!(store()
+            .find_resolve(&lens, &DomainNameSystem::Lens)
+            .await?
+            .is_none());
+        assert!(store()
+            .find_identity(&Platform::Lens, &lens)
+            .await?
+            .is_some());
+
+        let entries = audit_log().entries()?;
+        assert!(entries
+            .iter()
+            .any(|e| e.action == Action::Remove && e.note == "ticket 43" && e.to.contains(&eth)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_splits_graph() -> Result<(), Error> {
+        let eth = unique("0x");
+        let github = unique("a");
+        let proof = Link::Proof {
+            from: identity(Platform::Ethereum, &eth).await?,
+            to: identity(Platform::Github, &github).await?,
+            level: ProofLevel::VeryConfident,
+        };
+        curation::add(&tester(), &proof, "ticket 51").await?;
+        let graph_of = |platform: Platform, name: String| async move {
+            store()
+                .find_identity_graph(&platform, &name, None, None)
+                .await
+                .map(|graph| graph.expect("identity should stay in a graph"))
+        };
+        let joined = graph_of(Platform::Ethereum, eth.clone()).await?;
+        assert_eq!(joined.vertices.len(), 2);
+
+        curation::remove(&tester(), &proof, "ticket 52").await?;
+        let kept = graph_of(Platform::Ethereum, eth.clone()).await?;
+        let split = graph_of(Platform::Github, github.clone()).await?;
+        assert_eq!(kept.graph_id, joined.graph_id);
+        assert_ne!(split.graph_id, joined.graph_id);
+        assert_eq!(kept.vertices.len(), 1);
+        assert_eq!(split.vertices.len(), 1);
+        assert_eq!(split.vertices[0].identity, github);
+
+        // Fetching more of the split side must not pull it back into the old graph.
+        let twitter = Link::Proof {
+            from: identity(Platform::Github, &github).await?,
+            to: identity(Platform::Twitter, &unique("a")).await?,
+            level: ProofLevel::VeryConfident,
+        };
+        curation::add(&tester(), &twitter, "ticket 53").await?;
+        let grown = graph_of(Platform::Github, github.clone()).await?;
+        assert_eq!(grown.graph_id, split.graph_id);
+        assert_eq!(grown.vertices.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mutation() -> Result<(), Error> {
+        let github = unique("alice");
+        let query = format!(
+            r#"mutation {{ addProof(fromPlatform: ethereum, fromIdentity: "{}",
+                toPlatform: github, toIdentity: "{}", note: "verified gist") }}"#,
+            unique("0x"),
+            github
+        );
+        let resp = schema().execute(Request::new(query.clone())).await;
+        assert_eq!(resp.errors.len(), 1);
+        assert!(resp.errors[0].message.contains("Unauthorized"));
+        assert!(store()
+            .find_identity(&Platform::Github, &github)
+            .await?
+            .is_none());
+
+        let resp = schema().execute(Request::new(query).data(tester())).await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        assert!(store()
+            .find_identity(&Platform::Github, &github)
+            .await?
+            .is_some());
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use async_graphql::ErrorExtensions;
 use lambda_http::http::StatusCode;
 use thiserror::Error;
 
@@ -49,6 +50,16 @@ pub enum Error {
     SolanaClientError(#[from] solana_client::client_error::ClientError),
     #[error("ParsePubkeyError error: {0}")]
     ParsePubkeyError(#[from] solana_program::pubkey::ParsePubkeyError),
+    #[error("SqliteError error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+    #[error("RedisError error: {0}")]
+    RedisError(#[from] redis::RedisError),
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+    #[error("Circuit open: {0}")]
+    CircuitOpen(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
 }
 
 impl Error {
@@ -76,6 +87,44 @@ impl Error {
             Error::SnsError(_) => StatusCode::BAD_REQUEST,
             Error::SolanaClientError(_) => StatusCode::BAD_REQUEST,
             Error::ParsePubkeyError(_) => StatusCode::BAD_REQUEST,
+            Error::SqliteError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::RedisError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            Error::CircuitOpen(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Error::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// Stable machine-readable name of the variant, given as `extensions.code` in GraphQL.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::General(_, _) => "GENERAL",
+            Error::ParamMissing(_) => "PARAM_MISSING",
+            Error::ParamError(_) => "PARAM_ERROR",
+            Error::BodyMissing => "BODY_MISSING",
+            Error::NoResult => "NO_RESULT",
+            Error::JSONParseError(_) => "JSON_PARSE_ERROR",
+            Error::HttpError(_) => "HTTP_ERROR",
+            Error::ConfigError(_) => "CONFIG_ERROR",
+            Error::SignatureValidationError(_) => "SIGNATURE_VALIDATION_ERROR",
+            Error::HttpClientError(_) => "HTTP_CLIENT_ERROR",
+            Error::ManualHttpClientError(_) => "MANUAL_HTTP_CLIENT_ERROR",
+            Error::UuidError(_) => "UUID_ERROR",
+            Error::EnumParseError(_) => "ENUM_PARSE_ERROR",
+            Error::ParseIntError(_) => "PARSE_INT_ERROR",
+            Error::DatetimeParseError(_) => "DATETIME_PARSE_ERROR",
+            Error::GraphQLError(_) => "GRAPHQL_ERROR",
+            Error::PoolError(_) => "POOL_ERROR",
+            Error::IsahcError(_) => "ISAHC_ERROR",
+            Error::StdIOError(_) => "STD_IO_ERROR",
+            Error::SnsError(_) => "SNS_ERROR",
+            Error::SolanaClientError(_) => "SOLANA_CLIENT_ERROR",
+            Error::ParsePubkeyError(_) => "PARSE_PUBKEY_ERROR",
+            Error::SqliteError(_) => "SQLITE_ERROR",
+            Error::RedisError(_) => "REDIS_ERROR",
+            Error::RateLimited(_) => "RATE_LIMITED",
+            Error::CircuitOpen(_) => "CIRCUIT_OPEN",
+            Error::Unauthorized(_) => "UNAUTHORIZED",
         }
     }
 }
@@ -86,3 +135,25 @@ unsafe impl Sync for Error {}
 unsafe impl Send for Error {}
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// `Error` returned by GraphQL resolvers.
+/// Unlike the blanket conversion of `Display` errors, it keeps `code` and `status` in `extensions`.
+#[derive(Debug)]
+pub struct FieldError(pub Error);
+
+impl<E: Into<Error>> From<E> for FieldError {
+    fn from(err: E) -> Self {
+        FieldError(err.into())
+    }
+}
+
+impl From<FieldError> for async_graphql::Error {
+    fn from(FieldError(err): FieldError) -> Self {
+        async_graphql::Error::new(err.to_string()).extend_with(|_, extensions| {
+            extensions.set("code", err.code());
+            extensions.set("status", err.http_status().as_u16());
+        })
+    }
+}
+
+pub type FieldResult<T> = std::result::Result<T, FieldError>;
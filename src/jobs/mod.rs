@@ -0,0 +1,467 @@
+mod tests;
+
+use crate::{
+    config::C,
+    error::Error,
+    rank::rank_all,
+    storage::store,
+    upstream::{fetch_all, fetch_domains, fetch_upstream, trim_name, Target},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use http::StatusCode;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use strum_macros::{Display, EnumString};
+use tokio::{sync::watch, sync::Mutex, sync::Notify, task::JoinHandle};
+use tracing::{error, info, warn};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    dedup_key TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_after INTEGER NOT NULL,
+    last_error TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS jobs_dedup_key ON jobs (dedup_key) WHERE status != 'failed';
+CREATE INDEX IF NOT EXISTS jobs_due ON jobs (status, run_after);";
+
+/// How long an idle worker waits before looking for due jobs again.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Upper bound of retry backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(3600);
+
+lazy_static! {
+    /// Job queue configured by `[jobs]`, or why it failed to open.
+    static ref QUEUE: Result<Arc<JobQueue>, String> = JobQueue::open(
+        &C.jobs.path,
+        C.jobs.max_attempts,
+        Duration::from_secs(C.jobs.backoff_seconds),
+    )
+    .map(Arc::new)
+    .map_err(|err| {
+        error!(%err, path = C.jobs.path, "Failed to open job queue");
+        err.to_string()
+    });
+}
+
+/// Get the global job queue.
+/// Fails if it can not be opened, e.g. `[jobs] path` is on a read-only filesystem.
+pub fn queue() -> Result<Arc<JobQueue>, Error> {
+    QUEUE.clone().map_err(|err| {
+        Error::General(
+            format!("Job queue unavailable: {}", err),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })
+}
+
+/// Queue `kind` on behalf of a request, which is still answered from what is stored
+/// if the job can not be queued. Failures are only logged.
+pub async fn enqueue_or_warn(kind: JobKind, delay: Duration) {
+    let queued = match queue() {
+        Ok(queue) => queue.enqueue(kind.clone(), delay).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = queued {
+        warn!(%err, ?kind, "Failed to queue job");
+    }
+}
+
+/// Background work RelationService does outside of a request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobKind {
+    /// Keep crawling from `Target` until exhausted. Leftover of `fetch_all(depth)`.
+    Expand(Target),
+    /// Delete connections inside the `IdentitiesGraph` of `v_id`, then fetch `target` again.
+    Refetch { v_id: String, target: Target },
+    /// Delete cached domain search result of `name`, then search all domain systems again.
+    RefetchDomains(String),
+    /// Ask one upstream (by its registry name) again after being rate limited.
+    /// Upstreams sharing a `DataSource` are deferred separately.
+    FetchUpstream { upstream: String, target: Target },
+    /// Score every `IdentitiesGraph` and its `Identities` with IdentityRank.
+    IdentityRank,
+}
+
+impl JobKind {
+    /// Jobs sharing a key are queued only once until they finish.
+    pub fn dedup_key(&self) -> String {
+        match self {
+            JobKind::Expand(target) => format!("expand:{}", target),
+            JobKind::Refetch { target, .. } => format!("refetch:{}", target),
+            JobKind::RefetchDomains(name) => format!("refetch_domains:{}", trim_name(name)),
+            JobKind::FetchUpstream { upstream, target } => {
+                format!("fetch_upstream:{}:{}", upstream, target)
+            }
+            JobKind::IdentityRank => "identity_rank".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum JobStatus {
+    /// Waiting for `run_after`.
+    Pending,
+    /// Taken by a worker.
+    Running,
+    /// Gave up after `max_attempts`. Kept for inspection.
+    Failed,
+}
+
+/// A job record in queue.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: i64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// How many times this job has been taken by a worker.
+    pub attempts: u32,
+    /// Job won't be taken before this time.
+    pub run_after: NaiveDateTime,
+    pub last_error: Option<String>,
+}
+
+/// Executes a job taken from queue.
+#[async_trait]
+pub trait JobHandler: Send + Sync + 'static {
+    async fn run(&self, kind: &JobKind) -> Result<(), Error>;
+}
+
+/// Runs jobs against upstreams and the configured graph store.
+pub struct FetchJobHandler;
+
+#[async_trait]
+impl JobHandler for FetchJobHandler {
+    async fn run(&self, kind: &JobKind) -> Result<(), Error> {
+        match kind {
+            JobKind::Expand(target) => fetch_all(vec![target.clone()], None).await,
+            JobKind::Refetch { v_id, target } => {
+                store().delete_graph_inner_connection(v_id.clone()).await?;
+                fetch_all(vec![target.clone()], Some(3)).await
+            }
+            JobKind::RefetchDomains(name) => {
+                store().delete_domain_collection(&trim_name(name)).await?;
+                fetch_domains(name).await
+            }
+            JobKind::FetchUpstream { upstream, target } => fetch_upstream(upstream, target).await,
+            JobKind::IdentityRank => rank_all(store().as_ref()).await.map(|_| ()),
+        }
+    }
+}
+
+/// Durable job queue persisted in SQLite.
+/// Queries run on blocking threads, so a slow disk never stalls request handling.
+pub struct JobQueue {
+    conn: Arc<Mutex<Connection>>,
+    notify: Notify,
+    max_attempts: u32,
+    backoff: Duration,
+}
+
+fn now_millis() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+fn millis_to_naive(ms: i64) -> NaiveDateTime {
+    DateTime::from_timestamp_millis(ms)
+        .unwrap_or_default()
+        .naive_utc()
+}
+
+fn job_from_row(row: &Row) -> rusqlite::Result<(i64, String, String, u32, i64, Option<String>)> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+    ))
+}
+
+impl JobQueue {
+    /// Open (or create) a queue file at `path`, or a queue in memory at `":memory:"`.
+    /// Jobs left `running` by a previous process are put back to `pending`.
+    pub fn open(path: &str, max_attempts: u32, backoff: Duration) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?, max_attempts, backoff)
+    }
+
+    /// A throwaway queue living in memory. For tests.
+    pub fn open_in_memory(max_attempts: u32, backoff: Duration) -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?, max_attempts, backoff)
+    }
+
+    fn from_connection(
+        conn: Connection,
+        max_attempts: u32,
+        backoff: Duration,
+    ) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA)?;
+        let recovered = conn.execute(
+            "UPDATE jobs SET status = ?1 WHERE status = ?2",
+            params![
+                JobStatus::Pending.to_string(),
+                JobStatus::Running.to_string()
+            ],
+        )?;
+        if recovered > 0 {
+            info!(recovered, "Interrupted jobs are queued again");
+        }
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            notify: Notify::new(),
+            max_attempts: max_attempts.max(1),
+            backoff,
+        })
+    }
+
+    /// Run `f` with the connection on a blocking thread, off the async runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, Error> + Send + 'static,
+    {
+        let mut conn = self.conn.clone().lock_owned().await;
+        tokio::task::spawn_blocking(move || f(&mut conn))
+            .await
+            .map_err(|err| {
+                Error::General(
+                    format!("Job queue task aborted: {}", err),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+            })?
+    }
+
+    /// Queue a job to run after `delay`.
+    /// Returns `false` if the same job is already waiting or running.
+    pub async fn enqueue(&self, kind: JobKind, delay: Duration) -> Result<bool, Error> {
+        let (dedup_key, kind) = (kind.dedup_key(), serde_json::to_string(&kind)?);
+        let inserted = self
+            .with_conn(move |conn| {
+                let now = now_millis();
+                Ok(conn.execute(
+                    "INSERT OR IGNORE INTO jobs (dedup_key, kind, status, run_after, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                    params![
+                        dedup_key,
+                        kind,
+                        JobStatus::Pending.to_string(),
+                        now + delay.as_millis() as i64,
+                        now,
+                    ],
+                )?)
+            })
+            .await?;
+        if inserted > 0 {
+            self.notify.notify_one();
+        }
+        Ok(inserted > 0)
+    }
+
+    /// Take the next due job and mark it as running.
+    pub async fn claim(&self) -> Result<Option<Job>, Error> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction()?;
+            let found = tx
+                .query_row(
+                    "SELECT id, kind, status, attempts, run_after, last_error FROM jobs
+                     WHERE status = ?1 AND run_after <= ?2 ORDER BY run_after, id LIMIT 1",
+                    params![JobStatus::Pending.to_string(), now_millis()],
+                    job_from_row,
+                )
+                .optional()?;
+            let (id, kind, _, attempts, run_after, last_error) = match found {
+                None => return Ok(None),
+                Some(found) => found,
+            };
+            tx.execute(
+                "UPDATE jobs SET status = ?1, attempts = attempts + 1, updated_at = ?2 WHERE id = ?3",
+                params![JobStatus::Running.to_string(), now_millis(), id],
+            )?;
+            tx.commit()?;
+            Ok(Some(Job {
+                id,
+                kind: serde_json::from_str(&kind)?,
+                status: JobStatus::Running,
+                attempts: attempts + 1,
+                run_after: millis_to_naive(run_after),
+                last_error,
+            }))
+        })
+        .await
+    }
+
+    /// Remove a finished job.
+    pub async fn complete(&self, job: &Job) -> Result<(), Error> {
+        let id = job.id;
+        self.with_conn(move |conn| {
+            conn.execute("DELETE FROM jobs WHERE id = ?1", params![id])?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Schedule a retry with exponential backoff, or give up after `max_attempts`.
+    pub async fn fail(&self, job: &Job, err: &Error) -> Result<(), Error> {
+        let (id, err) = (job.id, err.to_string());
+        if job.attempts >= self.max_attempts {
+            return self
+                .with_conn(move |conn| {
+                    conn.execute(
+                        "UPDATE jobs SET status = ?1, last_error = ?2, updated_at = ?3 WHERE id = ?4",
+                        params![JobStatus::Failed.to_string(), err, now_millis(), id],
+                    )?;
+                    Ok(())
+                })
+                .await;
+        }
+        let backoff = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(job.attempts.saturating_sub(1)))
+            .min(MAX_BACKOFF);
+        self.with_conn(move |conn| {
+            let now = now_millis();
+            conn.execute(
+                "UPDATE jobs SET status = ?1, run_after = ?2, last_error = ?3, updated_at = ?4 WHERE id = ?5",
+                params![
+                    JobStatus::Pending.to_string(),
+                    now + backoff.as_millis() as i64,
+                    err,
+                    now,
+                    id
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// All jobs currently recorded, oldest first.
+    pub async fn jobs(&self) -> Result<Vec<Job>, Error> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, kind, status, attempts, run_after, last_error FROM jobs ORDER BY id",
+            )?;
+            let rows = stmt.query_map([], job_from_row)?;
+            let mut jobs = vec![];
+            for row in rows {
+                let (id, kind, status, attempts, run_after, last_error) = row?;
+                jobs.push(Job {
+                    id,
+                    kind: serde_json::from_str(&kind)?,
+                    status: status.parse()?,
+                    attempts,
+                    run_after: millis_to_naive(run_after),
+                    last_error,
+                });
+            }
+            Ok(jobs)
+        })
+        .await
+    }
+
+    /// How many jobs are in `status`.
+    pub async fn count(&self, status: JobStatus) -> Result<usize, Error> {
+        self.with_conn(move |conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM jobs WHERE status = ?1",
+                params![status.to_string()],
+                |row| row.get(0),
+            )?;
+            Ok(count as usize)
+        })
+        .await
+    }
+
+    /// Queue `kind` now and then every `period`.
+    /// A tick is skipped if the previous one is still waiting or running.
+    pub fn repeat(self: &Arc<Self>, kind: JobKind, period: Duration) -> JoinHandle<()> {
+        let queue = self.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(period);
+            loop {
+                ticks.tick().await;
+                if let Err(err) = queue.enqueue(kind.clone(), Duration::ZERO).await {
+                    warn!(%err, ?kind, "Failed to queue repeated job");
+                }
+            }
+        })
+    }
+
+    /// Spawn `workers` tasks running jobs with `handler`.
+    pub fn start<H: JobHandler>(self: &Arc<Self>, handler: Arc<H>, workers: usize) -> Workers {
+        let (shutdown, watcher) = watch::channel(false);
+        let handles = (0..workers.max(1))
+            .map(|worker| {
+                tokio::spawn(work(self.clone(), handler.clone(), watcher.clone(), worker))
+            })
+            .collect();
+        info!(workers, "Job workers started");
+        Workers { shutdown, handles }
+    }
+}
+
+async fn work<H: JobHandler>(
+    queue: Arc<JobQueue>,
+    handler: Arc<H>,
+    mut shutdown: watch::Receiver<bool>,
+    worker: usize,
+) {
+    while !*shutdown.borrow() {
+        let job = match queue.claim().await {
+            Ok(Some(job)) => job,
+            Ok(None) => {
+                tokio::select! {
+                    _ = queue.notify.notified() => {}
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    _ = shutdown.changed() => {}
+                }
+                continue;
+            }
+            Err(err) => {
+                warn!(worker, %err, "Failed to take job");
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+        };
+        let result = match handler.run(&job.kind).await {
+            Ok(()) => queue.complete(&job).await,
+            Err(err) => {
+                warn!(worker, job = job.id, attempts = job.attempts, %err, "Job failed");
+                queue.fail(&job, &err).await
+            }
+        };
+        if let Err(err) = result {
+            warn!(worker, job = job.id, %err, "Failed to update job");
+        }
+    }
+}
+
+/// Handle of running workers.
+pub struct Workers {
+    shutdown: watch::Sender<bool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Workers {
+    /// Stop taking new jobs and wait for running ones to finish.
+    /// Jobs not taken yet stay in queue for the next start.
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(true);
+        for handle in self.handles.into_iter() {
+            if let Err(err) = handle.await {
+                warn!(%err, "Job worker panicked");
+            }
+        }
+        info!("Job workers stopped");
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        jobs::{JobHandler, JobKind, JobQueue, JobStatus},
+        upstream::{Platform, Target},
+    };
+    use async_trait::async_trait;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn expand(identity: &str) -> JobKind {
+        JobKind::Expand(Target::Identity(Platform::Github, identity.to_string()))
+    }
+
+    struct CountHandler {
+        runs: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl JobHandler for CountHandler {
+        async fn run(&self, _kind: &JobKind) -> Result<(), Error> {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_dedup() -> Result<(), Error> {
+        let queue = JobQueue::open_in_memory(3, Duration::from_secs(1))?;
+        assert!(queue.enqueue(expand("alice"), Duration::ZERO).await?);
+        assert!(!queue.enqueue(expand("alice"), Duration::ZERO).await?);
+        assert!(queue.enqueue(expand("bob"), Duration::ZERO).await?);
+        assert_eq!(queue.jobs().await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_upstream_keyed_by_name() -> Result<(), Error> {
+        let queue = JobQueue::open_in_memory(3, Duration::from_secs(1))?;
+        let target = Target::Identity(Platform::Farcaster, "alice".to_string());
+        // Both are `DataSource::Farcaster`.
+        for upstream in ["farcaster", "farcaster_follow"] {
+            let kind = JobKind::FetchUpstream {
+                upstream: upstream.to_string(),
+                target: target.clone(),
+            };
+            assert!(queue.enqueue(kind, Duration::ZERO).await?);
+        }
+        assert_eq!(queue.jobs().await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_claim_and_complete() -> Result<(), Error> {
+        let queue = JobQueue::open_in_memory(3, Duration::from_secs(1))?;
+        queue
+            .enqueue(expand("later"), Duration::from_secs(60))
+            .await?;
+        assert!(queue.claim().await?.is_none());
+
+        queue.enqueue(expand("alice"), Duration::ZERO).await?;
+        let job = queue.claim().await?.expect("job should be due");
+        assert_eq!(job.kind, expand("alice"));
+        assert_eq!(job.attempts, 1);
+        assert!(queue.claim().await?.is_none());
+        assert_eq!(queue.count(JobStatus::Pending).await?, 1);
+        assert_eq!(queue.count(JobStatus::Running).await?, 1);
+        // Still deduplicated while running.
+        assert!(!queue.enqueue(expand("alice"), Duration::ZERO).await?);
+
+        queue.complete(&job).await?;
+        assert_eq!(queue.jobs().await?.len(), 1);
+        assert!(queue.enqueue(expand("alice"), Duration::ZERO).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_fail() -> Result<(), Error> {
+        let queue = JobQueue::open_in_memory(2, Duration::ZERO)?;
+        queue.enqueue(expand("alice"), Duration::ZERO).await?;
+        let err = Error::General("boom".to_string(), http::StatusCode::BAD_GATEWAY);
+
+        let job = queue.claim().await?.expect("job should be due");
+        queue.fail(&job, &err).await?;
+        let job = queue.claim().await?.expect("job should be retried");
+        assert_eq!(job.attempts, 2);
+        queue.fail(&job, &err).await?;
+        assert!(queue.claim().await?.is_none());
+
+        let jobs = queue.jobs().await?;
+        assert_eq!(jobs[0].status, JobStatus::Failed);
+        assert!(jobs[0].last_error.is_some());
+        // A failed job doesn't block the same job from being queued again.
+        assert!(queue.enqueue(expand("alice"), Duration::ZERO).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_workers_drain_on_shutdown() -> Result<(), Error> {
+        let queue = Arc::new(JobQueue::open_in_memory(3, Duration::from_secs(1))?);
+        let handler = Arc::new(CountHandler {
+            runs: AtomicUsize::new(0),
+        });
+        let workers = queue.start(handler.clone(), 2);
+        for name in ["a", "b", "c"] {
+            queue.enqueue(expand(name), Duration::ZERO).await?;
+        }
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        workers.shutdown().await;
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 3);
+        assert!(queue.jobs().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_running_jobs_recovered_on_reopen() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!("relation_jobs_{}.sqlite", Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_string();
+        {
+            let queue = JobQueue::open(&path, 3, Duration::from_secs(1))?;
+            queue.enqueue(expand("alice"), Duration::ZERO).await?;
+            queue.claim().await?.expect("job should be due");
+        }
+
+        let queue = JobQueue::open(&path, 3, Duration::from_secs(1))?;
+        let job = queue
+            .claim()
+            .await?
+            .expect("interrupted job should be queued again");
+        assert_eq!(job.kind, expand("alice"));
+        assert_eq!(job.attempts, 2);
+
+        drop(queue);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_queue_in_memory() -> Result<(), Error> {
+        // `[jobs] path` of read-only deployments, e.g. Lambda.
+        let queue = JobQueue::open(":memory:", 3, Duration::from_secs(1))?;
+        assert!(queue.enqueue(expand("alice"), Duration::ZERO).await?);
+        assert_eq!(queue.claim().await?.unwrap().kind, expand("alice"));
+
+        let unwritable = std::env::temp_dir()
+            .join(Uuid::new_v4().to_string())
+            .join("relation_jobs.sqlite");
+        assert!(JobQueue::open(unwritable.to_str().unwrap(), 3, Duration::from_secs(1)).is_err());
+        Ok(())
+    }
+}
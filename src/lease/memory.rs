@@ -0,0 +1,87 @@
+use crate::{
+    error::Error,
+    lease::{Lease, LeaseState, LeaseStore},
+    util::naive_now,
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Leases kept in this process only. Replicas don't see each other.
+#[derive(Default)]
+pub struct MemoryLease {
+    leases: Mutex<HashMap<String, Lease>>,
+}
+
+impl MemoryLease {
+    fn leases(&self) -> MutexGuard<'_, HashMap<String, Lease>> {
+        self.leases.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl LeaseStore for MemoryLease {
+    async fn acquire(
+        &self,
+        key: &str,
+        holder: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<LeaseState, Error> {
+        let now = naive_now();
+        let mut leases = self.leases();
+        if let Some(lease) = leases.get(key) {
+            if lease.expires_at > now {
+                return Ok(LeaseState::Held(lease.clone()));
+            }
+        }
+        leases.insert(
+            key.to_string(),
+            Lease {
+                key: key.to_string(),
+                holder: holder.to_string(),
+                token: token.to_string(),
+                expires_at: expires_at(now, ttl),
+            },
+        );
+        Ok(LeaseState::Acquired)
+    }
+
+    async fn release(&self, key: &str, token: &str) -> Result<(), Error> {
+        let mut leases = self.leases();
+        if leases.get(key).is_some_and(|lease| lease.token == token) {
+            leases.remove(key);
+        }
+        Ok(())
+    }
+
+    async fn renew(&self, key: &str, token: &str, ttl: Duration) -> Result<bool, Error> {
+        let now = naive_now();
+        let mut leases = self.leases();
+        match leases.get_mut(key) {
+            Some(lease) if lease.token == token && lease.expires_at > now => {
+                lease.expires_at = expires_at(now, ttl);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn current(&self, key: &str) -> Result<Option<Lease>, Error> {
+        let now = naive_now();
+        Ok(self
+            .leases()
+            .get(key)
+            .filter(|lease| lease.expires_at > now)
+            .cloned())
+    }
+}
+
+fn expires_at(now: NaiveDateTime, ttl: Duration) -> NaiveDateTime {
+    chrono::Duration::from_std(ttl)
+        .ok()
+        .and_then(|ttl| now.checked_add_signed(ttl))
+        .unwrap_or(NaiveDateTime::MAX)
+}
@@ -0,0 +1,171 @@
+pub mod memory;
+pub mod redis;
+mod tests;
+
+pub use self::redis::RedisLease;
+pub use memory::MemoryLease;
+
+use crate::{
+    config::{LeaseBackend, C},
+    error::Error,
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Shared handle of a lease backend.
+pub type DynLeaseStore = Arc<dyn LeaseStore>;
+
+lazy_static! {
+    /// Lease backend selected by `[lease] backend` in config.
+    static ref LEASES: DynLeaseStore = match C.lease.backend {
+        LeaseBackend::Memory => Arc::new(MemoryLease::default()),
+        LeaseBackend::Redis => Arc::new(
+            RedisLease::open(&C.lease.url).expect("Failed to open Redis lease backend")
+        ),
+    };
+
+    /// Name of this process when holding a lease.
+    static ref HOLDER: String = C.lease.holder.clone().unwrap_or_else(default_holder);
+}
+
+/// Get the globally configured lease backend.
+pub fn leases() -> DynLeaseStore {
+    LEASES.clone()
+}
+
+/// Lease holder name of this process.
+pub fn holder() -> &'static str {
+    &HOLDER
+}
+
+/// `hostname:pid`, which tells replicas apart in logs.
+fn default_holder() -> String {
+    let hostname = std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    format!("{}:{}", hostname, std::process::id())
+}
+
+/// A lock on `key` owned by `holder` until `expires_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    pub key: String,
+    pub holder: String,
+    /// Unique to one acquisition. Tasks of one process share `holder`, not `token`.
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Result of `LeaseStore::acquire`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseState {
+    /// Caller is now the holder.
+    Acquired,
+    /// Someone (maybe this process itself) is holding it.
+    Held(Lease),
+}
+
+/// Mutual exclusion of work between tasks and replicas.
+/// A lease is released by its holder, or expires after `ttl` so a crashed holder
+/// won't block others forever.
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    /// Try to take `key` for `ttl` as `holder`, with `token` to release or renew it. Never waits.
+    async fn acquire(
+        &self,
+        key: &str,
+        holder: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<LeaseState, Error>;
+
+    /// Give up `key` if it is still held with `token`.
+    async fn release(&self, key: &str, token: &str) -> Result<(), Error>;
+
+    /// Push the expiry of `key` to `ttl` from now if it is still held with `token`.
+    /// Returns `false` when the lease has been lost.
+    async fn renew(&self, key: &str, token: &str, ttl: Duration) -> Result<bool, Error>;
+
+    /// Current unexpired lease on `key`.
+    async fn current(&self, key: &str) -> Result<Option<Lease>, Error>;
+}
+
+/// Leases taken by one task on a set of keys, all with a token of its own.
+/// Released by `release()`, or in background when dropped early (e.g. by `?`),
+/// so an aborted fetch won't keep others out until expiry.
+pub struct LeaseGuard {
+    leases: DynLeaseStore,
+    token: String,
+    keys: Vec<String>,
+}
+
+impl LeaseGuard {
+    pub fn new(leases: DynLeaseStore) -> Self {
+        Self {
+            leases,
+            token: Uuid::new_v4().to_string(),
+            keys: vec![],
+        }
+    }
+
+    /// Try to take `key` for `ttl`, keeping it in this guard if taken.
+    pub async fn acquire(&mut self, key: &str, ttl: Duration) -> Result<LeaseState, Error> {
+        let state = self.leases.acquire(key, holder(), &self.token, ttl).await?;
+        if state == LeaseState::Acquired {
+            self.keys.push(key.to_string());
+        }
+        Ok(state)
+    }
+
+    /// Extend every held lease by `ttl`. Lost ones are dropped from the guard.
+    pub async fn renew(&mut self, ttl: Duration) {
+        let mut kept = Vec::with_capacity(self.keys.len());
+        for key in self.keys.drain(..) {
+            match self.leases.renew(&key, &self.token, ttl).await {
+                Ok(true) => kept.push(key),
+                Ok(false) => warn!(key, "Lease lost before renewal."),
+                Err(err) => {
+                    warn!(key, %err, "Failed to renew lease.");
+                    kept.push(key);
+                }
+            }
+        }
+        self.keys = kept;
+    }
+
+    /// Give up all held leases.
+    pub async fn release(mut self) {
+        let keys = std::mem::take(&mut self.keys);
+        release_all(self.leases.clone(), self.token.clone(), keys).await
+    }
+}
+
+impl Drop for LeaseGuard {
+    fn drop(&mut self) {
+        if self.keys.is_empty() {
+            return;
+        }
+        let keys = std::mem::take(&mut self.keys);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(release_all(self.leases.clone(), self.token.clone(), keys));
+            }
+            Err(_) => warn!(?keys, "No runtime to release leases. They will expire."),
+        }
+    }
+}
+
+async fn release_all(leases: DynLeaseStore, token: String, keys: Vec<String>) {
+    for key in keys.iter() {
+        if let Err(err) = leases.release(key, &token).await {
+            warn!(key, %err, "Failed to release lease. It will expire.");
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    error::Error,
+    lease::{Lease, LeaseState, LeaseStore},
+    util::naive_now,
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use redis::{aio::MultiplexedConnection, Client, Script};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+/// Prefix of every lease key written into Redis.
+const KEY_PREFIX: &str = "relation_server:lease:";
+
+/// Between the token and the holder in a lease value, `{token}|{holder}`.
+const SEPARATOR: char = '|';
+
+lazy_static! {
+    /// Delete the key only if it is still ours.
+    static ref RELEASE: Script = Script::new(
+        r#"local value = redis.call("GET", KEYS[1])
+if value and string.sub(value, 1, string.len(ARGV[1])) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+else
+    return 0
+end"#
+    );
+
+    /// Extend the expiry only if the key is still ours.
+    static ref RENEW: Script = Script::new(
+        r#"local value = redis.call("GET", KEYS[1])
+if value and string.sub(value, 1, string.len(ARGV[1])) == ARGV[1] then
+    return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+else
+    return 0
+end"#
+    );
+}
+
+/// Leases shared by all replicas through a Redis-compatible server.
+/// Expiry is handled by Redis itself (`SET NX PX`).
+pub struct RedisLease {
+    client: Client,
+    conn: OnceCell<MultiplexedConnection>,
+}
+
+impl RedisLease {
+    /// `url` is like `redis://127.0.0.1:6379/0`. Connects lazily on first use.
+    pub fn open(url: &str) -> Result<Self, Error> {
+        Ok(Self {
+            client: Client::open(url)?,
+            conn: OnceCell::new(),
+        })
+    }
+
+    async fn conn(&self) -> Result<MultiplexedConnection, Error> {
+        let conn = self
+            .conn
+            .get_or_try_init(|| self.client.get_multiplexed_tokio_connection())
+            .await?;
+        Ok(conn.clone())
+    }
+}
+
+fn redis_key(key: &str) -> String {
+    format!("{}{}", KEY_PREFIX, key)
+}
+
+/// What lease values start with when taken with `token`.
+fn token_prefix(token: &str) -> String {
+    format!("{}{}", token, SEPARATOR)
+}
+
+fn ttl_ms(ttl: Duration) -> i64 {
+    ttl.as_millis().clamp(1, i64::MAX as u128) as i64
+}
+
+#[async_trait]
+impl LeaseStore for RedisLease {
+    async fn acquire(
+        &self,
+        key: &str,
+        holder: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<LeaseState, Error> {
+        let mut conn = self.conn().await?;
+        let ttl_ms = ttl_ms(ttl);
+        loop {
+            let set: Option<String> = redis::cmd("SET")
+                .arg(redis_key(key))
+                .arg(format!("{}{}", token_prefix(token), holder))
+                .arg("NX")
+                .arg("PX")
+                .arg(ttl_ms)
+                .query_async(&mut conn)
+                .await?;
+            if set.is_some() {
+                return Ok(LeaseState::Acquired);
+            }
+            // Expired between `SET` and `GET`: try again.
+            if let Some(lease) = self.current(key).await? {
+                return Ok(LeaseState::Held(lease));
+            }
+        }
+    }
+
+    async fn release(&self, key: &str, token: &str) -> Result<(), Error> {
+        let mut conn = self.conn().await?;
+        RELEASE
+            .key(redis_key(key))
+            .arg(token_prefix(token))
+            .invoke_async::<_, i64>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn renew(&self, key: &str, token: &str, ttl: Duration) -> Result<bool, Error> {
+        let mut conn = self.conn().await?;
+        let renewed: i64 = RENEW
+            .key(redis_key(key))
+            .arg(token_prefix(token))
+            .arg(ttl_ms(ttl))
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(renewed == 1)
+    }
+
+    async fn current(&self, key: &str) -> Result<Option<Lease>, Error> {
+        let mut conn = self.conn().await?;
+        let (value, pttl): (Option<String>, i64) = redis::pipe()
+            .cmd("GET")
+            .arg(redis_key(key))
+            .cmd("PTTL")
+            .arg(redis_key(key))
+            .query_async(&mut conn)
+            .await?;
+        // PTTL is -2 when the key is gone, -1 when it never expires.
+        Ok(value.filter(|_| pttl != -2).map(|value| {
+            let (token, holder) = value.split_once(SEPARATOR).unwrap_or_default();
+            Lease {
+                key: key.to_string(),
+                holder: holder.to_string(),
+                token: token.to_string(),
+                expires_at: if pttl < 0 {
+                    NaiveDateTime::MAX
+                } else {
+                    naive_now() + chrono::Duration::milliseconds(pttl)
+                },
+            }
+        }))
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        lease::{holder, DynLeaseStore, LeaseGuard, LeaseState, LeaseStore, MemoryLease},
+    };
+    use std::{sync::Arc, time::Duration};
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn test_acquire_and_release() -> Result<(), Error> {
+        let leases = MemoryLease::default();
+        assert_eq!(
+            leases.acquire("github,alice", "a:1", "t1", TTL).await?,
+            LeaseState::Acquired
+        );
+        match leases.acquire("github,alice", "b:2", "t2", TTL).await? {
+            LeaseState::Held(lease) => assert_eq!(lease.holder, "a:1"),
+            LeaseState::Acquired => panic!("lease should be held by a:1"),
+        }
+        assert_eq!(
+            leases.acquire("github,bob", "b:2", "t2", TTL).await?,
+            LeaseState::Acquired
+        );
+
+        // Only the holder can release.
+        leases.release("github,alice", "t2").await?;
+        assert!(leases.current("github,alice").await?.is_some());
+        leases.release("github,alice", "t1").await?;
+        assert!(leases.current("github,alice").await?.is_none());
+        assert_eq!(
+            leases.acquire("github,alice", "b:2", "t2", TTL).await?,
+            LeaseState::Acquired
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lease_expires() -> Result<(), Error> {
+        let leases = MemoryLease::default();
+        leases
+            .acquire("github,alice", "a:1", "t1", Duration::from_millis(20))
+            .await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(leases.current("github,alice").await?.is_none());
+        assert_eq!(
+            leases.acquire("github,alice", "b:2", "t2", TTL).await?,
+            LeaseState::Acquired
+        );
+        let lease = leases
+            .current("github,alice")
+            .await?
+            .expect("lease should be taken over");
+        assert_eq!(lease.holder, "b:2");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_renew() -> Result<(), Error> {
+        let leases = MemoryLease::default();
+        leases
+            .acquire("github,alice", "a:1", "t1", Duration::from_millis(20))
+            .await?;
+        assert!(!leases.renew("github,alice", "t2", TTL).await?);
+        assert!(leases.renew("github,alice", "t1", TTL).await?);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let lease = leases
+            .current("github,alice")
+            .await?
+            .expect("renewed lease should not expire");
+        assert_eq!(lease.holder, "a:1");
+
+        leases.release("github,alice", "t1").await?;
+        assert!(!leases.renew("github,alice", "t1", TTL).await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_guard_releases_on_drop() -> Result<(), Error> {
+        let leases: DynLeaseStore = Arc::new(MemoryLease::default());
+        let mut guard = LeaseGuard::new(leases.clone());
+        assert_eq!(
+            guard.acquire("github,alice", TTL).await?,
+            LeaseState::Acquired
+        );
+        drop(guard);
+        tokio::task::yield_now().await;
+        assert!(leases.current("github,alice").await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_guards_of_one_process() -> Result<(), Error> {
+        let leases: DynLeaseStore = Arc::new(MemoryLease::default());
+        let mut first = LeaseGuard::new(leases.clone());
+        first
+            .acquire("github,alice", Duration::from_millis(20))
+            .await?;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Expired, then taken by another task of this process.
+        let mut second = LeaseGuard::new(leases.clone());
+        assert_eq!(
+            second.acquire("github,alice", TTL).await?,
+            LeaseState::Acquired
+        );
+        first.renew(TTL).await;
+        first.release().await;
+        let lease = leases
+            .current("github,alice")
+            .await?
+            .expect("lease of the second task should be kept");
+        assert_eq!(lease.holder, holder());
+
+        second.release().await;
+        assert!(leases.current("github,alice").await?.is_none());
+        Ok(())
+    }
+}
@@ -3,7 +3,13 @@ extern crate lazy_static;
 
 pub mod config;
 pub mod controller;
+pub mod curation;
 pub mod error;
+pub mod jobs;
+pub mod lease;
+pub mod metrics;
+pub mod rank;
+pub mod storage;
 pub mod tigergraph;
 pub mod util;
 
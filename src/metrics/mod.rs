@@ -0,0 +1,171 @@
+mod tests;
+
+use crate::{
+    error::Error,
+    jobs::{queue, JobStatus},
+    upstream::DataSource,
+};
+use http::StatusCode;
+use prometheus::{
+    core::Collector, exponential_buckets, Encoder, Histogram, HistogramOpts, HistogramVec,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::time::Duration;
+use tracing::warn;
+
+lazy_static! {
+    /// Everything served on `/metrics`.
+    static ref REGISTRY: Registry = Registry::new();
+    static ref FETCH_ROUNDS: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new("relation_fetch_all_rounds", "Rounds run by one fetch_all.")
+            .buckets(vec![1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0]),
+    ));
+    static ref FETCH_TARGETS: Histogram = register(Histogram::with_opts(
+        HistogramOpts::new(
+            "relation_fetch_all_targets",
+            "Targets processed by one fetch_all."
+        )
+        .buckets(exponential_buckets(1.0, 4.0, 8).unwrap()),
+    ));
+    static ref FETCHING_TARGETS: IntGauge = register(IntGauge::new(
+        "relation_fetching_targets",
+        "Targets leased and being fetched by this process."
+    ));
+    static ref UPSTREAM_REQUESTS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new("relation_upstream_requests_total", "Calls made to upstreams."),
+        &["source"]
+    ));
+    static ref UPSTREAM_ERRORS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new(
+            "relation_upstream_errors_total",
+            "Calls failed by upstream faults, including timeouts. Not-found answers are not counted."
+        ),
+        &["source"]
+    ));
+    static ref UPSTREAM_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new(
+            "relation_upstream_request_duration_seconds",
+            "Latency of calls made to upstreams."
+        )
+        .buckets(exponential_buckets(0.05, 2.0, 11).unwrap()),
+        &["source"]
+    ));
+    static ref TIGERGRAPH_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new(
+            "relation_tigergraph_query_duration_seconds",
+            "Latency of TigerGraph requests, by installed query."
+        )
+        .buckets(exponential_buckets(0.005, 2.0, 12).unwrap()),
+        &["query"]
+    ));
+    static ref TIGERGRAPH_ERRORS: IntCounterVec = register(IntCounterVec::new(
+        Opts::new(
+            "relation_tigergraph_query_errors_total",
+            "TigerGraph requests failed or answered with an error status, by installed query."
+        ),
+        &["query"]
+    ));
+    static ref JOB_QUEUE_DEPTH: IntGaugeVec = register(IntGaugeVec::new(
+        Opts::new("relation_job_queue_depth", "Jobs in background queue."),
+        &["status"]
+    ));
+    static ref GRAPHQL_DURATION: HistogramVec = register(HistogramVec::new(
+        HistogramOpts::new(
+            "relation_graphql_operation_duration_seconds",
+            "Time spent executing GraphQL operations."
+        )
+        .buckets(exponential_buckets(0.005, 2.0, 14).unwrap()),
+        &["operation"]
+    ));
+}
+
+fn register<M: Collector + Clone + 'static>(metric: prometheus::Result<M>) -> M {
+    let metric = metric.expect("Invalid metric");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("Metric registered twice");
+    metric
+}
+
+/// Record a finished `fetch_all`.
+pub fn observe_fetch_all(rounds: u16, targets: usize) {
+    FETCH_ROUNDS.observe(rounds as f64);
+    FETCH_TARGETS.observe(targets as f64);
+}
+
+/// Counts `targets` in `relation_fetching_targets` until dropped.
+pub struct Fetching(i64);
+
+pub fn fetching(targets: usize) -> Fetching {
+    FETCHING_TARGETS.add(targets as i64);
+    Fetching(targets as i64)
+}
+
+impl Drop for Fetching {
+    fn drop(&mut self) {
+        FETCHING_TARGETS.sub(self.0);
+    }
+}
+
+/// Record a call made to an upstream of `source`.
+pub fn observe_upstream(source: DataSource, elapsed: Duration, failed: bool) {
+    let source = source.to_string();
+    UPSTREAM_REQUESTS.with_label_values(&[&source]).inc();
+    if failed {
+        UPSTREAM_ERRORS.with_label_values(&[&source]).inc();
+    }
+    UPSTREAM_DURATION
+        .with_label_values(&[&source])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record a TigerGraph request of installed `query`.
+pub fn observe_tigergraph(query: &str, elapsed: Duration, failed: bool) {
+    if failed {
+        TIGERGRAPH_ERRORS.with_label_values(&[query]).inc();
+    }
+    TIGERGRAPH_DURATION
+        .with_label_values(&[query])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record an executed GraphQL `operation`.
+pub fn observe_graphql(operation: &str, elapsed: Duration) {
+    GRAPHQL_DURATION
+        .with_label_values(&[operation])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// All metrics in Prometheus text format.
+pub async fn render() -> Result<String, Error> {
+    // A broken queue only loses its gauge, the rest is still scraped.
+    match queue() {
+        Ok(queue) => {
+            for status in [JobStatus::Pending, JobStatus::Running, JobStatus::Failed] {
+                match queue.count(status).await {
+                    Ok(count) => JOB_QUEUE_DEPTH
+                        .with_label_values(&[&status.to_string()])
+                        .set(count as i64),
+                    Err(err) => warn!(%err, %status, "Failed to count jobs for metrics"),
+                }
+            }
+        }
+        Err(err) => warn!(%err, "Job queue unavailable for metrics"),
+    }
+
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&REGISTRY.gather(), &mut buffer)
+        .map_err(|err| {
+            Error::General(
+                format!("Metrics encode error: {}", err),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?;
+    String::from_utf8(buffer).map_err(|err| {
+        Error::General(
+            format!("Metrics encode error: {}", err),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+    })
+}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        metrics::{fetching, observe_tigergraph, observe_upstream, render},
+        upstream::DataSource,
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_render() -> Result<(), Error> {
+        observe_upstream(DataSource::Keybase, Duration::from_millis(120), true);
+        observe_tigergraph("neighbors", Duration::from_millis(8), false);
+        let _fetching = fetching(2);
+
+        let text = render().await?;
+        assert!(text.contains(r#"relation_upstream_requests_total{source="keybase"}"#));
+        assert!(text.contains(r#"relation_upstream_errors_total{source="keybase"}"#));
+        assert!(
+            text.contains(r#"relation_tigergraph_query_duration_seconds_count{query="neighbors"}"#)
+        );
+        assert!(text.contains("relation_fetching_targets"));
+        assert!(text.contains(r#"relation_job_queue_depth{status="pending"}"#));
+        Ok(())
+    }
+}
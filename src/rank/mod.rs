@@ -0,0 +1,212 @@
+mod tests;
+
+use crate::{
+    error::Error,
+    storage::GraphStore,
+    upstream::{DataSource, Platform, ProofLevel},
+    util::naive_now,
+};
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, HashSet};
+use tracing::info;
+
+/// How many `IdentitiesGraph`s are read from store at once.
+pub const RANK_PAGE: u16 = 100;
+
+/// Chance of following a proof instead of jumping, as in PageRank.
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 30;
+
+/// Weights of each signal in the score of an `IdentitiesGraph`. They sum to 1.
+const PROOF_WEIGHT: f64 = 0.3;
+const DIVERSITY_WEIGHT: f64 = 0.3;
+const AGE_WEIGHT: f64 = 0.2;
+const DOMAIN_WEIGHT: f64 = 0.1;
+const SYBIL_WEIGHT: f64 = 0.1;
+
+/// A signal counts in full once it reaches these.
+const FULL_PROOFS: f64 = 4.0;
+const FULL_PLATFORMS: f64 = 5.0;
+const FULL_AGE_DAYS: f64 = 5.0 * 365.0;
+const FULL_DOMAINS: f64 = 3.0;
+
+/// An `Identities` vertex, as IdentityRank sees it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankIdentity {
+    pub v_id: String,
+    pub platform: Platform,
+    /// Account creation time on its platform, if given.
+    pub created_at: Option<NaiveDateTime>,
+    /// How many domain names it holds.
+    pub domains: u32,
+}
+
+/// A `Proof_Forward` between two `Identities` of the same `IdentitiesGraph`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankProof {
+    pub from_id: String,
+    pub to_id: String,
+    pub source: DataSource,
+    pub level: ProofLevel,
+}
+
+/// Everything IdentityRank reads of one `IdentitiesGraph`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankGraph {
+    pub graph_id: String,
+    pub identities: Vec<RankIdentity>,
+    pub proofs: Vec<RankProof>,
+}
+
+/// Scores of one `IdentitiesGraph` and of its `Identities`, all in `[0, 1]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphRank {
+    pub graph_id: String,
+    pub rank: f64,
+    /// `Identities` v_id -> score
+    pub identities: HashMap<String, f64>,
+}
+
+fn age_score(created_at: Option<NaiveDateTime>, now: NaiveDateTime) -> f64 {
+    created_at.map_or(0.0, |created_at| {
+        ((now - created_at).num_days() as f64 / FULL_AGE_DAYS).clamp(0.0, 1.0)
+    })
+}
+
+fn domain_score(domains: u32) -> f64 {
+    (domains as f64 / FULL_DOMAINS).min(1.0)
+}
+
+fn level_weight(level: ProofLevel) -> f64 {
+    level as i32 as f64 / ProofLevel::VeryConfident as i32 as f64
+}
+
+impl RankGraph {
+    /// Score of the graph is a weighted sum of its proofs (by `ProofLevel`),
+    /// platform diversity, oldest account, domain holdings and Sybil-list membership.
+    /// It is shared among identities by a PageRank walk over proofs,
+    /// which jumps more often to identities with older accounts, domains or Sybil-list proofs.
+    /// The most central identity gets the score of the graph.
+    pub fn rank(&self, now: NaiveDateTime) -> GraphRank {
+        let mut ranked = GraphRank {
+            graph_id: self.graph_id.clone(),
+            rank: 0.0,
+            identities: HashMap::new(),
+        };
+        if self.identities.is_empty() {
+            return ranked;
+        }
+        let index: HashMap<&str, usize> = self
+            .identities
+            .iter()
+            .enumerate()
+            .map(|(idx, identity)| (identity.v_id.as_str(), idx))
+            .collect();
+        let proofs: Vec<(usize, usize, f64)> = self
+            .proofs
+            .iter()
+            .filter_map(
+                |p| match (index.get(p.from_id.as_str()), index.get(p.to_id.as_str())) {
+                    (Some(from), Some(to)) if from != to => {
+                        Some((*from, *to, level_weight(p.level)))
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        let sybil: HashSet<&str> = self
+            .proofs
+            .iter()
+            .filter(|p| p.source == DataSource::SybilList)
+            .flat_map(|p| [p.from_id.as_str(), p.to_id.as_str()])
+            .collect();
+
+        let ages: Vec<f64> = self
+            .identities
+            .iter()
+            .map(|identity| age_score(identity.created_at, now))
+            .collect();
+        let platforms: HashSet<Platform> = self.identities.iter().map(|i| i.platform).collect();
+        let domains: u32 = self.identities.iter().map(|i| i.domains).sum();
+        ranked.rank = PROOF_WEIGHT
+            * (proofs.iter().map(|(_, _, w)| w).sum::<f64>() / FULL_PROOFS).min(1.0)
+            + DIVERSITY_WEIGHT * ((platforms.len() - 1) as f64 / (FULL_PLATFORMS - 1.0)).min(1.0)
+            + AGE_WEIGHT * ages.iter().cloned().fold(0.0, f64::max)
+            + DOMAIN_WEIGHT * domain_score(domains)
+            + SYBIL_WEIGHT * if sybil.is_empty() { 0.0 } else { 1.0 };
+
+        // Jump more often to identities with stronger signals of their own.
+        let own_weight = AGE_WEIGHT + DOMAIN_WEIGHT + SYBIL_WEIGHT;
+        let jump: Vec<f64> = self
+            .identities
+            .iter()
+            .zip(ages.iter())
+            .map(|(identity, age)| {
+                let listed = if sybil.contains(identity.v_id.as_str()) {
+                    1.0
+                } else {
+                    0.0
+                };
+                1.0 + (AGE_WEIGHT * age
+                    + DOMAIN_WEIGHT * domain_score(identity.domains)
+                    + SYBIL_WEIGHT * listed)
+                    / own_weight
+            })
+            .collect();
+        let total: f64 = jump.iter().sum();
+        let jump: Vec<f64> = jump.iter().map(|j| j / total).collect();
+
+        let n = self.identities.len();
+        let mut out_weight = vec![0.0; n];
+        for (from, to, weight) in proofs.iter() {
+            out_weight[*from] += weight;
+            out_weight[*to] += weight;
+        }
+        let mut score = jump.clone();
+        for _ in 0..ITERATIONS {
+            let dangling: f64 = (0..n)
+                .filter(|v| out_weight[*v] == 0.0)
+                .map(|v| score[v])
+                .sum();
+            let mut next: Vec<f64> = jump
+                .iter()
+                .map(|j| (1.0 - DAMPING) * j + DAMPING * dangling * j)
+                .collect();
+            for (from, to, weight) in proofs.iter() {
+                next[*to] += DAMPING * score[*from] * weight / out_weight[*from];
+                next[*from] += DAMPING * score[*to] * weight / out_weight[*to];
+            }
+            score = next;
+        }
+        let top = score.iter().cloned().fold(0.0, f64::max);
+        ranked.identities = self
+            .identities
+            .iter()
+            .zip(score.iter())
+            .map(|(identity, s)| (identity.v_id.clone(), ranked.rank * s / top))
+            .collect();
+        ranked
+    }
+}
+
+/// Rank every `IdentitiesGraph` in `store`, page by page.
+/// Returns how many graphs are ranked.
+pub async fn rank_all(store: &dyn GraphStore) -> Result<usize, Error> {
+    let now = naive_now();
+    let mut ranked = 0;
+    let mut page = 0;
+    loop {
+        let graphs = store.rank_inputs(RANK_PAGE, page).await?;
+        let ranks: Vec<GraphRank> = graphs.iter().map(|graph| graph.rank(now)).collect();
+        if !ranks.is_empty() {
+            store.save_ranks(&ranks).await?;
+        }
+        ranked += ranks.len();
+        if graphs.len() < RANK_PAGE as usize {
+            break;
+        }
+        page += 1;
+    }
+    info!(ranked, "IdentityRank updated");
+    Ok(ranked)
+}
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        rank::{rank_all, RankGraph, RankIdentity, RankProof},
+        storage::{GraphStore, MemoryStore},
+        tigergraph::{
+            edge::{HyperEdge, Proof, Wrapper, HYPER_EDGE, PROOF_EDGE, PROOF_REVERSE_EDGE},
+            vertex::{IdentitiesGraph, Identity},
+            EdgeWrapperEnum,
+        },
+        upstream::{DataSource, Platform, ProofLevel},
+        util::naive_now,
+    };
+    use chrono::{Duration, NaiveDateTime};
+    use uuid::Uuid;
+
+    fn rank_identity(v_id: &str, platform: Platform) -> RankIdentity {
+        RankIdentity {
+            v_id: v_id.to_string(),
+            platform,
+            created_at: None,
+            domains: 0,
+        }
+    }
+
+    fn rank_proof(from: &str, to: &str, source: DataSource, level: ProofLevel) -> RankProof {
+        RankProof {
+            from_id: from.to_string(),
+            to_id: to.to_string(),
+            source,
+            level,
+        }
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2024-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_rank_lonely_identity() {
+        let graph = RankGraph {
+            graph_id: "g".to_string(),
+            identities: vec![rank_identity("ethereum,0xa", Platform::Ethereum)],
+            proofs: vec![],
+        };
+        let ranked = graph.rank(now());
+        assert_eq!(ranked.rank, 0.0);
+        assert_eq!(ranked.identities["ethereum,0xa"], 0.0);
+
+        let empty = RankGraph {
+            graph_id: "e".to_string(),
+            identities: vec![],
+            proofs: vec![],
+        };
+        assert!(empty.rank(now()).identities.is_empty());
+    }
+
+    #[test]
+    fn test_rank_signals() {
+        let weak = RankGraph {
+            graph_id: "weak".to_string(),
+            identities: vec![
+                rank_identity("ethereum,0xa", Platform::Ethereum),
+                rank_identity("twitter,a", Platform::Twitter),
+            ],
+            proofs: vec![rank_proof(
+                "twitter,a",
+                "ethereum,0xa",
+                DataSource::NextID,
+                ProofLevel::Insecure,
+            )],
+        };
+        let mut strong = weak.clone();
+        strong.graph_id = "strong".to_string();
+        strong.proofs[0].level = ProofLevel::VeryConfident;
+        let weak_rank = weak.rank(now());
+        let strong_rank = strong.rank(now());
+        assert!(strong_rank.rank > weak_rank.rank, "level weights proofs");
+
+        let mut diverse = strong.clone();
+        diverse
+            .identities
+            .push(rank_identity("github,a", Platform::Github));
+        diverse.proofs.push(rank_proof(
+            "github,a",
+            "ethereum,0xa",
+            DataSource::NextID,
+            ProofLevel::VeryConfident,
+        ));
+        let diverse_rank = diverse.rank(now());
+        assert!(diverse_rank.rank > strong_rank.rank);
+        let hub = diverse_rank.identities["ethereum,0xa"];
+        assert_eq!(
+            hub, diverse_rank.rank,
+            "most central identity gets graph rank"
+        );
+        assert!(diverse_rank.identities["github,a"] < hub);
+
+        let mut aged = diverse.clone();
+        aged.identities[2].created_at = Some(now() - Duration::days(10 * 365));
+        aged.identities[2].domains = 5;
+        aged.proofs.push(rank_proof(
+            "github,a",
+            "ethereum,0xa",
+            DataSource::SybilList,
+            ProofLevel::VeryConfident,
+        ));
+        let aged_rank = aged.rank(now());
+        assert!(aged_rank.rank > diverse_rank.rank);
+        assert!(aged_rank.rank <= 1.0);
+        assert!(
+            aged_rank.identities["github,a"] / aged_rank.rank
+                > diverse_rank.identities["github,a"] / diverse_rank.rank,
+            "own signals draw more of the walk"
+        );
+    }
+
+    fn identity(platform: Platform, name: &str) -> Identity {
+        Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: name.to_string(),
+            created_at: Some(naive_now() - Duration::days(3 * 365)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rank_all() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let twitter = identity(Platform::Twitter, "a");
+        let proof = Proof {
+            source: DataSource::SybilList,
+            level: ProofLevel::VeryConfident,
+            ..Default::default()
+        };
+        let hv = IdentitiesGraph::default();
+        let edges = vec![
+            EdgeWrapperEnum::new_proof_forward(proof.wrapper(&twitter, &eth, PROOF_EDGE)),
+            EdgeWrapperEnum::new_proof_backward(proof.wrapper(&eth, &twitter, PROOF_REVERSE_EDGE)),
+            EdgeWrapperEnum::new_hyper_edge(HyperEdge {}.wrapper(&hv, &eth, HYPER_EDGE)),
+            EdgeWrapperEnum::new_hyper_edge(HyperEdge {}.wrapper(&hv, &twitter, HYPER_EDGE)),
+        ];
+        store.batch_upsert(edges).await?;
+        let ids = vec!["ethereum,0xa".to_string(), "github,nobody".to_string()];
+        assert_eq!(
+            store.identity_ranks(ids.clone()).await?["ethereum,0xa"],
+            None
+        );
+
+        let inputs = store.rank_inputs(100, 0).await?;
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].identities.len(), 2);
+        assert_eq!(inputs[0].proofs.len(), 1, "`Proof_Backward` is not counted");
+        assert!(store.rank_inputs(100, 1).await?.is_empty());
+
+        assert_eq!(rank_all(&store).await?, 1);
+        let ranks = store.identity_ranks(ids).await?;
+        let rank = ranks["ethereum,0xa"].expect("identity should be ranked");
+        assert!(rank > 0.0 && rank <= 1.0);
+        assert_eq!(ranks["github,nobody"], None);
+        Ok(())
+    }
+}
@@ -0,0 +1,2039 @@
+use crate::{
+    error::Error,
+    rank::{GraphRank, RankGraph, RankIdentity, RankProof},
+    storage::GraphStore,
+    tigergraph::{
+        edge::{
+            resolve::{Domain, HoldRecordObject, ResolveRecordObject, ResolveReverse},
+            shortest_paths, AvailableDomain, EdgeUnion, FollowRecord, HoldRecord, IdentityPath,
+            PartOfCollectionRecord, PathHop, ProofRecord, RelationTXRecord, RelationUniqueTXRecord,
+            ResolveEdge, ResolveRecord, SocialFeedRecord, FOLLOW, HOLD_CONTRACT, HOLD_IDENTITY,
+            PART_OF_COLLECTION, PROOF_EDGE, RELATION_TX, RELATION_UNIQUE_TX, RESOLVE,
+            RESOLVE_CONTRACT, REVERSE_RESOLVE, REVERSE_RESOLVE_CONTRACT, SOCIAL_FEED,
+        },
+        vertex::{
+            contract::VERTEX_NAME as CONTRACTS, domain_collection::DomainAvailableSearch,
+            identity::VERTEX_NAME as IDENTITIES, identity_graph::VERTEX_NAME as IDENTITIES_GRAPH,
+            Address, Contract, ContractRecord, DomainCollection, ExpandIdentityRecord,
+            IdentitiesGraph, Identity, IdentityConnection, IdentityGraph, IdentityRecord,
+            IdentityWithSource, Vertex, VertexRecord,
+        },
+        EdgeList, EdgeWrapperEnum,
+    },
+    upstream::{Chain, ContractCategory, DataSource, DomainNameSystem, Platform, ProofLevel},
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use uuid::Uuid;
+
+/// Graph id used by upstreams before `IdentitiesGraph` allocation.
+const FAKE_GRAPH_ID: &str = "fake_uuid_v4";
+
+/// Same as `@@domainSystems` in GSQL queries.
+const DOMAIN_PLATFORMS: [Platform; 10] = [
+    Platform::Dotbit,
+    Platform::Lens,
+    Platform::UnstoppableDomains,
+    Platform::SpaceId,
+    Platform::Crossbell,
+    Platform::ENS,
+    Platform::SNS,
+    Platform::Genome,
+    Platform::Clusters,
+    Platform::Basenames,
+];
+
+/// Domain platforms whose `Hold` is not drawn as an edge in identity graph.
+const RESOLVE_PLATFORMS: [Platform; 4] = [
+    Platform::Genome,
+    Platform::ENS,
+    Platform::SNS,
+    Platform::Basenames,
+];
+
+/// `(e_type, from_id, to_id, discriminator)`
+type EdgeKey = (String, String, String, String);
+
+/// A row touched by a write. Collected only when the store is journaled.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Row {
+    Identity(String),
+    Contract(String),
+    Graph(String),
+    PartOf(String),
+    Collection(String),
+    Proof(EdgeKey),
+    Hold(EdgeKey),
+    Resolve(EdgeKey),
+    PartOfCollection(EdgeKey),
+    RelationTX(EdgeKey),
+    RelationUniqueTX(EdgeKey),
+    Follow(EdgeKey),
+    SocialFeed(EdgeKey),
+    Rank(String),
+}
+
+/// Current state of a row changed since the last `MemoryStore::take_changes`.
+/// `value` is `None` if the row has been deleted.
+pub(crate) struct RowChange {
+    pub kind: &'static str,
+    pub key: String,
+    pub value: Option<String>,
+    row: Row,
+}
+
+#[derive(Default)]
+struct Tables {
+    journal: Option<BTreeSet<Row>>,
+    identities: HashMap<String, Identity>,
+    contracts: HashMap<String, Contract>,
+    graphs: HashMap<String, IdentitiesGraph>,
+    /// Identity v_id -> IdentitiesGraph id
+    part_of: HashMap<String, String>,
+    collections: HashMap<String, DomainCollection>,
+    proofs: BTreeMap<EdgeKey, ProofRecord>,
+    holds: BTreeMap<EdgeKey, HoldRecord>,
+    resolves: BTreeMap<EdgeKey, ResolveRecord>,
+    /// Domain name -> keys of its `resolves`, so domains are found without a scan.
+    resolve_names: HashMap<String, BTreeSet<EdgeKey>>,
+    part_of_collections: BTreeMap<EdgeKey, PartOfCollectionRecord>,
+    relation_txs: BTreeMap<EdgeKey, RelationTXRecord>,
+    relation_unique_txs: BTreeMap<EdgeKey, RelationUniqueTXRecord>,
+    /// Kept between identities. Their `IdentitiesGraph`s are looked up when read.
+    follows: BTreeMap<EdgeKey, FollowRecord>,
+    social_feeds: BTreeMap<EdgeKey, SocialFeedRecord>,
+    /// `identity_rank` of `Identities` and `IdentitiesGraph`s, by v_id.
+    ranks: HashMap<String, f64>,
+}
+
+/// `GraphStore` living in process memory.
+/// Meant for running standalone without TigerGraph and for tests: identities
+/// and domain names are looked up by key, but traversals scan the edge tables,
+/// so it is sized for development datasets, not production ones.
+#[derive(Default)]
+pub struct MemoryStore {
+    tables: RwLock<Tables>,
+}
+
+impl MemoryStore {
+    fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A store recording every touched row, to be persisted elsewhere.
+    pub(crate) fn journaled() -> Self {
+        let store = Self::default();
+        store.write().journal = Some(BTreeSet::new());
+        store
+    }
+
+    /// Drain rows touched since last call, with their current values.
+    pub(crate) fn take_changes(&self) -> Result<Vec<RowChange>, Error> {
+        let mut tables = self.write();
+        let rows = tables
+            .journal
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default();
+        rows.into_iter().map(|row| tables.dump(row)).collect()
+    }
+
+    /// Put back changes which failed to persist, so the next `take_changes` retries them
+    /// with their values by then.
+    pub(crate) fn requeue(&self, changes: Vec<RowChange>) {
+        let mut tables = self.write();
+        for change in changes {
+            tables.touch(change.row);
+        }
+    }
+
+    /// Load a row persisted from `take_changes`.
+    pub(crate) fn restore(&self, kind: &str, key: &str, value: &str) -> Result<(), Error> {
+        self.write().load(kind, key, value)
+    }
+
+    /// Upsert vertices and edges as given, without any `IdentitiesGraph` allocation.
+    #[cfg(test)]
+    pub(crate) fn upsert(
+        &self,
+        identities: &[Identity],
+        contracts: &[Contract],
+        collections: &[DomainCollection],
+        edges: &EdgeList,
+    ) {
+        let mut tables = self.write();
+        identities.iter().for_each(|i| tables.upsert_identity(i));
+        contracts.iter().for_each(|c| tables.upsert_contract(c));
+        collections
+            .iter()
+            .for_each(|c| tables.upsert_domain_collection(c));
+        tables.apply_edges(edges);
+    }
+
+    /// Put `vids` into one `IdentitiesGraph`, which is `proposed` unless any of them has one already.
+    #[cfg(test)]
+    pub(crate) fn allocate(&self, vids: &[String], proposed: IdentitiesGraph) -> IdentitiesGraph {
+        self.write()
+            .allocate_graph(vids, Some(proposed.clone()))
+            .unwrap_or(proposed)
+    }
+
+    /// Put `vids` into `graph`, leaving any `IdentitiesGraph` they were in.
+    #[cfg(test)]
+    pub(crate) fn reassign(&self, vids: &[String], graph: IdentitiesGraph) -> IdentitiesGraph {
+        self.write().reassign_graph(vids, &graph);
+        graph
+    }
+
+    /// Take `v_id` out of `IdentitiesGraph` `graph_id`. `false` if it was not in it.
+    #[cfg(test)]
+    pub(crate) fn leave_graph(&self, v_id: &str, graph_id: &str) -> bool {
+        let mut tables = self.write();
+        let left = tables.part_of.get(v_id).is_some_and(|id| id == graph_id);
+        if left {
+            tables.part_of.remove(v_id);
+            tables.touch(Row::PartOf(v_id.to_string()));
+        }
+        left
+    }
+
+    /// Uncollapsed result of `find_resolve`, as given by `domain2` query.
+    pub(crate) fn find_domain(&self, name: &str, domain_system: &DomainNameSystem) -> Domain {
+        let tables = self.read();
+        let (resolve_type, reverse_type) = if *domain_system == DomainNameSystem::ENS {
+            (RESOLVE_CONTRACT, REVERSE_RESOLVE_CONTRACT)
+        } else {
+            (RESOLVE, REVERSE_RESOLVE)
+        };
+        let matched = |r: &&ResolveRecord| r.system == *domain_system && r.name == name;
+
+        let record = tables
+            .resolves_of_name(resolve_type, name)
+            .filter(matched)
+            .last();
+        let resolved: Vec<IdentityRecord> = record
+            .and_then(|r| tables.identity_record(&r.to_id))
+            .into_iter()
+            .collect();
+        let reverse_record: Vec<IdentityRecord> = tables
+            .resolves_of_name(reverse_type, name)
+            .filter(matched)
+            .filter_map(|r| tables.identity_record(&r.from_id))
+            .collect();
+        let hold = if *domain_system == DomainNameSystem::ENS {
+            tables
+                .holds_of_type(HOLD_CONTRACT)
+                .filter(|h| h.id == name)
+                .last()
+        } else {
+            tables
+                .holds_of_type(HOLD_IDENTITY)
+                .filter(|h| {
+                    tables.identities.get(&h.to_id).is_some_and(|v| {
+                        v.platform.to_string() == domain_system.to_string() && v.identity == name
+                    })
+                })
+                .last()
+        };
+        let owner: Vec<IdentityRecord> = hold
+            .and_then(|h| tables.identity_record(&h.from_id))
+            .into_iter()
+            .collect();
+
+        Domain {
+            record: record.cloned().map(ResolveRecordObject::Nonempty),
+            hold: hold.cloned().map(HoldRecordObject::Nonempty),
+            resolved: Some(resolved),
+            owner,
+            reverse: !reverse_record.is_empty(),
+            reverse_record: Some(reverse_record),
+        }
+    }
+
+    /// `DomainCollection` named `name` with the domains taken in it.
+    pub(crate) fn collection_domains(
+        &self,
+        name: &str,
+    ) -> Option<(DomainCollection, Vec<AvailableDomain>)> {
+        let tables = self.read();
+        let collection = tables.collections.get(name)?.clone();
+        let existing: Vec<AvailableDomain> = tables
+            .part_of_collections
+            .values()
+            .filter(|e| e.from_id == name)
+            .map(|e| AvailableDomain {
+                platform: e.platform,
+                name: e.name.clone(),
+                tld: e.tld.clone(),
+                expired_at: tables.identities.get(&e.to_id).and_then(|i| i.expired_at),
+                availability: false,
+                status: e.status,
+            })
+            .collect();
+        Some((collection, existing))
+    }
+}
+
+/// Proofs are discriminated by `source`, as in GSQL schema.
+fn proof_key(e_type: &str, from: &dyn Vertex, to: &dyn Vertex, record: &ProofRecord) -> EdgeKey {
+    (
+        e_type.to_string(),
+        from.primary_key(),
+        to.primary_key(),
+        record.source.to_string(),
+    )
+}
+
+/// NFT holds are also discriminated by transaction and token id.
+fn hold_key(e_type: &str, from: &dyn Vertex, to: &dyn Vertex, record: &HoldRecord) -> EdgeKey {
+    let discriminator = if e_type == HOLD_CONTRACT {
+        format!(
+            "{},{},{}",
+            record.source,
+            record.transaction.clone().unwrap_or_default(),
+            record.id
+        )
+    } else {
+        record.source.to_string()
+    };
+    (
+        e_type.to_string(),
+        from.primary_key(),
+        to.primary_key(),
+        discriminator,
+    )
+}
+
+fn resolve_key(
+    e_type: &str,
+    from: &dyn Vertex,
+    to: &dyn Vertex,
+    record: &ResolveRecord,
+) -> EdgeKey {
+    (
+        e_type.to_string(),
+        from.primary_key(),
+        to.primary_key(),
+        format!("{},{},{}", record.source, record.system, record.name),
+    )
+}
+
+fn max_option(a: Option<NaiveDateTime>, b: Option<NaiveDateTime>) -> Option<NaiveDateTime> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn is_domain_platform(platform: &Platform) -> bool {
+    DOMAIN_PLATFORMS.contains(platform)
+}
+
+fn is_domain_system(system: &DomainNameSystem) -> bool {
+    DOMAIN_PLATFORMS
+        .iter()
+        .any(|p| DomainNameSystem::from_str(&p.to_string()).ok() == Some(*system))
+}
+
+fn address_of(identity: &Identity) -> Address {
+    Address {
+        chain: Chain::from_str(&identity.platform.to_string()).unwrap_or_default(),
+        address: identity.identity.clone(),
+    }
+}
+
+fn push_address(list: &mut Vec<Address>, address: Address) {
+    if !list
+        .iter()
+        .any(|a| a.chain == address.chain && a.address == address.address)
+    {
+        list.push(address)
+    }
+}
+
+/// Filter of `reverse_flag` shared by `neighbors_with_source_reverse` and `find_identity_graph`.
+fn match_reverse(reverse: Option<bool>, platform: &Platform, is_reverse: bool) -> bool {
+    match reverse {
+        None => true,
+        Some(flag) => !is_domain_platform(platform) || is_reverse == flag,
+    }
+}
+
+/// Filter of `min_level` shared by `neighbors_with_source_reverse`, `find_identity_graph` and `shortest_path`.
+fn match_level(level: ProofLevel, min_level: Option<ProofLevel>) -> bool {
+    min_level.is_none_or(|min_level| level >= min_level)
+}
+
+impl Tables {
+    fn touch(&mut self, row: Row) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.insert(row);
+        }
+    }
+
+    fn dump(&self, row: Row) -> Result<RowChange, Error> {
+        fn encode<T: serde::Serialize>(value: Option<&T>) -> Result<Option<String>, Error> {
+            Ok(value.map(serde_json::to_string).transpose()?)
+        }
+        let (kind, key, value) = match &row {
+            Row::Identity(k) => ("identity", k.clone(), encode(self.identities.get(k))?),
+            Row::Contract(k) => ("contract", k.clone(), encode(self.contracts.get(k))?),
+            Row::Graph(k) => ("graph", k.clone(), encode(self.graphs.get(k))?),
+            Row::PartOf(k) => ("part_of", k.clone(), encode(self.part_of.get(k))?),
+            Row::Collection(k) => ("collection", k.clone(), encode(self.collections.get(k))?),
+            Row::Proof(k) => (
+                "proof",
+                serde_json::to_string(k)?,
+                encode(self.proofs.get(k))?,
+            ),
+            Row::Hold(k) => (
+                "hold",
+                serde_json::to_string(k)?,
+                encode(self.holds.get(k))?,
+            ),
+            Row::Resolve(k) => (
+                "resolve",
+                serde_json::to_string(k)?,
+                encode(self.resolves.get(k))?,
+            ),
+            Row::PartOfCollection(k) => (
+                "part_of_collection",
+                serde_json::to_string(k)?,
+                encode(self.part_of_collections.get(k))?,
+            ),
+            Row::RelationTX(k) => (
+                "relation_tx",
+                serde_json::to_string(k)?,
+                encode(self.relation_txs.get(k))?,
+            ),
+            Row::RelationUniqueTX(k) => (
+                "relation_unique_tx",
+                serde_json::to_string(k)?,
+                encode(self.relation_unique_txs.get(k))?,
+            ),
+            Row::Follow(k) => (
+                "follow",
+                serde_json::to_string(k)?,
+                encode(self.follows.get(k))?,
+            ),
+            Row::SocialFeed(k) => (
+                "social_feed",
+                serde_json::to_string(k)?,
+                encode(self.social_feeds.get(k))?,
+            ),
+            Row::Rank(k) => ("rank", k.clone(), encode(self.ranks.get(k))?),
+        };
+        Ok(RowChange {
+            kind,
+            key,
+            value,
+            row,
+        })
+    }
+
+    fn load(&mut self, kind: &str, key: &str, value: &str) -> Result<(), Error> {
+        let key = key.to_string();
+        match kind {
+            "identity" => _ = self.identities.insert(key, serde_json::from_str(value)?),
+            "contract" => _ = self.contracts.insert(key, serde_json::from_str(value)?),
+            "graph" => _ = self.graphs.insert(key, serde_json::from_str(value)?),
+            "part_of" => _ = self.part_of.insert(key, serde_json::from_str(value)?),
+            "collection" => _ = self.collections.insert(key, serde_json::from_str(value)?),
+            "proof" => {
+                _ = self
+                    .proofs
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "hold" => {
+                _ = self
+                    .holds
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "resolve" => {
+                self.insert_resolve(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "part_of_collection" => {
+                _ = self
+                    .part_of_collections
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "relation_tx" => {
+                _ = self
+                    .relation_txs
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "relation_unique_tx" => {
+                _ = self
+                    .relation_unique_txs
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "follow" => {
+                _ = self
+                    .follows
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "social_feed" => {
+                _ = self
+                    .social_feeds
+                    .insert(serde_json::from_str(&key)?, serde_json::from_str(value)?)
+            }
+            "rank" => _ = self.ranks.insert(key, serde_json::from_str(value)?),
+            _ => return Err(Error::ParamError(format!("unknown row kind {}", kind))),
+        }
+        Ok(())
+    }
+
+    fn identity_record(&self, v_id: &str) -> Option<IdentityRecord> {
+        self.identities.get(v_id).map(|identity| {
+            IdentityRecord(VertexRecord {
+                v_type: IDENTITIES.to_string(),
+                v_id: v_id.to_string(),
+                attributes: identity.clone(),
+            })
+        })
+    }
+
+    fn contract_record(&self, v_id: &str) -> Option<ContractRecord> {
+        self.contracts.get(v_id).map(|contract| {
+            ContractRecord(VertexRecord {
+                v_type: CONTRACTS.to_string(),
+                v_id: v_id.to_string(),
+                attributes: contract.clone(),
+            })
+        })
+    }
+
+    fn upsert_identity(&mut self, identity: &Identity) {
+        let v_id = identity.primary_key();
+        match self.identities.get_mut(&v_id) {
+            None => {
+                let mut identity = identity.clone();
+                identity.reverse = Some(identity.reverse.unwrap_or(false));
+                self.identities.insert(v_id.clone(), identity);
+            }
+            Some(exist) => {
+                if identity.uid.is_some() {
+                    exist.uid = identity.uid.clone();
+                }
+                if identity.display_name.is_some() {
+                    exist.display_name = identity.display_name.clone();
+                }
+                if identity.profile_url.is_some() {
+                    exist.profile_url = identity.profile_url.clone();
+                }
+                if identity.avatar_url.is_some() {
+                    exist.avatar_url = identity.avatar_url.clone();
+                }
+                if exist.uuid.is_none() {
+                    exist.uuid = identity.uuid;
+                }
+                if exist.created_at.is_none() {
+                    exist.created_at = identity.created_at;
+                }
+                exist.updated_at = exist.updated_at.max(identity.updated_at);
+                exist.expired_at = max_option(exist.expired_at, identity.expired_at);
+                exist.reverse =
+                    Some(exist.reverse.unwrap_or(false) || identity.reverse.unwrap_or(false));
+            }
+        }
+        self.touch(Row::Identity(v_id));
+    }
+
+    /// Add `identity` unless it exists. Followees are met without being fetched,
+    /// so what is known about them is not overwritten.
+    fn insert_identity(&mut self, identity: &Identity) {
+        if !self.identities.contains_key(&identity.primary_key()) {
+            self.upsert_identity(identity);
+        }
+    }
+
+    fn upsert_contract(&mut self, contract: &Contract) {
+        let v_id = contract.primary_key();
+        match self.contracts.get_mut(&v_id) {
+            None => {
+                self.contracts.insert(v_id.clone(), contract.clone());
+            }
+            Some(exist) => {
+                exist.category = contract.category;
+                if contract.symbol.is_some() {
+                    exist.symbol = contract.symbol.clone();
+                }
+                exist.updated_at = exist.updated_at.max(contract.updated_at);
+            }
+        }
+        self.touch(Row::Contract(v_id));
+    }
+
+    fn upsert_domain_collection(&mut self, collection: &DomainCollection) {
+        self.collections
+            .entry(collection.primary_key())
+            .and_modify(|exist| exist.updated_at = exist.updated_at.max(collection.updated_at))
+            .or_insert_with(|| collection.clone());
+        self.touch(Row::Collection(collection.primary_key()));
+    }
+
+    fn upsert_proof(&mut self, e_type: &str, from: &Identity, to: &Identity, record: &ProofRecord) {
+        let key = proof_key(e_type, from, to, record);
+        self.touch(Row::Proof(key.clone()));
+        match self.proofs.get_mut(&key) {
+            None => {
+                let mut record = record.clone();
+                record.0.e_type = e_type.to_string();
+                record.0.from_id = from.primary_key();
+                record.0.from_type = IDENTITIES.to_string();
+                record.0.to_id = to.primary_key();
+                record.0.to_type = IDENTITIES.to_string();
+                self.proofs.insert(key, record);
+            }
+            Some(exist) => {
+                exist.0.attributes.level = record.level;
+                exist.0.attributes.record_id = record.record_id.clone();
+                exist.0.attributes.fetcher = record.fetcher;
+                if exist.created_at.is_none() {
+                    exist.0.attributes.created_at = record.created_at;
+                }
+                exist.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+            }
+        }
+    }
+
+    fn upsert_hold(
+        &mut self,
+        e_type: &str,
+        from: &Identity,
+        to: &dyn Vertex,
+        to_type: &str,
+        record: &HoldRecord,
+    ) {
+        let key = hold_key(e_type, from, to, record);
+        self.touch(Row::Hold(key.clone()));
+        match self.holds.get_mut(&key) {
+            None => {
+                let mut record = record.clone();
+                record.0.e_type = e_type.to_string();
+                record.0.from_id = from.primary_key();
+                record.0.from_type = IDENTITIES.to_string();
+                record.0.to_id = to.primary_key();
+                record.0.to_type = to_type.to_string();
+                self.holds.insert(key, record);
+            }
+            Some(exist) => {
+                exist.0.attributes.source = record.source;
+                exist.0.attributes.transaction = record.transaction.clone();
+                exist.0.attributes.fetcher = record.fetcher;
+                if exist.created_at.is_none() {
+                    exist.0.attributes.created_at = record.created_at;
+                }
+                exist.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+                exist.0.attributes.expired_at = max_option(exist.expired_at, record.expired_at);
+            }
+        }
+    }
+
+    fn upsert_resolve(
+        &mut self,
+        e_type: &str,
+        from: &dyn Vertex,
+        to: &dyn Vertex,
+        record: &ResolveRecord,
+    ) {
+        let key = resolve_key(e_type, from, to, record);
+        self.touch(Row::Resolve(key.clone()));
+        match self.resolves.get_mut(&key) {
+            None => {
+                let mut record = record.clone();
+                record.0.e_type = e_type.to_string();
+                record.0.from_id = from.primary_key();
+                record.0.from_type = from.vertex_type();
+                record.0.to_id = to.primary_key();
+                record.0.to_type = to.vertex_type();
+                self.insert_resolve(key, record);
+            }
+            Some(exist) => {
+                exist.0.attributes.source = record.source;
+                exist.0.attributes.system = record.system;
+                exist.0.attributes.fetcher = record.fetcher;
+                exist.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+            }
+        }
+    }
+
+    fn upsert_part_of_collection(
+        &mut self,
+        from: &DomainCollection,
+        to: &Identity,
+        record: &PartOfCollectionRecord,
+    ) {
+        let key = (
+            PART_OF_COLLECTION.to_string(),
+            from.primary_key(),
+            to.primary_key(),
+            format!(
+                "{},{},{},{}",
+                record.platform, record.name, record.tld, record.status
+            ),
+        );
+        let mut record = record.clone();
+        record.0.e_type = PART_OF_COLLECTION.to_string();
+        record.0.from_id = from.primary_key();
+        record.0.from_type = from.vertex_type();
+        record.0.to_id = to.primary_key();
+        record.0.to_type = IDENTITIES.to_string();
+        self.touch(Row::PartOfCollection(key.clone()));
+        self.part_of_collections.insert(key, record);
+    }
+
+    /// Transactions are discriminated by `transaction_hash`, as in GSQL schema.
+    fn upsert_relation_tx(&mut self, from: &Identity, to: &Identity, record: &RelationTXRecord) {
+        let key = (
+            RELATION_TX.to_string(),
+            from.primary_key(),
+            to.primary_key(),
+            format!("{},{}", record.transaction_hash, record.contract_address),
+        );
+        let mut record = record.clone();
+        record.0.e_type = RELATION_TX.to_string();
+        record.0.from_id = from.primary_key();
+        record.0.from_type = IDENTITIES.to_string();
+        record.0.to_id = to.primary_key();
+        record.0.to_type = IDENTITIES.to_string();
+        if let Some(exist) = self.relation_txs.get(&key) {
+            record.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+        }
+        self.touch(Row::RelationTX(key.clone()));
+        self.relation_txs.insert(key, record);
+    }
+
+    /// At most one aggregation between two identities per token contract. Overwritten on every fetch.
+    fn upsert_relation_unique_tx(
+        &mut self,
+        from: &Identity,
+        to: &Identity,
+        record: &RelationUniqueTXRecord,
+    ) {
+        let key = (
+            RELATION_UNIQUE_TX.to_string(),
+            from.primary_key(),
+            to.primary_key(),
+            record.contract_address.clone(),
+        );
+        let mut record = record.clone();
+        record.0.e_type = RELATION_UNIQUE_TX.to_string();
+        record.0.from_id = from.primary_key();
+        record.0.from_type = IDENTITIES.to_string();
+        record.0.to_id = to.primary_key();
+        record.0.to_type = IDENTITIES.to_string();
+        if let Some(exist) = self.relation_unique_txs.get(&key) {
+            record.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+        }
+        self.touch(Row::RelationUniqueTX(key.clone()));
+        self.relation_unique_txs.insert(key, record);
+    }
+
+    /// Follows are discriminated by `original_from`, `original_to` and `source`, as in GSQL schema.
+    /// Identities not in any `IdentitiesGraph` yet get one of their own,
+    /// as `insert_follow_connection` does.
+    fn upsert_follow(&mut self, from: &Identity, to: &Identity, record: &FollowRecord) {
+        for identity in [from, to] {
+            let v_id = identity.primary_key();
+            if !self.part_of.contains_key(&v_id) {
+                self.allocate_graph(&[v_id], None);
+            }
+        }
+        let key = (
+            FOLLOW.to_string(),
+            record.original_from.clone(),
+            record.original_to.clone(),
+            record.source.to_string(),
+        );
+        let mut record = record.clone();
+        record.0.e_type = FOLLOW.to_string();
+        record.0.from_id = from.primary_key();
+        record.0.from_type = IDENTITIES.to_string();
+        record.0.to_id = to.primary_key();
+        record.0.to_type = IDENTITIES.to_string();
+        if let Some(exist) = self.follows.get(&key) {
+            record.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+        }
+        self.touch(Row::Follow(key.clone()));
+        self.follows.insert(key, record);
+    }
+
+    /// `Social_Feed` has no discriminator: at most one between two identities.
+    fn upsert_social_feed(&mut self, from: &Identity, to: &Identity, record: &SocialFeedRecord) {
+        let key = (
+            SOCIAL_FEED.to_string(),
+            from.primary_key(),
+            to.primary_key(),
+            String::new(),
+        );
+        let mut record = record.clone();
+        record.0.e_type = SOCIAL_FEED.to_string();
+        record.0.from_id = from.primary_key();
+        record.0.from_type = IDENTITIES.to_string();
+        record.0.to_id = to.primary_key();
+        record.0.to_type = IDENTITIES.to_string();
+        if let Some(exist) = self.social_feeds.get(&key) {
+            record.0.attributes.updated_at = exist.updated_at.max(record.updated_at);
+        }
+        self.touch(Row::SocialFeed(key.clone()));
+        self.social_feeds.insert(key, record);
+    }
+
+    /// Upsert every vertex and edge in `edges`.
+    /// Returns identities which should be put into the allocated `IdentitiesGraph`.
+    fn apply_edges(&mut self, edges: &EdgeList) -> Vec<String> {
+        let mut connected = Vec::new();
+        for edge in edges.iter() {
+            match edge {
+                EdgeWrapperEnum::ProofForward(w) | EdgeWrapperEnum::ProofBackward(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_proof(edge.e_type(), &w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::HoldIdentity(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_hold(HOLD_IDENTITY, &w.source, &w.target, IDENTITIES, &w.edge);
+                }
+                EdgeWrapperEnum::HoldContract(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_contract(&w.target);
+                    self.upsert_hold(HOLD_CONTRACT, &w.source, &w.target, CONTRACTS, &w.edge);
+                }
+                EdgeWrapperEnum::Resolve(w) | EdgeWrapperEnum::ReverseResolve(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_resolve(edge.e_type(), &w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::ResolveContract(w) => {
+                    self.upsert_contract(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_resolve(RESOLVE_CONTRACT, &w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::ReverseResolveContract(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_contract(&w.target);
+                    self.upsert_resolve(REVERSE_RESOLVE_CONTRACT, &w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::PartOfIdentitiesGraph(w) => {
+                    self.upsert_identity(&w.target);
+                    if w.source.id == FAKE_GRAPH_ID {
+                        connected.push(w.target.primary_key());
+                    } else {
+                        self.graphs
+                            .entry(w.source.id.clone())
+                            .or_insert_with(|| w.source.clone());
+                        self.part_of
+                            .insert(w.target.primary_key(), w.source.id.clone());
+                        self.touch(Row::Graph(w.source.id.clone()));
+                        self.touch(Row::PartOf(w.target.primary_key()));
+                    }
+                }
+                EdgeWrapperEnum::PartOfCollection(w) => {
+                    self.upsert_domain_collection(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_part_of_collection(&w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::RelationTX(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_relation_tx(&w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::RelationUniqueTX(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_relation_unique_tx(&w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::Follow(w) => {
+                    self.upsert_identity(&w.source);
+                    self.insert_identity(&w.target);
+                    self.upsert_follow(&w.source, &w.target, &w.edge);
+                }
+                EdgeWrapperEnum::SocialFeed(w) => {
+                    self.upsert_identity(&w.source);
+                    self.upsert_identity(&w.target);
+                    self.upsert_social_feed(&w.source, &w.target, &w.edge);
+                }
+            }
+        }
+        connected
+    }
+
+    /// Delete Proof / Hold / Resolve edges in `edges`. Vertices and graph membership are kept.
+    fn remove_edges(&mut self, edges: &EdgeList) {
+        for edge in edges.iter() {
+            let row = match edge {
+                EdgeWrapperEnum::ProofForward(w) | EdgeWrapperEnum::ProofBackward(w) => {
+                    Row::Proof(proof_key(edge.e_type(), &w.source, &w.target, &w.edge))
+                }
+                EdgeWrapperEnum::HoldIdentity(w) => {
+                    Row::Hold(hold_key(HOLD_IDENTITY, &w.source, &w.target, &w.edge))
+                }
+                EdgeWrapperEnum::HoldContract(w) => {
+                    Row::Hold(hold_key(HOLD_CONTRACT, &w.source, &w.target, &w.edge))
+                }
+                EdgeWrapperEnum::Resolve(w) | EdgeWrapperEnum::ReverseResolve(w) => {
+                    Row::Resolve(resolve_key(edge.e_type(), &w.source, &w.target, &w.edge))
+                }
+                EdgeWrapperEnum::ResolveContract(w) => {
+                    Row::Resolve(resolve_key(RESOLVE_CONTRACT, &w.source, &w.target, &w.edge))
+                }
+                EdgeWrapperEnum::ReverseResolveContract(w) => Row::Resolve(resolve_key(
+                    REVERSE_RESOLVE_CONTRACT,
+                    &w.source,
+                    &w.target,
+                    &w.edge,
+                )),
+                EdgeWrapperEnum::PartOfIdentitiesGraph(_)
+                | EdgeWrapperEnum::PartOfCollection(_)
+                | EdgeWrapperEnum::RelationTX(_)
+                | EdgeWrapperEnum::RelationUniqueTX(_)
+                | EdgeWrapperEnum::Follow(_)
+                | EdgeWrapperEnum::SocialFeed(_) => continue,
+            };
+            match &row {
+                Row::Proof(key) => _ = self.proofs.remove(key),
+                Row::Hold(key) => _ = self.holds.remove(key),
+                Row::Resolve(key) => self.remove_resolve(key),
+                _ => {}
+            }
+            self.touch(row);
+        }
+    }
+
+    /// Local version of `id_allocation`: reuse the oldest `IdentitiesGraph`
+    /// among `vids`, merge the others into it, or create `proposed` (a new one if `None`).
+    fn allocate_graph(
+        &mut self,
+        vids: &[String],
+        proposed: Option<IdentitiesGraph>,
+    ) -> Option<IdentitiesGraph> {
+        if vids.is_empty() {
+            return None;
+        }
+        let existing: BTreeSet<String> = vids
+            .iter()
+            .filter_map(|vid| self.part_of.get(vid).cloned())
+            .collect();
+        let oldest = existing
+            .iter()
+            .filter_map(|id| self.graphs.get(id))
+            .min_by_key(|g| (g.updated_nanosecond, g.id.clone()))
+            .cloned();
+        let graph = match oldest {
+            Some(graph) => graph,
+            None => {
+                let graph = proposed.unwrap_or_else(|| IdentitiesGraph {
+                    id: Uuid::new_v4().to_string(),
+                    updated_nanosecond: chrono::Utc::now().timestamp_micros(),
+                });
+                self.graphs.insert(graph.id.clone(), graph.clone());
+                self.touch(Row::Graph(graph.id.clone()));
+                graph
+            }
+        };
+        let mut moved: Vec<String> = vids.to_vec();
+        for merged in existing.iter().filter(|id| **id != graph.id) {
+            self.graphs.remove(merged);
+            self.touch(Row::Graph(merged.clone()));
+            self.ranks.remove(merged);
+            self.touch(Row::Rank(merged.clone()));
+            for (vid, graph_id) in self.part_of.iter_mut() {
+                if graph_id == merged {
+                    *graph_id = graph.id.clone();
+                    moved.push(vid.clone());
+                }
+            }
+        }
+        for vid in vids.iter() {
+            self.part_of.insert(vid.clone(), graph.id.clone());
+        }
+        for vid in moved.into_iter() {
+            self.touch(Row::PartOf(vid));
+        }
+        Some(graph)
+    }
+
+    /// Move `vids` into `graph`, created if missing, whatever graph they were in.
+    fn reassign_graph(&mut self, vids: &[String], graph: &IdentitiesGraph) {
+        if !self.graphs.contains_key(&graph.id) {
+            self.graphs.insert(graph.id.clone(), graph.clone());
+            self.touch(Row::Graph(graph.id.clone()));
+        }
+        for vid in vids.iter() {
+            self.part_of.insert(vid.clone(), graph.id.clone());
+            self.touch(Row::PartOf(vid.clone()));
+        }
+    }
+
+    fn proofs_between<'a>(
+        &'a self,
+        a: &'a str,
+        b: &'a str,
+    ) -> impl Iterator<Item = &'a ProofRecord> {
+        self.proofs
+            .values()
+            .filter(move |p| (p.from_id == a && p.to_id == b) || (p.from_id == b && p.to_id == a))
+    }
+
+    /// Identities connected with `v_id` by any proof edge.
+    fn proof_neighbors(&self, v_id: &str) -> BTreeSet<String> {
+        self.proofs
+            .values()
+            .filter_map(|p| {
+                if p.from_id == v_id {
+                    Some(p.to_id.clone())
+                } else if p.to_id == v_id {
+                    Some(p.from_id.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Hops from `v_id` walking `Relation_Unique_TX` in both directions, up to `depth`.
+    fn relation_distances(&self, v_id: &str, depth: u16) -> HashMap<String, u16> {
+        let mut distances = HashMap::from([(v_id.to_string(), 0)]);
+        let mut queue = VecDeque::from([v_id.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            let hops = distances[&current];
+            if hops >= depth {
+                continue;
+            }
+            for r in self.relation_unique_txs.values() {
+                let next = if r.from_id == current {
+                    &r.to_id
+                } else if r.to_id == current {
+                    &r.from_id
+                } else {
+                    continue;
+                };
+                if !distances.contains_key(next) {
+                    distances.insert(next.clone(), hops + 1);
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+        distances
+    }
+
+    /// `Follow` edges into (`incoming`) or out of the `IdentitiesGraph` `graph_id`,
+    /// with ends replaced by the `IdentitiesGraph`s of `original_from` / `original_to`.
+    fn follows_of(&self, graph_id: &str, incoming: bool) -> Vec<FollowRecord> {
+        let graph_of = |v_id: &str| self.part_of.get(v_id).cloned().unwrap_or_default();
+        self.follows
+            .values()
+            .filter(|f| {
+                let end = if incoming { &f.to_id } else { &f.from_id };
+                graph_of(end) == graph_id
+            })
+            .map(|f| {
+                let mut record = f.clone();
+                record.0.from_id = graph_of(&f.from_id);
+                record.0.from_type = IDENTITIES_GRAPH.to_string();
+                record.0.to_id = graph_of(&f.to_id);
+                record.0.to_type = IDENTITIES_GRAPH.to_string();
+                record
+            })
+            .collect()
+    }
+
+    fn holds_of_type<'a>(&'a self, e_type: &'a str) -> impl Iterator<Item = &'a HoldRecord> {
+        self.holds.values().filter(move |h| h.e_type == e_type)
+    }
+
+    fn resolves_of_type<'a>(&'a self, e_type: &'a str) -> impl Iterator<Item = &'a ResolveRecord> {
+        self.resolves.values().filter(move |r| r.e_type == e_type)
+    }
+
+    /// `resolves_of_type` narrowed to domain `name`, in the same order.
+    fn resolves_of_name<'a>(
+        &'a self,
+        e_type: &'a str,
+        name: &str,
+    ) -> impl Iterator<Item = &'a ResolveRecord> {
+        self.resolve_names
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter_map(|key| self.resolves.get(key))
+            .filter(move |r| r.e_type == e_type)
+    }
+
+    fn insert_resolve(&mut self, key: EdgeKey, record: ResolveRecord) {
+        self.resolve_names
+            .entry(record.name.clone())
+            .or_default()
+            .insert(key.clone());
+        self.resolves.insert(key, record);
+    }
+
+    fn remove_resolve(&mut self, key: &EdgeKey) {
+        let Some(record) = self.resolves.remove(key) else {
+            return;
+        };
+        if let Some(keys) = self.resolve_names.get_mut(&record.name) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.resolve_names.remove(&record.name);
+            }
+        }
+    }
+
+    fn platform_of(&self, v_id: &str) -> Platform {
+        self.identities
+            .get(v_id)
+            .map(|i| i.platform)
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl GraphStore for MemoryStore {
+    async fn batch_upsert(&self, edges: EdgeList) -> Result<(), Error> {
+        let mut tables = self.write();
+        let vids = tables.apply_edges(&edges);
+        tables.allocate_graph(&vids, None);
+        Ok(())
+    }
+
+    async fn batch_upsert_domains(&self, edges: EdgeList) -> Result<(), Error> {
+        // Same as TigerGraph: domain search results are not allocated into any IdentitiesGraph.
+        self.write().apply_edges(&edges);
+        Ok(())
+    }
+
+    async fn upsert_domain_collection(&self, name: &str) -> Result<(), Error> {
+        self.write().upsert_domain_collection(&DomainCollection {
+            id: name.to_string(),
+            ..Default::default()
+        });
+        Ok(())
+    }
+
+    async fn find_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<IdentityRecord>, Error> {
+        Ok(self
+            .read()
+            .identity_record(&format!("{},{}", platform, identity)))
+    }
+
+    async fn identities_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
+        let tables = self.read();
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| tables.identity_record(&id).map(|r| (id, Some(r))))
+            .collect())
+    }
+
+    async fn owners_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
+        let tables = self.read();
+        let mut result = HashMap::new();
+        for id in ids.into_iter() {
+            let owner = tables
+                .holds_of_type(HOLD_IDENTITY)
+                .filter(|h| h.to_id == id && tables.platform_of(&h.from_id) == Platform::Ethereum)
+                .last()
+                .and_then(|h| tables.identity_record(&h.from_id));
+            if let Some(owner) = owner {
+                result.insert(id, Some(owner));
+            }
+        }
+        Ok(result)
+    }
+
+    async fn contracts_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<ContractRecord>>, Error> {
+        let tables = self.read();
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| tables.contract_record(&id).map(|r| (id, Some(r))))
+            .collect())
+    }
+
+    async fn neighbors(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityWithSource>, Error> {
+        let tables = self.read();
+        let start = identity.v_id.clone();
+        if !tables.identities.contains_key(&start) {
+            return Ok(vec![]);
+        }
+        let mut visited: HashSet<String> = HashSet::from([start.clone()]);
+        let mut visited2: HashSet<String> = HashSet::from([start.clone()]);
+        let mut sources: HashMap<String, Vec<DataSource>> = HashMap::new();
+        let mut result_set: Vec<String> = vec![start.clone()];
+        let mut seed: BTreeSet<String> = BTreeSet::from([start.clone()]);
+
+        fn add_sources(
+            sources: &mut HashMap<String, Vec<DataSource>>,
+            v_id: &str,
+            new: &[DataSource],
+        ) {
+            let list = sources.entry(v_id.to_string()).or_default();
+            for s in new.iter() {
+                if !list.contains(s) {
+                    list.push(*s);
+                }
+            }
+        }
+
+        for _ in 0..depth {
+            if seed.is_empty() {
+                break;
+            }
+            let mut pool: BTreeSet<String> = BTreeSet::new();
+
+            // proof: two proof edges (both directions) between `s` and `t`
+            let mut found: BTreeMap<String, Vec<DataSource>> = BTreeMap::new();
+            for s in seed.iter() {
+                for t in tables.proof_neighbors(s) {
+                    if visited.contains(&t) {
+                        continue;
+                    }
+                    let edges: Vec<&ProofRecord> = tables
+                        .proofs_between(s, &t)
+                        .filter(|e| match_level(e.level, min_level))
+                        .collect();
+                    if edges.len() < 2 {
+                        continue;
+                    }
+                    let entry = found.entry(t.clone()).or_default();
+                    entry.extend(sources.get(s).cloned().unwrap_or_default());
+                    entry.extend(edges.iter().map(|e| e.source));
+                }
+            }
+            for (t, new_sources) in found.into_iter() {
+                add_sources(&mut sources, &t, &new_sources);
+                visited.insert(t.clone());
+                if !result_set.contains(&t) {
+                    result_set.push(t.clone());
+                }
+                pool.insert(t);
+            }
+
+            // hold: Hold_Identity in both directions, never through ENS
+            let mut found: BTreeMap<String, Vec<DataSource>> = BTreeMap::new();
+            for s in seed.iter() {
+                if tables.platform_of(s) == Platform::ENS {
+                    continue;
+                }
+                for h in tables.holds_of_type(HOLD_IDENTITY) {
+                    let v = if h.from_id == *s {
+                        &h.to_id
+                    } else if h.to_id == *s {
+                        &h.from_id
+                    } else {
+                        continue;
+                    };
+                    if visited2.contains(v) || tables.platform_of(v) == Platform::ENS {
+                        continue;
+                    }
+                    let entry = found.entry(v.clone()).or_default();
+                    entry.extend(sources.get(s).cloned().unwrap_or_default());
+                    entry.push(h.source);
+                }
+            }
+            for (v, new_sources) in found.into_iter() {
+                add_sources(&mut sources, &v, &new_sources);
+                visited2.insert(v.clone());
+                if !result_set.contains(&v) {
+                    result_set.push(v.clone());
+                }
+                pool.insert(v);
+            }
+
+            seed = pool;
+        }
+
+        let members: HashSet<&String> = result_set.iter().collect();
+        let mut reversed: HashSet<String> = HashSet::new();
+        for r in tables.resolves_of_type(REVERSE_RESOLVE) {
+            if members.contains(&r.from_id)
+                && members.contains(&r.to_id)
+                && is_domain_system(&r.system)
+            {
+                reversed.insert(r.to_id.clone());
+            }
+        }
+        for r in tables.resolves_of_type(REVERSE_RESOLVE_CONTRACT) {
+            if members.contains(&r.from_id)
+                && [
+                    DomainNameSystem::Genome,
+                    DomainNameSystem::ENS,
+                    DomainNameSystem::SNS,
+                ]
+                .contains(&r.system)
+            {
+                reversed.insert(r.from_id.clone());
+            }
+        }
+
+        result_set.sort();
+        Ok(result_set
+            .iter()
+            .filter(|v_id| **v_id != start)
+            .filter_map(|v_id| tables.identity_record(v_id))
+            .filter(|record| {
+                match_reverse(reverse, &record.platform, reversed.contains(&record.v_id))
+            })
+            .map(|record| {
+                let platform = record.platform;
+                let is_reverse = reversed.contains(&record.v_id);
+                IdentityWithSource {
+                    sources: sources.get(&record.v_id).cloned().unwrap_or_default(),
+                    reverse: (DomainNameSystem::from(platform) != DomainNameSystem::Unknown
+                        || platform == Platform::Ethereum)
+                        .then_some(is_reverse),
+                    identity: record,
+                }
+            })
+            .collect())
+    }
+
+    async fn neighbors_with_traversal(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<EdgeUnion>, Error> {
+        let tables = self.read();
+        let mut visited: HashSet<String> = HashSet::new();
+        let mut visited2: HashSet<String> = HashSet::new();
+        let mut proof_keys: BTreeSet<EdgeKey> = BTreeSet::new();
+        let mut hold_keys: BTreeSet<EdgeKey> = BTreeSet::new();
+        let mut seed: BTreeSet<String> = BTreeSet::from([identity.v_id.clone()]);
+
+        for _ in 0..depth {
+            if seed.is_empty() {
+                break;
+            }
+            let mut pool: BTreeSet<String> = BTreeSet::new();
+
+            let mut candidates: BTreeMap<String, Vec<EdgeKey>> = BTreeMap::new();
+            for (key, p) in tables.proofs.iter() {
+                let v = if seed.contains(&p.from_id) {
+                    &p.to_id
+                } else if seed.contains(&p.to_id) {
+                    &p.from_id
+                } else {
+                    continue;
+                };
+                if !visited.contains(v) {
+                    candidates.entry(v.clone()).or_default().push(key.clone());
+                }
+            }
+            for (v, keys) in candidates.into_iter() {
+                if keys.len() < 2 {
+                    continue;
+                }
+                proof_keys.extend(keys);
+                visited.insert(v.clone());
+                pool.insert(v);
+            }
+
+            let mut found: BTreeSet<String> = BTreeSet::new();
+            for (key, h) in tables.holds.iter() {
+                if h.e_type != HOLD_IDENTITY {
+                    continue;
+                }
+                let v = if seed.contains(&h.from_id) {
+                    &h.to_id
+                } else if seed.contains(&h.to_id) {
+                    &h.from_id
+                } else {
+                    continue;
+                };
+                let platform = tables.platform_of(v);
+                if visited2.contains(v)
+                    || platform == Platform::Genome
+                    || platform == Platform::ENS
+                    || platform == Platform::SNS
+                {
+                    continue;
+                }
+                hold_keys.insert(key.clone());
+                found.insert(v.clone());
+            }
+            visited2.extend(found.iter().cloned());
+            pool.extend(found);
+
+            seed = pool;
+        }
+
+        let mut edges: Vec<EdgeUnion> = proof_keys
+            .iter()
+            .filter_map(|key| tables.proofs.get(key).cloned())
+            .map(EdgeUnion::ProofRecord)
+            .collect();
+        edges.extend(
+            hold_keys
+                .iter()
+                .filter_map(|key| tables.holds.get(key).cloned())
+                .map(EdgeUnion::HoldRecord),
+        );
+        edges.sort_by_cached_key(EdgeUnion::sort_key);
+        Ok(edges)
+    }
+
+    async fn neighbors_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<IdentityWithSource>, Error> {
+        let neighbors = self.neighbors(identity, depth, reverse, min_level).await?;
+        Ok(neighbors.into_iter().skip(skip).take(limit).collect())
+    }
+
+    async fn neighbors_with_traversal_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<EdgeUnion>, Error> {
+        let edges = self.neighbors_with_traversal(identity, depth).await?;
+        Ok(edges.into_iter().skip(skip).take(limit).collect())
+    }
+
+    async fn relation(
+        &self,
+        source: &IdentityRecord,
+        target: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error> {
+        let tables = self.read();
+        let from_source = tables.relation_distances(&source.v_id, depth);
+        let from_target = tables.relation_distances(&target.v_id, depth);
+        let on_path = |a: &str, b: &str| match (from_source.get(a), from_target.get(b)) {
+            (Some(s), Some(t)) => u32::from(*s) + 1 + u32::from(*t) <= u32::from(depth),
+            _ => false,
+        };
+        Ok(tables
+            .relation_unique_txs
+            .values()
+            .filter(|r| on_path(&r.from_id, &r.to_id) || on_path(&r.to_id, &r.from_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn expand_relation(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error> {
+        let tables = self.read();
+        let distances = tables.relation_distances(&identity.v_id, depth);
+        let walked = |v_id: &str| distances.get(v_id).is_some_and(|hops| *hops < depth);
+        Ok(tables
+            .relation_unique_txs
+            .values()
+            .filter(|r| walked(&r.from_id) || walked(&r.to_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn path(
+        &self,
+        from: &IdentityRecord,
+        to: &IdentityRecord,
+        max_depth: u16,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityPath>, Error> {
+        let tables = self.read();
+        // `Proof_Backward` only mirrors `Proof_Forward`.
+        let proofs = tables
+            .proofs
+            .values()
+            .filter(|p| p.e_type == PROOF_EDGE && match_level(p.level, min_level))
+            .map(|p| PathHop {
+                e_type: p.e_type.clone(),
+                from_id: p.from_id.clone(),
+                to_id: p.to_id.clone(),
+                source: p.source,
+                level: Some(p.level),
+                updated_at: p.updated_at,
+            });
+        let holds = tables
+            .holds
+            .values()
+            .filter(|h| h.e_type == HOLD_IDENTITY)
+            .map(|h| PathHop {
+                e_type: h.e_type.clone(),
+                from_id: h.from_id.clone(),
+                to_id: h.to_id.clone(),
+                source: h.source,
+                level: None,
+                updated_at: h.updated_at,
+            });
+        let resolves = tables
+            .resolves
+            .values()
+            .filter(|r| r.e_type == RESOLVE || r.e_type == REVERSE_RESOLVE)
+            .map(|r| PathHop {
+                e_type: r.e_type.clone(),
+                from_id: r.from_id.clone(),
+                to_id: r.to_id.clone(),
+                source: r.source,
+                level: None,
+                updated_at: r.updated_at,
+            });
+        let hops: Vec<PathHop> = proofs.chain(holds).chain(resolves).collect();
+        Ok(shortest_paths(&hops, &from.v_id, &to.v_id, max_depth))
+    }
+
+    async fn followers(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        Ok(self
+            .read()
+            .follows_of(graph_id, true)
+            .into_iter()
+            .skip(offset as usize * limit as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn following(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        Ok(self
+            .read()
+            .follows_of(graph_id, false)
+            .into_iter()
+            .skip(offset as usize * limit as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn nfts(
+        &self,
+        identity: &IdentityRecord,
+        category: Option<Vec<ContractCategory>>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<HoldRecord>, Error> {
+        if identity.platform != Platform::Ethereum && identity.platform != Platform::Solana {
+            return Ok(vec![]);
+        }
+        let categories = category.unwrap_or_default();
+        let tables = self.read();
+        let mut holds: Vec<&HoldRecord> = tables
+            .holds_of_type(HOLD_CONTRACT)
+            .filter(|h| h.from_id == identity.v_id)
+            .filter(|h| {
+                categories.is_empty()
+                    || tables
+                        .contracts
+                        .get(&h.to_id)
+                        .is_some_and(|c| categories.contains(&c.category))
+            })
+            .collect();
+        holds.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+        Ok(holds.into_iter().skip(skip).take(limit).cloned().collect())
+    }
+
+    async fn resolve_reverse_domains(
+        &self,
+        identity: &IdentityRecord,
+    ) -> Result<Vec<ResolveReverse>, Error> {
+        let tables = self.read();
+        let resolves_back = |e_type: &str, from: &str| {
+            tables
+                .resolves_of_type(e_type)
+                .any(|r| r.from_id == from && r.to_id == identity.v_id)
+        };
+        let mut result = vec![];
+        for r in tables.resolves.values() {
+            if r.from_id != identity.v_id {
+                continue;
+            }
+            let matched = (r.e_type == REVERSE_RESOLVE && resolves_back(RESOLVE, &r.to_id))
+                || (r.e_type == REVERSE_RESOLVE_CONTRACT
+                    && resolves_back(RESOLVE_CONTRACT, &r.to_id));
+            if matched {
+                let mut resolve_reverse = ResolveReverse::from(r.attributes.clone());
+                resolve_reverse.reverse = true;
+                result.push(resolve_reverse);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn find_expand_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<ExpandIdentityRecord>, Error> {
+        let tables = self.read();
+        let v_id = format!("{},{}", platform, identity);
+        let record = match tables.identity_record(&v_id) {
+            None => return Ok(None),
+            Some(record) => record,
+        };
+        let mut owner_address = vec![];
+        let mut resolve_address = vec![];
+        if is_domain_platform(platform) || *platform == Platform::Farcaster {
+            for h in tables.holds_of_type(HOLD_IDENTITY) {
+                if h.to_id == v_id {
+                    if let Some(owner) = tables.identities.get(&h.from_id) {
+                        push_address(&mut owner_address, address_of(owner));
+                    }
+                }
+            }
+        }
+        if is_domain_platform(platform) {
+            for r in tables.resolves_of_type(RESOLVE) {
+                if r.from_id == v_id {
+                    if let Some(target) = tables.identities.get(&r.to_id) {
+                        push_address(&mut resolve_address, address_of(target));
+                    }
+                }
+            }
+        }
+        Ok(Some(ExpandIdentityRecord {
+            record,
+            owner_address: (!owner_address.is_empty()).then_some(owner_address),
+            resolve_address: (!resolve_address.is_empty()).then_some(resolve_address),
+        }))
+    }
+
+    async fn find_identity_graph(
+        &self,
+        platform: &Platform,
+        identity: &str,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Option<IdentityGraph>, Error> {
+        let tables = self.read();
+        let seed = format!("{},{}", platform, identity);
+        if !tables.identities.contains_key(&seed) {
+            return Ok(None);
+        }
+        let graph_id = match tables.part_of.get(&seed) {
+            None => return Ok(None),
+            Some(graph_id) => graph_id.clone(),
+        };
+
+        let mut members: Vec<String> = tables
+            .part_of
+            .iter()
+            .filter(|(_, g)| **g == graph_id)
+            .map(|(v_id, _)| v_id.clone())
+            .filter(|v_id| {
+                tables.identities.get(v_id).is_some_and(|i| {
+                    match_reverse(reverse, &i.platform, i.reverse.unwrap_or(false))
+                })
+            })
+            .collect();
+        members.sort();
+        members.truncate(500);
+        let vset: HashSet<&String> = members.iter().collect();
+
+        let mut degree: HashMap<String, u32> = HashMap::from([(seed.clone(), 1)]);
+        let mut owner: HashMap<String, Vec<Address>> = HashMap::new();
+        let mut resolve: HashMap<String, Vec<Address>> = HashMap::new();
+        let mut seen: HashSet<(String, String, String, String)> = HashSet::new();
+        let mut edges: Vec<IdentityConnection> = vec![];
+        let mut connect = |source: &str, target: &str, data_source: DataSource, edge_type: &str| {
+            let key = (
+                source.to_string(),
+                target.to_string(),
+                data_source.to_string(),
+                edge_type.to_string(),
+            );
+            if seen.insert(key) {
+                edges.push(IdentityConnection {
+                    edge_type: edge_type.to_string(),
+                    data_source,
+                    source: source.to_string(),
+                    target: target.to_string(),
+                });
+            }
+        };
+        let mut inc = |v_id: &str| *degree.entry(v_id.to_string()).or_default() += 1;
+
+        for p in tables.proofs.values() {
+            if vset.contains(&p.from_id)
+                && vset.contains(&p.to_id)
+                && match_level(p.level, min_level)
+            {
+                connect(&p.from_id, &p.to_id, p.source, "Proof");
+                inc(&p.from_id);
+                inc(&p.to_id);
+            }
+        }
+        for h in tables.holds_of_type(HOLD_IDENTITY) {
+            if !vset.contains(&h.from_id) || !vset.contains(&h.to_id) {
+                continue;
+            }
+            let (Some(holder), Some(held)) = (
+                tables.identities.get(&h.from_id),
+                tables.identities.get(&h.to_id),
+            ) else {
+                continue;
+            };
+            push_address(
+                owner.entry(h.to_id.clone()).or_default(),
+                address_of(holder),
+            );
+            if RESOLVE_PLATFORMS.contains(&held.platform) {
+                continue;
+            }
+            connect(&h.from_id, &h.to_id, h.source, "Hold");
+            inc(&h.from_id);
+            inc(&h.to_id);
+            for r in tables.resolves_of_type(RESOLVE) {
+                if r.from_id == h.to_id && vset.contains(&r.to_id) {
+                    if let Some(resolved) = tables.identities.get(&r.to_id) {
+                        push_address(
+                            resolve.entry(h.to_id.clone()).or_default(),
+                            address_of(resolved),
+                        );
+                    }
+                    inc(&h.from_id);
+                    inc(&r.to_id);
+                }
+            }
+        }
+        for r in tables.resolves_of_type(RESOLVE) {
+            if !vset.contains(&r.from_id) || !vset.contains(&r.to_id) {
+                continue;
+            }
+            if !RESOLVE_PLATFORMS.contains(&tables.platform_of(&r.from_id)) {
+                continue;
+            }
+            connect(&r.from_id, &r.to_id, r.source, "Resolve");
+            if let Some(resolved) = tables.identities.get(&r.to_id) {
+                push_address(
+                    resolve.entry(r.from_id.clone()).or_default(),
+                    address_of(resolved),
+                );
+            }
+            inc(&r.from_id);
+            inc(&r.to_id);
+        }
+        for r in tables.resolves_of_type(REVERSE_RESOLVE) {
+            if vset.contains(&r.from_id) && vset.contains(&r.to_id) {
+                connect(&r.from_id, &r.to_id, r.source, "Reverse_Resolve");
+                inc(&r.from_id);
+                inc(&r.to_id);
+            }
+        }
+
+        let vertices: Vec<ExpandIdentityRecord> = members
+            .iter()
+            .filter(|v_id| degree.get(*v_id).copied().unwrap_or_default() > 0)
+            .filter_map(|v_id| tables.identity_record(v_id))
+            .map(|record| ExpandIdentityRecord {
+                owner_address: owner.remove(&record.v_id),
+                resolve_address: resolve.remove(&record.v_id),
+                record,
+            })
+            .collect();
+
+        if edges.is_empty() && vertices.len() > 1 {
+            return Ok(None);
+        }
+        let mut graph = IdentityGraph {
+            graph_id,
+            vertices,
+            edges,
+        };
+        graph.sort();
+        Ok(Some(graph))
+    }
+
+    async fn find_resolve(
+        &self,
+        name: &str,
+        domain_system: &DomainNameSystem,
+    ) -> Result<Option<ResolveEdge>, Error> {
+        Ok(self
+            .find_domain(name, domain_system)
+            .into_resolve_edge(domain_system))
+    }
+
+    async fn find_hold_nft(
+        &self,
+        id: &str,
+        chain: &Chain,
+        address: &str,
+    ) -> Result<Option<HoldRecord>, Error> {
+        let tables = self.read();
+        let hold = tables
+            .holds_of_type(HOLD_CONTRACT)
+            .find(|h| {
+                h.id == id
+                    && tables
+                        .contracts
+                        .get(&h.to_id)
+                        .is_some_and(|c| c.chain == *chain && c.address == address)
+            })
+            .cloned();
+        Ok(hold)
+    }
+
+    async fn domain_available_search(
+        &self,
+        name: &str,
+    ) -> Result<Option<DomainAvailableSearch>, Error> {
+        Ok(self
+            .collection_domains(name)
+            .map(|(collection, existing)| DomainAvailableSearch::fill(name, collection, &existing)))
+    }
+
+    async fn delete_graph_inner_connection(&self, v_id: String) -> Result<(), Error> {
+        if v_id.is_empty() {
+            return Err(Error::ParamError("v_id is required".to_string()));
+        }
+        let mut tables = self.write();
+        let graph_id = match tables.part_of.get(&v_id) {
+            None => return Ok(()),
+            Some(graph_id) => graph_id.clone(),
+        };
+        let members: HashSet<String> = tables
+            .part_of
+            .iter()
+            .filter(|(_, g)| **g == graph_id)
+            .map(|(v, _)| v.clone())
+            .collect();
+        // Contracts are never members, so an edge touching one member and one
+        // contract counts as inner connection too.
+        let inner = |from: &String, to: &String, to_contract: bool, from_contract: bool| {
+            (members.contains(from) || from_contract) && (members.contains(to) || to_contract)
+        };
+        let proofs: Vec<EdgeKey> = tables
+            .proofs
+            .iter()
+            .filter(|(_, p)| inner(&p.from_id, &p.to_id, false, false))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let holds: Vec<EdgeKey> = tables
+            .holds
+            .iter()
+            .filter(|(_, h)| inner(&h.from_id, &h.to_id, h.e_type == HOLD_CONTRACT, false))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let resolves: Vec<EdgeKey> = tables
+            .resolves
+            .iter()
+            .filter(|(_, r)| {
+                inner(
+                    &r.from_id,
+                    &r.to_id,
+                    r.e_type == REVERSE_RESOLVE_CONTRACT,
+                    r.e_type == RESOLVE_CONTRACT,
+                )
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in proofs.into_iter() {
+            tables.proofs.remove(&key);
+            tables.touch(Row::Proof(key));
+        }
+        for key in holds.into_iter() {
+            tables.holds.remove(&key);
+            tables.touch(Row::Hold(key));
+        }
+        for key in resolves.into_iter() {
+            tables.remove_resolve(&key);
+            tables.touch(Row::Resolve(key));
+        }
+        Ok(())
+    }
+
+    async fn delete_edges(&self, edges: EdgeList) -> Result<(), Error> {
+        self.write().remove_edges(&edges);
+        Ok(())
+    }
+
+    async fn split_identity_graph(
+        &self,
+        graph_id: &str,
+        identities: Vec<Identity>,
+    ) -> Result<String, Error> {
+        if identities.is_empty() {
+            return Err(Error::ParamError("identities are required".to_string()));
+        }
+        let graph = IdentitiesGraph {
+            id: Uuid::new_v4().to_string(),
+            updated_nanosecond: chrono::Utc::now().timestamp_micros(),
+        };
+        let vids: Vec<String> = identities.iter().map(|i| i.primary_key()).collect();
+        let mut tables = self.write();
+        let vids: Vec<String> = vids
+            .into_iter()
+            .filter(|v_id| tables.part_of.get(v_id).is_some_and(|id| id == graph_id))
+            .collect();
+        tables.reassign_graph(&vids, &graph);
+        Ok(graph.id)
+    }
+
+    async fn delete_domain_collection(&self, name: &str) -> Result<(), Error> {
+        if name.is_empty() {
+            return Err(Error::ParamError("name is required".to_string()));
+        }
+        let mut tables = self.write();
+        let basenames: HashSet<String> = tables
+            .identities
+            .iter()
+            .filter(|(_, i)| i.platform == Platform::Basenames)
+            .map(|(v_id, _)| v_id.clone())
+            .collect();
+        let removed: Vec<EdgeKey> = tables
+            .part_of_collections
+            .iter()
+            .filter(|(_, e)| e.from_id == name && !basenames.contains(&e.to_id))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in removed.into_iter() {
+            tables.part_of_collections.remove(&key);
+            tables.touch(Row::PartOfCollection(key));
+        }
+        Ok(())
+    }
+
+    async fn clear_domain_search_cache(&self) -> Result<usize, Error> {
+        let mut tables = self.write();
+        let keys: Vec<EdgeKey> = tables.part_of_collections.keys().cloned().collect();
+        for key in keys.into_iter() {
+            tables.part_of_collections.remove(&key);
+            tables.touch(Row::PartOfCollection(key));
+        }
+        let names: Vec<String> = tables.collections.keys().cloned().collect();
+        let removed = names.len();
+        for name in names.into_iter() {
+            tables.collections.remove(&name);
+            tables.touch(Row::Collection(name));
+        }
+        Ok(removed)
+    }
+
+    async fn rank_inputs(&self, limit: u16, page: u32) -> Result<Vec<RankGraph>, Error> {
+        let tables = self.read();
+        let mut members: BTreeMap<&String, Vec<&String>> = tables
+            .graphs
+            .keys()
+            .map(|graph_id| (graph_id, vec![]))
+            .collect();
+        for (v_id, graph_id) in tables.part_of.iter() {
+            if let Some(vids) = members.get_mut(graph_id) {
+                vids.push(v_id);
+            }
+        }
+        let domains_of = |v_id: &String| {
+            let identities = tables.holds_of_type(HOLD_IDENTITY).filter(|h| {
+                h.from_id == *v_id
+                    && tables
+                        .identities
+                        .get(&h.to_id)
+                        .is_some_and(|to| is_domain_platform(&to.platform))
+            });
+            let contracts = tables.holds_of_type(HOLD_CONTRACT).filter(|h| {
+                h.from_id == *v_id
+                    && tables
+                        .contracts
+                        .get(&h.to_id)
+                        .is_some_and(|to| to.category == ContractCategory::ENS)
+            });
+            (identities.count() + contracts.count()) as u32
+        };
+        Ok(members
+            .into_iter()
+            .skip(page as usize * limit as usize)
+            .take(limit as usize)
+            .map(|(graph_id, mut vids)| {
+                vids.sort();
+                let identities: Vec<RankIdentity> = vids
+                    .iter()
+                    .filter_map(|v_id| {
+                        tables.identities.get(*v_id).map(|identity| RankIdentity {
+                            v_id: v_id.to_string(),
+                            platform: identity.platform,
+                            created_at: identity.created_at,
+                            domains: domains_of(v_id),
+                        })
+                    })
+                    .collect();
+                let is_member = |v_id: &String| tables.part_of.get(v_id) == Some(graph_id);
+                let proofs: Vec<RankProof> = tables
+                    .proofs
+                    .values()
+                    .filter(|p| p.e_type == PROOF_EDGE)
+                    .filter(|p| is_member(&p.from_id) && is_member(&p.to_id))
+                    .map(|p| RankProof {
+                        from_id: p.from_id.clone(),
+                        to_id: p.to_id.clone(),
+                        source: p.source,
+                        level: p.level,
+                    })
+                    .collect();
+                RankGraph {
+                    graph_id: graph_id.clone(),
+                    identities,
+                    proofs,
+                }
+            })
+            .collect())
+    }
+
+    async fn save_ranks(&self, ranks: &[GraphRank]) -> Result<(), Error> {
+        let mut tables = self.write();
+        for graph in ranks.iter() {
+            let scores = std::iter::once((&graph.graph_id, &graph.rank)).chain(&graph.identities);
+            for (v_id, rank) in scores {
+                tables.ranks.insert(v_id.clone(), *rank);
+                tables.touch(Row::Rank(v_id.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    async fn identity_ranks(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<f64>>, Error> {
+        let tables = self.read();
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let rank = tables.ranks.get(&id).copied();
+                (id, rank)
+            })
+            .collect())
+    }
+}
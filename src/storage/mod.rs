@@ -0,0 +1,259 @@
+pub mod memory;
+pub mod sqlite;
+mod tests;
+pub mod tigergraph;
+
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+pub use tigergraph::TigerGraphStore;
+
+use crate::{
+    config::{StoreBackend, C},
+    error::Error,
+    rank::{GraphRank, RankGraph},
+    tigergraph::{
+        edge::{
+            resolve::ResolveReverse, EdgeUnion, FollowRecord, HoldRecord, IdentityPath,
+            RelationUniqueTXRecord, ResolveEdge,
+        },
+        vertex::{
+            domain_collection::DomainAvailableSearch, ContractRecord, ExpandIdentityRecord,
+            Identity, IdentityGraph, IdentityRecord, IdentityWithSource,
+        },
+        EdgeList,
+    },
+    upstream::{Chain, ContractCategory, DomainNameSystem, Platform, ProofLevel},
+    util::make_http_client,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Shared handle of a graph storage backend.
+pub type DynGraphStore = Arc<dyn GraphStore>;
+
+lazy_static! {
+    /// Storage backend selected by `[store] backend` in config.
+    static ref STORE: DynGraphStore = match C.store.backend {
+        StoreBackend::TigerGraph => Arc::new(TigerGraphStore::new(make_http_client())),
+        StoreBackend::Memory => Arc::new(MemoryStore::default()),
+        StoreBackend::Sqlite => Arc::new(
+            SqliteStore::open(&C.store.path).expect("Failed to open SQLite graph store")
+        ),
+    };
+}
+
+/// Get the globally configured storage backend.
+pub fn store() -> DynGraphStore {
+    STORE.clone()
+}
+
+/// Everything RelationService reads from / writes into the identity graph.
+/// Implementations must behave the same as the GSQL queries installed in TigerGraph
+/// (see `src/config/tdb/migrations/LoadingJob_SocialGraph.gsql`).
+#[async_trait]
+pub trait GraphStore: Send + Sync {
+    /// Upsert all vertices and edges fetched in one round,
+    /// and attach connected identities to one `IdentitiesGraph`.
+    async fn batch_upsert(&self, edges: EdgeList) -> Result<(), Error>;
+
+    /// Upsert vertices and edges found by domain search.
+    async fn batch_upsert_domains(&self, edges: EdgeList) -> Result<(), Error>;
+
+    /// Create or refresh a `DomainCollection` vertex.
+    async fn upsert_domain_collection(&self, name: &str) -> Result<(), Error>;
+
+    /// Find `IdentityRecord` by given platform and identity.
+    async fn find_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<IdentityRecord>, Error>;
+
+    /// Batch load identities by their vertex id (`platform,identity`).
+    async fn identities_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error>;
+
+    /// Batch load the ethereum wallet holding each given identity.
+    async fn owners_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error>;
+
+    /// Batch load contracts by their vertex id (`chain,address`).
+    async fn contracts_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<ContractRecord>>, Error>;
+
+    /// All neighbors of `identity` (excluding itself) with the sources that connect them,
+    /// ordered by v_id. Proofs below `min_level` are not walked through.
+    async fn neighbors(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityWithSource>, Error>;
+
+    /// Proof / Hold edges walked through when traversing from `identity`,
+    /// ordered by `EdgeUnion::sort_key`.
+    async fn neighbors_with_traversal(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<EdgeUnion>, Error>;
+
+    /// `neighbors`, at most `limit` of them after the first `skip` ones.
+    async fn neighbors_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<IdentityWithSource>, Error>;
+
+    /// `neighbors_with_traversal`, at most `limit` of them after the first `skip` ones.
+    async fn neighbors_with_traversal_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<EdgeUnion>, Error>;
+
+    /// `Relation_Unique_TX` edges on paths of at most `depth` hops between `source` and `target`.
+    /// Edges are walked in both directions.
+    async fn relation(
+        &self,
+        source: &IdentityRecord,
+        target: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error>;
+
+    /// `Relation_Unique_TX` edges walked within `depth` hops from `identity`.
+    async fn expand_relation(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error>;
+
+    /// Shortest chains of `Proof`, `Hold` and `Resolve` edges between `from` and `to`,
+    /// walked in both directions within `max_depth` hops.
+    /// Proofs below `min_level` are not walked through.
+    async fn path(
+        &self,
+        from: &IdentityRecord,
+        to: &IdentityRecord,
+        max_depth: u16,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityPath>, Error>;
+
+    /// `Follow` edges into the `IdentitiesGraph` `graph_id`.
+    /// `offset` is a page number of `limit` records.
+    async fn followers(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error>;
+
+    /// `Follow` edges out of the `IdentitiesGraph` `graph_id`.
+    /// `offset` is a page number of `limit` records.
+    async fn following(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error>;
+
+    /// NFTs held by `identity`, ordered by contract then token id.
+    /// At most `limit` records after the first `skip` ones.
+    async fn nfts(
+        &self,
+        identity: &IdentityRecord,
+        category: Option<Vec<ContractCategory>>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<HoldRecord>, Error>;
+
+    /// Primary domain names of `identity`.
+    async fn resolve_reverse_domains(
+        &self,
+        identity: &IdentityRecord,
+    ) -> Result<Vec<ResolveReverse>, Error>;
+
+    /// Identity with its owner / resolved addresses.
+    async fn find_expand_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<ExpandIdentityRecord>, Error>;
+
+    /// The whole `IdentitiesGraph` which `platform,identity` belongs to, in `IdentityGraph::sort` order.
+    /// Proofs below `min_level` are left out, so are identities only connected by them.
+    async fn find_identity_graph(
+        &self,
+        platform: &Platform,
+        identity: &str,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Option<IdentityGraph>, Error>;
+
+    /// Resolve / ownership info of a domain.
+    async fn find_resolve(
+        &self,
+        name: &str,
+        domain_system: &DomainNameSystem,
+    ) -> Result<Option<ResolveEdge>, Error>;
+
+    /// Holding record of an NFT.
+    async fn find_hold_nft(
+        &self,
+        id: &str,
+        chain: &Chain,
+        address: &str,
+    ) -> Result<Option<HoldRecord>, Error>;
+
+    /// Domains taken / available under a `DomainCollection`.
+    async fn domain_available_search(
+        &self,
+        name: &str,
+    ) -> Result<Option<DomainAvailableSearch>, Error>;
+
+    /// Remove connections inside the `IdentitiesGraph` of `v_id` before it gets refetched.
+    async fn delete_graph_inner_connection(&self, v_id: String) -> Result<(), Error>;
+
+    /// Remove Proof / Hold / Resolve edges in `edges`, matched by type, ends and discriminator.
+    /// Vertices and their `IdentitiesGraph` are kept.
+    async fn delete_edges(&self, edges: EdgeList) -> Result<(), Error>;
+
+    /// Move `identities` out of `IdentitiesGraph` `graph_id` into a new one,
+    /// after the edges holding them in were deleted. Returns the new graph id.
+    async fn split_identity_graph(
+        &self,
+        graph_id: &str,
+        identities: Vec<Identity>,
+    ) -> Result<String, Error>;
+
+    /// Remove domains linked to a `DomainCollection` before it gets refetched.
+    async fn delete_domain_collection(&self, name: &str) -> Result<(), Error>;
+
+    /// Remove every `DomainCollection` and its domain links, so all names are searched again.
+    /// Returns how many collections were removed.
+    async fn clear_domain_search_cache(&self) -> Result<usize, Error>;
+
+    /// One page of `IdentitiesGraph`s, ordered by id, with what IdentityRank reads of them.
+    async fn rank_inputs(&self, limit: u16, page: u32) -> Result<Vec<RankGraph>, Error>;
+
+    /// Persist `identity_rank` of `IdentitiesGraph`s and their `Identities`.
+    async fn save_ranks(&self, ranks: &[GraphRank]) -> Result<(), Error>;
+
+    /// `identity_rank` of `Identities` by v_id. `None` if not ranked yet.
+    async fn identity_ranks(&self, ids: Vec<String>)
+        -> Result<HashMap<String, Option<f64>>, Error>;
+}
@@ -0,0 +1,371 @@
+use crate::{
+    error::Error,
+    rank::{GraphRank, RankGraph},
+    storage::{memory::RowChange, GraphStore, MemoryStore},
+    tigergraph::{
+        edge::{
+            resolve::ResolveReverse, EdgeUnion, FollowRecord, HoldRecord, IdentityPath,
+            RelationUniqueTXRecord, ResolveEdge,
+        },
+        vertex::{
+            domain_collection::DomainAvailableSearch, ContractRecord, ExpandIdentityRecord,
+            Identity, IdentityGraph, IdentityRecord, IdentityWithSource,
+        },
+        EdgeList,
+    },
+    upstream::{Chain, ContractCategory, DomainNameSystem, Platform, ProofLevel},
+};
+use async_trait::async_trait;
+use http::StatusCode;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS graph_rows (
+    kind TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (kind, key)
+)";
+
+/// `GraphStore` persisted into a single SQLite file.
+/// Every row is loaded into a `MemoryStore` on startup, which serves all queries
+/// (including `IdentitiesGraph` allocation); writes are flushed to disk right after.
+/// The whole graph must therefore fit in memory and startup time grows with it:
+/// this is a backend for development and small single-node datasets, use
+/// TigerGraph for production-sized graphs.
+pub struct SqliteStore {
+    memory: MemoryStore,
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database file at `path`.
+    pub fn open(path: &str) -> Result<Self, Error> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// A throwaway database living in memory. For tests.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute(SCHEMA, [])?;
+        let memory = MemoryStore::journaled();
+        let mut stmt = conn.prepare("SELECT kind, key, value FROM graph_rows")?;
+        let mut rows = stmt.query([])?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let kind: String = row.get(0)?;
+            let key: String = row.get(1)?;
+            let value: String = row.get(2)?;
+            memory.restore(&kind, &key, &value)?;
+            count += 1;
+        }
+        drop(rows);
+        drop(stmt);
+        info!(rows = count, "SQLite graph store loaded");
+        Ok(Self {
+            memory,
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Write rows changed in memory into SQLite on a blocking thread.
+    /// Holding the connection while draining keeps concurrent flushes in order.
+    /// Rows failed to be written are kept in journal and retried by the next flush.
+    async fn flush(&self) -> Result<(), Error> {
+        let conn = self.conn.clone().lock_owned().await;
+        let changes = self.memory.take_changes()?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let (changes, result) = tokio::task::spawn_blocking(move || {
+            let mut conn = conn;
+            let result = write_changes(&mut conn, &changes);
+            (changes, result)
+        })
+        .await
+        .map_err(|err| {
+            Error::General(
+                format!("SQLite flush aborted: {}", err),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        })?;
+        if let Err(err) = &result {
+            warn!(%err, rows = changes.len(), "Failed to flush into SQLite. Will retry.");
+            self.memory.requeue(changes);
+        }
+        result
+    }
+}
+
+fn write_changes(conn: &mut Connection, changes: &[RowChange]) -> Result<(), Error> {
+    let tx = conn.transaction()?;
+    for change in changes.iter() {
+        match &change.value {
+            Some(value) => tx.execute(
+                "INSERT OR REPLACE INTO graph_rows (kind, key, value) VALUES (?1, ?2, ?3)",
+                params![change.kind, change.key, value],
+            )?,
+            None => tx.execute(
+                "DELETE FROM graph_rows WHERE kind = ?1 AND key = ?2",
+                params![change.kind, change.key],
+            )?,
+        };
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[async_trait]
+impl GraphStore for SqliteStore {
+    async fn batch_upsert(&self, edges: EdgeList) -> Result<(), Error> {
+        self.memory.batch_upsert(edges).await?;
+        self.flush().await
+    }
+
+    async fn batch_upsert_domains(&self, edges: EdgeList) -> Result<(), Error> {
+        self.memory.batch_upsert_domains(edges).await?;
+        self.flush().await
+    }
+
+    async fn upsert_domain_collection(&self, name: &str) -> Result<(), Error> {
+        self.memory.upsert_domain_collection(name).await?;
+        self.flush().await
+    }
+
+    async fn find_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<IdentityRecord>, Error> {
+        self.memory.find_identity(platform, identity).await
+    }
+
+    async fn identities_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
+        self.memory.identities_by_ids(ids).await
+    }
+
+    async fn owners_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
+        self.memory.owners_by_ids(ids).await
+    }
+
+    async fn contracts_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<ContractRecord>>, Error> {
+        self.memory.contracts_by_ids(ids).await
+    }
+
+    async fn neighbors(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityWithSource>, Error> {
+        self.memory
+            .neighbors(identity, depth, reverse, min_level)
+            .await
+    }
+
+    async fn neighbors_with_traversal(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<EdgeUnion>, Error> {
+        self.memory.neighbors_with_traversal(identity, depth).await
+    }
+
+    async fn neighbors_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<IdentityWithSource>, Error> {
+        self.memory
+            .neighbors_page(identity, depth, reverse, min_level, limit, skip)
+            .await
+    }
+
+    async fn neighbors_with_traversal_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<EdgeUnion>, Error> {
+        self.memory
+            .neighbors_with_traversal_page(identity, depth, limit, skip)
+            .await
+    }
+
+    async fn relation(
+        &self,
+        source: &IdentityRecord,
+        target: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error> {
+        self.memory.relation(source, target, depth).await
+    }
+
+    async fn expand_relation(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error> {
+        self.memory.expand_relation(identity, depth).await
+    }
+
+    async fn path(
+        &self,
+        from: &IdentityRecord,
+        to: &IdentityRecord,
+        max_depth: u16,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityPath>, Error> {
+        self.memory.path(from, to, max_depth, min_level).await
+    }
+
+    async fn followers(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        self.memory.followers(graph_id, limit, offset).await
+    }
+
+    async fn following(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        self.memory.following(graph_id, limit, offset).await
+    }
+
+    async fn nfts(
+        &self,
+        identity: &IdentityRecord,
+        category: Option<Vec<ContractCategory>>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<HoldRecord>, Error> {
+        self.memory.nfts(identity, category, limit, skip).await
+    }
+
+    async fn resolve_reverse_domains(
+        &self,
+        identity: &IdentityRecord,
+    ) -> Result<Vec<ResolveReverse>, Error> {
+        self.memory.resolve_reverse_domains(identity).await
+    }
+
+    async fn find_expand_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<ExpandIdentityRecord>, Error> {
+        self.memory.find_expand_identity(platform, identity).await
+    }
+
+    async fn find_identity_graph(
+        &self,
+        platform: &Platform,
+        identity: &str,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Option<IdentityGraph>, Error> {
+        self.memory
+            .find_identity_graph(platform, identity, reverse, min_level)
+            .await
+    }
+
+    async fn find_resolve(
+        &self,
+        name: &str,
+        domain_system: &DomainNameSystem,
+    ) -> Result<Option<ResolveEdge>, Error> {
+        self.memory.find_resolve(name, domain_system).await
+    }
+
+    async fn find_hold_nft(
+        &self,
+        id: &str,
+        chain: &Chain,
+        address: &str,
+    ) -> Result<Option<HoldRecord>, Error> {
+        self.memory.find_hold_nft(id, chain, address).await
+    }
+
+    async fn domain_available_search(
+        &self,
+        name: &str,
+    ) -> Result<Option<DomainAvailableSearch>, Error> {
+        self.memory.domain_available_search(name).await
+    }
+
+    async fn delete_graph_inner_connection(&self, v_id: String) -> Result<(), Error> {
+        self.memory.delete_graph_inner_connection(v_id).await?;
+        self.flush().await
+    }
+
+    async fn delete_edges(&self, edges: EdgeList) -> Result<(), Error> {
+        self.memory.delete_edges(edges).await?;
+        self.flush().await
+    }
+
+    async fn split_identity_graph(
+        &self,
+        graph_id: &str,
+        identities: Vec<Identity>,
+    ) -> Result<String, Error> {
+        let graph_id = self
+            .memory
+            .split_identity_graph(graph_id, identities)
+            .await?;
+        self.flush().await?;
+        Ok(graph_id)
+    }
+
+    async fn delete_domain_collection(&self, name: &str) -> Result<(), Error> {
+        self.memory.delete_domain_collection(name).await?;
+        self.flush().await
+    }
+
+    async fn clear_domain_search_cache(&self) -> Result<usize, Error> {
+        let removed = self.memory.clear_domain_search_cache().await?;
+        self.flush().await?;
+        Ok(removed)
+    }
+
+    async fn rank_inputs(&self, limit: u16, page: u32) -> Result<Vec<RankGraph>, Error> {
+        self.memory.rank_inputs(limit, page).await
+    }
+
+    async fn save_ranks(&self, ranks: &[GraphRank]) -> Result<(), Error> {
+        self.memory.save_ranks(ranks).await?;
+        self.flush().await
+    }
+
+    async fn identity_ranks(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<f64>>, Error> {
+        self.memory.identity_ranks(ids).await
+    }
+}
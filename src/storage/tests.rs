@@ -0,0 +1,702 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        storage::{GraphStore, MemoryStore, SqliteStore},
+        tigergraph::{
+            edge::{
+                Follow, Hold, HyperEdge, Proof, Resolve, Wrapper, FOLLOW, HOLD_CONTRACT,
+                HOLD_IDENTITY, HYPER_EDGE, PROOF_EDGE, PROOF_REVERSE_EDGE, RESOLVE,
+                REVERSE_RESOLVE,
+            },
+            vertex::{Contract, IdentitiesGraph, Identity, Vertex},
+            EdgeList, EdgeWrapperEnum,
+        },
+        upstream::{Chain, DataSource, DomainNameSystem, Platform, ProofLevel},
+    };
+    use uuid::Uuid;
+
+    fn identity(platform: Platform, name: &str) -> Identity {
+        Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn proof_pair(edges: &mut EdgeList, from: &Identity, to: &Identity, source: DataSource) {
+        let proof = Proof {
+            source,
+            ..Default::default()
+        };
+        edges.push(EdgeWrapperEnum::new_proof_forward(
+            proof.wrapper(from, to, PROOF_EDGE),
+        ));
+        edges.push(EdgeWrapperEnum::new_proof_backward(proof.wrapper(
+            to,
+            from,
+            PROOF_REVERSE_EDGE,
+        )));
+    }
+
+    fn hold(edges: &mut EdgeList, from: &Identity, to: &Identity, source: DataSource) {
+        let hold = Hold {
+            source,
+            ..Default::default()
+        };
+        edges.push(EdgeWrapperEnum::new_hold_identity(hold.wrapper(
+            from,
+            to,
+            HOLD_IDENTITY,
+        )));
+    }
+
+    fn resolve(
+        edges: &mut EdgeList,
+        from: &Identity,
+        to: &Identity,
+        system: DomainNameSystem,
+        reverse: bool,
+    ) {
+        let resolve = Resolve {
+            source: DataSource::TheGraph,
+            system,
+            name: if reverse {
+                to.identity.clone()
+            } else {
+                from.identity.clone()
+            },
+            ..Default::default()
+        };
+        if reverse {
+            edges.push(EdgeWrapperEnum::new_reverse_resolve(resolve.wrapper(
+                from,
+                to,
+                REVERSE_RESOLVE,
+            )));
+        } else {
+            edges.push(EdgeWrapperEnum::new_resolve(
+                resolve.wrapper(from, to, RESOLVE),
+            ));
+        }
+    }
+
+    fn connect_graph(edges: &mut EdgeList, identities: &[&Identity]) {
+        let hv = IdentitiesGraph::default();
+        for i in identities.iter() {
+            edges.push(EdgeWrapperEnum::new_hyper_edge(
+                HyperEdge {}.wrapper(&hv, i, HYPER_EDGE),
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_merges_identity() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let github = identity(Platform::Github, "alice");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &eth, &github, DataSource::NextID);
+        store.batch_upsert(edges).await?;
+
+        let mut renamed = github.clone();
+        renamed.uuid = Some(Uuid::new_v4());
+        renamed.display_name = Some("Alice".to_string());
+        let mut edges = vec![];
+        proof_pair(&mut edges, &eth, &renamed, DataSource::NextID);
+        store.batch_upsert(edges).await?;
+
+        let found = store
+            .find_identity(&Platform::Github, "alice")
+            .await?
+            .expect("identity should exist");
+        assert_eq!(found.uuid, github.uuid);
+        assert_eq!(found.display_name, Some("Alice".to_string()));
+        assert_eq!(found.reverse, Some(false));
+        assert!(store
+            .find_identity(&Platform::Github, "bob")
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_graph_allocation_merges_graphs() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let a = identity(Platform::Ethereum, "0xa");
+        let b = identity(Platform::Github, "a");
+        let c = identity(Platform::Ethereum, "0xc");
+        let d = identity(Platform::Twitter, "c");
+
+        let mut edges = vec![];
+        proof_pair(&mut edges, &a, &b, DataSource::NextID);
+        connect_graph(&mut edges, &[&a, &b]);
+        store.batch_upsert(edges).await?;
+        let first = store
+            .find_identity_graph(&Platform::Github, "a", None, None)
+            .await?
+            .expect("graph should exist");
+
+        let mut edges = vec![];
+        proof_pair(&mut edges, &c, &d, DataSource::NextID);
+        connect_graph(&mut edges, &[&c, &d]);
+        store.batch_upsert(edges).await?;
+
+        let mut edges = vec![];
+        proof_pair(&mut edges, &b, &c, DataSource::NextID);
+        connect_graph(&mut edges, &[&b, &c]);
+        store.batch_upsert(edges).await?;
+
+        let merged = store
+            .find_identity_graph(&Platform::Twitter, "c", None, None)
+            .await?
+            .expect("graph should exist");
+        assert_eq!(merged.graph_id, first.graph_id);
+        assert_eq!(merged.vertices.len(), 4);
+        assert_eq!(merged.edges.len(), 6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_neighbors() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let github = identity(Platform::Github, "alice");
+        let lens = identity(Platform::Lens, "alice.lens");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &eth, &github, DataSource::NextID);
+        hold(&mut edges, &eth, &lens, DataSource::Lens);
+        resolve(&mut edges, &eth, &lens, DomainNameSystem::Lens, true);
+        store.batch_upsert(edges).await?;
+
+        let seed = store
+            .find_identity(&Platform::Github, "alice")
+            .await?
+            .expect("identity should exist");
+        let neighbors = store.neighbors(&seed, 5, None, None).await?;
+        assert_eq!(neighbors.len(), 2);
+        let found_lens = neighbors
+            .iter()
+            .find(|n| n.identity.platform == Platform::Lens)
+            .expect("lens should be found");
+        assert_eq!(found_lens.reverse, Some(true));
+        assert!(found_lens.sources.contains(&DataSource::NextID));
+        assert!(found_lens.sources.contains(&DataSource::Lens));
+
+        let only_not_reverse = store.neighbors(&seed, 5, Some(false), None).await?;
+        assert_eq!(only_not_reverse.len(), 1);
+
+        let traversal = store.neighbors_with_traversal(&seed, 5).await?;
+        assert_eq!(traversal.len(), 3);
+
+        let page = store.neighbors_page(&seed, 5, None, None, 1, 1).await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].identity.v_id, neighbors[1].identity.v_id);
+        let page = store.neighbors_with_traversal_page(&seed, 5, 2, 2).await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].sort_key(), traversal[2].sort_key());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_resolve() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let lens = identity(Platform::Lens, "alice.lens");
+        let mut edges = vec![];
+        hold(&mut edges, &eth, &lens, DataSource::Lens);
+        resolve(&mut edges, &lens, &eth, DomainNameSystem::Lens, false);
+        resolve(&mut edges, &eth, &lens, DomainNameSystem::Lens, true);
+        store.batch_upsert(edges).await?;
+
+        let found = store
+            .find_resolve("alice.lens", &DomainNameSystem::Lens)
+            .await?
+            .expect("domain should be found");
+        assert!(found.reverse);
+        assert_eq!(
+            found.owner.map(|o| o.identity.clone()),
+            Some("0xa".to_string())
+        );
+        assert!(store
+            .find_resolve("bob.lens", &DomainNameSystem::Lens)
+            .await?
+            .is_none());
+
+        let eth_record = store
+            .find_identity(&Platform::Ethereum, "0xa")
+            .await?
+            .expect("identity should exist");
+        let reverse = store.resolve_reverse_domains(&eth_record).await?;
+        assert_eq!(reverse.len(), 1);
+        assert!(reverse[0].reverse);
+
+        let expand = store
+            .find_expand_identity(&Platform::Lens, "alice.lens")
+            .await?
+            .expect("identity should exist");
+        assert_eq!(expand.owner_address.map(|a| a.len()), Some(1));
+        assert_eq!(expand.resolve_address.map(|a| a.len()), Some(1));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nfts_and_hold_nft() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let contract = Contract {
+            chain: Chain::Ethereum,
+            address: "0xcontract".to_string(),
+            ..Default::default()
+        };
+        let mut edges = vec![];
+        for id in ["1", "2", "3"] {
+            let hold = Hold {
+                id: id.to_string(),
+                source: DataSource::Rss3,
+                ..Default::default()
+            };
+            edges.push(EdgeWrapperEnum::new_hold_contract(hold.wrapper(
+                &eth,
+                &contract,
+                HOLD_CONTRACT,
+            )));
+        }
+        store.batch_upsert(edges).await?;
+
+        let record = store
+            .find_identity(&Platform::Ethereum, "0xa")
+            .await?
+            .expect("identity should exist");
+        assert_eq!(store.nfts(&record, None, 2, 0).await?.len(), 2);
+        assert_eq!(store.nfts(&record, None, 2, 2).await?.len(), 1);
+        let ids: Vec<String> = store
+            .nfts(&record, None, 2, 1)
+            .await?
+            .iter()
+            .map(|hold| hold.id.clone())
+            .collect();
+        assert_eq!(ids, vec!["2", "3"]);
+        assert!(store
+            .find_hold_nft("2", &Chain::Ethereum, "0xcontract")
+            .await?
+            .is_some());
+        assert!(store
+            .find_hold_nft("4", &Chain::Ethereum, "0xcontract")
+            .await?
+            .is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_graph_inner_connection() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let a = identity(Platform::Ethereum, "0xa");
+        let b = identity(Platform::Github, "a");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &a, &b, DataSource::NextID);
+        connect_graph(&mut edges, &[&a, &b]);
+        store.batch_upsert(edges).await?;
+
+        assert!(store
+            .delete_graph_inner_connection("".to_string())
+            .await
+            .is_err());
+        store
+            .delete_graph_inner_connection("ethereum,0xa".to_string())
+            .await?;
+        let record = store
+            .find_identity(&Platform::Ethereum, "0xa")
+            .await?
+            .expect("vertex should be kept");
+        assert!(store.neighbors(&record, 1, None, None).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_edges() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let github = identity(Platform::Github, "alice");
+        let lens = identity(Platform::Lens, "alice.lens");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &eth, &github, DataSource::NextID);
+        store.batch_upsert(edges).await?;
+
+        let mut manual = vec![];
+        proof_pair(&mut manual, &eth, &github, DataSource::ManuallyAdded);
+        hold(&mut manual, &eth, &lens, DataSource::ManuallyAdded);
+        store.batch_upsert(manual.clone()).await?;
+        let seed = store
+            .find_identity(&Platform::Ethereum, "0xa")
+            .await?
+            .expect("identity should exist");
+        assert_eq!(store.neighbors_with_traversal(&seed, 1).await?.len(), 5);
+
+        store.delete_edges(manual).await?;
+        let traversal = store.neighbors_with_traversal(&seed, 1).await?;
+        assert_eq!(traversal.len(), 2);
+        assert!(store
+            .find_identity(&Platform::Lens, "alice.lens")
+            .await?
+            .is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_split_identity_graph() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0xa");
+        let github = identity(Platform::Github, "alice");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &eth, &github, DataSource::ManuallyAdded);
+        connect_graph(&mut edges, &[&eth, &github]);
+        store.batch_upsert(edges.clone()).await?;
+        store.delete_edges(edges).await?;
+
+        let joined = store
+            .find_identity_graph(&Platform::Github, "alice", None, None)
+            .await?
+            .expect("graph should exist");
+        let graph_id = store
+            .split_identity_graph(&joined.graph_id, vec![github.clone()])
+            .await?;
+        let kept = store
+            .find_identity_graph(&Platform::Ethereum, "0xa", None, None)
+            .await?
+            .expect("graph should exist");
+        let split = store
+            .find_identity_graph(&Platform::Github, "alice", None, None)
+            .await?
+            .expect("graph should exist");
+        assert_ne!(kept.graph_id, graph_id);
+        assert_eq!(split.graph_id, graph_id);
+        assert_eq!(kept.vertices.len(), 1);
+        assert_eq!(split.vertices.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_domain_collection() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        assert!(store.domain_available_search("alice").await?.is_none());
+        store.upsert_domain_collection("alice").await?;
+        let search = store
+            .domain_available_search("alice")
+            .await?
+            .expect("collection should exist");
+        assert_eq!(search.collection.id, "alice");
+        assert!(store.delete_domain_collection("").await.is_err());
+        store.delete_domain_collection("alice").await?;
+
+        store.upsert_domain_collection("bob").await?;
+        assert_eq!(store.clear_domain_search_cache().await?, 2);
+        assert!(store.domain_available_search("alice").await?.is_none());
+        assert_eq!(store.clear_domain_search_cache().await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_follows() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let a = identity(Platform::Farcaster, "a");
+        let b = identity(Platform::Farcaster, "b");
+        let c = identity(Platform::Farcaster, "c");
+        let b_eth = identity(Platform::Ethereum, "0xb");
+
+        let mut edges = vec![];
+        proof_pair(&mut edges, &b, &b_eth, DataSource::Farcaster);
+        connect_graph(&mut edges, &[&b, &b_eth]);
+        for followee in [&b, &c] {
+            let follow = Follow::new(&a, followee, DataSource::Farcaster);
+            edges.push(EdgeWrapperEnum::new_follow(
+                follow.wrapper(&a, followee, FOLLOW),
+            ));
+        }
+        store.batch_upsert(edges.clone()).await?;
+        store.batch_upsert(edges).await?;
+
+        let graph_of = |name: &'static str| {
+            let store = &store;
+            async move {
+                store
+                    .find_identity_graph(&Platform::Farcaster, name, None, None)
+                    .await?
+                    .map(|graph| graph.graph_id)
+                    .ok_or(Error::NoResult)
+            }
+        };
+        let (a_graph, b_graph) = (graph_of("a").await?, graph_of("b").await?);
+        let following = store.following(&a_graph, 100, 0).await?;
+        assert_eq!(following.len(), 2, "refetched follows should be merged");
+        assert_eq!(store.following(&a_graph, 1, 1).await?.len(), 1);
+        assert!(store.following(&a_graph, 1, 2).await?.is_empty());
+
+        let followers = store.followers(&b_graph, 100, 0).await?;
+        assert_eq!(followers.len(), 1);
+        assert_eq!(followers[0].from_id, a_graph);
+        assert_eq!(followers[0].original_from, a.primary_key());
+        assert_eq!(followers[0].original_to, b.primary_key());
+        assert!(store.followers(&a_graph, 100, 0).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_follow_keeps_followee() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let a = identity(Platform::Farcaster, "a");
+        let b = Identity {
+            display_name: Some("Bob".to_string()),
+            ..identity(Platform::Farcaster, "b")
+        };
+        let mut edges = vec![];
+        connect_graph(&mut edges, &[&b]);
+        store.batch_upsert(edges).await?;
+        let stored = store
+            .find_identity(&Platform::Farcaster, "b")
+            .await?
+            .expect("followee should exist");
+
+        let met = Identity {
+            display_name: Some("someone".to_string()),
+            updated_at: b.updated_at + chrono::Duration::days(1),
+            ..identity(Platform::Farcaster, "b")
+        };
+        let follow = Follow::new(&a, &met, DataSource::Farcaster);
+        store
+            .batch_upsert(vec![EdgeWrapperEnum::new_follow(
+                follow.wrapper(&a, &met, FOLLOW),
+            )])
+            .await?;
+        let found = store
+            .find_identity(&Platform::Farcaster, "b")
+            .await?
+            .expect("followee should exist");
+        assert_eq!(found.uuid, stored.uuid);
+        assert_eq!(found.display_name, stored.display_name);
+        assert_eq!(found.updated_at, stored.updated_at);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_path() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let twitter = identity(Platform::Twitter, "alice");
+        let github = identity(Platform::Github, "alice");
+        let eth = identity(Platform::Ethereum, "0xa");
+        let lens = identity(Platform::Lens, "alice.lens");
+        let lonely = identity(Platform::Twitter, "bob");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &twitter, &eth, DataSource::NextID);
+        hold(&mut edges, &eth, &lens, DataSource::Lens);
+        resolve(&mut edges, &lens, &eth, DomainNameSystem::Lens, false);
+        proof_pair(&mut edges, &twitter, &github, DataSource::NextID);
+        proof_pair(&mut edges, &lens, &github, DataSource::Keybase);
+        connect_graph(&mut edges, &[&twitter, &github, &eth, &lens]);
+        connect_graph(&mut edges, &[&lonely]);
+        store.batch_upsert(edges).await?;
+
+        let find = |platform: Platform, name: &'static str| {
+            let store = &store;
+            async move {
+                store
+                    .find_identity(&platform, name)
+                    .await?
+                    .ok_or(Error::NoResult)
+            }
+        };
+        let from = find(Platform::Twitter, "alice").await?;
+        let to = find(Platform::Lens, "alice.lens").await?;
+        let paths = store.path(&from, &to, 5, None).await?;
+        assert_eq!(paths.len(), 3, "parallel edges make separate paths");
+        assert!(paths.iter().all(|path| path.hops.len() == 2));
+        let via_eth: Vec<_> = paths
+            .iter()
+            .filter(|path| path.hops[0].to_id == eth.primary_key())
+            .collect();
+        assert_eq!(via_eth.len(), 2);
+        assert!(via_eth
+            .iter()
+            .all(|path| path.hops[0].e_type == PROOF_EDGE && path.hops[0].level.is_some()));
+        let mut last: Vec<_> = via_eth
+            .iter()
+            .map(|path| path.hops[1].e_type.as_str())
+            .collect();
+        last.sort();
+        assert_eq!(last, vec![HOLD_IDENTITY, RESOLVE]);
+        assert_eq!(via_eth[0].hops[1].level, None);
+
+        // Walked against the stored direction.
+        let back = store.path(&to, &from, 5, None).await?;
+        assert_eq!(back.len(), 3);
+        assert!(back
+            .iter()
+            .all(|path| path.hops[1].from_id == from.v_id || path.hops[1].to_id == from.v_id));
+
+        assert!(store.path(&from, &to, 1, None).await?.is_empty());
+        let lonely = find(Platform::Twitter, "bob").await?;
+        assert!(store.path(&from, &lonely, 5, None).await?.is_empty());
+        assert!(store.path(&from, &from, 5, None).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_min_level() -> Result<(), Error> {
+        let store = MemoryStore::default();
+        let twitter = identity(Platform::Twitter, "alice");
+        let eth = identity(Platform::Ethereum, "0xa");
+        let github = identity(Platform::Github, "alice");
+        let mut edges = vec![];
+        let proof = Proof {
+            source: DataSource::NextID,
+            level: DataSource::NextID.proof_level(),
+            ..Default::default()
+        };
+        edges.push(EdgeWrapperEnum::new_proof_forward(
+            proof.wrapper(&twitter, &eth, PROOF_EDGE),
+        ));
+        edges.push(EdgeWrapperEnum::new_proof_backward(proof.wrapper(
+            &eth,
+            &twitter,
+            PROOF_REVERSE_EDGE,
+        )));
+        // Left at `ProofLevel::Insecure`.
+        proof_pair(&mut edges, &github, &twitter, DataSource::EthLeaderboard);
+        connect_graph(&mut edges, &[&twitter, &eth, &github]);
+        store.batch_upsert(edges).await?;
+        let from = store
+            .find_identity(&Platform::Twitter, "alice")
+            .await?
+            .ok_or(Error::NoResult)?;
+        let to = store
+            .find_identity(&Platform::Github, "alice")
+            .await?
+            .ok_or(Error::NoResult)?;
+
+        assert_eq!(store.neighbors(&from, 1, None, None).await?.len(), 2);
+        let confident = store
+            .neighbors(&from, 1, None, Some(ProofLevel::Confident))
+            .await?;
+        assert_eq!(confident.len(), 1);
+        assert_eq!(confident[0].identity.v_id, eth.primary_key());
+
+        let graph = store
+            .find_identity_graph(
+                &Platform::Twitter,
+                "alice",
+                None,
+                Some(ProofLevel::Confident),
+            )
+            .await?
+            .ok_or(Error::NoResult)?;
+        let mut vertices: Vec<_> = graph
+            .vertices
+            .iter()
+            .map(|v| v.record.v_id.clone())
+            .collect();
+        vertices.sort();
+        assert_eq!(vertices, vec![eth.primary_key(), twitter.primary_key()]);
+        assert!(graph
+            .edges
+            .iter()
+            .all(|e| e.data_source == DataSource::NextID));
+
+        assert_eq!(store.path(&from, &to, 5, None).await?.len(), 1);
+        assert!(store
+            .path(&from, &to, 5, Some(ProofLevel::Cautious))
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_store_survives_reopen() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!("relation_{}.sqlite", Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_string();
+        let a = identity(Platform::Ethereum, "0xa");
+        let b = identity(Platform::Github, "a");
+        let c = identity(Platform::Twitter, "a");
+        {
+            let store = SqliteStore::open(&path)?;
+            let mut edges = vec![];
+            proof_pair(&mut edges, &a, &b, DataSource::NextID);
+            connect_graph(&mut edges, &[&a, &b]);
+            store.batch_upsert(edges).await?;
+            let mut edges = vec![];
+            proof_pair(&mut edges, &a, &c, DataSource::NextID);
+            connect_graph(&mut edges, &[&a, &c]);
+            store.batch_upsert(edges).await?;
+            let lens = identity(Platform::Lens, "alice.lens");
+            let mut edges = vec![];
+            hold(&mut edges, &a, &lens, DataSource::Lens);
+            resolve(&mut edges, &lens, &a, DomainNameSystem::Lens, false);
+            store.batch_upsert(edges).await?;
+            store.upsert_domain_collection("alice").await?;
+        }
+
+        let store = SqliteStore::open(&path)?;
+        let graph = store
+            .find_identity_graph(&Platform::Twitter, "a", None, None)
+            .await?
+            .expect("graph should be persisted");
+        assert_eq!(graph.vertices.len(), 3);
+        assert!(store.domain_available_search("alice").await?.is_some());
+        let found = store
+            .find_resolve("alice.lens", &DomainNameSystem::Lens)
+            .await?
+            .expect("domain should be persisted");
+        assert_eq!(
+            found.resolved.map(|r| r.identity.clone()),
+            Some("0xa".to_string())
+        );
+
+        store
+            .delete_graph_inner_connection("github,a".to_string())
+            .await?;
+        drop(store);
+        let store = SqliteStore::open(&path)?;
+        let record = store
+            .find_identity(&Platform::Github, "a")
+            .await?
+            .expect("vertex should be kept");
+        assert!(store.neighbors(&record, 1, None, None).await?.is_empty());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_flush_retried_after_failure() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!("relation_{}.sqlite", Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_string();
+        let a = identity(Platform::Ethereum, "0xa");
+        let b = identity(Platform::Github, "a");
+        {
+            let store = SqliteStore::open(&path)?;
+            let other = rusqlite::Connection::open(&path)?;
+            other.execute(
+                "CREATE TRIGGER read_only BEFORE INSERT ON graph_rows
+                 BEGIN SELECT RAISE(ABORT, 'read only'); END",
+                [],
+            )?;
+            let mut edges = vec![];
+            proof_pair(&mut edges, &a, &b, DataSource::NextID);
+            connect_graph(&mut edges, &[&a, &b]);
+            assert!(store.batch_upsert(edges).await.is_err());
+
+            other.execute("DROP TRIGGER read_only", [])?;
+            store.upsert_domain_collection("alice").await?;
+        }
+
+        let store = SqliteStore::open(&path)?;
+        let graph = store
+            .find_identity_graph(&Platform::Github, "a", None, None)
+            .await?
+            .expect("failed rows should be flushed later");
+        assert_eq!(graph.vertices.len(), 2);
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{
+    error::Error,
+    rank::{GraphRank, RankGraph},
+    storage::GraphStore,
+    tigergraph::{
+        batch_upsert, batch_upsert_domains, clear_domain_search_cache, delete_domain_collection,
+        delete_edges, delete_graph_inner_connection,
+        edge::{
+            resolve::ResolveReverse, EdgeUnion, Follow, FollowRecord, Hold, HoldRecord,
+            IdentityPath, RelationUniqueTX, RelationUniqueTXRecord, Resolve, ResolveEdge,
+        },
+        rank::{get_identity_ranks_by_ids, rank_inputs, save_ranks},
+        split_identity_graph, upsert_domain_collection,
+        vertex::{
+            contract::get_contracts_by_ids,
+            domain_collection::DomainAvailableSearch,
+            identity::{get_identities_by_ids, get_owners_by_ids},
+            ContractRecord, DomainCollection, ExpandIdentityRecord, Identity, IdentityGraph,
+            IdentityRecord, IdentityWithSource,
+        },
+        EdgeList,
+    },
+    upstream::{Chain, ContractCategory, DomainNameSystem, Platform, ProofLevel},
+};
+use async_trait::async_trait;
+use hyper::{client::HttpConnector, Client};
+use std::collections::HashMap;
+
+/// `GraphStore` backed by TigerGraph REST++ API and installed GSQL queries.
+#[derive(Clone)]
+pub struct TigerGraphStore {
+    client: Client<HttpConnector>,
+}
+
+impl TigerGraphStore {
+    pub fn new(client: Client<HttpConnector>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl GraphStore for TigerGraphStore {
+    async fn batch_upsert(&self, edges: EdgeList) -> Result<(), Error> {
+        batch_upsert(&self.client, edges).await
+    }
+
+    async fn batch_upsert_domains(&self, edges: EdgeList) -> Result<(), Error> {
+        batch_upsert_domains(&self.client, edges).await
+    }
+
+    async fn upsert_domain_collection(&self, name: &str) -> Result<(), Error> {
+        upsert_domain_collection(&self.client, name).await
+    }
+
+    async fn find_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<IdentityRecord>, Error> {
+        Identity::find_by_platform_identity(&self.client, platform, identity).await
+    }
+
+    async fn identities_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
+        get_identities_by_ids(&self.client, ids).await
+    }
+
+    async fn owners_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
+        get_owners_by_ids(&self.client, ids).await
+    }
+
+    async fn contracts_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<ContractRecord>>, Error> {
+        get_contracts_by_ids(&self.client, ids).await
+    }
+
+    async fn neighbors(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityWithSource>, Error> {
+        identity
+            .neighbors(&self.client, depth, reverse, min_level, None)
+            .await
+    }
+
+    async fn neighbors_with_traversal(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<EdgeUnion>, Error> {
+        identity
+            .neighbors_with_traversal(&self.client, depth, None)
+            .await
+    }
+
+    async fn neighbors_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<IdentityWithSource>, Error> {
+        identity
+            .neighbors(&self.client, depth, reverse, min_level, Some((limit, skip)))
+            .await
+    }
+
+    async fn neighbors_with_traversal_page(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<EdgeUnion>, Error> {
+        identity
+            .neighbors_with_traversal(&self.client, depth, Some((limit, skip)))
+            .await
+    }
+
+    async fn relation(
+        &self,
+        source: &IdentityRecord,
+        target: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error> {
+        RelationUniqueTX::relation(&self.client, source, target, depth).await
+    }
+
+    async fn expand_relation(
+        &self,
+        identity: &IdentityRecord,
+        depth: u16,
+    ) -> Result<Vec<RelationUniqueTXRecord>, Error> {
+        RelationUniqueTX::expand(&self.client, identity, depth).await
+    }
+
+    async fn path(
+        &self,
+        from: &IdentityRecord,
+        to: &IdentityRecord,
+        max_depth: u16,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityPath>, Error> {
+        IdentityPath::find(&self.client, from, to, max_depth, min_level).await
+    }
+
+    async fn followers(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        Follow::followers(&self.client, graph_id, limit, offset).await
+    }
+
+    async fn following(
+        &self,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        Follow::following(&self.client, graph_id, limit, offset).await
+    }
+
+    async fn nfts(
+        &self,
+        identity: &IdentityRecord,
+        category: Option<Vec<ContractCategory>>,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<HoldRecord>, Error> {
+        identity.nfts(&self.client, category, limit, skip).await
+    }
+
+    async fn resolve_reverse_domains(
+        &self,
+        identity: &IdentityRecord,
+    ) -> Result<Vec<ResolveReverse>, Error> {
+        identity.resolve_reverse_domains(&self.client).await
+    }
+
+    async fn find_expand_identity(
+        &self,
+        platform: &Platform,
+        identity: &str,
+    ) -> Result<Option<ExpandIdentityRecord>, Error> {
+        IdentityGraph::find_expand_identity(&self.client, platform, identity).await
+    }
+
+    async fn find_identity_graph(
+        &self,
+        platform: &Platform,
+        identity: &str,
+        reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Option<IdentityGraph>, Error> {
+        IdentityGraph::find_graph_by_platform_identity(
+            &self.client,
+            platform,
+            identity,
+            reverse,
+            min_level,
+        )
+        .await
+    }
+
+    async fn find_resolve(
+        &self,
+        name: &str,
+        domain_system: &DomainNameSystem,
+    ) -> Result<Option<ResolveEdge>, Error> {
+        Resolve::find_by_name_system(&self.client, name, domain_system).await
+    }
+
+    async fn find_hold_nft(
+        &self,
+        id: &str,
+        chain: &Chain,
+        address: &str,
+    ) -> Result<Option<HoldRecord>, Error> {
+        Hold::find_by_id_chain_address(&self.client, id, chain, address).await
+    }
+
+    async fn domain_available_search(
+        &self,
+        name: &str,
+    ) -> Result<Option<DomainAvailableSearch>, Error> {
+        DomainCollection::domain_available_search(&self.client, name).await
+    }
+
+    async fn delete_graph_inner_connection(&self, v_id: String) -> Result<(), Error> {
+        delete_graph_inner_connection(&self.client, v_id).await
+    }
+
+    async fn delete_edges(&self, edges: EdgeList) -> Result<(), Error> {
+        delete_edges(&self.client, &edges).await
+    }
+
+    async fn split_identity_graph(
+        &self,
+        graph_id: &str,
+        identities: Vec<Identity>,
+    ) -> Result<String, Error> {
+        split_identity_graph(&self.client, graph_id, &identities).await
+    }
+
+    async fn delete_domain_collection(&self, name: &str) -> Result<(), Error> {
+        delete_domain_collection(&self.client, name).await
+    }
+
+    async fn clear_domain_search_cache(&self) -> Result<usize, Error> {
+        clear_domain_search_cache(&self.client).await
+    }
+
+    async fn rank_inputs(&self, limit: u16, page: u32) -> Result<Vec<RankGraph>, Error> {
+        rank_inputs(&self.client, limit, page).await
+    }
+
+    async fn save_ranks(&self, ranks: &[GraphRank]) -> Result<(), Error> {
+        save_ranks(&self.client, ranks).await
+    }
+
+    async fn identity_ranks(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<HashMap<String, Option<f64>>, Error> {
+        get_identity_ranks_by_ids(&self.client, ids).await
+    }
+}
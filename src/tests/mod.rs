@@ -1,3 +1,16 @@
+pub mod replay;
+pub mod tigergraph;
+
+use crate::{
+    error::Error,
+    storage::store,
+    tigergraph::{
+        vertex::{IdentityRecord, Vertex},
+        EdgeList,
+    },
+    upstream::Platform,
+    util::naive_now,
+};
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
 /// Bootstrap function to be defined before each test case.
@@ -19,4 +32,53 @@ fn before_each() {
 
     tracing::subscriber::set_global_default(log_subscriber)
         .expect("Setting default subscriber failed");
+
+    // Upstream calls are answered by recorded fixtures.
+    if let Err(err) = replay::Recorder::from_env().spawn(replay::ADDR.parse().unwrap()) {
+        tracing::warn!(?err, addr = replay::ADDR, "Fixture server not started");
+    }
+
+    // TigerGraph calls are answered by an in-memory stand-in.
+    if let Err(err) = tigergraph::FakeTigerGraph::default().spawn(
+        tigergraph::ADDR.parse().unwrap(),
+        tigergraph::ALLOCATION_ADDR.parse().unwrap(),
+    ) {
+        tracing::warn!(?err, addr = tigergraph::ADDR, "Fake TigerGraph not started");
+    }
+}
+
+/// Snapshot an upstream result as JSON, with fields differing on every run redacted.
+macro_rules! assert_upstream_snapshot {
+    ($value:expr) => {
+        insta::assert_json_snapshot!($value, {
+            ".**.uuid" => "[uuid]",
+            ".**.updated_at" => "[updated_at]",
+            ".**.added_at" => "[added_at]",
+        })
+    };
+}
+pub(crate) use assert_upstream_snapshot;
+
+/// Save what an upstream fetched into the configured store, as `fetch_all` does,
+/// then look (`platform`, `identity`) up in it.
+pub async fn save_and_find(
+    edges: EdgeList,
+    platform: &Platform,
+    identity: &str,
+) -> Result<Option<IdentityRecord>, Error> {
+    if !edges.is_empty() {
+        store().batch_upsert(edges).await?;
+    }
+    store().find_identity(platform, identity).await
+}
+
+/// `updated_at` of `record` is refreshed by the fetch just made.
+pub fn assert_just_updated(record: &IdentityRecord) {
+    let elapsed = naive_now() - record.updated_at;
+    assert!(
+        elapsed < chrono::Duration::try_minutes(1).unwrap(),
+        "{} was updated {} ago",
+        record.primary_key(),
+        elapsed
+    );
 }
@@ -0,0 +1,334 @@
+mod tests;
+
+use http::{header, HeaderValue, Method, Request, Response, StatusCode};
+use hyper::{
+    body::{Bytes, HttpBody},
+    client::HttpConnector,
+    service::{make_service_fn, service_fn},
+    Body, Client, Server,
+};
+use hyper_tls::HttpsConnector;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Upstream URLs in `config/testing.toml` point here.
+pub const ADDR: &str = "127.0.0.1:3724";
+
+/// Headers not forwarded to real upstreams when recording.
+const SKIPPED_HEADERS: [header::HeaderName; 4] = [
+    header::HOST,
+    header::CONTENT_LENGTH,
+    header::ACCEPT_ENCODING,
+    header::CONNECTION,
+];
+
+/// How upstream calls made in tests are answered.
+/// Set by `RELATION_FIXTURES` env: `replay` (default) or `record`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Answer from fixtures on disk only. Unrecorded calls fail.
+    Replay,
+    /// Forward to the real upstream configured in `config/main.toml` and save its answer.
+    Record,
+}
+
+impl Mode {
+    pub fn from_env() -> Self {
+        match std::env::var("RELATION_FIXTURES").as_deref() {
+            Ok("record") => Mode::Record,
+            _ => Mode::Replay,
+        }
+    }
+}
+
+/// One recorded upstream call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub method: String,
+    /// Path and query, relative to the upstream endpoint.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub request: Value,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    pub response: Value,
+}
+
+/// Serves `http://ADDR/{endpoint}/{path}` from fixtures,
+/// where `endpoint` is an upstream config key, e.g. `the_graph.ens`.
+pub struct Recorder {
+    mode: Mode,
+    /// Fixtures are saved in `{dir}/{endpoint}/`.
+    dir: PathBuf,
+    /// Real URL of each endpoint, only needed in `record` mode.
+    endpoints: HashMap<String, String>,
+    client: Client<HttpsConnector<HttpConnector>>,
+}
+
+impl Recorder {
+    pub fn new(mode: Mode, dir: impl Into<PathBuf>, endpoints: HashMap<String, String>) -> Self {
+        Self {
+            mode,
+            dir: dir.into(),
+            endpoints,
+            client: Client::builder().build(HttpsConnector::new()),
+        }
+    }
+
+    /// Recorder of the test suite, using fixtures under `src/tests/fixtures`.
+    pub fn from_env() -> Self {
+        let mode = Mode::from_env();
+        let endpoints = match mode {
+            Mode::Replay => HashMap::new(),
+            Mode::Record => real_endpoints(),
+        };
+        Self::new(
+            mode,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/fixtures"),
+            endpoints,
+        )
+    }
+
+    /// Serve on `addr` in a background thread. Returns the address bound.
+    pub fn spawn(self, addr: SocketAddr) -> std::io::Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let bound = listener.local_addr()?;
+        let recorder = Arc::new(self);
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build fixture server runtime");
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let recorder = recorder.clone();
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |req| recorder.clone().handle(req)))
+                    }
+                });
+                let result = match Server::from_tcp(listener) {
+                    Ok(builder) => builder.serve(make_service).await,
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = result {
+                    error!(?err, "Fixture server stopped");
+                }
+            });
+        });
+        Ok(bound)
+    }
+
+    async fn handle(self: Arc<Self>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let (parts, body) = req.into_parts();
+        let full = parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/")
+            .trim_start_matches('/')
+            .to_string();
+        let (endpoint, path) = match full.find(['/', '?']) {
+            Some(idx) => full.split_at(idx),
+            None => (full.as_str(), ""),
+        };
+        let body = match read_body(body).await {
+            Ok(body) => body,
+            Err(err) => return Ok(text(StatusCode::BAD_REQUEST, err.to_string())),
+        };
+        let request = body_to_value(&body);
+        let file = self
+            .dir
+            .join(endpoint)
+            .join(fixture_name(&parts.method, path, &request));
+
+        let fixture = match self.mode {
+            Mode::Replay => match std::fs::read(&file) {
+                Ok(content) => match serde_json::from_slice::<Fixture>(&content) {
+                    Ok(fixture) => fixture,
+                    Err(err) => {
+                        return Ok(text(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Broken fixture {}: {}", file.display(), err),
+                        ))
+                    }
+                },
+                Err(_) => {
+                    warn!(endpoint, path, file = %file.display(), "No fixture recorded");
+                    return Ok(text(
+                        StatusCode::NOT_IMPLEMENTED,
+                        format!(
+                            "No fixture recorded for {} /{}{}. Record it with RELATION_FIXTURES=record.",
+                            parts.method, endpoint, path
+                        ),
+                    ));
+                }
+            },
+            Mode::Record => {
+                let fixture = match self.forward(endpoint, path, parts, body).await {
+                    Ok(fixture) => fixture,
+                    Err(err) => return Ok(text(StatusCode::BAD_GATEWAY, err)),
+                };
+                if let Err(err) = save(&file, &fixture) {
+                    error!(?err, file = %file.display(), "Failed to save fixture");
+                } else {
+                    info!(endpoint, path, file = %file.display(), "Fixture recorded");
+                }
+                fixture
+            }
+        };
+        Ok(respond(&fixture, &request))
+    }
+
+    /// Call the real upstream of `endpoint`.
+    async fn forward(
+        &self,
+        endpoint: &str,
+        path: &str,
+        parts: http::request::Parts,
+        body: Bytes,
+    ) -> Result<Fixture, String> {
+        let base = self
+            .endpoints
+            .get(endpoint)
+            .ok_or_else(|| format!("Unknown upstream endpoint {}", endpoint))?;
+        let url = match (base.ends_with('/'), path.strip_prefix('/')) {
+            (true, Some(stripped)) => format!("{}{}", base, stripped),
+            _ => format!("{}{}", base, path),
+        };
+        let mut req = Request::builder().method(parts.method.clone()).uri(&url);
+        for (name, value) in parts.headers.iter() {
+            if !SKIPPED_HEADERS.contains(name) {
+                req = req.header(name, value);
+            }
+        }
+        let req = req
+            .body(Body::from(body.clone()))
+            .map_err(|err| err.to_string())?;
+        let resp = self
+            .client
+            .request(req)
+            .await
+            .map_err(|err| format!("{}: {}", url, err))?;
+        let status = resp.status().as_u16();
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let response = read_body(resp.into_body())
+            .await
+            .map_err(|err| err.to_string())?;
+        Ok(Fixture {
+            method: parts.method.to_string(),
+            path: path.to_string(),
+            request: body_to_value(&body),
+            status,
+            content_type,
+            response: body_to_value(&response),
+        })
+    }
+}
+
+/// `http(s)://` URLs under `[upstream]` of `config/main.toml` (and `KV__` env),
+/// keyed as `{section}.{field}`.
+pub fn real_endpoints() -> HashMap<String, String> {
+    let upstream = config::Config::builder()
+        .add_source(config::File::with_name("./config/main").required(false))
+        .add_source(config::Environment::with_prefix("KV").separator("__"))
+        .build()
+        .and_then(|c| c.get_table("upstream"))
+        .unwrap_or_default();
+    let mut endpoints = HashMap::new();
+    for (section, value) in upstream {
+        for (field, value) in value.into_table().unwrap_or_default() {
+            if let Ok(url) = value.into_string() {
+                if url.starts_with("http") {
+                    endpoints.insert(format!("{}.{}", section, field), url);
+                }
+            }
+        }
+    }
+    endpoints
+}
+
+pub async fn read_body(mut body: Body) -> Result<Bytes, hyper::Error> {
+    let mut bytes: Vec<u8> = vec![];
+    while let Some(chunk) = body.data().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    Ok(bytes.into())
+}
+
+/// JSON bodies are kept structured so fixtures stay readable.
+fn body_to_value(body: &[u8]) -> Value {
+    if body.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+}
+
+/// JSON-RPC request IDs are counters of the client, so not part of the match.
+fn rpc_id(request: &Value) -> Option<&Value> {
+    request.get("jsonrpc").and_then(|_| request.get("id"))
+}
+
+/// Fixture file of a call: `{method}-{hash of path and body}.json`.
+pub fn fixture_name(method: &Method, path: &str, request: &Value) -> String {
+    let mut request = request.clone();
+    if rpc_id(&request).is_some() {
+        request["id"] = Value::Null;
+    }
+    let key = format!("{} {} {}", method, path, request);
+    // FNV-1a: stable across platforms and Rust versions.
+    let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{}-{:016x}.json", method.as_str().to_lowercase(), hash)
+}
+
+fn save(file: &PathBuf, fixture: &Fixture) -> std::io::Result<()> {
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut content = serde_json::to_vec_pretty(fixture)?;
+    content.push(b'\n');
+    std::fs::write(file, content)
+}
+
+fn respond(fixture: &Fixture, request: &Value) -> Response<Body> {
+    let body = match (&fixture.response, rpc_id(request)) {
+        (Value::Null, _) => vec![],
+        (Value::String(s), _) => s.clone().into_bytes(),
+        (response, Some(id)) if response.get("jsonrpc").is_some() => {
+            let mut response = response.clone();
+            response["id"] = id.clone();
+            response.to_string().into_bytes()
+        }
+        (response, _) => response.to_string().into_bytes(),
+    };
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = StatusCode::from_u16(fixture.status).unwrap_or(StatusCode::OK);
+    if let Some(value) = fixture
+        .content_type
+        .as_deref()
+        .and_then(|v| HeaderValue::from_str(v).ok())
+    {
+        resp.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    resp
+}
+
+fn text(status: StatusCode, body: String) -> Response<Body> {
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = status;
+    resp
+}
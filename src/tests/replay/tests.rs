@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use crate::tests::replay::{read_body, Mode, Recorder};
+    use http::{Method, Request, StatusCode};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Client, Response, Server,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    /// Fake upstream echoing the request path and body.
+    fn spawn_upstream() -> SocketAddr {
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(|req: Request<Body>| async move {
+                let path = req.uri().to_string();
+                let body = read_body(req.into_body()).await.unwrap();
+                let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+                let resp = match body.get("id") {
+                    Some(id) => json!({"jsonrpc": "2.0", "id": id, "result": path}),
+                    None => json!({"path": path, "echo": body}),
+                };
+                Ok::<_, Infallible>(
+                    Response::builder()
+                        .header("content-type", "application/json")
+                        .body(Body::from(resp.to_string()))
+                        .unwrap(),
+                )
+            }))
+        });
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    fn spawn_recorder(mode: Mode, dir: &PathBuf, upstream: Option<SocketAddr>) -> SocketAddr {
+        let endpoints = upstream
+            .map(|addr| HashMap::from([("fake.url".to_string(), format!("http://{}/api/", addr))]))
+            .unwrap_or_default();
+        Recorder::new(mode, dir, endpoints)
+            .spawn("127.0.0.1:0".parse().unwrap())
+            .unwrap()
+    }
+
+    async fn call(addr: SocketAddr, path: &str, body: Value) -> (StatusCode, Value) {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri(format!("http://{}/fake.url{}", addr, path))
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let resp = Client::new().request(req).await.unwrap();
+        let status = resp.status();
+        let body = read_body(resp.into_body()).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&body)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned())),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = std::env::temp_dir().join(format!("relation_fixtures_{}", Uuid::new_v4()));
+        let upstream = spawn_upstream();
+
+        let recorder = spawn_recorder(Mode::Record, &dir, Some(upstream));
+        let (status, recorded) = call(recorder, "/v1?q=1", json!({"name": "vitalik.eth"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(recorded["path"], "/api/v1?q=1");
+        assert_eq!(std::fs::read_dir(dir.join("fake.url")).unwrap().count(), 1);
+
+        // No upstream needed anymore.
+        let replayer = spawn_recorder(Mode::Replay, &dir, None);
+        let (status, replayed) = call(replayer, "/v1?q=1", json!({"name": "vitalik.eth"})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(replayed, recorded);
+
+        // Different body was never recorded.
+        let (status, _) = call(replayer, "/v1?q=1", json!({"name": "sujiyan.eth"})).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_json_rpc_id() {
+        let dir = std::env::temp_dir().join(format!("relation_fixtures_{}", Uuid::new_v4()));
+        let upstream = spawn_upstream();
+        let request = |id: u64| json!({"jsonrpc": "2.0", "id": id, "method": "getAccountInfo"});
+
+        let recorder = spawn_recorder(Mode::Record, &dir, Some(upstream));
+        let (_, recorded) = call(recorder, "", request(1)).await;
+        assert_eq!(recorded["id"], 1);
+
+        // Same call with another request ID is answered with that ID.
+        let replayer = spawn_recorder(Mode::Replay, &dir, None);
+        let (status, replayed) = call(replayer, "", request(7)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(replayed["id"], 7);
+        assert_eq!(replayed["result"], recorded["result"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1323 @@
+mod tests;
+
+use crate::{
+    error::Error,
+    rank::GraphRank,
+    storage::{GraphStore, MemoryStore},
+    tests::replay::read_body,
+    tigergraph::{
+        edge::{
+            EdgeUnion, FollowRecord, Hold, HoldRecord, HyperEdgeRecord, PartOfCollectionRecord,
+            Proof, ProofRecord, RelationTXRecord, RelationUniqueTXRecord, Resolve, ResolveRecord,
+            SocialFeedRecord, FOLLOW, HOLD_CONTRACT, HOLD_IDENTITY, HYPER_EDGE_REVERSE,
+            PART_OF_COLLECTION, PART_OF_IDENTITIES_GRAPH, PROOF_EDGE, PROOF_REVERSE_EDGE,
+            RELATION_TX, RELATION_UNIQUE_TX, RESOLVE, RESOLVE_CONTRACT, REVERSE_RESOLVE,
+            REVERSE_RESOLVE_CONTRACT, SOCIAL_FEED,
+        },
+        vertex::{
+            contract::VERTEX_NAME as CONTRACTS,
+            domain_collection::VERTEX_NAME as DOMAIN_COLLECTION,
+            identity::VERTEX_NAME as IDENTITIES, identity_graph::VERTEX_NAME as IDENTITIES_GRAPH,
+            Contract, DomainCollection, DomainCollectionRecord, ExpandIdentityRecord,
+            IdentitiesGraph, Identity, IdentityRecord, IdentityWithSource, VertexRecord,
+        },
+        EdgeList, EdgeWrapper, EdgeWrapperEnum, Graph, IdAllocation,
+    },
+    upstream::{Chain, ContractCategory, DomainNameSystem, Platform, ProofLevel},
+};
+use chrono::NaiveDateTime;
+use http::{header, Method, Request, Response, StatusCode};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Server,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+/// REST++ address, `[tdb] host` in `config/testing.toml`.
+pub const ADDR: &str = "127.0.0.1:9000";
+
+/// `id_allocation` service address. The client derives it from `[tdb] host`.
+pub const ALLOCATION_ADDR: &str = "127.0.0.1:9002";
+
+/// TigerGraph gives unset DATETIME attributes as this.
+const DATETIME_DEFAULT: &str = "1970-01-01 00:00:00";
+
+/// DATETIME attributes which upserts may leave out.
+const OPTIONAL_DATETIMES: [&str; 2] = ["created_at", "expired_at"];
+
+/// Error answered in TigerGraph's `{error, code, message}` shape.
+struct Failure {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl Failure {
+    fn not_found(message: String) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "REST-1000",
+            message,
+        }
+    }
+
+    fn bad_request(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "REST-30000",
+            message,
+        }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "GSQL-0000",
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Failure {
+    fn from(err: serde_json::Error) -> Self {
+        Self::bad_request(format!("Invalid JSON: {}", err))
+    }
+}
+
+/// Decoded query string. Keys may repeat (e.g. `categories`).
+struct Params(Vec<(String, String)>);
+
+impl Params {
+    fn parse(query: Option<&str>) -> Self {
+        let decode = |s: &str| {
+            urlencoding::decode(s)
+                .map(|s| s.into_owned())
+                .unwrap_or_else(|_| s.to_string())
+        };
+        Self(
+            query
+                .unwrap_or_default()
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| match pair.split_once('=') {
+                    Some((k, v)) => (decode(k), decode(v)),
+                    None => (decode(pair), String::new()),
+                })
+                .collect(),
+        )
+    }
+
+    fn get(&self, key: &str) -> Result<&str, Failure> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .ok_or_else(|| Failure::bad_request(format!("Parameter {} is missing", key)))
+    }
+
+    fn all(&self, key: &str) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    fn parsed<T: FromStr>(&self, key: &str) -> Result<T, Failure> {
+        let value = self.get(key)?;
+        value
+            .parse()
+            .map_err(|_| Failure::bad_request(format!("Parameter {}={} is invalid", key, value)))
+    }
+}
+
+/// Stand-in of TigerGraph for tests: REST++ upserts, vertex lookups and
+/// the queries of `LoadingJob_SocialGraph.gsql` which `TigerGraphStore` calls,
+/// plus the `id_allocation` service. Data lives in a `MemoryStore`.
+/// Anything else is answered with a TigerGraph-like error.
+#[derive(Default)]
+pub struct FakeTigerGraph {
+    store: MemoryStore,
+}
+
+impl FakeTigerGraph {
+    /// Serve REST++ on `addr` and `id_allocation` on `allocation_addr` in a background thread.
+    /// Returns the addresses bound.
+    pub fn spawn(
+        self,
+        addr: SocketAddr,
+        allocation_addr: SocketAddr,
+    ) -> std::io::Result<(SocketAddr, SocketAddr)> {
+        let bind = |addr: SocketAddr| -> std::io::Result<std::net::TcpListener> {
+            let listener = std::net::TcpListener::bind(addr)?;
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        };
+        let rest = bind(addr)?;
+        let allocation = bind(allocation_addr)?;
+        let bound = (rest.local_addr()?, allocation.local_addr()?);
+        let fake = Arc::new(self);
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build fake TigerGraph runtime");
+            runtime.block_on(async move {
+                let rest_fake = fake.clone();
+                let rest_service = make_service_fn(move |_| {
+                    let fake = rest_fake.clone();
+                    async move { Ok::<_, Infallible>(service_fn(move |req| fake.clone().rest(req))) }
+                });
+                let allocation_service = make_service_fn(move |_| {
+                    let fake = fake.clone();
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |req| fake.clone().allocation(req)))
+                    }
+                });
+                let result = match (Server::from_tcp(rest), Server::from_tcp(allocation)) {
+                    (Ok(rest), Ok(allocation)) => futures::try_join!(
+                        rest.serve(rest_service),
+                        allocation.serve(allocation_service)
+                    )
+                    .map(|_| ()),
+                    (Err(err), _) | (_, Err(err)) => Err(err),
+                };
+                if let Err(err) = result {
+                    error!(?err, "Fake TigerGraph stopped");
+                }
+            });
+        });
+        Ok(bound)
+    }
+
+    async fn rest(self: Arc<Self>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let (parts, body) = req.into_parts();
+        let path = urlencoding::decode(parts.uri.path())
+            .map(|p| p.into_owned())
+            .unwrap_or_else(|_| parts.uri.path().to_string());
+        let params = Params::parse(parts.uri.query());
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match read_json(body).await {
+            Err(err) => Err(err),
+            Ok(body) => match (&parts.method, segments.as_slice()) {
+                (&Method::POST, ["graph", graph]) => match check_graph(graph) {
+                    Ok(()) => self.upsert_graph(&body).await,
+                    Err(err) => Err(err),
+                },
+                (&Method::GET, ["graph", graph, "vertices", v_type]) => match check_graph(graph) {
+                    Ok(()) => self.find_vertices(v_type, &params).await,
+                    Err(err) => Err(err),
+                },
+                (
+                    &Method::DELETE,
+                    ["graph", graph, "edges", from_type, from_id, e_type, to_type, to_id],
+                ) => match check_graph(graph) {
+                    Ok(()) => {
+                        let ends = EdgeEnds {
+                            e_type,
+                            from_type,
+                            from_id,
+                            to_type,
+                            to_id,
+                        };
+                        self.delete_edge(&ends, &params).await
+                    }
+                    Err(err) => Err(err),
+                },
+                (_, ["query", graph, name]) => match check_graph(graph) {
+                    Ok(()) => self.query(name, &params, &body).await,
+                    Err(err) => Err(err),
+                },
+                _ => Err(Failure::not_found(format!(
+                    "Endpoint is not found from url = {}",
+                    path
+                ))),
+            },
+        };
+        Ok(match result {
+            Ok(results) => respond(
+                StatusCode::OK,
+                json!({"version": {"edition": "fake"}, "error": false, "message": "", "results": results}),
+            ),
+            Err(failure) => {
+                warn!(
+                    path,
+                    code = failure.code,
+                    message = failure.message,
+                    "Fake TigerGraph error"
+                );
+                respond(
+                    failure.status,
+                    json!({"error": true, "code": failure.code, "message": failure.message}),
+                )
+            }
+        })
+    }
+
+    /// `POST :9002/id_allocation/allocation`
+    async fn allocation(self: Arc<Self>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+        if req.uri().path() != "/id_allocation/allocation" {
+            return Ok(respond(
+                StatusCode::NOT_FOUND,
+                json!({"code": -1, "msg": "not found"}),
+            ));
+        }
+        let payload: Result<IdAllocation, Failure> = match read_json(req.into_body()).await {
+            Ok(body) => serde_json::from_value(body).map_err(Failure::from),
+            Err(err) => Err(err),
+        };
+        Ok(match payload {
+            Ok(payload) => {
+                let proposed = IdentitiesGraph {
+                    id: payload.graph_id,
+                    updated_nanosecond: payload.updated_nanosecond,
+                };
+                let graph = if payload.reassign {
+                    self.store.reassign(&payload.vids, proposed)
+                } else {
+                    self.store.allocate(&payload.vids, proposed)
+                };
+                respond(
+                    StatusCode::OK,
+                    json!({
+                        "code": 0,
+                        "msg": "",
+                        "data": {
+                            "return_graph_id": graph.id,
+                            "return_updated_nanosecond": graph.updated_nanosecond,
+                        },
+                    }),
+                )
+            }
+            Err(failure) => respond(
+                StatusCode::OK,
+                json!({"code": -1, "msg": failure.message, "data": null}),
+            ),
+        })
+    }
+
+    /// `POST /graph/{graph}`: vertices and edges keyed by type and id.
+    /// Edges whose vertices are neither given nor stored are skipped, as `vertex_must_exist=true`.
+    async fn upsert_graph(&self, body: &Value) -> Result<Value, Failure> {
+        let empty = Map::new();
+        let vertices = body
+            .get("vertices")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        let of_type = |v_type: &str| {
+            vertices
+                .get(v_type)
+                .and_then(Value::as_object)
+                .unwrap_or(&empty)
+        };
+        let mut known = Known::default();
+        for (v_id, attrs) in of_type(IDENTITIES) {
+            if create_only(attrs) && known.identity(&self.store, v_id).await?.is_some() {
+                continue;
+            }
+            known
+                .identities
+                .insert(v_id.clone(), from_attributes(unwrap_values(attrs))?);
+        }
+        for (v_id, attrs) in of_type(CONTRACTS) {
+            known
+                .contracts
+                .insert(v_id.clone(), from_attributes(unwrap_values(attrs))?);
+        }
+        for (v_id, attrs) in of_type(IDENTITIES_GRAPH) {
+            known
+                .graphs
+                .insert(v_id.clone(), from_attributes(unwrap_values(attrs))?);
+        }
+        for (v_id, attrs) in of_type(DOMAIN_COLLECTION) {
+            known
+                .collections
+                .insert(v_id.clone(), from_attributes(unwrap_values(attrs))?);
+        }
+        let accepted_vertices = known.identities.len()
+            + known.contracts.len()
+            + known.graphs.len()
+            + known.collections.len();
+
+        let mut edges: EdgeList = vec![];
+        let mut skipped: Vec<Value> = vec![];
+        let all_edges = body.get("edges").and_then(Value::as_object);
+        for (from_type, from_ids) in all_edges.into_iter().flatten() {
+            for (from_id, e_types) in from_ids.as_object().into_iter().flatten() {
+                for (e_type, to_types) in e_types.as_object().into_iter().flatten() {
+                    for (to_type, to_ids) in to_types.as_object().into_iter().flatten() {
+                        for (to_id, attrs) in to_ids.as_object().into_iter().flatten() {
+                            let ends = EdgeEnds {
+                                e_type,
+                                from_type,
+                                from_id,
+                                to_type,
+                                to_id,
+                            };
+                            match self.edge(&mut known, &ends, unwrap_values(attrs)).await? {
+                                Some(edge) => edges.push(edge),
+                                None => skipped.push(json!({"v_type": to_type, "v_id": to_id})),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let identities: Vec<Identity> = known.identities.into_values().collect();
+        let contracts: Vec<Contract> = known.contracts.into_values().collect();
+        let collections: Vec<DomainCollection> = known.collections.into_values().collect();
+        self.store
+            .upsert(&identities, &contracts, &collections, &edges);
+        Ok(json!([{
+            "accepted_vertices": accepted_vertices,
+            "accepted_edges": edges.len(),
+            "skipped_edges": skipped.len(),
+            "edge_vertices_not_exist": skipped,
+        }]))
+    }
+
+    /// Typed edge of `ends`. `None` if a vertex is missing or the edge type is unknown.
+    async fn edge(
+        &self,
+        known: &mut Known,
+        ends: &EdgeEnds<'_>,
+        attrs: Map<String, Value>,
+    ) -> Result<Option<EdgeWrapperEnum>, Failure> {
+        let edge = match ends.e_type {
+            PROOF_EDGE | PROOF_REVERSE_EDGE => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                let wrapper = EdgeWrapper {
+                    edge: ends.record::<ProofRecord>(attrs)?,
+                    source,
+                    target,
+                };
+                if ends.e_type == PROOF_EDGE {
+                    EdgeWrapperEnum::ProofForward(wrapper)
+                } else {
+                    EdgeWrapperEnum::ProofBackward(wrapper)
+                }
+            }
+            HOLD_IDENTITY => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                EdgeWrapperEnum::HoldIdentity(EdgeWrapper {
+                    edge: ends.record::<HoldRecord>(attrs)?,
+                    source,
+                    target,
+                })
+            }
+            HOLD_CONTRACT => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.contract(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                EdgeWrapperEnum::HoldContract(EdgeWrapper {
+                    edge: ends.record::<HoldRecord>(attrs)?,
+                    source,
+                    target,
+                })
+            }
+            RESOLVE | REVERSE_RESOLVE => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                let wrapper = EdgeWrapper {
+                    edge: ends.record::<ResolveRecord>(attrs)?,
+                    source,
+                    target,
+                };
+                if ends.e_type == RESOLVE {
+                    EdgeWrapperEnum::Resolve(wrapper)
+                } else {
+                    EdgeWrapperEnum::ReverseResolve(wrapper)
+                }
+            }
+            RESOLVE_CONTRACT => {
+                let (Some(source), Some(target)) = (
+                    known.contract(&self.store, ends.from_id).await?,
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                EdgeWrapperEnum::ResolveContract(EdgeWrapper {
+                    edge: ends.record::<ResolveRecord>(attrs)?,
+                    source,
+                    target,
+                })
+            }
+            REVERSE_RESOLVE_CONTRACT => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.contract(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                EdgeWrapperEnum::ReverseResolveContract(EdgeWrapper {
+                    edge: ends.record::<ResolveRecord>(attrs)?,
+                    source,
+                    target,
+                })
+            }
+            HYPER_EDGE_REVERSE => {
+                let (Some(source), Some(target)) = (
+                    known.graphs.get(ends.from_id).cloned(),
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                EdgeWrapperEnum::PartOfIdentitiesGraph(EdgeWrapper {
+                    edge: ends.record::<HyperEdgeRecord>(attrs)?,
+                    source,
+                    target,
+                })
+            }
+            PART_OF_COLLECTION => {
+                let (Some(source), Some(target)) = (
+                    known.collection(&self.store, ends.from_id),
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                EdgeWrapperEnum::PartOfCollection(EdgeWrapper {
+                    edge: ends.record::<PartOfCollectionRecord>(attrs)?,
+                    source,
+                    target,
+                })
+            }
+            RELATION_TX | RELATION_UNIQUE_TX => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                if ends.e_type == RELATION_TX {
+                    EdgeWrapperEnum::RelationTX(EdgeWrapper {
+                        edge: ends.record::<RelationTXRecord>(attrs)?,
+                        source,
+                        target,
+                    })
+                } else {
+                    EdgeWrapperEnum::RelationUniqueTX(EdgeWrapper {
+                        edge: ends.record::<RelationUniqueTXRecord>(attrs)?,
+                        source,
+                        target,
+                    })
+                }
+            }
+            FOLLOW | SOCIAL_FEED => {
+                let (Some(source), Some(target)) = (
+                    known.identity(&self.store, ends.from_id).await?,
+                    known.identity(&self.store, ends.to_id).await?,
+                ) else {
+                    return Ok(None);
+                };
+                if ends.e_type == FOLLOW {
+                    EdgeWrapperEnum::Follow(EdgeWrapper {
+                        edge: ends.record::<FollowRecord>(attrs)?,
+                        source,
+                        target,
+                    })
+                } else {
+                    EdgeWrapperEnum::SocialFeed(EdgeWrapper {
+                        edge: ends.record::<SocialFeedRecord>(attrs)?,
+                        source,
+                        target,
+                    })
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(edge))
+    }
+
+    /// `DELETE /graph/{graph}/edges/{from_type}/{from_id}/{e_type}/{to_type}/{to_id}?filter=..`
+    /// Only filtering by the discriminator attributes is emulated.
+    async fn delete_edge(&self, ends: &EdgeEnds<'_>, params: &Params) -> Result<Value, Failure> {
+        if ends.e_type == PART_OF_IDENTITIES_GRAPH && ends.from_type == IDENTITIES {
+            let deleted = self.store.leave_graph(ends.from_id, ends.to_id);
+            return Ok(json!([{"e_type": ends.e_type, "deleted_edges": deleted as usize}]));
+        }
+        let template = match ends.e_type {
+            PROOF_EDGE | PROOF_REVERSE_EDGE => serde_json::to_value(Proof::default())?,
+            HOLD_IDENTITY | HOLD_CONTRACT => serde_json::to_value(Hold::default())?,
+            RESOLVE | REVERSE_RESOLVE | RESOLVE_CONTRACT | REVERSE_RESOLVE_CONTRACT => {
+                serde_json::to_value(Resolve::default())?
+            }
+            _ => {
+                return Err(Failure::bad_request(format!(
+                    "Deleting {} is not emulated",
+                    ends.e_type
+                )))
+            }
+        };
+        let mut attrs = template.as_object().cloned().unwrap_or_default();
+        for (key, value) in parse_filter(params.get("filter")?) {
+            attrs.insert(key, Value::String(value));
+        }
+        let mut known = Known::default();
+        let edge = self.edge(&mut known, ends, attrs).await?;
+        let deleted = edge.is_some();
+        self.store.delete_edges(edge.into_iter().collect()).await?;
+        Ok(json!([{"e_type": ends.e_type, "deleted_edges": deleted as usize}]))
+    }
+
+    /// `GET /graph/{graph}/vertices/Identities?filter=platform="..",identity=".."`
+    /// Listing without a filter gives nothing, which is enough for `ping`.
+    async fn find_vertices(&self, v_type: &str, params: &Params) -> Result<Value, Failure> {
+        if params.all("filter").is_empty() {
+            return Ok(json!([]));
+        }
+        let filter = parse_filter(params.get("filter")?);
+        let (Some(platform), Some(identity), IDENTITIES) =
+            (filter.get("platform"), filter.get("identity"), v_type)
+        else {
+            return Err(Failure::bad_request(format!(
+                "Only filtering {} by platform and identity is emulated",
+                IDENTITIES
+            )));
+        };
+        let platform = Platform::from_str(platform)
+            .map_err(|_| Failure::bad_request(format!("Unknown platform {}", platform)))?;
+        let record = self.store.find_identity(&platform, identity).await?;
+        Ok(serde_json::to_value(
+            record.into_iter().collect::<Vec<_>>(),
+        )?)
+    }
+
+    /// `/query/{graph}/{name}`: installed GSQL queries.
+    async fn query(&self, name: &str, params: &Params, body: &Value) -> Result<Value, Failure> {
+        let store = &self.store;
+        let results = match name {
+            "neighbors_with_source_reverse" => {
+                let identity = self.vertex_param(params, "p").await?;
+                let (depth, reverse, min_level) = (
+                    params.parsed("depth")?,
+                    reverse_flag(params)?,
+                    min_level(params)?,
+                );
+                let neighbors = match page(params)? {
+                    Some((limit, skip)) => {
+                        store
+                            .neighbors_page(&identity, depth, reverse, min_level, limit, skip)
+                            .await?
+                    }
+                    None => {
+                        store
+                            .neighbors(&identity, depth, reverse, min_level)
+                            .await?
+                    }
+                };
+                let vertices: Vec<Value> = neighbors
+                    .iter()
+                    .map(identity_with_source)
+                    .collect::<Result<_, _>>()?;
+                json!([{ "vertices": vertices }])
+            }
+            "neighbors" => {
+                let identity = self.vertex_param(params, "p").await?;
+                let depth = params.parsed("depth")?;
+                let edges = match page(params)? {
+                    Some((limit, skip)) => {
+                        store
+                            .neighbors_with_traversal_page(&identity, depth, limit, skip)
+                            .await?
+                    }
+                    None => store.neighbors_with_traversal(&identity, depth).await?,
+                };
+                let edges: Vec<Value> = edges
+                    .iter()
+                    .map(|edge| match edge {
+                        EdgeUnion::HoldRecord(record) => serde_json::to_value(record),
+                        EdgeUnion::ProofRecord(record) => serde_json::to_value(record),
+                    })
+                    .collect::<Result<_, _>>()?;
+                json!([{ "edges": edges }])
+            }
+            "relation_single_pair" => {
+                let source = self.vertex_param(params, "v_source").await?;
+                let target = self.vertex_param(params, "target_v").await?;
+                let relations = store
+                    .relation(&source, &target, params.parsed("depth")?)
+                    .await?;
+                json!([{ "relations": relations }])
+            }
+            "expand" => {
+                let identity = self.vertex_param(params, "p").await?;
+                let relations = store
+                    .expand_relation(&identity, params.parsed("depth")?)
+                    .await?;
+                json!([{ "relations": relations }])
+            }
+            "shortest_path" => {
+                let from = self.vertex_param(params, "p").await?;
+                let to = self.vertex_param(params, "q").await?;
+                let paths = store
+                    .path(&from, &to, params.parsed("max_depth")?, min_level(params)?)
+                    .await?;
+                // GSQL prints the set of edges on any shortest path.
+                let mut edges: Vec<Value> = vec![];
+                for hop in paths.iter().flat_map(|path| path.hops.iter()) {
+                    let hop = serde_json::to_value(hop)?;
+                    let edge = json!({
+                        "e_type": hop["e_type"],
+                        "from_id": hop["from_id"],
+                        "to_id": hop["to_id"],
+                        "attributes": {
+                            "source": hop["source"],
+                            "level": hop["level"],
+                            "updated_at": hop["updated_at"],
+                        },
+                    });
+                    if !edges.contains(&edge) {
+                        edges.push(edge);
+                    }
+                }
+                json!([{ "edges": edges }])
+            }
+            "followers" | "following" => {
+                let graph_id = params.get("p")?;
+                let (limit, page) = (params.parsed("numPerPage")?, params.parsed("pageNum")?);
+                let edges = if name == "followers" {
+                    store.followers(graph_id, limit, page).await?
+                } else {
+                    store.following(graph_id, limit, page).await?
+                };
+                json!([{ "edges": edges }])
+            }
+            "reverse_domains" => {
+                let identity = self.vertex_param(params, "p").await?;
+                // Only `attributes` are read by the client.
+                let records: Vec<ResolveRecord> = store
+                    .resolve_reverse_domains(&identity)
+                    .await?
+                    .into_iter()
+                    .map(|reverse| {
+                        ResolveRecord(crate::tigergraph::edge::EdgeRecord {
+                            e_type: REVERSE_RESOLVE.to_string(),
+                            directed: true,
+                            from_id: identity.v_id.clone(),
+                            from_type: IDENTITIES.to_string(),
+                            to_id: format!("{},{}", reverse.system, reverse.name),
+                            to_type: IDENTITIES.to_string(),
+                            discriminator: None,
+                            attributes: reverse.record,
+                        })
+                    })
+                    .collect();
+                json!([{ "reverse_records": records }])
+            }
+            "nfts" => {
+                let identity = self.vertex_param(params, "p").await?;
+                let categories: Vec<ContractCategory> = params
+                    .all("categories")
+                    .into_iter()
+                    .filter_map(|c| ContractCategory::from_str(c).ok())
+                    .collect();
+                let holds = store
+                    .nfts(
+                        &identity,
+                        Some(categories),
+                        params.parsed("numPerPage")?,
+                        params.parsed("numSkip")?,
+                    )
+                    .await?;
+                json!([{ "edges": holds }])
+            }
+            "find_expand_identity" => {
+                let platform: Platform = params.parsed("platform")?;
+                let vlist: Vec<Value> = store
+                    .find_expand_identity(&platform, params.get("identity")?)
+                    .await?
+                    .iter()
+                    .map(expand_identity)
+                    .collect::<Result<_, _>>()?;
+                json!([{ "expand_vlist": vlist }])
+            }
+            "find_identity_graph" => {
+                let p = params.get("p")?;
+                let (platform, identity) = p
+                    .split_once(',')
+                    .ok_or_else(|| Failure::bad_request(format!("Invalid vertex id {}", p)))?;
+                let platform = Platform::from_str(platform)
+                    .map_err(|_| Failure::bad_request(format!("Unknown platform {}", platform)))?;
+                match store
+                    .find_identity_graph(
+                        &platform,
+                        identity,
+                        reverse_flag(params)?,
+                        min_level(params)?,
+                    )
+                    .await?
+                {
+                    None => json!([{ "graph_id": "", "vertices": [], "edges": [] }]),
+                    Some(graph) => {
+                        let vertices: Vec<Value> = graph
+                            .vertices
+                            .iter()
+                            .map(expand_identity)
+                            .collect::<Result<_, _>>()?;
+                        json!([{
+                            "graph_id": graph.graph_id,
+                            "vertices": vertices,
+                            "edges": graph.edges,
+                        }])
+                    }
+                }
+            }
+            "domain2" => {
+                let system: DomainNameSystem = params.parsed("system")?;
+                json!([store.find_domain(params.get("name")?, &system)])
+            }
+            "hold_nft" => {
+                let chain: Chain = params.parsed("chain")?;
+                let hold = store
+                    .find_hold_nft(params.get("id")?, &chain, params.get("address")?)
+                    .await?;
+                json!([{ "holds": hold.into_iter().collect::<Vec<_>>() }])
+            }
+            "domain_available_search" => {
+                let name = params.get("id")?;
+                match store.collection_domains(name) {
+                    None => json!([{ "collection": [], "domains": [] }]),
+                    Some((collection, domains)) => {
+                        let record = DomainCollectionRecord(VertexRecord {
+                            v_type: DOMAIN_COLLECTION.to_string(),
+                            v_id: name.to_string(),
+                            attributes: collection,
+                        });
+                        json!([{ "collection": [record], "domains": domains }])
+                    }
+                }
+            }
+            "identities_by_ids" => {
+                let vertices: Vec<IdentityRecord> = store
+                    .identities_by_ids(ids(body)?)
+                    .await?
+                    .into_values()
+                    .flatten()
+                    .collect();
+                json!([{ "vertices": vertices }])
+            }
+            "contracts_by_ids" => {
+                let vertices: Vec<_> = store
+                    .contracts_by_ids(ids(body)?)
+                    .await?
+                    .into_values()
+                    .flatten()
+                    .collect();
+                json!([{ "vertices": vertices }])
+            }
+            "owners_by_ids" => {
+                let owners: Vec<Value> = store
+                    .owners_by_ids(ids(body)?)
+                    .await?
+                    .into_iter()
+                    .map(|(query_id, owner)| {
+                        json!({"query_id": query_id, "identity": owner.into_iter().collect::<Vec<_>>()})
+                    })
+                    .collect();
+                json!(owners)
+            }
+            "delete_graph_inner_connection" => {
+                store
+                    .delete_graph_inner_connection(params.get("p")?.to_string())
+                    .await?;
+                json!([])
+            }
+            "delete_domain_collection" => {
+                store.delete_domain_collection(params.get("p")?).await?;
+                json!([])
+            }
+            "clear_domain_search_cache" => {
+                let removed = store.clear_domain_search_cache().await?;
+                json!([{ "vertex2delete.size()": removed }])
+            }
+            "identity_rank_inputs" => {
+                let graphs = store
+                    .rank_inputs(params.parsed("numPerPage")?, params.parsed("pageNum")?)
+                    .await?;
+                let mut vertices: Vec<Value> = vec![];
+                let mut identities: Vec<Value> = vec![];
+                let mut proofs: Vec<Value> = vec![];
+                for graph in graphs.into_iter() {
+                    vertices.push(json!({
+                        "v_id": graph.graph_id,
+                        "v_type": IDENTITIES_GRAPH,
+                        "attributes": { "id": graph.graph_id },
+                    }));
+                    identities.extend(graph.identities.into_iter().map(|identity| {
+                        json!({
+                            "v_id": identity.v_id,
+                            "v_type": IDENTITIES,
+                            "attributes": {
+                                "platform": identity.platform,
+                                "created_at": identity.created_at.map_or(
+                                    DATETIME_DEFAULT.to_string(),
+                                    |created_at| created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                                ),
+                                "@graph_id": graph.graph_id,
+                                "@domains": identity.domains,
+                            },
+                        })
+                    }));
+                    proofs.extend(graph.proofs.into_iter().map(|proof| {
+                        json!({
+                            "e_type": PROOF_EDGE,
+                            "from_id": proof.from_id,
+                            "to_id": proof.to_id,
+                            "attributes": { "source": proof.source, "level": proof.level },
+                        })
+                    }));
+                }
+                json!([{ "graphs": vertices, "identities": identities, "proofs": proofs }])
+            }
+            "upsert_identity_rank" => {
+                // Rows of each `IdentitiesGraph` are followed by rows of its `Identities`.
+                let rows: Vec<Map<String, Value>> = parse_str_field(body, "ranks_str")?;
+                let mut ranks: Vec<GraphRank> = vec![];
+                for row in rows.iter() {
+                    let v_id = row.get("v_id").and_then(Value::as_str).unwrap_or_default();
+                    let rank = row.get("rank").and_then(Value::as_f64).unwrap_or_default();
+                    match (row.get("v_type").and_then(Value::as_str), ranks.last_mut()) {
+                        (Some(IDENTITIES_GRAPH), _) => ranks.push(GraphRank {
+                            graph_id: v_id.to_string(),
+                            rank,
+                            identities: HashMap::new(),
+                        }),
+                        (Some(IDENTITIES), Some(graph)) => {
+                            graph.identities.insert(v_id.to_string(), rank);
+                        }
+                        _ => return Err(Failure::bad_request(format!("Invalid rank {:?}", row))),
+                    }
+                }
+                store.save_ranks(&ranks).await?;
+                json!([{ "updated": rows.len() }])
+            }
+            "identity_ranks_by_ids" => {
+                let rank_map: HashMap<String, f64> = store
+                    .identity_ranks(ids(body)?)
+                    .await?
+                    .into_iter()
+                    .filter_map(|(id, rank)| rank.map(|rank| (id, rank)))
+                    .collect();
+                json!([{ "rank_map": rank_map }])
+            }
+            "insert_contract_connection" => self.insert_contract_connection(body).await?,
+            "insert_follow_connection" => self.insert_follow_connection(body).await?,
+            "upsert_hyper_vertex" => {
+                let from: VertexRecord<Value> = parse_str_field(body, "from_str")?;
+                let to: VertexRecord<Value> = parse_str_field(body, "to_str")?;
+                self.upsert_connected(body, vec![from, to]).await?
+            }
+            "upsert_isolated_vertex" => {
+                let vertex: VertexRecord<Value> = parse_str_field(body, "vertex_str")?;
+                self.upsert_connected(body, vec![vertex]).await?
+            }
+            _ => {
+                return Err(Failure::not_found(format!(
+                    "Query {} is not emulated by fake TigerGraph",
+                    name
+                )))
+            }
+        };
+        Ok(results)
+    }
+
+    /// Identity given by vertex id parameter `key`, which must exist.
+    async fn vertex_param(&self, params: &Params, key: &str) -> Result<IdentityRecord, Failure> {
+        let v_id = params.get(key)?;
+        self.store
+            .identities_by_ids(vec![v_id.to_string()])
+            .await?
+            .remove(v_id)
+            .flatten()
+            .ok_or_else(|| {
+                Failure::bad_request(format!(
+                    "Failed to convert user vertex id for parameter {}: {}",
+                    key, v_id
+                ))
+            })
+    }
+
+    /// `insert_contract_connection`: flat edges (with `from_id`, `to_id` and `edge_type`)
+    /// between stored identities and contracts.
+    async fn insert_contract_connection(&self, body: &Value) -> Result<Value, Failure> {
+        let connections: Vec<Map<String, Value>> = parse_str_field(body, "edges_str")?;
+        let mut known = Known::default();
+        let mut edges: EdgeList = vec![];
+        for mut attrs in connections.into_iter() {
+            let mut take = |key: &str| match attrs.remove(key) {
+                Some(Value::String(s)) => s,
+                _ => String::new(),
+            };
+            let (e_type, from_id, to_id) = (take("edge_type"), take("from_id"), take("to_id"));
+            // GSQL has no null: unset `transaction` is sent as "".
+            if attrs.get("transaction") == Some(&json!("")) {
+                attrs.remove("transaction");
+            }
+            let (from_type, to_type) = match e_type.as_str() {
+                RESOLVE_CONTRACT => (CONTRACTS, IDENTITIES),
+                _ => (IDENTITIES, CONTRACTS),
+            };
+            let ends = EdgeEnds {
+                e_type: &e_type,
+                from_type,
+                from_id: &from_id,
+                to_type,
+                to_id: &to_id,
+            };
+            if let Some(edge) = self.edge(&mut known, &ends, attrs).await? {
+                edges.push(edge);
+            }
+        }
+        self.store.upsert(&[], &[], &[], &edges);
+        Ok(json!([{ "created_edges": edges.len() }]))
+    }
+
+    /// `insert_follow_connection`: flat `Follow` edges between stored identities,
+    /// which get an `IdentitiesGraph` of their own if they have none.
+    async fn insert_follow_connection(&self, body: &Value) -> Result<Value, Failure> {
+        let connections: Vec<Map<String, Value>> = parse_str_field(body, "edges_str")?;
+        let mut known = Known::default();
+        let mut edges: EdgeList = vec![];
+        for mut attrs in connections.into_iter() {
+            let mut take = |key: &str| match attrs.remove(key) {
+                Some(Value::String(s)) => s,
+                _ => String::new(),
+            };
+            let (from_id, to_id) = (take("from_id"), take("to_id"));
+            let ends = EdgeEnds {
+                e_type: FOLLOW,
+                from_type: IDENTITIES,
+                from_id: &from_id,
+                to_type: IDENTITIES,
+                to_id: &to_id,
+            };
+            if let Some(edge) = self.edge(&mut known, &ends, attrs).await? {
+                edges.push(edge);
+            }
+        }
+        self.store.upsert(&[], &[], &[], &edges);
+        Ok(json!([{ "created_edges": edges.len() }]))
+    }
+
+    /// `upsert_hyper_vertex` / `upsert_isolated_vertex`: upsert identities and
+    /// put them into one `IdentitiesGraph`.
+    async fn upsert_connected(
+        &self,
+        body: &Value,
+        vertices: Vec<VertexRecord<Value>>,
+    ) -> Result<Value, Failure> {
+        let updated_nanosecond = body
+            .get("updated_nanosecond")
+            .and_then(Value::as_i64)
+            .unwrap_or_default();
+        let mut identities: Vec<Identity> = vec![];
+        for vertex in vertices.into_iter() {
+            if vertex.v_type != IDENTITIES {
+                return Err(Failure::bad_request(format!(
+                    "Vertex type {} is not {}",
+                    vertex.v_type, IDENTITIES
+                )));
+            }
+            let attrs = match vertex.attributes {
+                Value::Object(attrs) => attrs,
+                _ => Map::new(),
+            };
+            identities.push(from_attributes(attrs)?);
+        }
+        self.store.upsert(&identities, &[], &[], &vec![]);
+        let vids: Vec<String> = identities
+            .iter()
+            .map(|i| format!("{},{}", i.platform, i.identity))
+            .collect();
+        let graph = self.store.allocate(
+            &vids,
+            IdentitiesGraph {
+                id: Uuid::new_v4().to_string(),
+                updated_nanosecond,
+            },
+        );
+        Ok(json!([{
+            "created_vertices": identities.len(),
+            "created_hyper_vertices": 1,
+            "final_identity_graph": graph.id,
+        }]))
+    }
+}
+
+/// Vertices of one upsert request, falling back to stored ones.
+#[derive(Default)]
+struct Known {
+    identities: HashMap<String, Identity>,
+    contracts: HashMap<String, Contract>,
+    graphs: HashMap<String, IdentitiesGraph>,
+    collections: HashMap<String, DomainCollection>,
+}
+
+impl Known {
+    async fn identity(
+        &mut self,
+        store: &MemoryStore,
+        v_id: &str,
+    ) -> Result<Option<Identity>, Failure> {
+        if let Some(identity) = self.identities.get(v_id) {
+            return Ok(Some(identity.clone()));
+        }
+        Ok(store
+            .identities_by_ids(vec![v_id.to_string()])
+            .await?
+            .remove(v_id)
+            .flatten()
+            .map(|record| record.0.attributes))
+    }
+
+    async fn contract(
+        &mut self,
+        store: &MemoryStore,
+        v_id: &str,
+    ) -> Result<Option<Contract>, Failure> {
+        if let Some(contract) = self.contracts.get(v_id) {
+            return Ok(Some(contract.clone()));
+        }
+        Ok(store
+            .contracts_by_ids(vec![v_id.to_string()])
+            .await?
+            .remove(v_id)
+            .flatten()
+            .map(|record| record.0.attributes))
+    }
+
+    fn collection(&mut self, store: &MemoryStore, v_id: &str) -> Option<DomainCollection> {
+        self.collections
+            .get(v_id)
+            .cloned()
+            .or_else(|| store.collection_domains(v_id).map(|(c, _)| c))
+    }
+}
+
+/// Type and ends of an edge in an upsert request.
+struct EdgeEnds<'a> {
+    e_type: &'a str,
+    from_type: &'a str,
+    from_id: &'a str,
+    to_type: &'a str,
+    to_id: &'a str,
+}
+
+impl EdgeEnds<'_> {
+    fn record<T: DeserializeOwned>(&self, attrs: Map<String, Value>) -> Result<T, Failure> {
+        Ok(serde_json::from_value(json!({
+            "e_type": self.e_type,
+            "directed": true,
+            "from_id": self.from_id,
+            "from_type": self.from_type,
+            "to_id": self.to_id,
+            "to_type": self.to_type,
+            "attributes": normalize(attrs),
+        }))?)
+    }
+}
+
+/// Both graphs are views of the same global vertices and edges, so they share one store.
+fn check_graph(graph: &str) -> Result<(), Failure> {
+    match Graph::from_str(graph) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Failure::not_found(format!(
+            "Graph {} does not exist",
+            graph
+        ))),
+    }
+}
+
+async fn read_json(body: Body) -> Result<Value, Failure> {
+    let body = read_body(body)
+        .await
+        .map_err(|err| Failure::bad_request(err.to_string()))?;
+    if body.is_empty() {
+        return Ok(Value::Null);
+    }
+    Ok(serde_json::from_slice(&body)?)
+}
+
+fn respond(status: StatusCode, body: Value) -> Response<Body> {
+    let mut resp = Response::new(Body::from(body.to_string()));
+    *resp.status_mut() = status;
+    resp.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    resp
+}
+
+/// Every attribute is `ignore_if_exists`: an existing vertex is left as is.
+fn create_only(attrs: &Value) -> bool {
+    attrs
+        .as_object()
+        .into_iter()
+        .flatten()
+        .all(|(_, attr)| attr.get("op") == Some(&json!("ignore_if_exists")))
+}
+
+/// `{attr: {"value": v, "op": ..}}` of REST++ upserts into `{attr: v}`.
+fn unwrap_values(attrs: &Value) -> Map<String, Value> {
+    attrs
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(k, attr)| (k.clone(), attr.get("value").cloned().unwrap_or(Value::Null)))
+        .collect()
+}
+
+/// Attributes as TigerGraph stores and gives them back.
+fn normalize(attrs: Map<String, Value>) -> Map<String, Value> {
+    let mut attrs: Map<String, Value> = attrs
+        .into_iter()
+        .map(|(k, v)| {
+            let v = match v {
+                Value::String(s) if k == "uuid" && s.is_empty() => Value::Null,
+                Value::String(s) => match NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f")
+                {
+                    Ok(dt) => Value::String(dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    Err(_) => Value::String(s),
+                },
+                v => v,
+            };
+            (k, v)
+        })
+        .collect();
+    for key in OPTIONAL_DATETIMES {
+        attrs
+            .entry(key)
+            .or_insert_with(|| Value::String(DATETIME_DEFAULT.to_string()));
+    }
+    attrs
+}
+
+fn from_attributes<T: DeserializeOwned>(attrs: Map<String, Value>) -> Result<T, Failure> {
+    Ok(serde_json::from_value(Value::Object(normalize(attrs)))?)
+}
+
+/// JSON given as a string field of the request, since GSQL takes no JSON parameter.
+fn parse_str_field<T: DeserializeOwned>(body: &Value, field: &str) -> Result<T, Failure> {
+    let raw = body
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Failure::bad_request(format!("Parameter {} is missing", field)))?;
+    Ok(serde_json::from_str(raw)?)
+}
+
+fn ids(body: &Value) -> Result<Vec<String>, Failure> {
+    Ok(serde_json::from_value(
+        body.get("ids").cloned().unwrap_or(Value::Null),
+    )?)
+}
+
+/// `reverse_flag`: 0 = all, 1 = primary domains only, 2 = non-primary domains only.
+fn reverse_flag(params: &Params) -> Result<Option<bool>, Failure> {
+    match params.parsed::<u8>("reverse_flag")? {
+        0 => Ok(None),
+        1 => Ok(Some(true)),
+        2 => Ok(Some(false)),
+        flag => Err(Failure::bad_request(format!(
+            "Invalid reverse_flag {}",
+            flag
+        ))),
+    }
+}
+
+/// `(numPerPage, numSkip)` of GSQL queries. Without `numPerPage`, or with a negative one,
+/// every record is printed.
+fn page(params: &Params) -> Result<Option<(usize, usize)>, Failure> {
+    if params.all("numPerPage").is_empty() || params.parsed::<i64>("numPerPage")? < 0 {
+        return Ok(None);
+    }
+    let skip = match params.all("numSkip").is_empty() {
+        true => 0,
+        false => params.parsed("numSkip")?,
+    };
+    Ok(Some((params.parsed("numPerPage")?, skip)))
+}
+
+/// `min_level` of GSQL queries. 0 (the default) keeps proofs of any level.
+fn min_level(params: &Params) -> Result<Option<ProofLevel>, Failure> {
+    if params.all("min_level").is_empty() {
+        return Ok(None);
+    }
+    match params.parsed::<i32>("min_level")? {
+        0 => Ok(None),
+        level => serde_json::from_value(json!(level))
+            .map(Some)
+            .map_err(|_| Failure::bad_request(format!("Invalid min_level {}", level))),
+    }
+}
+
+/// `platform="ethereum",identity="0x.."` into a map.
+fn parse_filter(filter: &str) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    let mut rest = filter;
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let Some((value, after)) = after.split_once('"') else {
+            break;
+        };
+        result.insert(key.trim_start_matches(',').to_string(), value.to_string());
+        rest = after;
+    }
+    result
+}
+
+/// `record` as a query result vertex, with accumulators merged into `attributes`.
+fn vertex_with<T: Serialize>(record: &T, accumulators: Value) -> Result<Value, Failure> {
+    let mut value = serde_json::to_value(record)?;
+    if let (Some(attributes), Value::Object(accumulators)) = (
+        value.get_mut("attributes").and_then(Value::as_object_mut),
+        accumulators,
+    ) {
+        attributes.extend(accumulators);
+    }
+    Ok(value)
+}
+
+fn identity_with_source(vertex: &IdentityWithSource) -> Result<Value, Failure> {
+    let sources: Vec<String> = vertex.sources.iter().map(|s| s.to_string()).collect();
+    vertex_with(
+        &vertex.identity,
+        json!({"@source_list": sources, "@reverse": vertex.reverse.unwrap_or(false)}),
+    )
+}
+
+fn expand_identity(vertex: &ExpandIdentityRecord) -> Result<Value, Failure> {
+    vertex_with(
+        &vertex.record,
+        json!({
+            "@owner_address": vertex.owner_address.clone().unwrap_or_default(),
+            "@resolve_address": vertex.resolve_address.clone().unwrap_or_default(),
+        }),
+    )
+}
@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::Error,
+        storage::{GraphStore, MemoryStore, TigerGraphStore},
+        tigergraph::{
+            edge::{
+                Hold, HyperEdge, Proof, Resolve, Wrapper, HOLD_CONTRACT, HOLD_IDENTITY, HYPER_EDGE,
+                PROOF_EDGE, PROOF_REVERSE_EDGE, RESOLVE, REVERSE_RESOLVE,
+            },
+            vertex::{Contract, IdentitiesGraph, Identity},
+            EdgeList, EdgeWrapperEnum,
+        },
+        upstream::{Chain, DataSource, DomainNameSystem, Platform},
+        util::make_http_client,
+    };
+    use http::{Request, StatusCode};
+    use hyper::{Body, Client};
+    use uuid::Uuid;
+
+    /// Identity with a name unique to this run, as the fake is shared by all tests.
+    fn identity(platform: Platform, prefix: &str) -> Identity {
+        Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform,
+            identity: format!("{}{}", prefix, Uuid::new_v4().simple()),
+            ..Default::default()
+        }
+    }
+
+    fn proof_pair(edges: &mut EdgeList, from: &Identity, to: &Identity) {
+        let proof = Proof {
+            source: DataSource::NextID,
+            ..Default::default()
+        };
+        edges.push(EdgeWrapperEnum::new_proof_forward(
+            proof.wrapper(from, to, PROOF_EDGE),
+        ));
+        edges.push(EdgeWrapperEnum::new_proof_backward(proof.wrapper(
+            to,
+            from,
+            PROOF_REVERSE_EDGE,
+        )));
+    }
+
+    fn connect_graph(edges: &mut EdgeList, identities: &[&Identity]) {
+        let hv = IdentitiesGraph::default();
+        for i in identities.iter() {
+            edges.push(EdgeWrapperEnum::new_hyper_edge(
+                HyperEdge {}.wrapper(&hv, i, HYPER_EDGE),
+            ));
+        }
+    }
+
+    fn store() -> TigerGraphStore {
+        TigerGraphStore::new(make_http_client())
+    }
+
+    #[tokio::test]
+    async fn test_batch_upsert_matches_memory_store() -> Result<(), Error> {
+        let tigergraph = store();
+        let memory = MemoryStore::default();
+        let eth = identity(Platform::Ethereum, "0x");
+        let github = identity(Platform::Github, "alice");
+        let twitter = identity(Platform::Twitter, "alice");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &eth, &github);
+        proof_pair(&mut edges, &github, &twitter);
+        connect_graph(&mut edges, &[&eth, &github, &twitter]);
+        tigergraph.batch_upsert(edges.clone()).await?;
+        memory.batch_upsert(edges).await?;
+
+        let found = tigergraph
+            .find_identity(&Platform::Github, &github.identity)
+            .await?
+            .expect("identity should exist");
+        assert_eq!(found.uuid, github.uuid);
+
+        let mut got: Vec<String> = tigergraph
+            .neighbors(&found, 5, None, None)
+            .await?
+            .iter()
+            .map(|n| n.identity.identity.clone())
+            .collect();
+        let mut want: Vec<String> = memory
+            .neighbors(&found, 5, None, None)
+            .await?
+            .iter()
+            .map(|n| n.identity.identity.clone())
+            .collect();
+        got.sort();
+        want.sort();
+        assert_eq!(got, want);
+        assert_eq!(got.len(), 2);
+        assert_eq!(
+            tigergraph.neighbors_with_traversal(&found, 5).await?.len(),
+            memory.neighbors_with_traversal(&found, 5).await?.len(),
+        );
+        // Pages are cut by the query, in the order of the whole result.
+        let page = tigergraph
+            .neighbors_page(&found, 5, None, None, 1, 1)
+            .await?;
+        let all = memory.neighbors(&found, 5, None, None).await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].identity.v_id, all[1].identity.v_id);
+        let page = tigergraph
+            .neighbors_with_traversal_page(&found, 5, 1, 0)
+            .await?;
+        let all = memory.neighbors_with_traversal(&found, 5).await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].sort_key(), all[0].sort_key());
+
+        let graph = tigergraph
+            .find_identity_graph(&Platform::Twitter, &twitter.identity, None, None)
+            .await?
+            .expect("graph should exist");
+        assert_eq!(graph.vertices.len(), 3);
+        assert_eq!(graph.edges.len(), 4);
+        let expand = tigergraph
+            .find_expand_identity(&Platform::Ethereum, &eth.identity)
+            .await?
+            .expect("identity should exist");
+        assert_eq!(expand.record.identity, eth.identity);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_upsert_merges_graphs() -> Result<(), Error> {
+        let tigergraph = store();
+        let a = identity(Platform::Ethereum, "0x");
+        let b = identity(Platform::Github, "a");
+        let c = identity(Platform::Twitter, "c");
+        let mut edges = vec![];
+        proof_pair(&mut edges, &a, &b);
+        connect_graph(&mut edges, &[&a, &b]);
+        tigergraph.batch_upsert(edges).await?;
+        let first = tigergraph
+            .find_identity_graph(&Platform::Github, &b.identity, None, None)
+            .await?
+            .expect("graph should exist");
+
+        let mut edges = vec![];
+        proof_pair(&mut edges, &b, &c);
+        connect_graph(&mut edges, &[&b, &c]);
+        tigergraph.batch_upsert(edges).await?;
+        let merged = tigergraph
+            .find_identity_graph(&Platform::Twitter, &c.identity, None, None)
+            .await?
+            .expect("graph should exist");
+        assert_eq!(merged.graph_id, first.graph_id);
+        assert_eq!(merged.vertices.len(), 3);
+
+        tigergraph
+            .delete_graph_inner_connection(format!("ethereum,{}", a.identity))
+            .await?;
+        let record = tigergraph
+            .find_identity(&Platform::Ethereum, &a.identity)
+            .await?
+            .expect("vertex should be kept");
+        assert!(tigergraph
+            .neighbors(&record, 1, None, None)
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_domain_and_nft() -> Result<(), Error> {
+        let tigergraph = store();
+        let eth = identity(Platform::Ethereum, "0x");
+        let mut lens = identity(Platform::Lens, "alice");
+        lens.identity.push_str(".lens");
+        let contract = Contract {
+            chain: Chain::Ethereum,
+            address: format!("0x{}", Uuid::new_v4().simple()),
+            ..Default::default()
+        };
+        let mut edges = vec![];
+        let hold = Hold {
+            source: DataSource::Lens,
+            ..Default::default()
+        };
+        edges.push(EdgeWrapperEnum::new_hold_identity(hold.wrapper(
+            &eth,
+            &lens,
+            HOLD_IDENTITY,
+        )));
+        for (to, from, e_type) in [(&eth, &lens, RESOLVE), (&lens, &eth, REVERSE_RESOLVE)] {
+            let resolve = Resolve {
+                source: DataSource::TheGraph,
+                system: DomainNameSystem::Lens,
+                name: lens.identity.clone(),
+                ..Default::default()
+            };
+            let wrapper = resolve.wrapper(from, to, e_type);
+            edges.push(match e_type {
+                RESOLVE => EdgeWrapperEnum::new_resolve(wrapper),
+                _ => EdgeWrapperEnum::new_reverse_resolve(wrapper),
+            });
+        }
+        let nft = Hold {
+            id: "1".to_string(),
+            source: DataSource::Rss3,
+            ..Default::default()
+        };
+        edges.push(EdgeWrapperEnum::new_hold_contract(nft.wrapper(
+            &eth,
+            &contract,
+            HOLD_CONTRACT,
+        )));
+        tigergraph.batch_upsert(edges).await?;
+
+        let found = tigergraph
+            .find_resolve(&lens.identity, &DomainNameSystem::Lens)
+            .await?
+            .expect("domain should be found");
+        assert!(found.reverse);
+        assert_eq!(
+            found.owner.map(|o| o.identity.clone()),
+            Some(eth.identity.clone())
+        );
+
+        let record = tigergraph
+            .find_identity(&Platform::Ethereum, &eth.identity)
+            .await?
+            .expect("identity should exist");
+        assert_eq!(tigergraph.resolve_reverse_domains(&record).await?.len(), 1);
+        assert_eq!(tigergraph.nfts(&record, None, 10, 0).await?.len(), 1);
+        assert!(tigergraph
+            .find_hold_nft("1", &Chain::Ethereum, &contract.address)
+            .await?
+            .is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_query() {
+        let req = Request::get(format!(
+            "http://{}/query/SocialGraph/no_such_query",
+            super::super::ADDR
+        ))
+        .body(Body::empty())
+        .unwrap();
+        let resp = Client::new().request(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body = crate::tests::replay::read_body(resp.into_body())
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"], true);
+    }
+}
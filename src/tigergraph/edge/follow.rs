@@ -0,0 +1,256 @@
+use crate::{
+    config::C,
+    error::Error,
+    tigergraph::{
+        edge::{EdgeRecord, FromWithParams, Wrapper},
+        request,
+        vertex::{Identity, Vertex},
+        Attribute, BaseResponse, EdgeWrapper, Graph, OpCode, Transfer,
+    },
+    upstream::DataSource,
+    util::{naive_datetime_from_string, naive_datetime_to_string, naive_now, parse_body},
+};
+
+use chrono::NaiveDateTime;
+use http::uri::InvalidUri;
+use hyper::{client::HttpConnector, Body, Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_json::value::{Map, Value};
+use std::collections::HashMap;
+use tracing::error;
+
+pub const FOLLOW: &str = "Follow";
+pub const IS_DIRECTED: bool = true;
+
+/// Edge to connect the `IdentitiesGraph`s of a follower and whom it follows.
+/// Upstreams give it between the two `Identity`s, see `original_from` / `original_to`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Follow {
+    /// `platform,identity` of the follower.
+    pub original_from: String,
+    /// `platform,identity` being followed.
+    pub original_to: String,
+    /// Who reports this follow.
+    pub source: DataSource,
+    #[serde(deserialize_with = "naive_datetime_from_string")]
+    #[serde(serialize_with = "naive_datetime_to_string")]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowRecord(pub EdgeRecord<Follow>);
+
+impl Default for Follow {
+    fn default() -> Self {
+        Self {
+            original_from: Default::default(),
+            original_to: Default::default(),
+            source: DataSource::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+impl Follow {
+    /// `follower` follows `followee` on `source`.
+    pub fn new(follower: &Identity, followee: &Identity, source: DataSource) -> Self {
+        Self {
+            original_from: follower.primary_key(),
+            original_to: followee.primary_key(),
+            source,
+            updated_at: naive_now(),
+        }
+    }
+}
+
+impl FromWithParams<Follow> for EdgeRecord<Follow> {
+    fn from_with_params(
+        e_type: String,
+        directed: bool,
+        from_id: String,
+        from_type: String,
+        to_id: String,
+        to_type: String,
+        attributes: Follow,
+    ) -> Self {
+        EdgeRecord {
+            e_type,
+            directed,
+            from_id,
+            from_type,
+            to_id,
+            to_type,
+            discriminator: None,
+            attributes,
+        }
+    }
+}
+
+impl From<EdgeRecord<Follow>> for FollowRecord {
+    fn from(record: EdgeRecord<Follow>) -> Self {
+        FollowRecord(record)
+    }
+}
+
+impl std::ops::Deref for FollowRecord {
+    type Target = EdgeRecord<Follow>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for FollowRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl std::ops::Deref for EdgeRecord<Follow> {
+    type Target = Follow;
+
+    fn deref(&self) -> &Self::Target {
+        &self.attributes
+    }
+}
+
+impl std::ops::DerefMut for EdgeRecord<Follow> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.attributes
+    }
+}
+
+impl Transfer for FollowRecord {
+    fn to_attributes_map(&self) -> HashMap<String, Attribute> {
+        self.to_json_value()
+            .into_iter()
+            .map(|(name, value)| {
+                let op = (name == "updated_at").then_some(OpCode::Max);
+                (name, Attribute { value, op })
+            })
+            .collect()
+    }
+
+    fn to_json_value(&self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert("original_from".to_string(), json!(self.original_from));
+        map.insert("original_to".to_string(), json!(self.original_to));
+        map.insert("source".to_string(), json!(self.source));
+        map.insert("updated_at".to_string(), json!(self.updated_at));
+        map
+    }
+}
+
+/// Ends are `Identity`s here. They are replaced by their `IdentitiesGraph`s when saved.
+impl Wrapper<FollowRecord, Identity, Identity> for Follow {
+    fn wrapper(
+        &self,
+        from: &Identity,
+        to: &Identity,
+        name: &str,
+    ) -> EdgeWrapper<FollowRecord, Identity, Identity> {
+        let record = EdgeRecord::from_with_params(
+            name.to_string(),
+            IS_DIRECTED,
+            from.primary_key(),
+            from.vertex_type(),
+            to.primary_key(),
+            to.vertex_type(),
+            self.to_owned(),
+        );
+        EdgeWrapper {
+            edge: FollowRecord(record),
+            source: from.to_owned(),
+            target: to.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct FollowResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<FollowEdges>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct FollowEdges {
+    edges: Vec<FollowRecord>,
+}
+
+impl Follow {
+    /// `Follow` edges into the `IdentitiesGraph` `graph_id`.
+    pub async fn followers(
+        client: &Client<HttpConnector>,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        Self::query(client, "followers", graph_id, limit, offset).await
+    }
+
+    /// `Follow` edges out of the `IdentitiesGraph` `graph_id`.
+    pub async fn following(
+        client: &Client<HttpConnector>,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        Self::query(client, "following", graph_id, limit, offset).await
+    }
+
+    async fn query(
+        client: &Client<HttpConnector>,
+        name: &str,
+        graph_id: &str,
+        limit: u16,
+        offset: u16,
+    ) -> Result<Vec<FollowRecord>, Error> {
+        // query see in Solution: followers(VERTEX<IdentitiesGraph> p, INT numPerPage, INT pageNum)
+        let uri: http::Uri = format!(
+            "{}/query/{}/{}?p={}&numPerPage={}&pageNum={}",
+            C.tdb.host,
+            Graph::SocialGraph,
+            name,
+            graph_id,
+            limit,
+            offset,
+        )
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+        let req = hyper::Request::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .header("Authorization", Graph::SocialGraph.token())
+            .body(Body::empty())
+            .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+        let mut resp = request(client, req).await.map_err(|err| {
+            Error::ManualHttpClientError(format!(
+                "query {} | Fail to request: {:?}",
+                name,
+                err.to_string()
+            ))
+        })?;
+        match parse_body::<FollowResponse>(&mut resp).await {
+            Ok(r) => {
+                if r.base.error {
+                    let err_message = format!(
+                        "TigerGraph query {} error | Code: {:?}, Message: {:?}",
+                        name, r.base.code, r.base.message
+                    );
+                    error!(err_message);
+                    return Err(Error::General(err_message, resp.status()));
+                }
+                Ok(r.results
+                    .and_then(|results| results.into_iter().next())
+                    .map_or(vec![], |result| result.edges))
+            }
+            Err(err) => {
+                let err_message = format!("TigerGraph query {} parse_body error: {:?}", name, err);
+                error!(err_message);
+                Err(err)
+            }
+        }
+    }
+}
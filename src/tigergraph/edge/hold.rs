@@ -3,7 +3,7 @@ use crate::{
     error::Error,
     tigergraph::{
         edge::{Edge, EdgeRecord, EdgeWrapper, FromWithParams, Wrapper},
-        upsert_graph,
+        request, upsert_graph,
         vertex::{contract::VERTEX_NAME as CONTRACTS, Contract, Identity, Vertex, VertexRecord},
         Attribute, BaseResponse, Edges, Graph, OpCode, Transfer, UpsertGraph,
     },
@@ -130,6 +130,18 @@ impl std::ops::DerefMut for HoldRecord {
     }
 }
 
+impl HoldRecord {
+    /// NFTs are paged in this order: contract, token id, then source and transaction.
+    pub fn sort_key(&self) -> (&str, &str, String, &str) {
+        (
+            &self.to_id,
+            &self.id,
+            self.source.to_string(),
+            self.transaction.as_deref().unwrap_or_default(),
+        )
+    }
+}
+
 impl std::ops::Deref for EdgeRecord<Hold> {
     type Target = Hold;
 
@@ -500,7 +512,7 @@ impl Hold {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query find_by_from_to | Fail to request: {:?}",
                 err.to_string()
@@ -555,7 +567,7 @@ impl Hold {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query holder | Fail to request: {:?}",
                 err.to_string()
@@ -1,23 +1,34 @@
+pub mod follow;
 pub mod hold;
 pub mod part_of_collection;
 pub mod part_of_identities_graph;
+pub mod path;
 pub mod proof;
 pub mod relation;
 pub mod resolve;
+pub mod social_feed;
+pub use follow::{Follow, FollowRecord, FOLLOW};
 pub use hold::{Hold, HoldRecord, HOLD_CONTRACT, HOLD_IDENTITY};
 pub use part_of_collection::{
     AvailableDomain, PartOfCollection, PartOfCollectionAttribute, PartOfCollectionRecord,
     PART_OF_COLLECTION,
 };
-pub use part_of_identities_graph::{HyperEdge, HyperEdgeRecord, HYPER_EDGE, HYPER_EDGE_REVERSE};
+pub use part_of_identities_graph::{
+    HyperEdge, HyperEdgeRecord, HYPER_EDGE, HYPER_EDGE_REVERSE, PART_OF_IDENTITIES_GRAPH,
+};
+pub use path::{shortest_paths, IdentityPath, PathHop, MAX_PATHS};
 pub use proof::{
     Proof, ProofRecord, EDGE_NAME as PROOF_EDGE, REVERSE_EDGE_NAME as PROOF_REVERSE_EDGE,
 };
-pub use relation::{RelationUniqueTX, RelationUniqueTXRecord, EDGE_NAME as RELATION_UNIQUE_TX};
+pub use relation::{
+    RelationTX, RelationTXRecord, RelationUniqueTX, RelationUniqueTXRecord,
+    EDGE_NAME as RELATION_UNIQUE_TX, TX_EDGE_NAME as RELATION_TX,
+};
 pub use resolve::{
     Resolve, ResolveEdge, ResolveRecord, RESOLVE, RESOLVE_CONTRACT, REVERSE_RESOLVE,
     REVERSE_RESOLVE_CONTRACT,
 };
+pub use social_feed::{SocialFeed, SocialFeedRecord, SOCIAL_FEED};
 
 use crate::{
     error::Error,
@@ -141,6 +152,35 @@ pub enum EdgeUnion {
     ProofRecord(ProofRecord),
 }
 
+impl EdgeUnion {
+    /// Traversed edges are paged in this order: edge type, from, to, then the rest of the discriminator.
+    pub fn sort_key(&self) -> (String, String, String, String) {
+        match self {
+            EdgeUnion::HoldRecord(hold) => (
+                hold.e_type.clone(),
+                hold.from_id.clone(),
+                hold.to_id.clone(),
+                format!(
+                    "{},{},{}",
+                    hold.source,
+                    hold.transaction.as_deref().unwrap_or_default(),
+                    hold.id
+                ),
+            ),
+            EdgeUnion::ProofRecord(proof) => (
+                proof.e_type.clone(),
+                proof.from_id.clone(),
+                proof.to_id.clone(),
+                format!(
+                    "{},{}",
+                    proof.source,
+                    proof.record_id.as_deref().unwrap_or_default()
+                ),
+            ),
+        }
+    }
+}
+
 // match record {
 //     EdgeUnion::HoldRecord(hold_record) => {
 //         println!("Hold record: {:?}", hold_record)
@@ -17,6 +17,8 @@ use uuid::Uuid;
 pub const HYPER_EDGE: &str = "PartOfIdentitiesGraph_Reverse";
 pub const HYPER_EDGE_REVERSE: &str = "PartOfIdentitiesGraph_Reverse";
 pub const IS_DIRECTED: bool = true;
+/// Identities -> IdentitiesGraph, of which `HYPER_EDGE` is the reverse.
+pub const PART_OF_IDENTITIES_GRAPH: &str = "PartOfIdentitiesGraph";
 
 /// HyperEdge
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -0,0 +1,218 @@
+use crate::{
+    config::C,
+    error::Error,
+    tigergraph::{request, vertex::Identity, vertex::Vertex, BaseResponse, Graph},
+    upstream::{DataSource, ProofLevel},
+    util::{naive_datetime_from_string, naive_datetime_to_string, parse_body},
+};
+
+use chrono::NaiveDateTime;
+use http::uri::InvalidUri;
+use hyper::{client::HttpConnector, Body, Client, Method};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::error;
+
+/// Chains given at most for one pair of identities.
+pub const MAX_PATHS: usize = 10;
+
+/// One `Proof`, `Hold` or `Resolve` edge on a path between two identities.
+/// `from_id` / `to_id` keep the direction of the edge, which may be against the walk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PathHop {
+    pub e_type: String,
+    pub from_id: String,
+    pub to_id: String,
+    pub source: DataSource,
+    /// Only given by `Proof`s.
+    pub level: Option<ProofLevel>,
+    #[serde(deserialize_with = "naive_datetime_from_string")]
+    #[serde(serialize_with = "naive_datetime_to_string")]
+    pub updated_at: NaiveDateTime,
+}
+
+/// Hops from one identity to another, in walking order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdentityPath {
+    pub hops: Vec<PathHop>,
+}
+
+/// Shortest chains from `from` to `to` through `hops` (walked in both directions),
+/// at most `max_depth` long and `MAX_PATHS` many.
+/// Parallel edges between two identities make separate chains.
+pub fn shortest_paths(hops: &[PathHop], from: &str, to: &str, max_depth: u16) -> Vec<IdentityPath> {
+    if from == to {
+        return vec![];
+    }
+    let mut adjacent: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+    for (idx, hop) in hops.iter().enumerate() {
+        if hop.from_id == hop.to_id {
+            continue;
+        }
+        adjacent
+            .entry(&hop.from_id)
+            .or_default()
+            .push((&hop.to_id, idx));
+        adjacent
+            .entry(&hop.to_id)
+            .or_default()
+            .push((&hop.from_id, idx));
+    }
+
+    // Hops reaching each identity in the layer it is first seen.
+    let mut reached_by: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+    let mut visited: HashSet<&str> = HashSet::from([from]);
+    let mut seed: Vec<&str> = vec![from];
+    for _ in 0..max_depth {
+        if seed.is_empty() || visited.contains(to) {
+            break;
+        }
+        let mut layer: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+        for s in seed.iter() {
+            for (v, idx) in adjacent.get(s).into_iter().flatten() {
+                if !visited.contains(v) {
+                    layer.entry(v).or_default().push((s, *idx));
+                }
+            }
+        }
+        visited.extend(layer.keys());
+        seed = layer.keys().copied().collect();
+        reached_by.extend(layer);
+    }
+    if !reached_by.contains_key(to) {
+        return vec![];
+    }
+
+    // Walk back from `to`, collecting hops in reverse.
+    let mut paths: Vec<IdentityPath> = vec![];
+    let mut stack: Vec<(&str, Vec<usize>)> = vec![(to, vec![])];
+    while let Some((v, tail)) = stack.pop() {
+        if paths.len() >= MAX_PATHS {
+            break;
+        }
+        if v == from {
+            paths.push(IdentityPath {
+                hops: tail.iter().rev().map(|idx| hops[*idx].clone()).collect(),
+            });
+            continue;
+        }
+        for (prev, idx) in reached_by.get(v).into_iter().flatten().rev() {
+            let mut tail = tail.clone();
+            tail.push(*idx);
+            stack.push((prev, tail));
+        }
+    }
+    paths
+}
+
+/// Edge as printed by GSQL.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PathEdgeRecord {
+    e_type: String,
+    from_id: String,
+    to_id: String,
+    attributes: PathEdgeAttributes,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PathEdgeAttributes {
+    source: DataSource,
+    #[serde(default)]
+    level: Option<ProofLevel>,
+    #[serde(deserialize_with = "naive_datetime_from_string")]
+    #[serde(serialize_with = "naive_datetime_to_string")]
+    updated_at: NaiveDateTime,
+}
+
+impl From<PathEdgeRecord> for PathHop {
+    fn from(record: PathEdgeRecord) -> Self {
+        PathHop {
+            e_type: record.e_type,
+            from_id: record.from_id,
+            to_id: record.to_id,
+            source: record.attributes.source,
+            level: record.attributes.level,
+            updated_at: record.attributes.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PathResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<PathEdges>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct PathEdges {
+    edges: Vec<PathEdgeRecord>,
+}
+
+impl IdentityPath {
+    /// Shortest chains between `from` and `to`.
+    /// TigerGraph gives the edges on any shortest path. They are chained here.
+    pub async fn find(
+        client: &Client<HttpConnector>,
+        from: &Identity,
+        to: &Identity,
+        max_depth: u16,
+        min_level: Option<ProofLevel>,
+    ) -> Result<Vec<IdentityPath>, Error> {
+        // query see in Solution: shortest_path(VERTEX<Identities> p, VERTEX<Identities> q, INT max_depth, INT min_level)
+        let uri: http::Uri = format!(
+            "{}/query/{}/shortest_path?p={}&q={}&max_depth={}&min_level={}",
+            C.tdb.host,
+            Graph::SocialGraph,
+            from.primary_key(),
+            to.primary_key(),
+            max_depth,
+            min_level.map_or(0, |level| level as i32),
+        )
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+        let req = hyper::Request::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .header("Authorization", Graph::SocialGraph.token())
+            .body(Body::empty())
+            .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+        let mut resp = request(client, req).await.map_err(|err| {
+            Error::ManualHttpClientError(format!(
+                "query shortest_path | Fail to request: {:?}",
+                err.to_string()
+            ))
+        })?;
+        match parse_body::<PathResponse>(&mut resp).await {
+            Ok(r) => {
+                if r.base.error {
+                    let err_message = format!(
+                        "TigerGraph query shortest_path error | Code: {:?}, Message: {:?}",
+                        r.base.code, r.base.message
+                    );
+                    error!(err_message);
+                    return Err(Error::General(err_message, resp.status()));
+                }
+                let hops: Vec<PathHop> = r
+                    .results
+                    .and_then(|results| results.into_iter().next())
+                    .map_or(vec![], |result| result.edges)
+                    .into_iter()
+                    .map(PathHop::from)
+                    .collect();
+                Ok(shortest_paths(
+                    &hops,
+                    &from.primary_key(),
+                    &to.primary_key(),
+                    max_depth,
+                ))
+            }
+            Err(err) => {
+                let err_message =
+                    format!("TigerGraph query shortest_path parse_body error: {:?}", err);
+                error!(err_message);
+                Err(err)
+            }
+        }
+    }
+}
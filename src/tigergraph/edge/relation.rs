@@ -2,9 +2,10 @@ use crate::{
     config::C,
     error::Error,
     tigergraph::{
-        edge::{Edge, EdgeRecord},
+        edge::{Edge, EdgeRecord, FromWithParams, Wrapper},
+        request,
         vertex::{Identity, Vertex, VertexRecord},
-        BaseResponse, Graph,
+        Attribute, BaseResponse, EdgeWrapper, Graph, OpCode, Transfer,
     },
     util::{naive_datetime_from_string, naive_datetime_to_string, naive_now, parse_body},
 };
@@ -12,21 +13,35 @@ use crate::{
 use chrono::NaiveDateTime;
 use http::uri::InvalidUri;
 use hyper::{client::HttpConnector, Body, Client, Method};
+use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_json::value::{Map, Value};
 use std::collections::HashMap;
 use tracing::error;
 use uuid::Uuid;
 
 pub const EDGE_NAME: &str = "Relation_Unique_TX";
+pub const TX_EDGE_NAME: &str = "Relation_TX";
 pub const IS_DIRECTED: bool = true;
 
 /// Edge to connect two `Identity`s.
+/// Aggregation of all `RelationTX` of one token contract from `from` to `to`.
+/// Values are uint256 in decimal, as tokens of any decimals are not comparable.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RelationUniqueTX {
-    pub count: u32,
-    pub sum: u32,
-    pub max: u32,
-    pub min: u32,
+    /// Token contract. Discriminator of this edge.
+    pub contract_address: String,
+    /// Number of transactions.
+    #[serde(rename = "tx_count")]
+    pub count: i64,
+    /// Total value transferred.
+    #[serde(rename = "tx_sum")]
+    pub sum: String,
+    #[serde(rename = "tx_max")]
+    pub max: String,
+    #[serde(rename = "tx_min")]
+    pub min: String,
     #[serde(deserialize_with = "naive_datetime_from_string")]
     #[serde(serialize_with = "naive_datetime_to_string")]
     pub updated_at: NaiveDateTime,
@@ -38,15 +53,39 @@ pub struct RelationUniqueTXRecord(pub EdgeRecord<RelationUniqueTX>);
 impl Default for RelationUniqueTX {
     fn default() -> Self {
         Self {
+            contract_address: Default::default(),
             count: 0,
-            sum: 0,
-            max: 0,
-            min: 0,
+            sum: "0".to_string(),
+            max: "0".to_string(),
+            min: "0".to_string(),
             updated_at: naive_now(),
         }
     }
 }
 
+impl FromWithParams<RelationUniqueTX> for EdgeRecord<RelationUniqueTX> {
+    fn from_with_params(
+        e_type: String,
+        directed: bool,
+        from_id: String,
+        from_type: String,
+        to_id: String,
+        to_type: String,
+        attributes: RelationUniqueTX,
+    ) -> Self {
+        EdgeRecord {
+            e_type,
+            directed,
+            from_id,
+            from_type,
+            to_id,
+            to_type,
+            discriminator: None,
+            attributes,
+        }
+    }
+}
+
 impl From<EdgeRecord<RelationUniqueTX>> for RelationUniqueTXRecord {
     fn from(record: EdgeRecord<RelationUniqueTX>) -> Self {
         RelationUniqueTXRecord(record)
@@ -81,6 +120,243 @@ impl std::ops::DerefMut for EdgeRecord<RelationUniqueTX> {
     }
 }
 
+impl Transfer for RelationUniqueTXRecord {
+    /// Aggregations are recomputed by the fetcher, so they overwrite what is stored.
+    fn to_attributes_map(&self) -> HashMap<String, Attribute> {
+        self.to_json_value()
+            .into_iter()
+            .map(|(name, value)| {
+                let op = (name == "updated_at").then_some(OpCode::Max);
+                (name, Attribute { value, op })
+            })
+            .collect()
+    }
+
+    fn to_json_value(&self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert("contract_address".to_string(), json!(self.contract_address));
+        map.insert("tx_count".to_string(), json!(self.count));
+        map.insert("tx_sum".to_string(), json!(self.sum));
+        map.insert("tx_max".to_string(), json!(self.max));
+        map.insert("tx_min".to_string(), json!(self.min));
+        map.insert("updated_at".to_string(), json!(self.updated_at));
+        map
+    }
+}
+
+impl Wrapper<RelationUniqueTXRecord, Identity, Identity> for RelationUniqueTX {
+    fn wrapper(
+        &self,
+        from: &Identity,
+        to: &Identity,
+        name: &str,
+    ) -> EdgeWrapper<RelationUniqueTXRecord, Identity, Identity> {
+        let record = EdgeRecord::from_with_params(
+            name.to_string(),
+            IS_DIRECTED,
+            from.primary_key(),
+            from.vertex_type(),
+            to.primary_key(),
+            to.vertex_type(),
+            self.to_owned(),
+        );
+        EdgeWrapper {
+            edge: RelationUniqueTXRecord(record),
+            source: from.to_owned(),
+            target: to.to_owned(),
+        }
+    }
+}
+
+impl RelationUniqueTX {
+    /// Aggregate value of all `txs`, which transfer the token of `contract_address`.
+    /// `None` if there is none.
+    pub fn aggregate<'a>(
+        contract_address: &str,
+        txs: impl IntoIterator<Item = &'a RelationTX>,
+    ) -> Option<Self> {
+        let mut values = txs.into_iter().map(|tx| tx.value()).peekable();
+        let first = values.peek()?.clone();
+        let (mut count, mut sum, mut max, mut min) = (0, BigUint::default(), first.clone(), first);
+        for value in values {
+            count += 1;
+            sum += &value;
+            max = max.max(value.clone());
+            min = min.min(value);
+        }
+        Some(Self {
+            contract_address: contract_address.to_string(),
+            count,
+            sum: sum.to_string(),
+            max: max.to_string(),
+            min: min.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Edge to connect two `Identity`s, one per transaction and token transferred from `from` to `to`.
+/// `tx_type` and `block_hash` columns are not known from transfer logs, so they are left unset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelationTX {
+    /// Discriminator of this edge, with `contract_address`.
+    pub transaction_hash: String,
+    pub transaction_index: i64,
+    /// Receipt status. Only succeeded (`1`) transactions emit logs.
+    pub tx_status: i64,
+    pub block_number: u64,
+    #[serde(deserialize_with = "naive_datetime_from_string")]
+    #[serde(serialize_with = "naive_datetime_to_string")]
+    pub block_timestamp: NaiveDateTime,
+    pub from_address: String,
+    pub to_address: String,
+    /// Value transferred in this transaction, uint256 in decimal.
+    pub tx_value: String,
+    /// Token contract transferred.
+    pub contract_address: String,
+    #[serde(deserialize_with = "naive_datetime_from_string")]
+    #[serde(serialize_with = "naive_datetime_to_string")]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RelationTXRecord(pub EdgeRecord<RelationTX>);
+
+impl Default for RelationTX {
+    fn default() -> Self {
+        Self {
+            transaction_hash: Default::default(),
+            transaction_index: 0,
+            tx_status: 1,
+            block_number: 0,
+            block_timestamp: Default::default(),
+            from_address: Default::default(),
+            to_address: Default::default(),
+            tx_value: "0".to_string(),
+            contract_address: Default::default(),
+            updated_at: naive_now(),
+        }
+    }
+}
+
+impl RelationTX {
+    /// `tx_value`, or 0 if it is not a decimal.
+    pub fn value(&self) -> BigUint {
+        self.tx_value.parse().unwrap_or_default()
+    }
+}
+
+impl FromWithParams<RelationTX> for EdgeRecord<RelationTX> {
+    fn from_with_params(
+        e_type: String,
+        directed: bool,
+        from_id: String,
+        from_type: String,
+        to_id: String,
+        to_type: String,
+        attributes: RelationTX,
+    ) -> Self {
+        EdgeRecord {
+            e_type,
+            directed,
+            from_id,
+            from_type,
+            to_id,
+            to_type,
+            discriminator: None,
+            attributes,
+        }
+    }
+}
+
+impl From<EdgeRecord<RelationTX>> for RelationTXRecord {
+    fn from(record: EdgeRecord<RelationTX>) -> Self {
+        RelationTXRecord(record)
+    }
+}
+
+impl std::ops::Deref for RelationTXRecord {
+    type Target = EdgeRecord<RelationTX>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for RelationTXRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl std::ops::Deref for EdgeRecord<RelationTX> {
+    type Target = RelationTX;
+
+    fn deref(&self) -> &Self::Target {
+        &self.attributes
+    }
+}
+
+impl std::ops::DerefMut for EdgeRecord<RelationTX> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.attributes
+    }
+}
+
+impl Transfer for RelationTXRecord {
+    fn to_attributes_map(&self) -> HashMap<String, Attribute> {
+        self.to_json_value()
+            .into_iter()
+            .map(|(name, value)| {
+                let op = (name == "updated_at").then_some(OpCode::Max);
+                (name, Attribute { value, op })
+            })
+            .collect()
+    }
+
+    fn to_json_value(&self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert("transaction_hash".to_string(), json!(self.transaction_hash));
+        map.insert(
+            "transaction_index".to_string(),
+            json!(self.transaction_index),
+        );
+        map.insert("tx_status".to_string(), json!(self.tx_status));
+        map.insert("block_number".to_string(), json!(self.block_number));
+        map.insert("block_timestamp".to_string(), json!(self.block_timestamp));
+        map.insert("from_address".to_string(), json!(self.from_address));
+        map.insert("to_address".to_string(), json!(self.to_address));
+        map.insert("tx_value".to_string(), json!(self.tx_value));
+        map.insert("contract_address".to_string(), json!(self.contract_address));
+        map.insert("updated_at".to_string(), json!(self.updated_at));
+        map
+    }
+}
+
+impl Wrapper<RelationTXRecord, Identity, Identity> for RelationTX {
+    fn wrapper(
+        &self,
+        from: &Identity,
+        to: &Identity,
+        name: &str,
+    ) -> EdgeWrapper<RelationTXRecord, Identity, Identity> {
+        let record = EdgeRecord::from_with_params(
+            name.to_string(),
+            IS_DIRECTED,
+            from.primary_key(),
+            from.vertex_type(),
+            to.primary_key(),
+            to.vertex_type(),
+            self.to_owned(),
+        );
+        EdgeWrapper {
+            edge: RelationTXRecord(record),
+            source: from.to_owned(),
+            target: to.to_owned(),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl Edge<Identity, Identity, RelationUniqueTXRecord> for RelationUniqueTXRecord {
     fn e_type(&self) -> String {
@@ -180,7 +456,7 @@ impl RelationUniqueTX {
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query relation | Fail to request: {:?}",
                 err.to_string()
@@ -233,7 +509,7 @@ impl RelationUniqueTX {
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query relation | Fail to request: {:?}",
                 err.to_string()
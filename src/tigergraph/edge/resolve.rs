@@ -3,7 +3,7 @@ use crate::{
     error::Error,
     tigergraph::{
         edge::{Edge, EdgeRecord, EdgeWrapper, FromWithParams, HoldRecord, Wrapper},
-        upsert_graph,
+        request, upsert_graph,
         vertex::{Contract, Identity, IdentityRecord, Vertex, VertexRecord},
         Attribute, BaseResponse, Edges, Graph, OpCode, Transfer, UpsertGraph,
     },
@@ -449,12 +449,80 @@ pub struct DomainResponse {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Domain {
-    record: Option<ResolveRecordObject>,
-    hold: Option<HoldRecordObject>,
-    resolved: Option<Vec<IdentityRecord>>,
-    owner: Vec<IdentityRecord>,
-    reverse: bool,
-    reverse_record: Option<Vec<IdentityRecord>>,
+    pub record: Option<ResolveRecordObject>,
+    pub hold: Option<HoldRecordObject>,
+    pub resolved: Option<Vec<IdentityRecord>>,
+    pub owner: Vec<IdentityRecord>,
+    pub reverse: bool,
+    pub reverse_record: Option<Vec<IdentityRecord>>,
+}
+
+impl Domain {
+    /// Collapse a `domain2` query result into a `ResolveEdge`.
+    /// Returns `None` if nobody holds this domain.
+    pub fn into_resolve_edge(self, domain_system: &DomainNameSystem) -> Option<ResolveEdge> {
+        self.hold.map_or(None, |hold_obj| {
+            use HoldRecordObject::*;
+            let resolve_edge = match hold_obj {
+                Nonempty(hold) => {
+                    use ResolveRecordObject::*;
+                    let resolve_edge = match self.record {
+                        Some(Empty {}) | None => {
+                            let mut resolve_edge: ResolveEdge =
+                                ResolveEdge::from(ResolveReverse::from(Resolve {
+                                    uuid: hold.attributes.uuid,
+                                    source: hold.attributes.source,
+                                    system: domain_system.clone(),
+                                    name: self
+                                        .owner
+                                        .first()
+                                        .unwrap()
+                                        .to_owned()
+                                        .attributes
+                                        .identity
+                                        .clone(),
+                                    fetcher: hold.attributes.fetcher,
+                                    updated_at: hold.attributes.updated_at,
+                                }));
+
+                            resolve_edge.reverse = self.reverse;
+                            resolve_edge.reverse_record = self
+                                .reverse_record
+                                .and_then(|records| records.first().cloned());
+                            resolve_edge.expired_at = hold.attributes.expired_at;
+                            resolve_edge.owner = self.owner.first().cloned();
+                            resolve_edge.resolved = None;
+                            resolve_edge
+                        }
+                        Some(Nonempty(record)) => {
+                            let mut resolve_edge =
+                                ResolveEdge::from(ResolveReverse::from(Resolve {
+                                    uuid: record.attributes.uuid,
+                                    source: record.attributes.source,
+                                    system: record.attributes.system,
+                                    name: record.attributes.name.clone(),
+                                    fetcher: record.attributes.fetcher,
+                                    updated_at: record.attributes.updated_at,
+                                }));
+
+                            resolve_edge.reverse = self.reverse;
+                            resolve_edge.reverse_record = self
+                                .reverse_record
+                                .and_then(|records| records.first().cloned());
+                            resolve_edge.expired_at = hold.attributes.expired_at;
+                            resolve_edge.owner = self.owner.first().cloned();
+                            resolve_edge.resolved =
+                                self.resolved.and_then(|resolves| resolves.first().cloned());
+                            resolve_edge
+                        }
+                    };
+                    Some(resolve_edge)
+                }
+                Empty {} => None,
+            };
+            resolve_edge
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -563,7 +631,7 @@ impl Resolve {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query domain | Fail to request: {:?}",
                 err.to_string()
@@ -583,70 +651,7 @@ impl Resolve {
                 let result = r
                     .results
                     .and_then(|domain_resp| domain_resp.first().cloned())
-                    .and_then(|domain| {
-                        domain.hold.map_or(None, |hold_obj| {
-                            use HoldRecordObject::*;
-                            let resolve_edge = match hold_obj {
-                                Nonempty(hold) => {
-                                    use ResolveRecordObject::*;
-                                    let resolve_edge = match domain.record {
-                                        Some(Empty {}) | None => {
-                                            let mut resolve_edge: ResolveEdge =
-                                                ResolveEdge::from(ResolveReverse::from(Resolve {
-                                                    uuid: hold.attributes.uuid,
-                                                    source: hold.attributes.source,
-                                                    system: domain_system.clone(),
-                                                    name: domain
-                                                        .owner
-                                                        .first()
-                                                        .unwrap()
-                                                        .to_owned()
-                                                        .attributes
-                                                        .identity
-                                                        .clone(),
-                                                    fetcher: hold.attributes.fetcher,
-                                                    updated_at: hold.attributes.updated_at,
-                                                }));
-
-                                            resolve_edge.reverse = domain.reverse;
-                                            resolve_edge.reverse_record = domain
-                                                .reverse_record
-                                                .and_then(|records| records.first().cloned());
-                                            resolve_edge.expired_at = hold.attributes.expired_at;
-                                            resolve_edge.owner = domain.owner.first().cloned();
-                                            resolve_edge.resolved = None;
-                                            resolve_edge
-                                        }
-                                        Some(Nonempty(record)) => {
-                                            let mut resolve_edge =
-                                                ResolveEdge::from(ResolveReverse::from(Resolve {
-                                                    uuid: record.attributes.uuid,
-                                                    source: record.attributes.source,
-                                                    system: record.attributes.system,
-                                                    name: record.attributes.name.clone(),
-                                                    fetcher: record.attributes.fetcher,
-                                                    updated_at: record.attributes.updated_at,
-                                                }));
-
-                                            resolve_edge.reverse = domain.reverse;
-                                            resolve_edge.reverse_record = domain
-                                                .reverse_record
-                                                .and_then(|records| records.first().cloned());
-                                            resolve_edge.expired_at = hold.attributes.expired_at;
-                                            resolve_edge.owner = domain.owner.first().cloned();
-                                            resolve_edge.resolved = domain
-                                                .resolved
-                                                .and_then(|resolves| resolves.first().cloned());
-                                            resolve_edge
-                                        }
-                                    };
-                                    Some(resolve_edge)
-                                }
-                                Empty {} => None,
-                            };
-                            resolve_edge
-                        })
-                    });
+                    .and_then(|domain| domain.into_resolve_edge(domain_system));
                 Ok(result)
             }
             Err(err) => {
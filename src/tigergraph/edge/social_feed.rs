@@ -0,0 +1,149 @@
+use crate::{
+    tigergraph::{
+        edge::{EdgeRecord, FromWithParams, Wrapper},
+        vertex::{Identity, Vertex},
+        Attribute, EdgeWrapper, OpCode, Transfer,
+    },
+    upstream::DataSource,
+    util::{naive_datetime_from_string, naive_datetime_to_string, naive_now},
+};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_json::value::{Map, Value};
+use std::collections::HashMap;
+
+pub const SOCIAL_FEED: &str = "Social_Feed";
+pub const IS_DIRECTED: bool = true;
+
+/// Edge to connect two `Identity`s interacting on a social platform,
+/// e.g. `from` commented on a post of `to`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SocialFeed {
+    /// Who reports this interaction.
+    pub source: DataSource,
+    /// Kind of the interaction, e.g. `like`, `comment`.
+    pub action: String,
+    /// How many times `from` did `action` to `to`. Overwritten on every fetch.
+    pub action_count: i64,
+    #[serde(deserialize_with = "naive_datetime_from_string")]
+    #[serde(serialize_with = "naive_datetime_to_string")]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SocialFeedRecord(pub EdgeRecord<SocialFeed>);
+
+impl Default for SocialFeed {
+    fn default() -> Self {
+        Self {
+            source: DataSource::default(),
+            action: Default::default(),
+            action_count: 0,
+            updated_at: naive_now(),
+        }
+    }
+}
+
+impl FromWithParams<SocialFeed> for EdgeRecord<SocialFeed> {
+    fn from_with_params(
+        e_type: String,
+        directed: bool,
+        from_id: String,
+        from_type: String,
+        to_id: String,
+        to_type: String,
+        attributes: SocialFeed,
+    ) -> Self {
+        EdgeRecord {
+            e_type,
+            directed,
+            from_id,
+            from_type,
+            to_id,
+            to_type,
+            discriminator: None,
+            attributes,
+        }
+    }
+}
+
+impl From<EdgeRecord<SocialFeed>> for SocialFeedRecord {
+    fn from(record: EdgeRecord<SocialFeed>) -> Self {
+        SocialFeedRecord(record)
+    }
+}
+
+impl std::ops::Deref for SocialFeedRecord {
+    type Target = EdgeRecord<SocialFeed>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for SocialFeedRecord {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl std::ops::Deref for EdgeRecord<SocialFeed> {
+    type Target = SocialFeed;
+
+    fn deref(&self) -> &Self::Target {
+        &self.attributes
+    }
+}
+
+impl std::ops::DerefMut for EdgeRecord<SocialFeed> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.attributes
+    }
+}
+
+impl Transfer for SocialFeedRecord {
+    fn to_attributes_map(&self) -> HashMap<String, Attribute> {
+        self.to_json_value()
+            .into_iter()
+            .map(|(name, value)| {
+                let op = (name == "updated_at").then_some(OpCode::Max);
+                (name, Attribute { value, op })
+            })
+            .collect()
+    }
+
+    fn to_json_value(&self) -> Map<String, Value> {
+        let mut map = Map::new();
+        map.insert("source".to_string(), json!(self.source));
+        map.insert("action".to_string(), json!(self.action));
+        map.insert("action_count".to_string(), json!(self.action_count));
+        map.insert("updated_at".to_string(), json!(self.updated_at));
+        map
+    }
+}
+
+impl Wrapper<SocialFeedRecord, Identity, Identity> for SocialFeed {
+    fn wrapper(
+        &self,
+        from: &Identity,
+        to: &Identity,
+        name: &str,
+    ) -> EdgeWrapper<SocialFeedRecord, Identity, Identity> {
+        let record = EdgeRecord::from_with_params(
+            name.to_string(),
+            IS_DIRECTED,
+            from.primary_key(),
+            from.vertex_type(),
+            to.primary_key(),
+            to.vertex_type(),
+            self.to_owned(),
+        );
+        EdgeWrapper {
+            edge: SocialFeedRecord(record),
+            source: from.to_owned(),
+            target: to.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,455 @@
+mod tests;
+
+use crate::{
+    config::ConfigTigerGraph,
+    error::Error,
+    tigergraph::{request, BaseResponse, Graph},
+    util::{naive_now, parse_body},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http::{uri::InvalidUri, StatusCode};
+use hyper::{body::HttpBody as _, client::HttpConnector, Body, Client, Method};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+
+/// Vertex recording each applied migration, keyed by file name.
+pub const VERTEX_NAME: &str = "SchemaMigration";
+
+/// Schema change which creates `SchemaMigration`.
+/// Migrations applied before it are recorded right after it.
+const TRACKED_SINCE: u32 = 4;
+
+macro_rules! gsql {
+    ($file:literal) => {
+        include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/config/tdb/migrations/",
+            $file
+        ))
+    };
+}
+
+/// A `.gsql` file of `src/config/tdb/migrations`.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Schema changes are applied once, in order of version.
+    /// Query files (`None`) are installed again whenever they change.
+    pub version: Option<u32>,
+    pub name: &'static str,
+    pub gsql: &'static str,
+}
+
+/// Everything `migrate` applies, schema changes first.
+/// Released schema changes must not be edited: add a new version instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: Some(1),
+        name: "global.gsql",
+        gsql: gsql!("global.gsql"),
+    },
+    Migration {
+        version: Some(2),
+        name: "graphs.gsql",
+        gsql: gsql!("graphs.gsql"),
+    },
+    Migration {
+        version: Some(3),
+        name: "SCHEMA_CHANGE.gsql",
+        gsql: gsql!("SCHEMA_CHANGE.gsql"),
+    },
+    Migration {
+        version: Some(TRACKED_SINCE),
+        name: "SchemaMigration.gsql",
+        gsql: gsql!("SchemaMigration.gsql"),
+    },
+    Migration {
+        version: Some(5),
+        name: "Alter_SocialGraph.gsql",
+        gsql: gsql!("Alter_SocialGraph.gsql"),
+    },
+    Migration {
+        version: Some(6),
+        name: "Alter_RelationTX.gsql",
+        gsql: gsql!("Alter_RelationTX.gsql"),
+    },
+    Migration {
+        version: None,
+        name: "LoadingJob_SocialGraph.gsql",
+        gsql: gsql!("LoadingJob_SocialGraph.gsql"),
+    },
+    Migration {
+        version: None,
+        name: "DBImportExport_IdentityGraph.gsql",
+        gsql: gsql!("DBImportExport_IdentityGraph.gsql"),
+    },
+];
+
+/// Installed queries this server calls.
+pub const REQUIRED_QUERIES: &[(Graph, &str)] = &[
+    (Graph::SocialGraph, "clear_domain_search_cache"),
+    (Graph::SocialGraph, "contracts_by_ids"),
+    (Graph::SocialGraph, "delete_domain_collection"),
+    (Graph::SocialGraph, "delete_graph_inner_connection"),
+    (Graph::SocialGraph, "domain2"),
+    (Graph::SocialGraph, "domain_available_search"),
+    (Graph::SocialGraph, "expand"),
+    (Graph::SocialGraph, "find_expand_identity"),
+    (Graph::SocialGraph, "find_identity_graph"),
+    (Graph::SocialGraph, "followers"),
+    (Graph::SocialGraph, "following"),
+    (Graph::SocialGraph, "hold_nft"),
+    (Graph::SocialGraph, "identities_by_ids"),
+    (Graph::SocialGraph, "identity_by_source"),
+    (Graph::SocialGraph, "identity_owned_by"),
+    (Graph::SocialGraph, "identity_rank_inputs"),
+    (Graph::SocialGraph, "identity_ranks_by_ids"),
+    (Graph::SocialGraph, "insert_contract_connection"),
+    (Graph::SocialGraph, "insert_follow_connection"),
+    (Graph::SocialGraph, "neighbors"),
+    (Graph::SocialGraph, "neighbors_with_source_reverse"),
+    (Graph::SocialGraph, "nfts"),
+    (Graph::SocialGraph, "owners_by_ids"),
+    (Graph::SocialGraph, "relation_single_pair"),
+    (Graph::SocialGraph, "reverse_domains"),
+    (Graph::SocialGraph, "shortest_path"),
+    (Graph::SocialGraph, "upsert_hyper_vertex"),
+    (Graph::SocialGraph, "upsert_identity_rank"),
+    (Graph::SocialGraph, "upsert_isolated_vertex"),
+    (Graph::IdentityGraph, "query_keybase_connections"),
+];
+
+/// Lines the GSQL server answers a failed statement with.
+const GSQL_ERRORS: [&str; 9] = [
+    "__GSQL__RETURN__CODE__,1",
+    "Encountered \"",
+    "SEMANTIC ERROR",
+    "Syntax Error",
+    "Failed to create",
+    "does not exist",
+    "is not a valid",
+    "Saved as draft",
+    "Failed to install",
+];
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.gsql.as_bytes()))
+    }
+
+    /// `applied` is the checksum of each applied migration, by name.
+    fn is_pending(&self, applied: &HashMap<String, String>) -> bool {
+        match (self.version, applied.get(self.name)) {
+            (_, None) => true,
+            (None, Some(checksum)) => *checksum != self.checksum(),
+            (Some(version), Some(checksum)) => {
+                if *checksum != self.checksum() {
+                    warn!(
+                        version,
+                        name = self.name,
+                        "Schema migration edited after applied"
+                    );
+                }
+                false
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SchemaMigrationsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<SchemaMigrationRecord>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SchemaMigrationRecord {
+    v_id: String,
+    attributes: SchemaMigrationAttributes,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SchemaMigrationAttributes {
+    checksum: String,
+}
+
+/// Apply every pending migration on `tdb`, recording each in `SchemaMigration`.
+/// Schema changes up to `baseline` are recorded without being run,
+/// for graphs migrated by hand before.
+/// Returns names of the migrations run.
+pub async fn migrate(
+    client: &Client<HttpConnector>,
+    tdb: &ConfigTigerGraph,
+    baseline: u32,
+) -> Result<Vec<&'static str>, Error> {
+    let applied = applied(client, tdb).await?;
+    let mut tracked = applied.is_some();
+    let applied = applied.unwrap_or_default();
+
+    let mut unrecorded = vec![];
+    let mut ran = vec![];
+    for migration in MIGRATIONS.iter().filter(|m| m.is_pending(&applied)) {
+        match migration.version {
+            Some(version) if version <= baseline => {
+                info!(version, name = migration.name, "Migration baselined")
+            }
+            _ => {
+                info!(name = migration.name, "Applying migration");
+                run_gsql(client, tdb, migration.gsql).await?;
+                ran.push(migration.name);
+            }
+        }
+        unrecorded.push(migration);
+        tracked |= migration.version >= Some(TRACKED_SINCE);
+        if tracked {
+            record(client, tdb, &unrecorded).await?;
+            unrecorded.clear();
+        }
+    }
+    Ok(ran)
+}
+
+/// Names of migrations `migrate` would apply on `tdb`.
+pub async fn pending(
+    client: &Client<HttpConnector>,
+    tdb: &ConfigTigerGraph,
+) -> Result<Vec<&'static str>, Error> {
+    let applied = applied(client, tdb).await?.unwrap_or_default();
+    Ok(MIGRATIONS
+        .iter()
+        .filter(|m| m.is_pending(&applied))
+        .map(|m| m.name)
+        .collect())
+}
+
+/// Check every query of `REQUIRED_QUERIES` is installed on `tdb`.
+/// Pending migrations are only warned about.
+pub async fn verify(client: &Client<HttpConnector>, tdb: &ConfigTigerGraph) -> Result<(), Error> {
+    let mut missing = vec![];
+    for graph in [Graph::SocialGraph, Graph::IdentityGraph] {
+        let installed = installed_queries(client, tdb, graph).await?;
+        missing.extend(
+            REQUIRED_QUERIES
+                .iter()
+                .filter(|(g, name)| *g == graph && !installed.contains(*name))
+                .map(|(g, name)| format!("{}/{}", g, name)),
+        );
+    }
+    match pending(client, tdb).await {
+        Ok(pending) if !pending.is_empty() => {
+            warn!(
+                ?pending,
+                "TigerGraph migrations pending, see `relation-admin migrate`"
+            )
+        }
+        Ok(_) => {}
+        Err(err) => warn!(%err, "Fail to read TigerGraph migrations"),
+    }
+    if !missing.is_empty() {
+        return Err(Error::General(
+            format!(
+                "TigerGraph | Installed queries missing: {}. Run `relation-admin migrate`.",
+                missing.join(", ")
+            ),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ));
+    }
+    Ok(())
+}
+
+/// Checksum of each applied migration, by name.
+/// `None` if `SchemaMigration` is not created yet.
+async fn applied(
+    client: &Client<HttpConnector>,
+    tdb: &ConfigTigerGraph,
+) -> Result<Option<HashMap<String, String>>, Error> {
+    let uri: http::Uri = format!(
+        "{}/graph/{}/vertices/{}",
+        tdb.host,
+        Graph::SocialGraph,
+        VERTEX_NAME
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Authorization", Graph::SocialGraph.token_in(tdb))
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to read {}: {:?}",
+            VERTEX_NAME,
+            err.to_string()
+        ))
+    })?;
+    let status = resp.status();
+    let result = parse_body::<SchemaMigrationsResponse>(&mut resp).await?;
+    if result.base.error {
+        let message = result.base.message.unwrap_or_default();
+        // Neither the vertex type nor the graph is created on a new TigerGraph.
+        if message.contains(VERTEX_NAME) || message.contains("does not exist") {
+            return Ok(None);
+        }
+        return Err(Error::General(
+            format!(
+                "TigerGraph | Fail to read {} | Code: {:?}, Message: {:?}",
+                VERTEX_NAME, result.base.code, message
+            ),
+            status,
+        ));
+    }
+    Ok(Some(
+        result
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| (r.v_id, r.attributes.checksum))
+            .collect(),
+    ))
+}
+
+/// Upsert a `SchemaMigration` of each of `migrations`.
+async fn record(
+    client: &Client<HttpConnector>,
+    tdb: &ConfigTigerGraph,
+    migrations: &[&Migration],
+) -> Result<(), Error> {
+    let applied_at = naive_now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let vertices: Map<String, Value> = migrations
+        .iter()
+        .map(|m| {
+            let attributes = json!({
+                "id": { "value": m.name },
+                "version": { "value": m.version.unwrap_or(0) },
+                "checksum": { "value": m.checksum() },
+                "applied_at": { "value": applied_at },
+            });
+            (m.name.to_string(), attributes)
+        })
+        .collect();
+    let payload = json!({ "vertices": { VERTEX_NAME: vertices } });
+
+    let uri: http::Uri = format!("{}/graph/{}", tdb.host, Graph::SocialGraph)
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Authorization", Graph::SocialGraph.token_in(tdb))
+        .body(Body::from(payload.to_string()))
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to record migrations: {:?}",
+            err.to_string()
+        ))
+    })?;
+    let status = resp.status();
+    let result = parse_body::<BaseResponse>(&mut resp).await?;
+    if result.error || !status.is_success() {
+        return Err(Error::General(
+            format!(
+                "TigerGraph | Fail to record migrations | Code: {:?}, Message: {:?}",
+                result.code, result.message
+            ),
+            status,
+        ));
+    }
+    Ok(())
+}
+
+/// Run `gsql` on the GSQL server as `gsql_client.jar` does. Returns its output.
+async fn run_gsql(
+    client: &Client<HttpConnector>,
+    tdb: &ConfigTigerGraph,
+    gsql: &str,
+) -> Result<String, Error> {
+    let uri: http::Uri = format!("{}/gsqlserver/gsql/file", tdb.gsql_host)
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let credential = STANDARD.encode(format!("{}:{}", tdb.username, tdb.password));
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Authorization", format!("Basic {}", credential))
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(Body::from(urlencoding::encode(gsql).into_owned()))
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!("GSQL | Fail to request: {:?}", err.to_string()))
+    })?;
+    let status = resp.status();
+    let mut body = vec![];
+    while let Some(chunk) = resp.body_mut().data().await {
+        body.extend_from_slice(&chunk?);
+    }
+    let output = String::from_utf8_lossy(&body).into_owned();
+
+    let failed = output
+        .lines()
+        .find(|line| GSQL_ERRORS.iter().any(|marker| line.contains(marker)));
+    match (status.is_success(), failed) {
+        (true, None) => Ok(output),
+        (_, failed) => Err(Error::General(
+            format!(
+                "GSQL | Fail to run statements | Status: {}, Output: {:?}",
+                status,
+                failed.unwrap_or(output.trim())
+            ),
+            if status.is_success() {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                status
+            },
+        )),
+    }
+}
+
+/// Names of the queries installed in `graph`.
+async fn installed_queries(
+    client: &Client<HttpConnector>,
+    tdb: &ConfigTigerGraph,
+    graph: Graph,
+) -> Result<HashSet<String>, Error> {
+    let uri: http::Uri = format!("{}/endpoints/{}?dynamic=true", tdb.host, graph)
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Authorization", graph.token_in(tdb))
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to list endpoints of {}: {:?}",
+            graph,
+            err.to_string()
+        ))
+    })?;
+    let status = resp.status();
+    // Keyed by endpoint, e.g. `GET /query/SocialGraph/neighbors`.
+    let endpoints = parse_body::<Map<String, Value>>(&mut resp).await?;
+    if endpoints.get("error") == Some(&Value::Bool(true)) || !status.is_success() {
+        return Err(Error::General(
+            format!(
+                "TigerGraph | Fail to list endpoints of {} | Message: {:?}",
+                graph,
+                endpoints.get("message")
+            ),
+            status,
+        ));
+    }
+    let prefix = format!("/query/{}/", graph);
+    Ok(endpoints
+        .keys()
+        .filter_map(|endpoint| endpoint.split_whitespace().nth(1))
+        .filter_map(|path| path.strip_prefix(&prefix))
+        .map(|name| name.to_string())
+        .collect())
+}
@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{ConfigTigerGraph, C},
+        error::Error,
+        tigergraph::migration::{
+            migrate, pending, verify, Migration, MIGRATIONS, REQUIRED_QUERIES, VERTEX_NAME,
+        },
+        util::make_http_client,
+    };
+    use http::{Method, Request, Response, StatusCode};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Server,
+    };
+    use regex::Regex;
+    use serde_json::{json, Map, Value};
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+
+    /// What the mock TigerGraph was told.
+    #[derive(Default)]
+    struct State {
+        /// GSQL files run, in order.
+        statements: Vec<String>,
+        /// `SchemaMigration` vertices, `None` until the vertex type is created.
+        migrations: Option<Map<String, Value>>,
+        /// `(graph, query)` created so far.
+        queries: Vec<(String, String)>,
+    }
+
+    /// Mock GSQL server and REST++, both served on the returned host.
+    fn spawn_tigergraph(state: Arc<Mutex<State>>) -> String {
+        let make_service = make_service_fn(move |_| {
+            let state = state.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let state = state.clone();
+                    async move {
+                        let method = req.method().clone();
+                        let path = req.uri().path().to_string();
+                        let body = crate::tests::replay::read_body(req.into_body())
+                            .await
+                            .unwrap();
+                        let body = String::from_utf8_lossy(&body).into_owned();
+                        let mut state = state.lock().unwrap();
+                        let (status, resp) = match (method, path.as_str()) {
+                            (Method::POST, "/gsqlserver/gsql/file") => {
+                                let gsql = urlencoding::decode(&body).unwrap().into_owned();
+                                if gsql.contains(&format!("ADD VERTEX {} TO", VERTEX_NAME)) {
+                                    state.migrations = Some(Map::new());
+                                }
+                                let query =
+                                    Regex::new(r"QUERY (\w+)\(.*\) FOR GRAPH (\w+)").unwrap();
+                                for cap in query.captures_iter(&gsql) {
+                                    state.queries.push((cap[2].to_string(), cap[1].to_string()));
+                                }
+                                state.statements.push(gsql);
+                                (StatusCode::OK, Value::String("Successfully ran".into()))
+                            }
+                            (Method::GET, "/graph/SocialGraph/vertices/SchemaMigration") => {
+                                match &state.migrations {
+                                    None => (
+                                        StatusCode::BAD_REQUEST,
+                                        json!({"error": true, "code": "REST-30000", "message": "SchemaMigration is not a valid vertex type"}),
+                                    ),
+                                    Some(migrations) => {
+                                        let results: Vec<Value> = migrations
+                                            .iter()
+                                            .map(|(id, attributes)| {
+                                                json!({"v_id": id, "v_type": VERTEX_NAME, "attributes": attributes})
+                                            })
+                                            .collect();
+                                        (
+                                            StatusCode::OK,
+                                            json!({"error": false, "message": "", "results": results}),
+                                        )
+                                    }
+                                }
+                            }
+                            (Method::POST, "/graph/SocialGraph") => {
+                                let body: Value = serde_json::from_str(&body).unwrap();
+                                let migrations = state.migrations.as_mut().unwrap();
+                                for (id, attributes) in
+                                    body["vertices"][VERTEX_NAME].as_object().unwrap()
+                                {
+                                    let attributes: Map<String, Value> = attributes
+                                        .as_object()
+                                        .unwrap()
+                                        .iter()
+                                        .map(|(k, v)| (k.clone(), v["value"].clone()))
+                                        .collect();
+                                    migrations.insert(id.clone(), Value::Object(attributes));
+                                }
+                                (StatusCode::OK, json!({"error": false, "message": ""}))
+                            }
+                            (Method::GET, path) if path.starts_with("/endpoints/") => {
+                                let graph = path.trim_start_matches("/endpoints/");
+                                let endpoints: Map<String, Value> = state
+                                    .queries
+                                    .iter()
+                                    .filter(|(g, _)| g == graph)
+                                    .map(|(g, q)| (format!("GET /query/{}/{}", g, q), json!({})))
+                                    .collect();
+                                (StatusCode::OK, Value::Object(endpoints))
+                            }
+                            _ => (
+                                StatusCode::NOT_FOUND,
+                                json!({"error": true, "message": "Not found"}),
+                            ),
+                        };
+                        let body = match resp {
+                            Value::String(text) => text,
+                            json => json.to_string(),
+                        };
+                        Ok::<_, Infallible>(
+                            Response::builder()
+                                .status(status)
+                                .body(Body::from(body))
+                                .unwrap(),
+                        )
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        format!("http://{}", addr)
+    }
+
+    fn tdb(host: String) -> ConfigTigerGraph {
+        ConfigTigerGraph {
+            host: host.clone(),
+            gsql_host: host,
+            ..C.tdb.clone()
+        }
+    }
+
+    fn names(migrations: &[&Migration]) -> Vec<&'static str> {
+        migrations.iter().map(|m| m.name).collect()
+    }
+
+    #[test]
+    fn test_required_queries_defined() {
+        for (graph, name) in REQUIRED_QUERIES {
+            let definition = format!("CREATE OR REPLACE QUERY {}(", name);
+            let defined = MIGRATIONS
+                .iter()
+                .filter(|m| m.version.is_none())
+                .flat_map(|m| m.gsql.lines())
+                .any(|line| {
+                    line.starts_with(&definition) && line.contains(&format!("FOR GRAPH {}", graph))
+                });
+            assert!(defined, "{}/{} is not defined", graph, name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrate() -> Result<(), Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let tdb = tdb(spawn_tigergraph(state.clone()));
+        let client = make_http_client();
+
+        assert!(verify(&client, &tdb).await.is_err());
+        let all: Vec<&Migration> = MIGRATIONS.iter().collect();
+        assert_eq!(pending(&client, &tdb).await?, names(&all));
+
+        assert_eq!(migrate(&client, &tdb, 0).await?, names(&all));
+        assert_eq!(state.lock().unwrap().statements.len(), MIGRATIONS.len());
+        assert_eq!(
+            state.lock().unwrap().migrations.as_ref().unwrap().len(),
+            MIGRATIONS.len()
+        );
+        verify(&client, &tdb).await?;
+
+        // Nothing changed since.
+        assert!(pending(&client, &tdb).await?.is_empty());
+        assert!(migrate(&client, &tdb, 0).await?.is_empty());
+        assert_eq!(state.lock().unwrap().statements.len(), MIGRATIONS.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_migrate_baseline() -> Result<(), Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let tdb = tdb(spawn_tigergraph(state.clone()));
+        let client = make_http_client();
+
+        // Schema up to version 3 was created by hand.
+        let ran = migrate(&client, &tdb, 3).await?;
+        let expected: Vec<&Migration> = MIGRATIONS
+            .iter()
+            .filter(|m| m.version.is_none_or(|v| v > 3))
+            .collect();
+        assert_eq!(ran, names(&expected));
+        assert!(!state.lock().unwrap().statements[0].contains("CREATE VERTEX Identities"));
+
+        // Baselined ones are recorded as well.
+        let state = state.lock().unwrap();
+        let migrations = state.migrations.as_ref().unwrap();
+        assert_eq!(migrations.len(), MIGRATIONS.len());
+        assert_eq!(migrations["global.gsql"]["version"], 1);
+        assert_eq!(
+            migrations["global.gsql"]["checksum"],
+            MIGRATIONS[0].checksum()
+        );
+        Ok(())
+    }
+}
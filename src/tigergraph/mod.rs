@@ -1,17 +1,23 @@
 pub mod edge;
+pub mod migration;
+pub mod rank;
 mod tests;
 pub mod upsert;
 pub mod vertex;
 
 use crate::{
-    config::C,
+    config::{ConfigTigerGraph, C},
     error::Error,
+    metrics::observe_tigergraph,
     tigergraph::{
         edge::{
-            Edge, Hold, HoldRecord, HyperEdgeRecord, PartOfCollectionRecord, Proof, ProofRecord,
-            Resolve, ResolveRecord, Wrapper, HOLD_CONTRACT, HOLD_IDENTITY, HYPER_EDGE_REVERSE,
-            PART_OF_COLLECTION, PROOF_EDGE, PROOF_REVERSE_EDGE, RESOLVE, RESOLVE_CONTRACT,
-            REVERSE_RESOLVE, REVERSE_RESOLVE_CONTRACT,
+            Edge, FollowRecord, Hold, HoldRecord, HyperEdge, HyperEdgeRecord,
+            PartOfCollectionRecord, Proof, ProofRecord, RelationTXRecord, RelationUniqueTXRecord,
+            Resolve, ResolveRecord, SocialFeedRecord, Wrapper, FOLLOW, HOLD_CONTRACT,
+            HOLD_IDENTITY, HYPER_EDGE, HYPER_EDGE_REVERSE, PART_OF_COLLECTION,
+            PART_OF_IDENTITIES_GRAPH, PROOF_EDGE, PROOF_REVERSE_EDGE, RELATION_TX,
+            RELATION_UNIQUE_TX, RESOLVE, RESOLVE_CONTRACT, REVERSE_RESOLVE,
+            REVERSE_RESOLVE_CONTRACT, SOCIAL_FEED,
         },
         vertex::{Contract, DomainCollection, IdentitiesGraph, Identity, Vertex},
     },
@@ -19,7 +25,7 @@ use crate::{
 };
 
 use chrono::NaiveDateTime;
-use http::uri::InvalidUri;
+use http::{uri::InvalidUri, StatusCode};
 use hyper::Method;
 use hyper::{client::HttpConnector, Body, Client};
 use serde::{Deserialize, Serialize};
@@ -27,6 +33,7 @@ use serde_json::json;
 use serde_json::value::{Map, Value};
 use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
+use std::time::Instant;
 use strum_macros::{Display, EnumIter, EnumString};
 use tracing::{error, trace};
 use uuid::Uuid;
@@ -108,10 +115,15 @@ pub enum Graph {
 
 impl Graph {
     pub fn token(&self) -> String {
+        self.token_in(&C.tdb)
+    }
+
+    /// `Authorization` of this graph on the TigerGraph `tdb`.
+    pub fn token_in(&self, tdb: &ConfigTigerGraph) -> String {
         use Graph::*;
         match self {
-            IdentityGraph => format!("Bearer {}", C.tdb.identity_graph_token),
-            SocialGraph => format!("Bearer {}", C.tdb.social_graph_token),
+            IdentityGraph => format!("Bearer {}", tdb.identity_graph_token),
+            SocialGraph => format!("Bearer {}", tdb.social_graph_token),
         }
     }
 }
@@ -121,6 +133,9 @@ pub struct IdAllocation {
     pub graph_id: String,
     pub updated_nanosecond: i64, // microseconds are one-millionth of a second (1/1,000,000 seconds)
     pub vids: Vec<String>,
+    /// Move `vids` to `graph_id` even if they have a graph already, as splitting a graph does.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reassign: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -138,10 +153,98 @@ pub struct IdAllocationResult {
     pub updated_nanosecond: i64,
 }
 
+/// Send `req` to TigerGraph REST++, recording its latency and errors by installed query name.
+pub async fn request(
+    client: &Client<HttpConnector>,
+    req: hyper::Request<Body>,
+) -> Result<hyper::Response<Body>, hyper::Error> {
+    let query = query_name(req.uri().path());
+    let started = Instant::now();
+    let resp = client.request(req).await;
+    let failed = resp
+        .as_ref()
+        .map_or(true, |resp| !resp.status().is_success());
+    observe_tigergraph(&query, started.elapsed(), failed);
+    resp
+}
+
+/// `name` of `/query/{graph}/{name}`, or the kind of builtin endpoint, e.g. `graph/vertices`.
+fn query_name(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let position = |name: &str| segments.iter().position(|s| *s == name);
+    if let Some(name) = position("query").and_then(|i| segments.get(i + 2)) {
+        return name.to_string();
+    }
+    match position("graph") {
+        Some(i) => match segments.get(i + 2) {
+            Some(kind) => format!("graph/{}", kind),
+            None => "graph".to_string(),
+        },
+        None => "other".to_string(),
+    }
+}
+
+/// Check REST++ is reachable and accepts the token of `graph`.
+pub async fn ping(client: &Client<HttpConnector>, graph: Graph) -> Result<(), Error> {
+    let uri: http::Uri = format!(
+        "{}/graph/{}/vertices/{}?limit=1",
+        C.tdb.host,
+        graph,
+        vertex::identity::VERTEX_NAME
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Authorization", graph.token())
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to ping {}: {:?}",
+            graph,
+            err.to_string()
+        ))
+    })?;
+    let status = resp.status();
+    let result = parse_body::<BaseResponse>(&mut resp).await?;
+    if result.error || !status.is_success() {
+        return Err(Error::General(
+            format!(
+                "TigerGraph | Fail to ping {} | Code: {:?}, Message: {:?}",
+                graph, result.code, result.message
+            ),
+            status,
+        ));
+    }
+    Ok(())
+}
+
+/// `id_allocation` service, on port 9002 of the `[tdb] host`.
+fn id_allocation_url() -> String {
+    format!("{}:{}", C.tdb.host.trim_end_matches(":9000"), "9002")
+}
+
+/// Check the `id_allocation` service is reachable. Nothing is allocated.
+pub async fn ping_id_allocation(client: &Client<HttpConnector>) -> Result<(), Error> {
+    let uri: http::Uri = format!("{}/id_allocation/allocation", id_allocation_url())
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    // Any answer to a GET tells the service is up.
+    client.get(uri).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to ping allocation: {:?}",
+            err.to_string()
+        ))
+    })?;
+    Ok(())
+}
+
 pub async fn id_allocation(payload: &IdAllocation) -> Result<IdAllocationResult, Error> {
     let http_client = make_client().await.unwrap();
-    let id_allocation_url = format!("{}:{}", C.tdb.host.trim_end_matches(":9000"), "9002");
-    let uri: http::Uri = format!("{}/id_allocation/allocation", id_allocation_url,)
+    let uri: http::Uri = format!("{}/id_allocation/allocation", id_allocation_url())
         .parse()
         .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
 
@@ -235,6 +338,37 @@ pub async fn upsert_domain_collection(
 pub async fn batch_upsert(
     client: &Client<HttpConnector>,
     edges: Vec<EdgeWrapperEnum>,
+) -> Result<(), Error> {
+    for layer in discriminated_layers(edges) {
+        batch_upsert_layer(client, layer).await?;
+    }
+    Ok(())
+}
+
+/// `edges` split so that no two edges of one type between the same vertices are in one layer.
+/// Those are told apart by their discriminator, but would overwrite each other in one `UpsertGraph`.
+fn discriminated_layers(edges: Vec<EdgeWrapperEnum>) -> Vec<EdgeList> {
+    let mut layers: Vec<EdgeList> = vec![];
+    let mut seen: HashMap<(String, String, String), usize> = HashMap::new();
+    for edge in edges {
+        let ends = (
+            edge.e_type().to_string(),
+            edge.source().primary_key(),
+            edge.target().primary_key(),
+        );
+        let count = seen.entry(ends).or_default();
+        if layers.len() <= *count {
+            layers.push(vec![]);
+        }
+        layers[*count].push(edge);
+        *count += 1;
+    }
+    layers
+}
+
+async fn batch_upsert_layer(
+    client: &Client<HttpConnector>,
+    edges: Vec<EdgeWrapperEnum>,
 ) -> Result<(), Error> {
     // let json_raw = serde_json::to_string(&edges).map_err(|err| Error::JSONParseError(err))?;
     // trace!("edges = {}", json_raw);
@@ -249,6 +383,7 @@ pub async fn batch_upsert(
         graph_id: generate_id.clone(),
         updated_nanosecond: updated_nanosecond.clone(),
         vids,
+        reassign: false,
     };
 
     let mut final_identity_graph = generate_id.clone();
@@ -281,8 +416,12 @@ pub async fn batch_upsert(
     // let json_raw = serde_json::to_string(&graph).map_err(|err| Error::JSONParseError(err))?;
     // trace!("graph = {}", json_raw);
     upsert_graph(client, &graph, Graph::SocialGraph).await?;
-    let contracts_req: ContractEdgesRequest = BatchEdges(edges).try_into()?;
+    let contracts_req: ContractEdgesRequest = BatchEdges(edges.clone()).try_into()?;
     insert_contract_connection(client, &contracts_req, Graph::SocialGraph).await?;
+    let follows_req: FollowEdgesRequest = BatchEdges(edges).try_into()?;
+    if follows_req.edges_str != "[]" {
+        insert_follow_connection(client, &follows_req, Graph::SocialGraph).await?;
+    }
     Ok(())
 }
 
@@ -307,7 +446,7 @@ pub async fn insert_contract_connection(
         .header("Authorization", graph_name.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to insert_contract_connection: {:?}",
             err.to_string()
@@ -330,6 +469,54 @@ pub async fn insert_contract_connection(
     Ok(())
 }
 
+/// `Follow` edges are inserted between the `IdentitiesGraph`s of their identities,
+/// which are looked up (or created) by GSQL query `insert_follow_connection`.
+pub async fn insert_follow_connection(
+    client: &Client<HttpConnector>,
+    payload: &FollowEdgesRequest,
+    graph_name: Graph,
+) -> Result<(), Error> {
+    let uri: http::Uri = format!(
+        "{}/query/{}/insert_follow_connection",
+        C.tdb.host, graph_name,
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+
+    let json_params = serde_json::to_string(&payload).map_err(Error::JSONParseError)?;
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Authorization", graph_name.token())
+        .body(Body::from(json_params))
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to insert_follow_connection: {:?}",
+            err.to_string()
+        ))
+    })?;
+    match parse_body::<ContractConnectionsResponse>(&mut resp).await {
+        Ok(result) => {
+            if result.base.error {
+                let err_message = format!(
+                    "TigerGraph fail to insert_follow_connection, Code: {:?}, Message: {:?}",
+                    result.base.code, result.base.message
+                );
+                error!(err_message);
+                return Err(Error::General(err_message, resp.status()));
+            }
+            trace!("TigerGraph insert_follow_connection {:?}", result.results);
+            Ok(())
+        }
+        Err(err) => {
+            let err_message = format!("insert_follow_connection parse_body error: {:?}", err);
+            error!(err_message);
+            Err(err)
+        }
+    }
+}
+
 pub async fn delete_graph_inner_connection(
     client: &Client<HttpConnector>,
     v_id: String,
@@ -352,7 +539,7 @@ pub async fn delete_graph_inner_connection(
         .header("Authorization", Graph::SocialGraph.token())
         .body(Body::empty())
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "delete_graph_inner_connection | Fail to request: {:?}",
             err.to_string()
@@ -383,6 +570,175 @@ pub async fn delete_graph_inner_connection(
     Ok(())
 }
 
+/// REST++ `filter` selecting one discriminated edge, `None` for edges which cannot be deleted this way.
+fn edge_filter(edge: &EdgeWrapperEnum) -> Option<String> {
+    let filter = match edge {
+        EdgeWrapperEnum::ProofForward(w) | EdgeWrapperEnum::ProofBackward(w) => {
+            format!("source=\"{}\"", w.edge.source)
+        }
+        EdgeWrapperEnum::HoldIdentity(w) => format!("source=\"{}\"", w.edge.source),
+        EdgeWrapperEnum::HoldContract(w) => format!(
+            "source=\"{}\",transaction=\"{}\",id=\"{}\"",
+            w.edge.source,
+            w.edge.transaction.clone().unwrap_or_default(),
+            w.edge.id
+        ),
+        EdgeWrapperEnum::Resolve(w) | EdgeWrapperEnum::ReverseResolve(w) => format!(
+            "source=\"{}\",system=\"{}\",name=\"{}\"",
+            w.edge.source, w.edge.system, w.edge.name
+        ),
+        EdgeWrapperEnum::ResolveContract(w) => format!(
+            "source=\"{}\",system=\"{}\",name=\"{}\"",
+            w.edge.source, w.edge.system, w.edge.name
+        ),
+        EdgeWrapperEnum::ReverseResolveContract(w) => format!(
+            "source=\"{}\",system=\"{}\",name=\"{}\"",
+            w.edge.source, w.edge.system, w.edge.name
+        ),
+        EdgeWrapperEnum::PartOfIdentitiesGraph(_)
+        | EdgeWrapperEnum::PartOfCollection(_)
+        | EdgeWrapperEnum::RelationTX(_)
+        | EdgeWrapperEnum::RelationUniqueTX(_)
+        | EdgeWrapperEnum::Follow(_)
+        | EdgeWrapperEnum::SocialFeed(_) => return None,
+    };
+    Some(filter)
+}
+
+/// Delete edges one by one through REST++ `DELETE /graph/{graph}/edges/...`.
+pub async fn delete_edges(client: &Client<HttpConnector>, edges: &EdgeList) -> Result<(), Error> {
+    for edge in edges.iter() {
+        let filter = match edge_filter(edge) {
+            Some(filter) => filter,
+            None => continue,
+        };
+        let uri: http::Uri = format!(
+            "{}/graph/{}/edges/{}/{}/{}/{}/{}?filter={}",
+            C.tdb.host,
+            Graph::SocialGraph.to_string(),
+            edge.source().vertex_type(),
+            urlencoding::encode(&edge.source().primary_key()),
+            edge.e_type(),
+            edge.target().vertex_type(),
+            urlencoding::encode(&edge.target().primary_key()),
+            urlencoding::encode(&filter),
+        )
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+        let req = hyper::Request::builder()
+            .method(Method::DELETE)
+            .uri(uri)
+            .header("Authorization", Graph::SocialGraph.token())
+            .body(Body::empty())
+            .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+        let mut resp = request(client, req).await.map_err(|err| {
+            Error::ManualHttpClientError(format!(
+                "delete_edges | Fail to request: {:?}",
+                err.to_string()
+            ))
+        })?;
+        match parse_body::<BaseResponse>(&mut resp).await {
+            Ok(r) => {
+                if r.error {
+                    let err_message = format!(
+                        "delete_edges error | Code: {:?}, Message: {:?}",
+                        r.code, r.message
+                    );
+                    error!(err_message);
+                    return Err(Error::General(err_message, resp.status()));
+                }
+            }
+            Err(err) => {
+                let err_message = format!("delete_edges parse_body error: {:?}", err);
+                error!(err_message);
+                return Err(err);
+            }
+        };
+    }
+    trace!("TigerGraph delete_edges...");
+    Ok(())
+}
+
+/// Move `identities` out of `IdentitiesGraph` `graph_id` into a new one. Returns the new graph id.
+/// `id_allocation` is told first, then identities join the new graph before leaving the old one,
+/// so a failure midway leaves them in both graphs rather than in none.
+pub async fn split_identity_graph(
+    client: &Client<HttpConnector>,
+    graph_id: &str,
+    identities: &[Identity],
+) -> Result<String, Error> {
+    if identities.is_empty() {
+        return Err(Error::ParamError("identities are required".to_string()));
+    }
+    let allocation_req = IdAllocation {
+        graph_id: Uuid::new_v4().to_string(),
+        updated_nanosecond: chrono::Utc::now().timestamp_micros(),
+        vids: identities.iter().map(|i| i.primary_key()).collect(),
+        reassign: true,
+    };
+    let allocated = id_allocation(&allocation_req).await?;
+    if allocated.graph_id != allocation_req.graph_id {
+        let err_message = format!(
+            "split_identity_graph | Allocation kept graph {} instead of {}",
+            allocated.graph_id, allocation_req.graph_id
+        );
+        error!(err_message);
+        return Err(Error::General(err_message, StatusCode::CONFLICT));
+    }
+    let hv = IdentitiesGraph {
+        id: allocated.graph_id,
+        updated_nanosecond: allocated.updated_nanosecond,
+    };
+    let edges: EdgeList = identities
+        .iter()
+        .map(|identity| {
+            EdgeWrapperEnum::new_hyper_edge(HyperEdge {}.wrapper(&hv, identity, HYPER_EDGE))
+        })
+        .collect();
+    let mut graph: UpsertGraph = BatchEdges(edges).into();
+    // Identities are stored already. Leave their attributes as they are.
+    graph.vertices.remove(vertex::identity::VERTEX_NAME);
+    upsert_graph(client, &graph, Graph::SocialGraph).await?;
+
+    for identity in identities.iter() {
+        let uri: http::Uri = format!(
+            "{}/graph/{}/edges/{}/{}/{}/{}/{}",
+            C.tdb.host,
+            Graph::SocialGraph,
+            identity.vertex_type(),
+            urlencoding::encode(&identity.primary_key()),
+            PART_OF_IDENTITIES_GRAPH,
+            hv.vertex_type(),
+            urlencoding::encode(graph_id),
+        )
+        .parse()
+        .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+        let req = hyper::Request::builder()
+            .method(Method::DELETE)
+            .uri(uri)
+            .header("Authorization", Graph::SocialGraph.token())
+            .body(Body::empty())
+            .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+        let mut resp = request(client, req).await.map_err(|err| {
+            Error::ManualHttpClientError(format!(
+                "split_identity_graph | Fail to request: {:?}",
+                err.to_string()
+            ))
+        })?;
+        let r = parse_body::<BaseResponse>(&mut resp).await?;
+        if r.error {
+            let err_message = format!(
+                "split_identity_graph error | Code: {:?}, Message: {:?}",
+                r.code, r.message
+            );
+            error!(err_message);
+            return Err(Error::General(err_message, resp.status()));
+        }
+    }
+    trace!(graph_id = hv.id, "TigerGraph split_identity_graph...");
+    Ok(hv.id)
+}
+
 pub async fn delete_domain_collection(
     client: &Client<HttpConnector>,
     name: &str,
@@ -404,7 +760,7 @@ pub async fn delete_domain_collection(
         .header("Authorization", Graph::SocialGraph.token())
         .body(Body::empty())
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "delete_domain_collection | Fail to request: {:?}",
             err.to_string()
@@ -435,6 +791,56 @@ pub async fn delete_domain_collection(
     Ok(())
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct ClearDomainSearchCacheResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<Map<String, Value>>>,
+}
+
+/// Remove every `DomainCollection`. Returns how many were removed.
+pub async fn clear_domain_search_cache(client: &Client<HttpConnector>) -> Result<usize, Error> {
+    let uri: http::Uri = format!(
+        "{}/query/{}/clear_domain_search_cache",
+        C.tdb.host,
+        Graph::SocialGraph.to_string(),
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Authorization", Graph::SocialGraph.token())
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "clear_domain_search_cache | Fail to request: {:?}",
+            err.to_string()
+        ))
+    })?;
+
+    let result = parse_body::<ClearDomainSearchCacheResponse>(&mut resp).await?;
+    if result.base.error {
+        let err_message = format!(
+            "clear_domain_search_cache error | Code: {:?}, Message: {:?}",
+            result.base.code, result.base.message
+        );
+        error!(err_message);
+        return Err(Error::General(err_message, resp.status()));
+    }
+    // `PRINT vertex2delete.size()` gives `[{"vertex2delete.size()": n}]`.
+    let removed = result
+        .results
+        .unwrap_or_default()
+        .first()
+        .and_then(|printed| printed.values().next())
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    trace!(removed, "TigerGraph clear_domain_search_cache...");
+    Ok(removed as usize)
+}
+
 pub async fn upsert_graph(
     client: &Client<HttpConnector>,
     payload: &UpsertGraph,
@@ -455,7 +861,7 @@ pub async fn upsert_graph(
         .header("Authorization", graph_name.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request upsert graph: {:?}",
             err.to_string()
@@ -691,6 +1097,11 @@ pub enum EdgeWrapperEnum {
     ReverseResolveContract(EdgeWrapper<ResolveRecord, Identity, Contract>),
     PartOfIdentitiesGraph(EdgeWrapper<HyperEdgeRecord, IdentitiesGraph, Identity>),
     PartOfCollection(EdgeWrapper<PartOfCollectionRecord, DomainCollection, Identity>),
+    RelationTX(EdgeWrapper<RelationTXRecord, Identity, Identity>),
+    RelationUniqueTX(EdgeWrapper<RelationUniqueTXRecord, Identity, Identity>),
+    /// Between `Identity`s as fetched. Saved between their `IdentitiesGraph`s.
+    Follow(EdgeWrapper<FollowRecord, Identity, Identity>),
+    SocialFeed(EdgeWrapper<SocialFeedRecord, Identity, Identity>),
 }
 
 impl Transfer for EdgeWrapperEnum {
@@ -706,6 +1117,10 @@ impl Transfer for EdgeWrapperEnum {
             EdgeWrapperEnum::ReverseResolveContract(wrapper) => wrapper.edge.to_attributes_map(),
             EdgeWrapperEnum::PartOfIdentitiesGraph(wrapper) => wrapper.edge.to_attributes_map(),
             EdgeWrapperEnum::PartOfCollection(wrapper) => wrapper.edge.to_attributes_map(),
+            EdgeWrapperEnum::RelationTX(wrapper) => wrapper.edge.to_attributes_map(),
+            EdgeWrapperEnum::RelationUniqueTX(wrapper) => wrapper.edge.to_attributes_map(),
+            EdgeWrapperEnum::Follow(wrapper) => wrapper.edge.to_attributes_map(),
+            EdgeWrapperEnum::SocialFeed(wrapper) => wrapper.edge.to_attributes_map(),
         }
     }
 
@@ -721,6 +1136,10 @@ impl Transfer for EdgeWrapperEnum {
             EdgeWrapperEnum::ReverseResolveContract(wrapper) => wrapper.edge.to_json_value(),
             EdgeWrapperEnum::PartOfIdentitiesGraph(wrapper) => wrapper.edge.to_json_value(),
             EdgeWrapperEnum::PartOfCollection(wrapper) => wrapper.edge.to_json_value(),
+            EdgeWrapperEnum::RelationTX(wrapper) => wrapper.edge.to_json_value(),
+            EdgeWrapperEnum::RelationUniqueTX(wrapper) => wrapper.edge.to_json_value(),
+            EdgeWrapperEnum::Follow(wrapper) => wrapper.edge.to_json_value(),
+            EdgeWrapperEnum::SocialFeed(wrapper) => wrapper.edge.to_json_value(),
         }
     }
 }
@@ -738,6 +1157,10 @@ impl EdgeWrapperEnum {
             EdgeWrapperEnum::ReverseResolveContract(wrapper) => &wrapper.source,
             EdgeWrapperEnum::PartOfIdentitiesGraph(wrapper) => &wrapper.source,
             EdgeWrapperEnum::PartOfCollection(wrapper) => &wrapper.source,
+            EdgeWrapperEnum::RelationTX(wrapper) => &wrapper.source,
+            EdgeWrapperEnum::RelationUniqueTX(wrapper) => &wrapper.source,
+            EdgeWrapperEnum::Follow(wrapper) => &wrapper.source,
+            EdgeWrapperEnum::SocialFeed(wrapper) => &wrapper.source,
         }
     }
 
@@ -753,6 +1176,10 @@ impl EdgeWrapperEnum {
             EdgeWrapperEnum::ReverseResolveContract(wrapper) => &wrapper.target,
             EdgeWrapperEnum::PartOfIdentitiesGraph(wrapper) => &wrapper.target,
             EdgeWrapperEnum::PartOfCollection(wrapper) => &wrapper.target,
+            EdgeWrapperEnum::RelationTX(wrapper) => &wrapper.target,
+            EdgeWrapperEnum::RelationUniqueTX(wrapper) => &wrapper.target,
+            EdgeWrapperEnum::Follow(wrapper) => &wrapper.target,
+            EdgeWrapperEnum::SocialFeed(wrapper) => &wrapper.target,
         }
     }
 
@@ -768,6 +1195,10 @@ impl EdgeWrapperEnum {
             EdgeWrapperEnum::ReverseResolveContract(_) => REVERSE_RESOLVE_CONTRACT,
             EdgeWrapperEnum::PartOfIdentitiesGraph(_) => HYPER_EDGE_REVERSE,
             EdgeWrapperEnum::PartOfCollection(_) => PART_OF_COLLECTION,
+            EdgeWrapperEnum::RelationTX(_) => RELATION_TX,
+            EdgeWrapperEnum::RelationUniqueTX(_) => RELATION_UNIQUE_TX,
+            EdgeWrapperEnum::Follow(_) => FOLLOW,
+            EdgeWrapperEnum::SocialFeed(_) => SOCIAL_FEED,
         }
     }
 }
@@ -818,6 +1249,24 @@ impl EdgeWrapperEnum {
     ) -> Self {
         EdgeWrapperEnum::PartOfCollection(wrapper)
     }
+
+    pub fn new_relation_tx(wrapper: EdgeWrapper<RelationTXRecord, Identity, Identity>) -> Self {
+        EdgeWrapperEnum::RelationTX(wrapper)
+    }
+
+    pub fn new_relation_unique_tx(
+        wrapper: EdgeWrapper<RelationUniqueTXRecord, Identity, Identity>,
+    ) -> Self {
+        EdgeWrapperEnum::RelationUniqueTX(wrapper)
+    }
+
+    pub fn new_follow(wrapper: EdgeWrapper<FollowRecord, Identity, Identity>) -> Self {
+        EdgeWrapperEnum::Follow(wrapper)
+    }
+
+    pub fn new_social_feed(wrapper: EdgeWrapper<SocialFeedRecord, Identity, Identity>) -> Self {
+        EdgeWrapperEnum::SocialFeed(wrapper)
+    }
 }
 
 /// List edges.
@@ -857,6 +1306,30 @@ impl TryFrom<BatchEdges> for ContractEdgesRequest {
     }
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowEdgesRequest {
+    pub edges_str: String,
+}
+
+impl TryFrom<BatchEdges> for FollowEdgesRequest {
+    type Error = Error;
+    fn try_from(edges: BatchEdges) -> Result<Self, Self::Error> {
+        let connections: Vec<Value> = edges
+            .0
+            .iter()
+            .filter(|edge| edge.e_type() == FOLLOW)
+            .map(|edge| {
+                let mut edge_attr_map = edge.to_json_value();
+                edge_attr_map.insert("from_id".to_string(), json!(edge.source().primary_key()));
+                edge_attr_map.insert("to_id".to_string(), json!(edge.target().primary_key()));
+                Value::Object(edge_attr_map)
+            })
+            .collect();
+        let edges_str = serde_json::to_string(&connections).map_err(Error::JSONParseError)?;
+        Ok(FollowEdgesRequest { edges_str })
+    }
+}
+
 impl From<BatchEdges> for UpsertGraph {
     fn from(edges: BatchEdges) -> Self {
         let mut edges_map = HashMap::new();
@@ -871,16 +1344,20 @@ impl From<BatchEdges> for UpsertGraph {
             let target_vertex_id = edge_wrapper_enum.target().primary_key();
             let edge_attributes = edge_wrapper_enum.to_attributes_map();
 
-            edges_map
-                .entry(source_vertex_type.clone())
-                .or_insert_with(HashMap::new)
-                .entry(source_vertex_id.clone())
-                .or_insert_with(HashMap::new)
-                .entry(edge_type.to_string())
-                .or_insert_with(HashMap::new)
-                .entry(target_vertex_type.clone())
-                .or_insert_with(HashMap::new)
-                .insert(target_vertex_id.clone(), edge_attributes);
+            // `Follow` connects `IdentitiesGraph`s, see `insert_follow_connection`.
+            // Only its identities are upserted here.
+            if edge_type != FOLLOW {
+                edges_map
+                    .entry(source_vertex_type.clone())
+                    .or_insert_with(HashMap::new)
+                    .entry(source_vertex_id.clone())
+                    .or_insert_with(HashMap::new)
+                    .entry(edge_type.to_string())
+                    .or_insert_with(HashMap::new)
+                    .entry(target_vertex_type.clone())
+                    .or_insert_with(HashMap::new)
+                    .insert(target_vertex_id.clone(), edge_attributes);
+            }
 
             // Helper function to merge vertex attributes
             fn merge_vertex_attributes(
@@ -979,12 +1456,29 @@ impl From<BatchEdges> for UpsertGraph {
                 .as_any()
                 .downcast_ref::<Identity>()
             {
-                merge_vertex_attributes(
-                    &mut vertices_map,
-                    &target_vertex_type,
-                    &target_vertex_id,
-                    target.to_attributes_map(),
-                );
+                // Followees are met without being fetched: create them, never overwrite.
+                if edge_type != FOLLOW {
+                    merge_vertex_attributes(
+                        &mut vertices_map,
+                        &target_vertex_type,
+                        &target_vertex_id,
+                        target.to_attributes_map(),
+                    );
+                } else {
+                    let attributes = target
+                        .to_attributes_map()
+                        .into_iter()
+                        .map(|(name, attr)| {
+                            let op = Some(OpCode::IgnoreIfExists);
+                            (name, Attribute { op, ..attr })
+                        })
+                        .collect();
+                    vertices_map
+                        .entry(target_vertex_type.clone())
+                        .or_default()
+                        .entry(target_vertex_id.clone())
+                        .or_insert(attributes);
+                }
             }
 
             if let Some(source) = edge_wrapper_enum
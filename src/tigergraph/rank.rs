@@ -0,0 +1,329 @@
+use crate::{
+    config::C,
+    error::Error,
+    rank::{GraphRank, RankGraph, RankIdentity, RankProof},
+    tigergraph::{
+        request,
+        vertex::{
+            identity::VERTEX_NAME as IDENTITIES, identity_graph::VERTEX_NAME as IDENTITIES_GRAPH,
+        },
+        BaseResponse, Graph,
+    },
+    upstream::{DataSource, Platform, ProofLevel},
+    util::{option_naive_datetime_from_string, parse_body},
+};
+
+use chrono::NaiveDateTime;
+use http::uri::InvalidUri;
+use hyper::{client::HttpConnector, Body, Client, Method};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use tracing::{error, trace};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankInputsResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<RankInputs>>,
+}
+
+/// Printed by `identity_rank_inputs` in one result object.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct RankInputs {
+    #[serde(default)]
+    graphs: Vec<RankGraphVertex>,
+    #[serde(default)]
+    identities: Vec<RankIdentityVertex>,
+    #[serde(default)]
+    proofs: Vec<RankProofEdge>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankGraphVertex {
+    v_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankIdentityVertex {
+    v_id: String,
+    attributes: RankIdentityAttributes,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankIdentityAttributes {
+    platform: Platform,
+    #[serde(default)]
+    #[serde(deserialize_with = "option_naive_datetime_from_string")]
+    created_at: Option<NaiveDateTime>,
+    #[serde(rename = "@graph_id")]
+    graph_id: String,
+    #[serde(rename = "@domains")]
+    domains: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankProofEdge {
+    from_id: String,
+    to_id: String,
+    attributes: RankProofAttributes,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankProofAttributes {
+    source: DataSource,
+    #[serde(default)]
+    level: ProofLevel,
+}
+
+impl From<RankInputs> for Vec<RankGraph> {
+    fn from(inputs: RankInputs) -> Self {
+        let mut graphs: BTreeMap<String, RankGraph> = inputs
+            .graphs
+            .into_iter()
+            .map(|graph| {
+                let ranked = RankGraph {
+                    graph_id: graph.v_id.clone(),
+                    identities: vec![],
+                    proofs: vec![],
+                };
+                (graph.v_id, ranked)
+            })
+            .collect();
+        let mut graph_of: HashMap<String, String> = HashMap::new();
+        for identity in inputs.identities.into_iter() {
+            let attributes = identity.attributes;
+            if let Some(graph) = graphs.get_mut(&attributes.graph_id) {
+                graph_of.insert(identity.v_id.clone(), attributes.graph_id.clone());
+                graph.identities.push(RankIdentity {
+                    v_id: identity.v_id,
+                    platform: attributes.platform,
+                    created_at: attributes.created_at,
+                    domains: attributes.domains,
+                });
+            }
+        }
+        for proof in inputs.proofs.into_iter() {
+            let graph = graph_of
+                .get(&proof.from_id)
+                .and_then(|graph_id| graphs.get_mut(graph_id));
+            if let Some(graph) = graph {
+                graph.proofs.push(RankProof {
+                    from_id: proof.from_id,
+                    to_id: proof.to_id,
+                    source: proof.attributes.source,
+                    level: proof.attributes.level,
+                });
+            }
+        }
+        graphs.into_values().collect()
+    }
+}
+
+/// One page of `IdentitiesGraph`s with what IdentityRank reads of them.
+pub async fn rank_inputs(
+    client: &Client<HttpConnector>,
+    limit: u16,
+    page: u32,
+) -> Result<Vec<RankGraph>, Error> {
+    // query see in Solution: identity_rank_inputs(INT numPerPage, INT pageNum)
+    let uri: http::Uri = format!(
+        "{}/query/{}/identity_rank_inputs?numPerPage={}&pageNum={}",
+        C.tdb.host,
+        Graph::SocialGraph,
+        limit,
+        page,
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Authorization", Graph::SocialGraph.token())
+        .body(Body::empty())
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "query identity_rank_inputs | Fail to request: {:?}",
+            err.to_string()
+        ))
+    })?;
+    match parse_body::<RankInputsResponse>(&mut resp).await {
+        Ok(r) => {
+            if r.base.error {
+                let err_message = format!(
+                    "TigerGraph query identity_rank_inputs error | Code: {:?}, Message: {:?}",
+                    r.base.code, r.base.message
+                );
+                error!(err_message);
+                return Err(Error::General(err_message, resp.status()));
+            }
+            // PRINT gives one object for each printed name.
+            let inputs = r.results.unwrap_or_default().into_iter().fold(
+                RankInputs::default(),
+                |mut all, result| {
+                    all.graphs.extend(result.graphs);
+                    all.identities.extend(result.identities);
+                    all.proofs.extend(result.proofs);
+                    all
+                },
+            );
+            Ok(inputs.into())
+        }
+        Err(err) => {
+            let err_message = format!(
+                "TigerGraph query identity_rank_inputs parse_body error: {:?}",
+                err
+            );
+            error!(err_message);
+            Err(err)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RanksRequest {
+    ranks_str: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct UpsertRanksResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<serde_json::Value>>,
+}
+
+/// Set `identity_rank` of `IdentitiesGraph`s and their `Identities`.
+pub async fn save_ranks(client: &Client<HttpConnector>, ranks: &[GraphRank]) -> Result<(), Error> {
+    let uri: http::Uri = format!(
+        "{}/query/{}/upsert_identity_rank",
+        C.tdb.host,
+        Graph::SocialGraph,
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let rows: Vec<serde_json::Value> = ranks
+        .iter()
+        .flat_map(|graph| {
+            let identities = graph
+                .identities
+                .iter()
+                .map(|(v_id, rank)| json!({"v_type": IDENTITIES, "v_id": v_id, "rank": rank}));
+            std::iter::once(
+                json!({"v_type": IDENTITIES_GRAPH, "v_id": graph.graph_id, "rank": graph.rank}),
+            )
+            .chain(identities)
+        })
+        .collect();
+    let payload = RanksRequest {
+        ranks_str: serde_json::to_string(&rows)?,
+    };
+    let json_params = serde_json::to_string(&payload)?;
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Authorization", Graph::SocialGraph.token())
+        .body(Body::from(json_params))
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to upsert_identity_rank: {:?}",
+            err.to_string()
+        ))
+    })?;
+    match parse_body::<UpsertRanksResponse>(&mut resp).await {
+        Ok(result) => {
+            if result.base.error {
+                let err_message = format!(
+                    "TigerGraph fail to upsert_identity_rank, Code: {:?}, Message: {:?}",
+                    result.base.code, result.base.message
+                );
+                error!(err_message);
+                return Err(Error::General(err_message, resp.status()));
+            }
+            trace!("TigerGraph upsert_identity_rank {:?}", result.results);
+            Ok(())
+        }
+        Err(err) => {
+            let err_message = format!("upsert_identity_rank parse_body error: {:?}", err);
+            error!(err_message);
+            Err(err)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct VertexIds {
+    ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankMapResponse {
+    #[serde(flatten)]
+    base: BaseResponse,
+    results: Option<Vec<RankMapResult>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct RankMapResult {
+    rank_map: HashMap<String, f64>,
+}
+
+/// `identity_rank` of `Identities` by v_id. `None` if not ranked yet.
+pub async fn get_identity_ranks_by_ids(
+    client: &Client<HttpConnector>,
+    ids: Vec<String>,
+) -> Result<HashMap<String, Option<f64>>, Error> {
+    let uri: http::Uri = format!(
+        "{}/query/{}/identity_ranks_by_ids",
+        C.tdb.host,
+        Graph::SocialGraph,
+    )
+    .parse()
+    .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
+    let payload = VertexIds { ids: ids.clone() };
+    let json_params = serde_json::to_string(&payload)?;
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Authorization", Graph::SocialGraph.token())
+        .body(Body::from(json_params))
+        .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
+    let mut resp = request(client, req).await.map_err(|err| {
+        Error::ManualHttpClientError(format!(
+            "TigerGraph | Fail to request identity_ranks_by_ids: {:?}",
+            err.to_string()
+        ))
+    })?;
+    match parse_body::<RankMapResponse>(&mut resp).await {
+        Ok(r) => {
+            if r.base.error {
+                let err_message = format!(
+                    "TigerGraph identity_ranks_by_ids error | Code: {:?}, Message: {:?}",
+                    r.base.code, r.base.message
+                );
+                error!(err_message);
+                return Err(Error::General(err_message, resp.status()));
+            }
+            let rank_map = r
+                .results
+                .and_then(|results| results.into_iter().next())
+                .map_or(HashMap::new(), |result| result.rank_map);
+            Ok(ids
+                .into_iter()
+                .map(|id| {
+                    let rank = rank_map.get(&id).copied();
+                    (id, rank)
+                })
+                .collect())
+        }
+        Err(err) => {
+            let err_message = format!(
+                "TigerGraph identity_ranks_by_ids parse_body error: {:?}",
+                err
+            );
+            error!(err_message);
+            Err(err)
+        }
+    }
+}
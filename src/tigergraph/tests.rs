@@ -6,16 +6,112 @@ mod tests {
     use crate::tigergraph::{
         create_contract_to_identity_resolve_record, create_identity_domain_resolve_record,
         create_identity_to_contract_hold_record, create_identity_to_identity_hold_record,
-        create_identity_to_identity_proof_two_way_binding,
+        create_identity_to_identity_proof_two_way_binding, BatchEdges, EdgeWrapperEnum, OpCode,
+        UpsertGraph,
     };
     use crate::{
         tigergraph::{
-            edge::{Hold, Proof, Resolve},
-            vertex::{Contract, Identity, IdentityGraph, NeighborsResponse},
+            edge::{Follow, Hold, Proof, Resolve, Wrapper, FOLLOW},
+            vertex::{Contract, Identity, IdentityGraph, NeighborsResponse, Vertex},
         },
         upstream::{Chain, ContractCategory, DataSource, DomainNameSystem, Platform, ProofLevel},
         util::make_http_client,
     };
+    use hyper::{client::HttpConnector, Client};
+
+    /// `Ethereum` identity `address` holding and resolving to ENS `name`.
+    /// Lookups seed their own data, as tests share one TigerGraph and run in any order.
+    async fn seed_ens(
+        client: &Client<HttpConnector>,
+        name: &str,
+        address: &str,
+    ) -> Result<(), Error> {
+        let identity = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: Platform::Ethereum,
+            identity: address.to_string(),
+            ..Default::default()
+        };
+        let contract = Contract {
+            uuid: Uuid::new_v4(),
+            category: ContractCategory::ENS,
+            chain: Chain::Ethereum,
+            address: ContractCategory::ENS.default_contract_address().unwrap(),
+            ..Default::default()
+        };
+        let hold = Hold {
+            uuid: Uuid::new_v4(),
+            source: DataSource::TheGraph,
+            id: name.to_string(),
+            ..Default::default()
+        };
+        create_identity_to_contract_hold_record(client, &identity, &contract, &hold).await?;
+
+        let resolve = Resolve {
+            uuid: Uuid::new_v4(),
+            system: DomainNameSystem::ENS,
+            name: name.to_string(),
+            source: DataSource::TheGraph,
+            ..Default::default()
+        };
+        create_contract_to_identity_resolve_record(client, &contract, &identity, &resolve).await
+    }
+
+    /// `Ethereum` identity `address` proved by a `NextID` one.
+    async fn seed_proof(client: &Client<HttpConnector>, address: &str) -> Result<(), Error> {
+        let from = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: Platform::Ethereum,
+            identity: address.to_string(),
+            ..Default::default()
+        };
+        let to = Identity {
+            uuid: Some(Uuid::new_v4()),
+            platform: Platform::NextID,
+            identity: format!("0x{}", Uuid::new_v4().simple()),
+            ..Default::default()
+        };
+        let proof = Proof {
+            uuid: Uuid::new_v4(),
+            source: DataSource::NextID,
+            level: ProofLevel::VeryConfident,
+            ..Default::default()
+        };
+        let reverse = Proof {
+            uuid: Uuid::new_v4(),
+            ..proof.clone()
+        };
+        create_identity_to_identity_proof_two_way_binding(client, &from, &to, &proof, &reverse)
+            .await
+    }
+
+    #[test]
+    fn test_follow_upsert_creates_followee_only() {
+        let follower = Identity {
+            platform: Platform::Farcaster,
+            identity: "a".to_string(),
+            ..Default::default()
+        };
+        let followee = Identity {
+            platform: Platform::Farcaster,
+            identity: "b".to_string(),
+            ..Default::default()
+        };
+        let follow = Follow::new(&follower, &followee, DataSource::Farcaster);
+        let graph: UpsertGraph = BatchEdges(vec![EdgeWrapperEnum::new_follow(
+            follow.wrapper(&follower, &followee, FOLLOW),
+        )])
+        .into();
+        let identities = &graph.vertices[&follower.vertex_type()];
+        let op_of = |v_id: String, name: &str| identities[&v_id][name].op;
+        assert_eq!(
+            op_of(follower.primary_key(), "updated_at"),
+            Some(OpCode::Max)
+        );
+        assert!(identities[&followee.primary_key()]
+            .values()
+            .all(|attr| attr.op == Some(OpCode::IgnoreIfExists)));
+    }
 
     #[tokio::test]
     async fn test_create_i2i_proof_two_way_binding() -> Result<(), Error> {
@@ -155,6 +251,12 @@ mod tests {
     #[tokio::test]
     async fn test_query_domain() -> Result<(), Error> {
         let client = make_http_client();
+        seed_ens(
+            &client,
+            "tinpeiling.eth",
+            "0x0e8f2e7d8d0d8ee1e1b4c4e6cf2c6f1c1a7c2f2e",
+        )
+        .await?;
         if let Some(found) =
             Resolve::find_by_name_system(&client, "tinpeiling.eth", &DomainNameSystem::ENS).await?
         {
@@ -171,6 +273,7 @@ mod tests {
     #[tokio::test]
     async fn test_query_holder() -> Result<(), Error> {
         let client = make_http_client();
+        seed_ens(&client, "maskbook.eth", "d").await?;
         if let Some(found) = Hold::find_by_id_chain_address(
             &client,
             "maskbook.eth",
@@ -192,6 +295,7 @@ mod tests {
     #[tokio::test]
     async fn test_query_nfts() -> Result<(), Error> {
         let client = make_http_client();
+        seed_ens(&client, "maskbook.eth", "d").await?;
         if let Some(found) =
             Identity::find_by_platform_identity(&client, &Platform::Ethereum, "d").await?
         {
@@ -219,6 +323,7 @@ mod tests {
     #[tokio::test]
     async fn test_neighbors_with_traversal() -> Result<(), Error> {
         let client = make_http_client();
+        seed_proof(&client, "0xb2be2887a26f44555835eeacc47d65b88b6b42c2").await?;
         if let Some(found) = Identity::find_by_platform_identity(
             &client,
             &Platform::Ethereum,
@@ -227,7 +332,7 @@ mod tests {
         .await?
         {
             println!("found = {:?}", found);
-            let edges = found.neighbors_with_traversal(&client, 1).await?;
+            let edges = found.neighbors_with_traversal(&client, 1, None).await?;
             let json_raw =
                 serde_json::to_string(&edges).map_err(|err| Error::JSONParseError(err))?;
             println!("neighbors: {}", json_raw);
@@ -240,11 +345,12 @@ mod tests {
     #[tokio::test]
     async fn test_neighbors_with_source() -> Result<(), Error> {
         let client = make_http_client();
+        seed_proof(&client, "d").await?;
         if let Some(found) =
             Identity::find_by_platform_identity(&client, &Platform::Ethereum, "d").await?
         {
             println!("found = {:?}", found);
-            let edges = found.neighbors(&client, 3, None).await?;
+            let edges = found.neighbors(&client, 3, None, None, None).await?;
             let json_raw =
                 serde_json::to_string(&edges).map_err(|err| Error::JSONParseError(err))?;
             println!("neighbors_with_source: {}", json_raw);
@@ -278,6 +384,7 @@ mod tests {
             &Platform::ENS,
             "yisiliu.eth",
             Some(false),
+            None,
         )
         .await?
         {
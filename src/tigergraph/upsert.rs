@@ -7,6 +7,7 @@ use crate::{
             HOLD_CONTRACT, HOLD_IDENTITY, PROOF_EDGE, PROOF_REVERSE_EDGE, RESOLVE,
             RESOLVE_CONTRACT, REVERSE_RESOLVE, REVERSE_RESOLVE_CONTRACT,
         },
+        request,
         vertex::{Contract, FromWithJsonValue, Identity, Vertex},
         Attribute, BaseResponse, EdgeWrapper, Edges, Graph, Transfer,
     },
@@ -88,7 +89,7 @@ pub async fn upsert_vertices(
         .header("Authorization", graph_name.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request upsert vertices: {:?}",
             err.to_string()
@@ -190,7 +191,7 @@ async fn upsert_edge(
         .header("Authorization", graph_name.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request upsert graph: {:?}",
             err.to_string()
@@ -325,7 +326,7 @@ async fn upsert_isolated_vertex(
         .header("Authorization", graph.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request upsert_isolated_vertex: {:?}",
             err.to_string()
@@ -373,7 +374,7 @@ async fn upsert_hyper_vertex(
         .header("Authorization", graph.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request upsert_hyper_vertex: {:?}",
             err.to_string()
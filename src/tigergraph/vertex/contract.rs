@@ -1,8 +1,9 @@
 use crate::{
     config::C,
     error::Error,
+    storage::DynGraphStore,
     tigergraph::{
-        upsert_graph,
+        request, upsert_graph,
         vertex::{FromWithParams, Vertex, VertexRecord},
         Attribute, BaseResponse, Graph, OpCode, Transfer, UpsertGraph, Vertices,
     },
@@ -259,7 +260,7 @@ impl Contract {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query filter error | Fail to request: {:?}",
                 err.to_string()
@@ -310,7 +311,7 @@ impl Contract {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query filter error | Fail to request: {:?}",
                 err.to_string()
@@ -341,7 +342,7 @@ impl Contract {
 }
 
 pub struct ContractLoadFn {
-    pub client: Client<HttpConnector>,
+    pub store: DynGraphStore,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -365,7 +366,7 @@ struct VertexIdsResult {
 impl BatchFn<String, Option<ContractRecord>> for ContractLoadFn {
     async fn load(&mut self, ids: &[String]) -> HashMap<String, Option<ContractRecord>> {
         trace!(ids = ids.len(), "Loading Contract id");
-        let records = get_contracts_by_ids(&self.client, ids.to_vec()).await;
+        let records = self.store.contracts_by_ids(ids.to_vec()).await;
         match records {
             Ok(records) => records,
             // HOLD ON: Not sure if `Err` need to return
@@ -374,7 +375,7 @@ impl BatchFn<String, Option<ContractRecord>> for ContractLoadFn {
     }
 }
 
-async fn get_contracts_by_ids(
+pub(crate) async fn get_contracts_by_ids(
     client: &Client<HttpConnector>,
     ids: Vec<String>,
 ) -> Result<HashMap<String, Option<ContractRecord>>, Error> {
@@ -393,7 +394,7 @@ async fn get_contracts_by_ids(
         .header("Authorization", Graph::SocialGraph.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request contracts_by_ids graph: {:?}",
             err.to_string()
@@ -3,6 +3,7 @@ use crate::{
     error::Error,
     tigergraph::{
         edge::AvailableDomain,
+        request,
         vertex::{FromWithParams, Vertex, VertexRecord},
         Attribute, BaseResponse, Graph, OpCode, Transfer,
     },
@@ -161,6 +162,116 @@ pub struct DomainAvailableSearch {
     pub domains: Vec<AvailableDomain>,
 }
 
+impl DomainAvailableSearch {
+    /// Merge the domains already taken in `collection` with the full list of
+    /// extensions we support, marking everything else as available.
+    pub fn fill(name: &str, collection: DomainCollection, existing: &[AvailableDomain]) -> Self {
+        // Fill the domain available name list
+        // list must be returned in the specified order
+        let mut available_domains: Vec<AvailableDomain> = Vec::new();
+
+        let mut exist_tld_map: HashMap<(Platform, String), AvailableDomain> = HashMap::new();
+        for exist in existing.iter() {
+            exist_tld_map.insert(
+                (exist.platform.clone(), exist.tld.clone()),
+                exist.to_owned(),
+            );
+        }
+
+        // specified order
+        let return_order = vec![
+            Platform::ENS,
+            Platform::Farcaster,
+            Platform::Lens,
+            Platform::Clusters,
+            Platform::Basenames,
+            Platform::UnstoppableDomains,
+            Platform::SNS,
+            Platform::SpaceId,
+            Platform::Dotbit,
+            Platform::Crossbell,
+            Platform::Zeta,
+            Platform::Mode,
+            Platform::Arbitrum,
+            Platform::Taiko,
+            Platform::Mint,
+            Platform::Zkfair,
+            Platform::Manta,
+            Platform::Lightlink,
+            Platform::Genome,
+            Platform::Merlin,
+            Platform::AlienX,
+            Platform::Tomo,
+            Platform::Ailayer,
+        ];
+
+        for domain_order in return_order.iter() {
+            if let Some(required_exts) = EXTENSION.get(domain_order) {
+                if *domain_order == Platform::Clusters {
+                    if let Some(exist_domain) =
+                        exist_tld_map.get(&(Platform::Clusters, EXT::ClustersRoot.to_string()))
+                    {
+                        available_domains.push(exist_domain.to_owned());
+                    } else {
+                        let cluster_parent = format!("{}", name);
+                        available_domains.push(AvailableDomain {
+                            platform: domain_order.clone(),
+                            name: cluster_parent,
+                            tld: EXT::ClustersRoot.to_string(),
+                            availability: true,
+                            status: DomainStatus::Available,
+                            expired_at: None,
+                        });
+                    }
+                } else if *domain_order == Platform::Farcaster {
+                    if let Some(exist_domain) =
+                        exist_tld_map.get(&(Platform::Farcaster, EXT::Eth.to_string()))
+                    {
+                        available_domains.push(exist_domain.to_owned());
+                    }
+                    if let Some(exist_domain) =
+                        exist_tld_map.get(&(Platform::Farcaster, "".to_string()))
+                    {
+                        available_domains.push(exist_domain.to_owned());
+                    } else {
+                        available_domains.push(AvailableDomain {
+                            platform: domain_order.clone(),
+                            name: name.to_string(),
+                            tld: "".to_string(),
+                            availability: true,
+                            status: DomainStatus::Available,
+                            expired_at: None,
+                        });
+                    }
+                } else {
+                    for ext in required_exts {
+                        if let Some(exist_domain) =
+                            exist_tld_map.get(&(*domain_order, ext.to_string()))
+                        {
+                            available_domains.push(exist_domain.to_owned());
+                        } else {
+                            let domain_name = format!("{}.{}", name, ext);
+                            available_domains.push(AvailableDomain {
+                                platform: domain_order.clone(),
+                                name: domain_name,
+                                tld: ext.to_string(),
+                                availability: true,
+                                status: DomainStatus::Available,
+                                expired_at: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        DomainAvailableSearch {
+            collection,
+            domains: available_domains,
+        }
+    }
+}
+
 impl DomainCollection {
     pub fn is_outdated(&self) -> bool {
         let current_time = naive_now();
@@ -201,7 +312,7 @@ impl DomainCollection {
                 ))
             })?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query domain_available_search?id={} | Fail to request: {:?}",
                 name,
@@ -226,113 +337,14 @@ impl DomainCollection {
                         if result.collection.is_empty() {
                             return Ok(None);
                         }
-                        // Fill the domain available name list
-                        // list must be returned in the specified order
-                        let mut available_domains: Vec<AvailableDomain> = Vec::new();
-
-                        let mut exist_tld_map: HashMap<(Platform, String), AvailableDomain> =
-                            HashMap::new();
-                        for exist in result.domains.iter() {
-                            exist_tld_map.insert(
-                                (exist.platform.clone(), exist.tld.clone()),
-                                exist.to_owned(),
-                            );
-                        }
-
-                        // specified order
-                        let return_order = vec![
-                            Platform::ENS,
-                            Platform::Farcaster,
-                            Platform::Lens,
-                            Platform::Clusters,
-                            Platform::Basenames,
-                            Platform::UnstoppableDomains,
-                            Platform::SNS,
-                            Platform::SpaceId,
-                            Platform::Dotbit,
-                            Platform::Crossbell,
-                            Platform::Zeta,
-                            Platform::Mode,
-                            Platform::Arbitrum,
-                            Platform::Taiko,
-                            Platform::Mint,
-                            Platform::Zkfair,
-                            Platform::Manta,
-                            Platform::Lightlink,
-                            Platform::Genome,
-                            Platform::Merlin,
-                            Platform::AlienX,
-                            Platform::Tomo,
-                            Platform::Ailayer,
-                        ];
-
-                        for domain_order in return_order.iter() {
-                            if let Some(required_exts) = EXTENSION.get(domain_order) {
-                                if *domain_order == Platform::Clusters {
-                                    if let Some(exist_domain) = exist_tld_map
-                                        .get(&(Platform::Clusters, EXT::ClustersRoot.to_string()))
-                                    {
-                                        available_domains.push(exist_domain.to_owned());
-                                    } else {
-                                        let cluster_parent = format!("{}", name);
-                                        available_domains.push(AvailableDomain {
-                                            platform: domain_order.clone(),
-                                            name: cluster_parent,
-                                            tld: EXT::ClustersRoot.to_string(),
-                                            availability: true,
-                                            status: DomainStatus::Available,
-                                            expired_at: None,
-                                        });
-                                    }
-                                } else if *domain_order == Platform::Farcaster {
-                                    if let Some(exist_domain) = exist_tld_map
-                                        .get(&(Platform::Farcaster, EXT::Eth.to_string()))
-                                    {
-                                        available_domains.push(exist_domain.to_owned());
-                                    }
-                                    if let Some(exist_domain) =
-                                        exist_tld_map.get(&(Platform::Farcaster, "".to_string()))
-                                    {
-                                        available_domains.push(exist_domain.to_owned());
-                                    } else {
-                                        available_domains.push(AvailableDomain {
-                                            platform: domain_order.clone(),
-                                            name: name.to_string(),
-                                            tld: "".to_string(),
-                                            availability: true,
-                                            status: DomainStatus::Available,
-                                            expired_at: None,
-                                        });
-                                    }
-                                } else {
-                                    for ext in required_exts {
-                                        if let Some(exist_domain) =
-                                            exist_tld_map.get(&(*domain_order, ext.to_string()))
-                                        {
-                                            available_domains.push(exist_domain.to_owned());
-                                        } else {
-                                            let domain_name = format!("{}.{}", name, ext);
-                                            available_domains.push(AvailableDomain {
-                                                platform: domain_order.clone(),
-                                                name: domain_name,
-                                                tld: ext.to_string(),
-                                                availability: true,
-                                                status: DomainStatus::Available,
-                                                expired_at: None,
-                                            });
-                                        }
-                                    }
-                                }
-                            }
-                        }
-
                         match result.collection.first().cloned() {
                             None => return Ok(None),
                             Some(c) => {
-                                return Ok(Some(DomainAvailableSearch {
-                                    collection: c.attributes.clone(),
-                                    domains: available_domains,
-                                }))
+                                return Ok(Some(DomainAvailableSearch::fill(
+                                    name,
+                                    c.attributes.clone(),
+                                    &result.domains,
+                                )))
                             }
                         }
                     }
@@ -1,17 +1,19 @@
 use crate::{
     config::C,
     error::Error,
+    storage::DynGraphStore,
     tigergraph::{
         edge::{
             resolve::{ResolveRecord, ResolveReverse},
             EdgeUnion, HoldRecord,
         },
-        upsert_graph,
+        request, upsert_graph,
         vertex::{FromWithParams, Vertex, VertexRecord},
         Attribute, BaseResponse, Graph, OpCode, Transfer, UpsertGraph, Vertices,
     },
     upstream::{
         vec_string_to_vec_datasource, ContractCategory, DataSource, DomainNameSystem, Platform,
+        ProofLevel,
     },
     util::{
         naive_datetime_from_string, naive_datetime_to_string, naive_now,
@@ -563,7 +565,7 @@ impl Identity {
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query filter error | Fail to request: {:?}",
                 err.to_string()
@@ -624,7 +626,7 @@ impl Identity {
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error | {}", _err)))?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query filter=platform=%22{}%22,identity=%22{}%22 error | Fail to request: {:?}",
                 platform.to_string(),
@@ -656,6 +658,14 @@ impl Identity {
     }
 }
 
+/// `numPerPage` / `numSkip` query params of a `(limit, skip)` page.
+/// GSQL queries print every record without them.
+fn page_params(page: Option<(usize, usize)>) -> String {
+    page.map_or(String::new(), |(limit, skip)| {
+        format!("&numPerPage={}&numSkip={}", limit, skip)
+    })
+}
+
 impl IdentityRecord {
     pub fn v_id(&self) -> String {
         self.v_id.clone()
@@ -665,12 +675,15 @@ impl IdentityRecord {
         self.v_type.clone()
     }
 
-    /// Return all neighbors of this identity with sources.
+    /// Return neighbors of this identity with sources, ordered by v_id.
+    /// `page` is `(limit, skip)`, all neighbors are returned if it is `None`.
     pub async fn neighbors(
         &self,
         client: &Client<HttpConnector>,
         depth: u16,
         reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
+        page: Option<(usize, usize)>,
     ) -> Result<Vec<IdentityWithSource>, Error> {
         // This reverse flag can be used as a filtering for Identity which type is domain system .
         // flag = 0, If `reverse=None` if omitted, there is no need to filter anything.
@@ -680,14 +693,18 @@ impl IdentityRecord {
             true => 1,
             false => 2,
         });
-        // query see in Solution: CREATE QUERY neighbors_with_source(VERTEX<Identities> p, INT depth)
+        // min_level = 0 walks through proofs of any level.
+        let min_level = min_level.map_or(0, |level| level as i32);
+        // query see in Solution: CREATE QUERY neighbors_with_source_reverse(VERTEX<Identities> p, INT depth, INT reverse_flag, INT min_level, INT numPerPage, INT numSkip)
         let uri: http::Uri = format!(
-            "{}/query/{}/neighbors_with_source_reverse?p={}&depth={}&reverse_flag={}",
+            "{}/query/{}/neighbors_with_source_reverse?p={}&depth={}&reverse_flag={}&min_level={}{}",
             C.tdb.host,
             Graph::SocialGraph.to_string(),
             self.v_id,
             depth,
             flag,
+            min_level,
+            page_params(page),
         )
         .parse()
         .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
@@ -698,7 +715,7 @@ impl IdentityRecord {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query neighbors_with_source | Fail to request: {:?}",
                 err.to_string()
@@ -716,7 +733,7 @@ impl IdentityRecord {
                     return Err(Error::General(err_message, resp.status()));
                 }
 
-                let result: Vec<IdentityWithSource> = r
+                let mut result: Vec<IdentityWithSource> = r
                     .results
                     .and_then(|vec_with_sources| vec_with_sources.first().cloned())
                     .map_or(vec![], |result| {
@@ -726,6 +743,7 @@ impl IdentityRecord {
                             .filter(|target| target.identity.v_id != self.v_id)
                             .collect()
                     });
+                result.sort_by(|a, b| a.identity.v_id.cmp(&b.identity.v_id));
                 Ok(result)
             }
             Err(err) => {
@@ -739,19 +757,22 @@ impl IdentityRecord {
         }
     }
 
-    /// Return all neighbors of this identity with traversal paths.
+    /// Return edges walked through from this identity, ordered by `EdgeUnion::sort_key`.
+    /// `page` is `(limit, skip)`, all edges are returned if it is `None`.
     pub async fn neighbors_with_traversal(
         &self,
         client: &Client<HttpConnector>,
         depth: u16,
+        page: Option<(usize, usize)>,
     ) -> Result<Vec<EdgeUnion>, Error> {
-        // query see in Solution: CREATE QUERY neighbors(VERTEX<Identities> p, INT depth)
+        // query see in Solution: CREATE QUERY neighbors(VERTEX<Identities> p, INT depth, INT numPerPage, INT numSkip)
         let uri: http::Uri = format!(
-            "{}/query/{}/neighbors?p={}&depth={}",
+            "{}/query/{}/neighbors?p={}&depth={}{}",
             C.tdb.host,
             Graph::SocialGraph.to_string(),
             self.v_id,
             depth,
+            page_params(page),
         )
         .parse()
         .map_err(|_err: InvalidUri| {
@@ -767,7 +788,7 @@ impl IdentityRecord {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query neighbors_with_traversal | Fail to request: {:?}",
                 err.to_string()
@@ -784,10 +805,11 @@ impl IdentityRecord {
                     return Err(Error::General(err_message, resp.status()));
                 }
 
-                let result = r
+                let mut result = r
                     .results
                     .and_then(|vec_unions| vec_unions.first().cloned())
                     .map_or(vec![], |union| union.edges);
+                result.sort_by_cached_key(EdgeUnion::sort_key);
                 Ok(result)
             }
             Err(err) => {
@@ -822,7 +844,7 @@ impl IdentityRecord {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query identity_by_source | Fail to request: {:?}",
                 err.to_string()
@@ -878,7 +900,7 @@ impl IdentityRecord {
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query reverse_domains | Fail to request: {:?}",
                 err.to_string()
@@ -954,7 +976,7 @@ impl IdentityRecord {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query owned_by | Fail to request: {:?}",
                 err.to_string()
@@ -990,24 +1012,24 @@ impl IdentityRecord {
         &self,
         client: &Client<HttpConnector>,
         category: Option<Vec<ContractCategory>>,
-        limit: u16,
-        offset: u16,
+        limit: usize,
+        skip: usize,
     ) -> Result<Vec<HoldRecord>, Error> {
         if self.attributes.platform != Platform::Ethereum
             && self.attributes.platform != Platform::Solana
         {
             return Ok(vec![]);
         }
-        // query see in Solution: nfts(VERTEX<Identities> p, SET<STRING> categories, INT numPerPage, INT pageNum)
+        // query see in Solution: nfts(VERTEX<Identities> p, SET<STRING> categories, INT numPerPage, INT numSkip)
         let uri: http::Uri;
         if category.is_none() || category.as_ref().unwrap().len() == 0 {
             uri = format!(
-                "{}/query/{}/nfts?p={}&numPerPage={}&pageNum={}",
+                "{}/query/{}/nfts?p={}&numPerPage={}&numSkip={}",
                 C.tdb.host,
                 Graph::SocialGraph.to_string(),
                 self.v_id.to_string(),
                 limit,
-                offset
+                skip
             )
             .parse()
             .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
@@ -1019,13 +1041,13 @@ impl IdentityRecord {
                 .collect();
             let combined = categories.join("&");
             uri = format!(
-                "{}/query/{}/nfts?p={}&{}&numPerPage={}&pageNum={}",
+                "{}/query/{}/nfts?p={}&{}&numPerPage={}&numSkip={}",
                 C.tdb.host,
                 Graph::SocialGraph.to_string(),
                 self.v_id.to_string(),
                 combined,
                 limit,
-                offset
+                skip
             )
             .parse()
             .map_err(|_err: InvalidUri| Error::ParamError(format!("Uri format Error {}", _err)))?;
@@ -1036,7 +1058,7 @@ impl IdentityRecord {
             .header("Authorization", Graph::SocialGraph.token())
             .body(Body::empty())
             .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query nfts | Fail to request: {:?}",
                 err.to_string()
@@ -1053,10 +1075,12 @@ impl IdentityRecord {
                     return Err(Error::General(err_message, resp.status()));
                 }
 
-                let result = r
+                let mut result = r
                     .results
                     .and_then(|vec_unions| vec_unions.first().cloned())
                     .map_or(vec![], |union| union.edges);
+                // TigerGraph prints a set, which keeps no order.
+                result.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
                 Ok(result)
             }
             Err(err) => {
@@ -1077,11 +1101,15 @@ pub struct NeighborReverseLoadFn {
 }
 
 pub struct OwnerLoadFn {
-    pub client: Client<HttpConnector>,
+    pub store: DynGraphStore,
 }
 
 pub struct IdentityLoadFn {
-    pub client: Client<HttpConnector>,
+    pub store: DynGraphStore,
+}
+
+pub struct RankLoadFn {
+    pub store: DynGraphStore,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1142,7 +1170,7 @@ struct ExpireTimeMapResult {
 impl BatchFn<String, Option<IdentityRecord>> for IdentityLoadFn {
     async fn load(&mut self, ids: &[String]) -> HashMap<String, Option<IdentityRecord>> {
         trace!(ids = ids.len(), "Loading Identity id for identities_by_ids");
-        let records = get_identities_by_ids(&self.client, ids.to_vec()).await;
+        let records = self.store.identities_by_ids(ids.to_vec()).await;
         match records {
             Ok(records) => records,
             // HOLD ON: Not sure if `Err` need to return
@@ -1151,11 +1179,22 @@ impl BatchFn<String, Option<IdentityRecord>> for IdentityLoadFn {
     }
 }
 
+#[async_trait::async_trait]
+impl BatchFn<String, Option<f64>> for RankLoadFn {
+    async fn load(&mut self, ids: &[String]) -> HashMap<String, Option<f64>> {
+        trace!(ids = ids.len(), "Loading Identity id for identity_ranks");
+        match self.store.identity_ranks(ids.to_vec()).await {
+            Ok(ranks) => ranks,
+            Err(_) => ids.iter().map(|k| (k.to_owned(), None)).collect(),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl BatchFn<String, Option<IdentityRecord>> for OwnerLoadFn {
     async fn load(&mut self, ids: &[String]) -> HashMap<String, Option<IdentityRecord>> {
         trace!(ids = ids.len(), "Loading Identity id for owners_by_ids");
-        let records = get_owners_by_ids(&self.client, ids.to_vec()).await;
+        let records = self.store.owners_by_ids(ids.to_vec()).await;
         match records {
             Ok(records) => records,
             // HOLD ON: Not sure if `Err` need to return
@@ -1211,7 +1250,7 @@ async fn get_expired_time_by_ids(
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
 
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request expired_time_by_ids: {:?}",
             err.to_string()
@@ -1274,7 +1313,7 @@ async fn get_neighbor_reverse_by_ids(
         .header("Authorization", Graph::SocialGraph.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request neighbor_reverse_by_ids: {:?}",
             err.to_string()
@@ -1307,7 +1346,7 @@ async fn get_neighbor_reverse_by_ids(
     }
 }
 
-async fn get_owners_by_ids(
+pub(crate) async fn get_owners_by_ids(
     client: &Client<HttpConnector>,
     ids: Vec<String>,
 ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
@@ -1326,7 +1365,7 @@ async fn get_owners_by_ids(
         .header("Authorization", Graph::SocialGraph.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request owners_by_ids: {:?}",
             err.to_string()
@@ -1359,7 +1398,7 @@ async fn get_owners_by_ids(
     }
 }
 
-async fn get_identities_by_ids(
+pub(crate) async fn get_identities_by_ids(
     client: &Client<HttpConnector>,
     ids: Vec<String>,
 ) -> Result<HashMap<String, Option<IdentityRecord>>, Error> {
@@ -1378,7 +1417,7 @@ async fn get_identities_by_ids(
         .header("Authorization", Graph::SocialGraph.token())
         .body(Body::from(json_params))
         .map_err(|_err| Error::ParamError(format!("ParamError Error {}", _err)))?;
-    let mut resp = client.request(req).await.map_err(|err| {
+    let mut resp = request(client, req).await.map_err(|err| {
         Error::ManualHttpClientError(format!(
             "TigerGraph | Fail to request identities_by_ids: {:?}",
             err.to_string()
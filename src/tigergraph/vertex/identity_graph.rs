@@ -2,10 +2,11 @@ use crate::{
     config::C,
     error::Error,
     tigergraph::{
+        request,
         vertex::{FromWithParams, Identity, IdentityRecord, Vertex, VertexRecord},
         Attribute, BaseResponse, Graph, OpCode, Transfer,
     },
-    upstream::{Chain, DataSource, Platform},
+    upstream::{Chain, DataSource, Platform, ProofLevel},
     util::parse_body,
 };
 use async_trait::async_trait;
@@ -180,6 +181,20 @@ struct ExpandVerticesList {
 }
 
 impl IdentityGraph {
+    /// Order vertices by v_id and edges by source, target, type and data source, so they can be paged.
+    pub fn sort(&mut self) {
+        self.vertices
+            .sort_by(|a, b| a.record.v_id.cmp(&b.record.v_id));
+        self.edges.sort_by_key(|edge| {
+            (
+                edge.source.clone(),
+                edge.target.clone(),
+                edge.edge_type.clone(),
+                edge.data_source.to_string(),
+            )
+        });
+    }
+
     pub async fn find_expand_identity(
         client: &Client<HttpConnector>,
         platform: &Platform,
@@ -215,7 +230,7 @@ impl IdentityGraph {
                 ))
             })?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query find_expand_identity | Fail to request: {:?}",
                 err.to_string()
@@ -255,6 +270,7 @@ impl IdentityGraph {
         platform: &Platform,
         identity: &str,
         reverse: Option<bool>,
+        min_level: Option<ProofLevel>,
     ) -> Result<Option<IdentityGraph>, Error> {
         // This reverse flag can be used as a filtering for Identity which type is domain system .
         // flag = 0, If `reverse=None` if omitted, there is no need to filter anything.
@@ -264,14 +280,17 @@ impl IdentityGraph {
             true => 1,
             false => 2,
         });
+        // min_level = 0 keeps proofs of any level.
+        let min_level = min_level.map_or(0, |level| level as i32);
         let p = format!("{},{}", platform, identity);
         let encoded_p = urlencoding::encode(&p);
         let uri: http::Uri = format!(
-            "{}/query/{}/find_identity_graph?p={}&reverse_flag={}",
+            "{}/query/{}/find_identity_graph?p={}&reverse_flag={}&min_level={}",
             C.tdb.host,
             Graph::SocialGraph.to_string(),
             encoded_p,
             flag,
+            min_level,
         )
         .parse()
         .map_err(|_err: InvalidUri| {
@@ -293,7 +312,7 @@ impl IdentityGraph {
                 ))
             })?;
 
-        let mut resp = client.request(req).await.map_err(|err| {
+        let mut resp = request(client, req).await.map_err(|err| {
             Error::ManualHttpClientError(format!(
                 "query find_identity_graph | Fail to request: {:?}",
                 err.to_string()
@@ -314,7 +333,7 @@ impl IdentityGraph {
                 let result = r.results.and_then(|vec_res| vec_res.first().cloned());
                 match result {
                     None => return Ok(None),
-                    Some(result) => {
+                    Some(mut result) => {
                         if result.graph_id == "" {
                             return Ok(None);
                         } else if result.edges.len() == 0 {
@@ -322,6 +341,7 @@ impl IdentityGraph {
                                 return Ok(None); // If vertices=1, it's isolated vertex
                             }
                         }
+                        result.sort();
                         return Ok(Some(result));
                     }
                 }
@@ -7,7 +7,7 @@ pub use contract::{Contract, ContractLoadFn, ContractRecord};
 pub use domain_collection::{DomainCollection, DomainCollectionAttribute, DomainCollectionRecord};
 pub use identity::{
     ExpireTimeLoadFn, Identity, IdentityLoadFn, IdentityRecord, IdentityWithSource,
-    NeighborReverseLoadFn, NeighborsResponse, OwnerLoadFn,
+    NeighborReverseLoadFn, NeighborsResponse, OwnerLoadFn, RankLoadFn,
 };
 pub use identity_graph::{
     Address, ExpandIdentityRecord, IdentitiesGraph, IdentityConnection, IdentityGraph,
@@ -7,7 +7,7 @@ use crate::tigergraph::edge::Proof;
 use crate::tigergraph::upsert::create_identity_to_identity_proof_two_way_binding;
 use crate::tigergraph::vertex::Identity;
 use crate::tigergraph::EdgeList;
-use crate::upstream::{DataSource, Fetcher, Platform, ProofLevel, TargetProcessedList};
+use crate::upstream::{DataSource, Fetcher, Platform, TargetProcessedList};
 use crate::util::{
     make_client, make_http_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive,
 };
@@ -199,15 +199,11 @@ async fn save_item(p: Record) -> Result<TargetProcessedList, Error> {
         debug!("AggregationService filter source={}", DataSource::Rss3);
         return Ok(vec![]);
     }
-    let level = if source == DataSource::NextID {
-        ProofLevel::VeryConfident
-    } else {
-        ProofLevel::Confident
-    };
+    let level = source.proof_level();
     let pf: Proof = Proof {
         uuid: Uuid::new_v4(),
         source,
-        level: level.clone(),
+        level,
         record_id: Some(p.id.clone()),
         created_at: timestamp_to_naive(
             p.create_timestamp.parse::<i64>().unwrap() / 1000,
@@ -224,7 +220,7 @@ async fn save_item(p: Record) -> Result<TargetProcessedList, Error> {
     let pb: Proof = Proof {
         uuid: Uuid::new_v4(),
         source,
-        level: level.clone(),
+        level,
         record_id: Some(p.id.clone()),
         created_at: timestamp_to_naive(
             p.create_timestamp.parse::<i64>().unwrap() / 1000,
@@ -7,6 +7,7 @@ use crate::{
 };
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_smoke_aggregation() -> Result<(), Error> {
     let target = Target::Identity(Platform::Twitter, "blake".to_string());
     let _ = Aggregation::fetch(&target).await?;
@@ -0,0 +1,186 @@
+mod tests;
+
+use crate::{config::C, error::Error, util::naive_now};
+use chrono::NaiveDateTime;
+use http::StatusCode;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use tracing::{info, warn};
+
+lazy_static! {
+    /// Breakers of every upstream contacted since startup.
+    static ref BREAKERS: Breakers = Breakers::new(
+        C.circuit_breaker.failure_threshold,
+        Duration::from_secs(C.circuit_breaker.open_seconds),
+    );
+}
+
+/// Get the global breakers.
+pub fn breakers() -> &'static Breakers {
+    &BREAKERS
+}
+
+/// Whether `err` means the upstream itself is unhealthy.
+/// "Not found"-like answers prove it is working fine.
+pub fn is_upstream_fault(err: &Error) -> bool {
+    match err {
+        Error::NoResult | Error::ParamError(_) | Error::ParamMissing(_) => false,
+        Error::General(_, status) => {
+            !status.is_client_error() || *status == StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => true,
+    }
+}
+
+/// State of a circuit breaker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, async_graphql::Enum)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Upstream works. All calls go through.
+    #[default]
+    #[graphql(name = "closed")]
+    Closed,
+    /// Upstream keeps failing. Calls are skipped until `openUntil`.
+    #[graphql(name = "open")]
+    Open,
+    /// Cooldown passed. One probe call is allowed to test recovery.
+    #[graphql(name = "half_open")]
+    HalfOpen,
+}
+
+/// Health of one upstream.
+#[derive(Debug, Clone, Serialize, async_graphql::SimpleObject)]
+pub struct UpstreamStatus {
+    /// Upstream name, e.g. `the_graph`.
+    pub name: String,
+    pub state: BreakerState,
+    /// Failures since last success.
+    pub consecutive_failures: u32,
+    /// Calls are skipped until then while `open`.
+    pub open_until: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<NaiveDateTime>,
+    pub last_failure_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Breaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    open_until: Option<NaiveDateTime>,
+    /// A half-open probe is running since then. Other calls are skipped meanwhile.
+    /// A probe never reported back (e.g. cancelled) is given up after `open_for`.
+    probing_since: Option<NaiveDateTime>,
+    last_error: Option<String>,
+    last_success_at: Option<NaiveDateTime>,
+    last_failure_at: Option<NaiveDateTime>,
+}
+
+/// Per-upstream circuit breakers.
+/// Opens after `failure_threshold` consecutive failures, stays open for `open_for`,
+/// then lets a single probe through (half-open). The probe's result closes or reopens it.
+pub struct Breakers {
+    failure_threshold: u32,
+    open_for: Duration,
+    breakers: Mutex<BTreeMap<&'static str, Breaker>>,
+}
+
+impl Breakers {
+    pub fn new(failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            breakers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn breakers(&self) -> MutexGuard<'_, BTreeMap<&'static str, Breaker>> {
+        self.breakers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether `upstream` may be called now.
+    /// Moves an expired open breaker to half-open and lets this call be the probe.
+    pub fn allow(&self, upstream: &'static str) -> bool {
+        let now = naive_now();
+        let open_for = chrono::Duration::from_std(self.open_for).unwrap_or_default();
+        let mut breakers = self.breakers();
+        let breaker = breakers.entry(upstream).or_default();
+        match breaker.state {
+            BreakerState::Closed => true,
+            BreakerState::Open if breaker.open_until.is_some_and(|until| until > now) => false,
+            BreakerState::HalfOpen
+                if breaker
+                    .probing_since
+                    .is_some_and(|since| since + open_for > now) =>
+            {
+                false
+            }
+            BreakerState::Open | BreakerState::HalfOpen => {
+                info!(upstream, "Circuit half-open. Probing.");
+                breaker.state = BreakerState::HalfOpen;
+                breaker.probing_since = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Record a successful call. Closes the breaker.
+    pub fn success(&self, upstream: &'static str) {
+        let mut breakers = self.breakers();
+        let breaker = breakers.entry(upstream).or_default();
+        if breaker.state != BreakerState::Closed {
+            info!(upstream, "Circuit closed. Upstream recovered.");
+        }
+        breaker.state = BreakerState::Closed;
+        breaker.consecutive_failures = 0;
+        breaker.open_until = None;
+        breaker.probing_since = None;
+        breaker.last_success_at = Some(naive_now());
+    }
+
+    /// Record a failed call. Opens the breaker after too many of them, or if the probe failed.
+    pub fn failure(&self, upstream: &'static str, err: &str) {
+        let now = naive_now();
+        let mut breakers = self.breakers();
+        let breaker = breakers.entry(upstream).or_default();
+        breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+        breaker.last_error = Some(err.to_string());
+        breaker.last_failure_at = Some(now);
+        breaker.probing_since = None;
+        let open = match breaker.state {
+            BreakerState::Closed => breaker.consecutive_failures >= self.failure_threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if open {
+            let open_for = chrono::Duration::from_std(self.open_for).unwrap_or_default();
+            breaker.state = BreakerState::Open;
+            breaker.open_until = Some(now + open_for);
+            warn!(
+                upstream,
+                failures = breaker.consecutive_failures,
+                err,
+                open_for = ?self.open_for,
+                "Circuit opened. Upstream skipped."
+            );
+        }
+    }
+
+    /// Status of every upstream, ordered by name.
+    pub fn statuses(&self) -> Vec<UpstreamStatus> {
+        self.breakers()
+            .iter()
+            .map(|(name, breaker)| UpstreamStatus {
+                name: name.to_string(),
+                state: breaker.state,
+                consecutive_failures: breaker.consecutive_failures,
+                open_until: breaker.open_until,
+                last_error: breaker.last_error.clone(),
+                last_success_at: breaker.last_success_at,
+                last_failure_at: breaker.last_failure_at,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use crate::upstream::circuit_breaker::{BreakerState, Breakers};
+    use std::time::Duration;
+
+    fn state(breakers: &Breakers, upstream: &str) -> BreakerState {
+        breakers
+            .statuses()
+            .into_iter()
+            .find(|s| s.name == upstream)
+            .expect("upstream should be tracked")
+            .state
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breakers = Breakers::new(3, Duration::from_secs(60));
+        assert!(breakers.allow("knn3"));
+        breakers.failure("knn3", "timeout");
+        breakers.failure("knn3", "timeout");
+        // A success in between resets the count.
+        breakers.success("knn3");
+        breakers.failure("knn3", "timeout");
+        breakers.failure("knn3", "timeout");
+        assert_eq!(state(&breakers, "knn3"), BreakerState::Closed);
+        assert!(breakers.allow("knn3"));
+
+        breakers.failure("knn3", "timeout");
+        assert_eq!(state(&breakers, "knn3"), BreakerState::Open);
+        assert!(!breakers.allow("knn3"));
+        // Others are not affected.
+        assert!(breakers.allow("the_graph"));
+
+        let status = breakers
+            .statuses()
+            .into_iter()
+            .find(|s| s.name == "knn3")
+            .unwrap();
+        assert_eq!(status.consecutive_failures, 3);
+        assert_eq!(status.last_error, Some("timeout".to_string()));
+        assert!(status.open_until.is_some());
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breakers = Breakers::new(1, Duration::from_millis(100));
+        breakers.failure("ens_reverse", "502");
+        assert_eq!(state(&breakers, "ens_reverse"), BreakerState::Open);
+        assert!(!breakers.allow("ens_reverse"));
+
+        // Cooldown passed: only one probe goes through.
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(breakers.allow("ens_reverse"));
+        assert_eq!(state(&breakers, "ens_reverse"), BreakerState::HalfOpen);
+        assert!(!breakers.allow("ens_reverse"));
+
+        // Failed probe reopens.
+        breakers.failure("ens_reverse", "502");
+        assert_eq!(state(&breakers, "ens_reverse"), BreakerState::Open);
+        assert!(!breakers.allow("ens_reverse"));
+
+        // Successful probe closes.
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(breakers.allow("ens_reverse"));
+        breakers.success("ens_reverse");
+        assert_eq!(state(&breakers, "ens_reverse"), BreakerState::Closed);
+        assert!(breakers.allow("ens_reverse"));
+        assert!(breakers.allow("ens_reverse"));
+    }
+}
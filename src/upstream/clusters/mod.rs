@@ -9,7 +9,7 @@ use crate::tigergraph::vertex::{DomainCollection, IdentitiesGraph, Identity};
 use crate::tigergraph::{EdgeList, EdgeWrapperEnum};
 use crate::upstream::{
     DataFetcher, DataSource, DomainNameSystem, DomainSearch, DomainStatus, Fetcher, Platform,
-    ProofLevel, TargetProcessedList, EXT,
+    TargetProcessedList, EXT,
 };
 use crate::util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive};
 
@@ -162,7 +162,7 @@ async fn batch_fetch_by_address(target: &Target) -> Result<(TargetProcessedList,
         let clusters_connect = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Clusters,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Clusters.proof_level(),
             record_id: None,
             created_at: updated_at_naive,
             updated_at: naive_now(),
@@ -172,7 +172,7 @@ async fn batch_fetch_by_address(target: &Target) -> Result<(TargetProcessedList,
         let proof_forward = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Clusters,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Clusters.proof_level(),
             record_id: None,
             created_at: updated_at_naive,
             updated_at: naive_now(),
@@ -349,7 +349,7 @@ async fn batch_fetch_by_clusters(
         let clusters_connect = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Clusters,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Clusters.proof_level(),
             record_id: None,
             created_at: created_at_naive,
             updated_at: naive_now(),
@@ -359,7 +359,7 @@ async fn batch_fetch_by_clusters(
         let proof_forward = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Clusters,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Clusters.proof_level(),
             record_id: None,
             created_at: created_at_naive,
             updated_at: naive_now(),
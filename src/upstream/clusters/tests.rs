@@ -1,12 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use crate::{error::Error, upstream::clusters::Clusters, upstream::DomainSearch};
+    use crate::{
+        error::Error, tests::assert_upstream_snapshot, upstream::clusters::Clusters,
+        upstream::DomainSearch,
+    };
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_domain_search() -> Result<(), Error> {
         let name = "suji";
         let edges = Clusters::domain_search(name).await?;
-        println!("data: {:?}", edges);
+        assert_upstream_snapshot!(edges);
         Ok(())
     }
 }
@@ -0,0 +1,141 @@
+use super::{crossbell_identity, query_by_handle, Character};
+use crate::{
+    config::C,
+    error::Error,
+    tigergraph::{
+        edge::{Follow, Wrapper, FOLLOW},
+        EdgeList, EdgeWrapperEnum,
+    },
+    upstream::{
+        as_followee, DataSource, Fetcher, Platform, Target, TargetProcessedList, FOLLOW_PAGES,
+    },
+};
+use async_trait::async_trait;
+use gql_client::Client as GQLClient;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Links fetched in one page.
+const PAGE_SIZE: usize = 100;
+
+#[derive(Serialize)]
+struct LinksVars {
+    character_id: i64,
+    take: usize,
+    skip: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LinksResponse {
+    links: Vec<Link>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Link {
+    #[serde(rename = "toCharacter")]
+    to_character: Option<Character>,
+}
+
+const QUERY_FOLLOW_LINKS: &str = r#"
+  query QueryFollowLinks($character_id: Int!, $take: Int!, $skip: Int!) {
+    links(
+      where: {fromCharacterId: {equals: $character_id}, linkType: {equals: "follow"}},
+      take: $take,
+      skip: $skip
+    ) {
+      toCharacter {
+        characterId
+        handle
+        owner
+        primary
+        createdAt
+        updatedAt
+        transactionHash
+        metadata {
+          content
+        }
+      }
+    }
+  }
+"#;
+
+/// Characters a Crossbell character links to as `follow`.
+/// Followees are saved but not fetched further.
+pub struct CrossbellLink {}
+
+#[async_trait]
+impl Fetcher for CrossbellLink {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+        // Follows are only saved through `batch_fetch`, and lead to nothing to fetch next.
+        Ok(vec![])
+    }
+
+    async fn batch_fetch(target: &Target) -> Result<(TargetProcessedList, EdgeList), Error> {
+        if !Self::can_fetch(target) {
+            return Ok((vec![], vec![]));
+        }
+        Ok((vec![], fetch_following(target).await?))
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Crossbell])
+    }
+}
+
+async fn query_links(character_id: i64, page: usize) -> Result<Vec<Link>, Error> {
+    let client = GQLClient::new(&C.upstream.crossbell_api.url);
+    let vars = LinksVars {
+        character_id,
+        take: PAGE_SIZE,
+        skip: page * PAGE_SIZE,
+    };
+    let resp = client.query_with_vars::<LinksResponse, LinksVars>(QUERY_FOLLOW_LINKS, vars);
+    match tokio::time::timeout(std::time::Duration::from_secs(5), resp).await {
+        Ok(Ok(resp)) => Ok(resp.map_or(vec![], |resp| resp.links)),
+        Ok(Err(err)) => {
+            warn!(character_id, ?err, "Crossbell: Failed to fetch links");
+            Ok(vec![])
+        }
+        Err(_) => {
+            warn!(character_id, "Crossbell timeout: no response in 5 seconds.");
+            Ok(vec![])
+        }
+    }
+}
+
+/// `Follow` edges from the character `target` to whom it follows, at most `FOLLOW_PAGES` pages.
+pub(crate) async fn fetch_following(target: &Target) -> Result<EdgeList, Error> {
+    let character = match query_by_handle(target)
+        .await?
+        .and_then(|res| res.characters.into_iter().next())
+    {
+        Some(character) => character,
+        None => {
+            debug!(?target, "Crossbell: No result");
+            return Ok(vec![]);
+        }
+    };
+    let Ok(character_id) = character.character_id.parse::<i64>() else {
+        return Ok(vec![]);
+    };
+    let follower = crossbell_identity(&character);
+    let mut edges = EdgeList::new();
+    for page in 0..FOLLOW_PAGES {
+        let links = query_links(character_id, page).await?;
+        let count = links.len();
+        for followee in links.iter().filter_map(|link| link.to_character.as_ref()) {
+            let followee = as_followee(crossbell_identity(followee));
+            let follow = Follow::new(&follower, &followee, DataSource::Crossbell);
+            edges.push(EdgeWrapperEnum::new_follow(
+                follow.wrapper(&follower, &followee, FOLLOW),
+            ));
+        }
+        if count < PAGE_SIZE {
+            break;
+        }
+    }
+    Ok(edges)
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests;
 
+pub mod link;
+
 use crate::config::C;
 use crate::error::Error;
 use crate::tigergraph::edge::{Hold, HyperEdge, PartOfCollection, Resolve, Wrapper};
@@ -222,39 +224,45 @@ async fn batch_fetch_by_wallet(target: &Target) -> Result<(TargetProcessedList,
     Ok((vec![], edges))
 }
 
+/// `Identity` of a Crossbell character.
+fn crossbell_identity(profile: &Character) -> Identity {
+    let handle = profile.handle.clone();
+    let csb = format!("{}.csb", handle);
+    let display_name = profile.metadata.clone().map_or(handle.clone(), |res| {
+        res.content.map_or(handle.clone(), |content| {
+            content.name.map_or(handle.clone(), |name| name)
+        })
+    });
+    let avatar = profile.metadata.clone().map_or(None, |res| {
+        res.content.map_or(None, |content| {
+            content
+                .avatars
+                .map_or(None, |avatars| avatars.first().cloned())
+        })
+    });
+
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Crossbell,
+        identity: csb,
+        uid: Some(profile.character_id.clone()),
+        created_at: profile.created_at,
+        display_name: Some(display_name),
+        added_at: naive_now(),
+        avatar_url: avatar,
+        profile_url: Some("https://xchar.app/".to_owned() + &profile.handle.clone()),
+        updated_at: naive_now(),
+        expired_at: None,
+        reverse: Some(false),
+    }
+}
+
 fn generate_edges(characters: &Vec<Character>) -> EdgeList {
     let mut edges = EdgeList::new();
     let hv = IdentitiesGraph::default();
     for profile in characters.iter() {
-        let handle = profile.handle.clone();
-        let csb = format!("{}.csb", handle);
-        let display_name = profile.metadata.clone().map_or(handle.clone(), |res| {
-            res.content.map_or(handle.clone(), |content| {
-                content.name.map_or(handle.clone(), |name| name)
-            })
-        });
-        let avatar = profile.metadata.clone().map_or(None, |res| {
-            res.content.map_or(None, |content| {
-                content
-                    .avatars
-                    .map_or(None, |avatars| avatars.first().cloned())
-            })
-        });
-
-        let mut crossbell = Identity {
-            uuid: Some(Uuid::new_v4()),
-            platform: Platform::Crossbell,
-            identity: csb.clone(),
-            uid: Some(profile.character_id.clone()),
-            created_at: profile.created_at,
-            display_name: Some(display_name),
-            added_at: naive_now(),
-            avatar_url: avatar,
-            profile_url: Some("https://xchar.app/".to_owned() + &profile.handle.clone()),
-            updated_at: naive_now(),
-            expired_at: None,
-            reverse: Some(false),
-        };
+        let mut crossbell = crossbell_identity(profile);
+        let csb = crossbell.identity.clone();
 
         let owner = Identity {
             uuid: Some(Uuid::new_v4()),
@@ -1,21 +1,19 @@
 use super::*;
+use crate::tests::{assert_upstream_snapshot, save_and_find};
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_fetch_success() -> Result<(), Error> {
     let target = Target::Identity(
         Platform::Ethereum,
         "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".into(),
     );
-    let cli = make_http_client();
-    ENSReverseLookup::fetch(&target).await?;
-    let found = Identity::find_by_platform_identity(
-        &cli,
-        &target.platform().unwrap(),
-        &target.identity().unwrap(),
-    )
-    .await
-    .expect("Should find without error")
-    .expect("Should find exact 1 result");
+    let (_, edges) = ENSReverseLookup::batch_fetch(&target).await?;
+    assert_upstream_snapshot!(edges);
+
+    let found = save_and_find(edges, &target.platform()?, &target.identity()?)
+        .await?
+        .expect("Should find exact 1 result");
     assert_eq!(found.display_name, Some("vitalik.eth".to_string()));
 
     Ok(())
@@ -0,0 +1,376 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    config::{ConfigEvmRPC, C},
+    error::Error,
+    tigergraph::{
+        edge::{RelationTX, RelationUniqueTX, Wrapper, RELATION_TX, RELATION_UNIQUE_TX},
+        vertex::Identity,
+        EdgeList, EdgeWrapperEnum,
+    },
+    upstream::{as_followee, Fetcher, Platform, Target, TargetProcessedList},
+    util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive},
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use hyper::{Body, Method, Request};
+use num_bigint::BigUint;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tracing::debug;
+use uuid::Uuid;
+
+/// `keccak256("Transfer(address,address,uint256)")`
+pub(super) const TRANSFER_TOPIC: &str =
+    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// Mints and burns are not a relation between two identities.
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Blocks asked for in one JSON-RPC batch of `eth_getBlockByNumber`.
+const BLOCKS_PER_BATCH: usize = 100;
+
+/// ERC-20 transfers of an Ethereum wallet, read from `Transfer` logs through JSON-RPC.
+/// Saved as `Relation_TX` per transaction and `Relation_Unique_TX` per counterparty.
+/// Counterparties are not fetched further, nor put into the identity graph.
+pub struct EvmTransfer {}
+
+#[async_trait]
+impl Fetcher for EvmTransfer {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+        // Transfers are only saved through `batch_fetch`, and lead to nothing to fetch next.
+        Ok(vec![])
+    }
+
+    async fn batch_fetch(target: &Target) -> Result<(TargetProcessedList, EdgeList), Error> {
+        if !Self::can_fetch(target) {
+            return Ok((vec![], vec![]));
+        }
+        let edges = fetch_transfers(&C.upstream.evm_rpc, &target.identity()?).await?;
+        Ok((vec![], edges))
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        !C.upstream.evm_rpc.rpc_url.is_empty()
+            && target.in_platform_supported(vec![Platform::Ethereum])
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RpcRequest<'a> {
+    jsonrpc: &'static str,
+    id: u32,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    #[serde(default)]
+    id: u32,
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+impl<T> RpcResponse<T> {
+    fn into_result(self, method: &str) -> Result<T, Error> {
+        if let Some(err) = self.error {
+            return Err(Error::ManualHttpClientError(format!(
+                "EVM RPC fetch | {} error: {} {}",
+                method, err.code, err.message
+            )));
+        }
+        self.result.ok_or(Error::NoResult)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Log {
+    address: String,
+    topics: Vec<String>,
+    data: String,
+    block_number: String,
+    transaction_hash: String,
+    transaction_index: String,
+    #[serde(default)]
+    removed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Block {
+    timestamp: String,
+}
+
+/// POST `body` (one request or a batch of them) to `rpc_url`.
+async fn post<R: DeserializeOwned>(rpc_url: &str, method: &str, body: String) -> Result<R, Error> {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri(rpc_url)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body))
+        .map_err(|err| Error::ParamError(format!("EVM RPC Build Request Error {}", err)))?;
+    let client = make_client().await?;
+    let mut resp = request_with_timeout(&client, req, None)
+        .await
+        .map_err(|err| {
+            Error::ManualHttpClientError(format!("EVM RPC fetch | {} error: {}", method, err))
+        })?;
+    parse_body(&mut resp).await
+}
+
+async fn call<T: DeserializeOwned>(rpc_url: &str, method: &str, params: Value) -> Result<T, Error> {
+    let body = serde_json::to_string(&RpcRequest {
+        jsonrpc: "2.0",
+        id: 1,
+        method,
+        params,
+    })?;
+    post::<RpcResponse<T>>(rpc_url, method, body)
+        .await?
+        .into_result(method)
+}
+
+/// `method` called with each of `params` in one JSON-RPC batch. Results are in order of `params`.
+async fn call_batch<T: DeserializeOwned>(
+    rpc_url: &str,
+    method: &str,
+    params: Vec<Value>,
+) -> Result<Vec<T>, Error> {
+    if params.is_empty() {
+        return Ok(vec![]);
+    }
+    let requests: Vec<RpcRequest> = params
+        .into_iter()
+        .enumerate()
+        .map(|(id, params)| RpcRequest {
+            jsonrpc: "2.0",
+            id: id as u32,
+            method,
+            params,
+        })
+        .collect();
+    let count = requests.len();
+    let body = serde_json::to_string(&requests)?;
+    // Nodes may answer a batch in any order.
+    let mut responses: Vec<RpcResponse<T>> = post(rpc_url, method, body).await?;
+    responses.sort_by_key(|resp| resp.id);
+    if responses.len() != count {
+        return Err(Error::ManualHttpClientError(format!(
+            "EVM RPC fetch | {} error: {} results for {} requests",
+            method,
+            responses.len(),
+            count
+        )));
+    }
+    responses
+        .into_iter()
+        .map(|resp| resp.into_result(method))
+        .collect()
+}
+
+/// `0x`-prefixed hex quantity.
+fn parse_quantity(hex: &str) -> Result<u64, Error> {
+    Ok(u64::from_str_radix(hex.trim_start_matches("0x"), 16)?)
+}
+
+/// `uint256` log data. 0 if empty or malformed.
+pub(super) fn parse_value(data: &str) -> BigUint {
+    BigUint::parse_bytes(data.trim_start_matches("0x").as_bytes(), 16).unwrap_or_default()
+}
+
+/// Indexed `address` topic, left-padded to 32 bytes.
+pub(super) fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x"))
+}
+
+fn topic_address(topic: &str) -> String {
+    let hex = topic.trim_start_matches("0x");
+    let start = hex.len().saturating_sub(40);
+    format!("0x{}", hex.get(start..).unwrap_or_default())
+}
+
+fn wallet(address: &str) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Ethereum,
+        identity: address.to_string(),
+        uid: None,
+        created_at: None,
+        // Don't use ETH's wallet as display_name, use ENS reversed lookup instead.
+        display_name: None,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        expired_at: None,
+        reverse: Some(false),
+    }
+}
+
+/// All ERC-20 `Transfer` logs from or to `address` since `config.from_block`,
+/// read `config.block_range` blocks at a time.
+/// NFT (ERC-721) transfers index their token id as a 4th topic, and are left out.
+async fn transfer_logs(config: &ConfigEvmRPC, address: &str) -> Result<Vec<Log>, Error> {
+    let topic = address_topic(address);
+    let latest: String = call(&config.rpc_url, "eth_blockNumber", json!([])).await?;
+    let latest = parse_quantity(&latest)?;
+    let range = config.block_range.max(1);
+    let mut logs: Vec<Log> = vec![];
+    let mut from_block = config.from_block;
+    while from_block <= latest {
+        let to_block = latest.min(from_block.saturating_add(range - 1));
+        for topics in [
+            json!([TRANSFER_TOPIC, topic, null]),
+            json!([TRANSFER_TOPIC, null, topic]),
+        ] {
+            let mut filter = json!({
+                "fromBlock": format!("0x{:x}", from_block),
+                "toBlock": format!("0x{:x}", to_block),
+                "topics": topics,
+            });
+            if !config.contracts.is_empty() {
+                filter["address"] = json!(config.contracts);
+            }
+            let found: Vec<Log> = call(&config.rpc_url, "eth_getLogs", json!([filter])).await?;
+            logs.extend(
+                found
+                    .into_iter()
+                    .filter(|log| !log.removed && log.topics.len() == 3),
+            );
+        }
+        match to_block.checked_add(1) {
+            Some(next) => from_block = next,
+            None => break,
+        }
+    }
+    Ok(logs)
+}
+
+/// Mining time of each of `blocks`, asked for `BLOCKS_PER_BATCH` at a time.
+async fn block_timestamps(
+    config: &ConfigEvmRPC,
+    blocks: &BTreeSet<u64>,
+) -> Result<HashMap<u64, NaiveDateTime>, Error> {
+    let blocks: Vec<u64> = blocks.iter().copied().collect();
+    let mut timestamps = HashMap::new();
+    for chunk in blocks.chunks(BLOCKS_PER_BATCH) {
+        let params = chunk
+            .iter()
+            .map(|number| json!([format!("0x{:x}", number), false]))
+            .collect();
+        let found: Vec<Block> = call_batch(&config.rpc_url, "eth_getBlockByNumber", params).await?;
+        for (number, block) in chunk.iter().zip(found) {
+            let timestamp =
+                timestamp_to_naive(parse_quantity(&block.timestamp)? as i64, 0).unwrap_or_default();
+            timestamps.insert(*number, timestamp);
+        }
+    }
+    Ok(timestamps)
+}
+
+/// `Relation_TX` of each transaction and token transferred between `address` and others,
+/// and `Relation_Unique_TX` aggregating them for each direction, counterparty and token.
+pub(crate) async fn fetch_transfers(
+    config: &ConfigEvmRPC,
+    address: &str,
+) -> Result<EdgeList, Error> {
+    let address = address.to_lowercase();
+    let logs: Vec<Log> = transfer_logs(config, &address)
+        .await?
+        .into_iter()
+        .filter(|log| {
+            let from = topic_address(&log.topics[1]).to_lowercase();
+            let to = topic_address(&log.topics[2]).to_lowercase();
+            from != to && from != ZERO_ADDRESS && to != ZERO_ADDRESS
+        })
+        .collect();
+    let blocks = logs
+        .iter()
+        .map(|log| parse_quantity(&log.block_number))
+        .collect::<Result<BTreeSet<u64>, Error>>()?;
+    let timestamps = block_timestamps(config, &blocks).await?;
+
+    // Transfers of one token in one transaction are merged,
+    // as `transaction_hash` and `contract_address` discriminate `Relation_TX`.
+    let mut txs: BTreeMap<(String, String, String, String), RelationTX> = BTreeMap::new();
+    for log in logs.iter() {
+        let from = topic_address(&log.topics[1]).to_lowercase();
+        let to = topic_address(&log.topics[2]).to_lowercase();
+        let contract = log.address.to_lowercase();
+        let value = parse_value(&log.data);
+        let key = (
+            from.clone(),
+            to.clone(),
+            contract.clone(),
+            log.transaction_hash.to_lowercase(),
+        );
+        if let Some(tx) = txs.get_mut(&key) {
+            tx.tx_value = (tx.value() + value).to_string();
+            continue;
+        }
+        let block_number = parse_quantity(&log.block_number)?;
+        txs.insert(
+            key,
+            RelationTX {
+                transaction_hash: log.transaction_hash.to_lowercase(),
+                transaction_index: parse_quantity(&log.transaction_index)? as i64,
+                tx_status: 1,
+                block_number,
+                block_timestamp: timestamps.get(&block_number).copied().unwrap_or_default(),
+                from_address: from,
+                to_address: to,
+                tx_value: value.to_string(),
+                contract_address: contract,
+                updated_at: naive_now(),
+            },
+        );
+    }
+    debug!(address, transactions = txs.len(), "EVM transfers fetched");
+
+    let mut wallets: HashMap<String, Identity> = HashMap::new();
+    let mut pairs: BTreeMap<(String, String, String), Vec<&RelationTX>> = BTreeMap::new();
+    let mut edges: EdgeList = vec![];
+    for ((from, to, contract, _), tx) in txs.iter() {
+        for other in [from, to] {
+            wallets.entry(other.clone()).or_insert_with(|| {
+                // Counterparties are not fetched, so they stay outdated.
+                if *other == address {
+                    wallet(other)
+                } else {
+                    as_followee(wallet(other))
+                }
+            });
+        }
+        edges.push(EdgeWrapperEnum::new_relation_tx(tx.wrapper(
+            &wallets[from],
+            &wallets[to],
+            RELATION_TX,
+        )));
+        pairs
+            .entry((from.clone(), to.clone(), contract.clone()))
+            .or_default()
+            .push(tx);
+    }
+    for ((from, to, contract), txs) in pairs.into_iter() {
+        if let Some(unique) = RelationUniqueTX::aggregate(&contract, txs) {
+            edges.push(EdgeWrapperEnum::new_relation_unique_tx(unique.wrapper(
+                &wallets[&from],
+                &wallets[&to],
+                RELATION_UNIQUE_TX,
+            )));
+        }
+    }
+    Ok(edges)
+}
@@ -0,0 +1,293 @@
+use crate::{
+    config::ConfigEvmRPC,
+    controller::tigergraphql::schema,
+    error::Error,
+    storage::store,
+    tigergraph::EdgeWrapperEnum,
+    upstream::evm_transfer::{address_topic, fetch_transfers, parse_value, TRANSFER_TOPIC},
+};
+use async_graphql::Request;
+use chrono::NaiveDateTime;
+use http::Response;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Server,
+};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+use uuid::Uuid;
+
+const USDC: &str = "0xA0B86991C6218B36C1D19D4A2E9EB0CE3606EB48";
+const DAI: &str = "0x6b175474e89094c44da98b954eedeac495271d0f";
+/// Block the mock node is at.
+const LATEST: u64 = 20;
+
+/// Address unique to this run, as the TigerGraph stand-in is shared by all tests.
+fn unique_address() -> String {
+    format!("0x{}00000000", Uuid::new_v4().simple())
+}
+
+fn transfer_log(contract: &str, from: &str, to: &str, value: &str, tx: &str, block: u64) -> Value {
+    json!({
+        "address": contract,
+        "topics": [TRANSFER_TOPIC, address_topic(from), address_topic(to)],
+        "data": value,
+        "blockNumber": format!("0x{:x}", block),
+        "transactionHash": tx,
+        "transactionIndex": "0x1",
+        "logIndex": "0x0",
+        "removed": false,
+    })
+}
+
+fn quantity(value: &Value) -> u64 {
+    let hex = value.as_str().unwrap_or_default().trim_start_matches("0x");
+    u64::from_str_radix(hex, 16).unwrap_or_default()
+}
+
+/// Mock Ethereum node at block `LATEST`, answering `eth_getLogs` with `sent` (topic 1 given)
+/// or `received` logs in the asked block range. Block `n` is mined at unix time `n * 1000`.
+/// Each request is recorded in the returned list as its method, batches as arrays of them.
+fn spawn_node(sent: Vec<Value>, received: Vec<Value>) -> (String, Arc<Mutex<Vec<Value>>>) {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+    let make_service = make_service_fn(move |_| {
+        let (sent, received, requests) = (sent.clone(), received.clone(), requests.clone());
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let (sent, received, requests) = (sent.clone(), received.clone(), requests.clone());
+                async move {
+                    let body = crate::tests::replay::read_body(req.into_body())
+                        .await
+                        .unwrap();
+                    let body: Value = serde_json::from_slice(&body).unwrap_or_default();
+                    let answer = |body: &Value| {
+                        let params = &body["params"][0];
+                        let result = match body["method"].as_str() {
+                            Some("eth_blockNumber") => json!(format!("0x{:x}", LATEST)),
+                            Some("eth_getLogs") => {
+                                let logs = if params["topics"][1].is_null() {
+                                    &received
+                                } else {
+                                    &sent
+                                };
+                                let range =
+                                    quantity(&params["fromBlock"])..=quantity(&params["toBlock"]);
+                                let found: Vec<&Value> = logs
+                                    .iter()
+                                    .filter(|log| range.contains(&quantity(&log["blockNumber"])))
+                                    .collect();
+                                json!(found)
+                            }
+                            Some("eth_getBlockByNumber") => {
+                                json!({ "timestamp": format!("0x{:x}", quantity(params) * 1000) })
+                            }
+                            _ => {
+                                let error = json!({"code": -32601, "message": "Method not found"});
+                                return json!({"jsonrpc": "2.0", "id": body["id"], "error": error});
+                            }
+                        };
+                        json!({"jsonrpc": "2.0", "id": body["id"], "result": result})
+                    };
+                    let resp = match body.as_array() {
+                        Some(batch) => {
+                            requests.lock().unwrap().push(json!(batch
+                                .iter()
+                                .map(|b| b["method"].clone())
+                                .collect::<Vec<_>>()));
+                            // Answered in reverse, as nodes may reorder a batch.
+                            json!(batch.iter().rev().map(answer).collect::<Vec<_>>())
+                        }
+                        None => {
+                            requests.lock().unwrap().push(body["method"].clone());
+                            answer(&body)
+                        }
+                    };
+                    Ok::<_, Infallible>(Response::new(Body::from(resp.to_string())))
+                }
+            }))
+        }
+    });
+    let server = Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(make_service);
+    let addr = server.local_addr();
+    tokio::spawn(server);
+    (format!("http://{}", addr), recorded)
+}
+
+/// `wallet` sent `b` 100 USDC then 50 + 10 USDC and 7 DAI in one transaction, and got 1 USDC back.
+/// `c` sent `wallet` more than `u64::MAX`.
+fn spawn_transfers(wallet: &str, b: &str, c: &str) -> (ConfigEvmRPC, Arc<Mutex<Vec<Value>>>) {
+    let zero = "0x0000000000000000000000000000000000000000";
+    let nft = json!({
+        "address": "0xbc4ca0eda7647a8ab7c2061c2e118a18a936f13d",
+        "topics": [TRANSFER_TOPIC, address_topic(wallet), address_topic(b), address_topic("0x1")],
+        "data": "0x",
+        "blockNumber": "0x12",
+        "transactionHash": "0xnft",
+        "transactionIndex": "0x0",
+    });
+    let sent = vec![
+        transfer_log(USDC, wallet, b, "0x64", "0xAAA", 16),
+        transfer_log(USDC, wallet, b, "0x32", "0xbbb", 17),
+        transfer_log(USDC, wallet, b, "0x0a", "0xbbb", 17),
+        transfer_log(DAI, wallet, b, "0x07", "0xbbb", 17),
+        nft,
+    ];
+    let received = vec![
+        transfer_log(USDC, b, wallet, "0x01", "0xccc", 16),
+        transfer_log(
+            USDC,
+            c,
+            wallet,
+            &format!("0x1{}", "0".repeat(32)),
+            "0xddd",
+            18,
+        ),
+        transfer_log(USDC, zero, wallet, "0x64", "0xeee", 18),
+    ];
+    let (rpc_url, requests) = spawn_node(sent, received);
+    let config = ConfigEvmRPC {
+        rpc_url,
+        from_block: 0,
+        contracts: vec![],
+        block_range: 8,
+    };
+    (config, requests)
+}
+
+#[test]
+fn test_parse_value() {
+    assert_eq!(parse_value("0x"), BigUint::default());
+    assert_eq!(
+        parse_value(&format!("0x{:0>64}", "64")),
+        BigUint::from(100u32)
+    );
+    assert_eq!(
+        parse_value(&format!("0x{:f>64}", "")).to_string(),
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_transfers() -> Result<(), Error> {
+    let (wallet, b, c) = (unique_address(), unique_address(), unique_address());
+    let (config, requests) = spawn_transfers(&wallet, &b, &c);
+    let edges = fetch_transfers(&config, &wallet).await?;
+
+    // Blocks 0..=20 are read in 3 ranges, each for sent and received logs,
+    // and the timestamps of blocks 16, 17 and 18 in one batch.
+    let requests = requests.lock().unwrap().clone();
+    let get_logs = requests
+        .iter()
+        .filter(|r| *r == &json!("eth_getLogs"))
+        .count();
+    assert_eq!(get_logs, 6);
+    assert_eq!(requests.last(), Some(&json!(vec!["eth_getBlockByNumber"; 3])));
+
+    let txs: Vec<_> = edges
+        .iter()
+        .filter_map(|e| match e {
+            EdgeWrapperEnum::RelationTX(w) => Some(w),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(txs.len(), 5, "mint and NFT transfer should be left out");
+    let merged = txs
+        .iter()
+        .map(|w| &w.edge)
+        .find(|tx| tx.transaction_hash == "0xbbb" && tx.contract_address == USDC.to_lowercase())
+        .expect("transaction should be found");
+    assert_eq!(merged.tx_value, "60");
+    assert_eq!(merged.block_number, 17);
+    assert_eq!(merged.block_timestamp.and_utc().timestamp(), 17000);
+    assert_eq!(merged.from_id, format!("ethereum,{}", wallet));
+    let dai = txs
+        .iter()
+        .map(|w| &w.edge)
+        .find(|tx| tx.transaction_hash == "0xbbb" && tx.contract_address == DAI)
+        .expect("DAI should not be merged into USDC");
+    assert_eq!(dai.tx_value, "7");
+
+    // Only `wallet` is fetched. Counterparties stay outdated to be fetched on their own.
+    let counterparty = txs
+        .iter()
+        .find(|w| w.edge.transaction_hash == "0xddd")
+        .expect("transaction should be found");
+    assert_eq!(counterparty.source.updated_at, NaiveDateTime::default());
+    assert_ne!(counterparty.target.updated_at, NaiveDateTime::default());
+
+    let unique: Vec<_> = edges
+        .iter()
+        .filter_map(|e| match e {
+            EdgeWrapperEnum::RelationUniqueTX(w) => Some(&w.edge),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(unique.len(), 4);
+    let sent = unique
+        .iter()
+        .find(|r| r.to_id == format!("ethereum,{}", b) && r.contract_address != DAI)
+        .expect("wallet -> b should be aggregated");
+    assert_eq!(
+        (
+            sent.count,
+            sent.sum.as_str(),
+            sent.max.as_str(),
+            sent.min.as_str()
+        ),
+        (2, "160", "100", "60")
+    );
+    let received = unique
+        .iter()
+        .find(|r| r.from_id == format!("ethereum,{}", c))
+        .expect("c -> wallet should be aggregated");
+    assert_eq!(received.count, 1);
+    assert_eq!(received.sum, "340282366920938463463374607431768211456");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_relation_query() -> Result<(), Error> {
+    let (wallet, b, c) = (unique_address(), unique_address(), unique_address());
+    let (config, _) = spawn_transfers(&wallet, &b, &c);
+    store()
+        .batch_upsert(fetch_transfers(&config, &wallet).await?)
+        .await?;
+
+    let query = format!(
+        r#"{{ expand(platform: "ethereum", identity: "{}") {{
+            count sum from {{ identity }} to {{ identity }} }} }}"#,
+        wallet
+    );
+    let resp = schema().execute(Request::new(query)).await;
+    assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+    let data = resp.data.into_json()?;
+    let relations = data["expand"]
+        .as_array()
+        .expect("relations should be listed");
+    assert_eq!(relations.len(), 4);
+    assert!(relations
+        .iter()
+        .any(|r| r["from"]["identity"] == json!(wallet) && r["sum"] == json!("160")));
+
+    let relation = |depth: u16| {
+        format!(
+            r#"{{ relation(sourcePlatform: "ethereum", sourceIdentity: "{}",
+                targetPlatform: "ethereum", targetIdentity: "{}", depth: {}) {{ count }} }}"#,
+            b, c, depth
+        )
+    };
+    let resp = schema().execute(Request::new(relation(1))).await;
+    assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+    assert_eq!(resp.data.into_json()?["relation"], json!([]));
+    let resp = schema().execute(Request::new(relation(2))).await;
+    assert_eq!(
+        resp.data.into_json()?["relation"].as_array().map(Vec::len),
+        Some(4)
+    );
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::{
+    error::Error,
+    tigergraph::{
+        edge::{Follow, Wrapper, FOLLOW},
+        vertex::Identity,
+        EdgeList, EdgeWrapperEnum,
+    },
+    upstream::{
+        as_followee,
+        farcaster::warpcast::{get_following, user_by_username},
+        DataSource, Fetcher, Platform, Target, TargetProcessedList, FOLLOW_PAGES,
+    },
+    util::naive_now,
+};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+/// Whom a Farcaster user follows, read from Warpcast.
+/// Followees are saved but not fetched further.
+pub struct FarcasterFollow {}
+
+#[async_trait]
+impl Fetcher for FarcasterFollow {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+        // Follows are only saved through `batch_fetch`, and lead to nothing to fetch next.
+        Ok(vec![])
+    }
+
+    async fn batch_fetch(target: &Target) -> Result<(TargetProcessedList, EdgeList), Error> {
+        if !Self::can_fetch(target) {
+            return Ok((vec![], vec![]));
+        }
+        Ok((vec![], fetch_following(&target.identity()?).await?))
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Farcaster])
+    }
+}
+
+fn farcaster(username: &str, fid: i64, display_name: Option<String>) -> Identity {
+    Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Farcaster,
+        identity: username.to_string(),
+        uid: Some(fid.to_string()),
+        created_at: None,
+        display_name,
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: None,
+        updated_at: naive_now(),
+        expired_at: None,
+        reverse: Some(false),
+    }
+}
+
+/// `Follow` edges from `username` to whom it follows, at most `FOLLOW_PAGES` pages.
+pub(crate) async fn fetch_following(username: &str) -> Result<EdgeList, Error> {
+    let user = match user_by_username(username).await? {
+        Some(user) => user,
+        None => return Ok(vec![]),
+    };
+    let follower = farcaster(&user.username, user.fid, Some(user.display_name.clone()));
+    let mut edges = EdgeList::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..FOLLOW_PAGES {
+        let (users, next) = get_following(user.fid, cursor.as_deref()).await?;
+        for followee in users.into_iter() {
+            // Users without a username are not addressable as identities.
+            let Some(name) = followee.username else {
+                continue;
+            };
+            let followee = as_followee(farcaster(&name, followee.fid, followee.display_name));
+            let follow = Follow::new(&follower, &followee, DataSource::Farcaster);
+            edges.push(EdgeWrapperEnum::new_follow(
+                follow.wrapper(&follower, &followee, FOLLOW),
+            ));
+        }
+        match next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    Ok(edges)
+}
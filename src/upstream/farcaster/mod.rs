@@ -1,3 +1,4 @@
+pub mod follow;
 mod tests;
 pub mod warpcast;
 use crate::config::C;
@@ -1,31 +1,80 @@
 #[cfg(test)]
 mod tests {
+    use crate::controller::tigergraphql::schema;
     use crate::error::Error;
+    use crate::storage::store;
+    use crate::tests::assert_upstream_snapshot;
+    use crate::upstream::farcaster::follow::fetch_following;
     use crate::upstream::farcaster::warpcast::{batch_fetch_by_signer, batch_fetch_by_username};
     use crate::upstream::farcaster::Farcaster;
     use crate::upstream::{DomainSearch, Platform};
+    use async_graphql::Request;
+    use serde_json::json;
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_get_farcaster_profile_by_username() -> Result<(), Error> {
         let username = "suji";
         let data = batch_fetch_by_username(&Platform::Farcaster, &username).await?;
-        println!("data: {:?}", data);
+        assert_upstream_snapshot!(data);
         Ok(())
     }
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_get_farcaster_profile_by_signer() -> Result<(), Error> {
         let address = "0x934b510d4c9103e6a87aef13b816fb080286d649";
         let data = batch_fetch_by_signer(&Platform::Farcaster, &address).await?;
-        println!("data: {:?}", data);
+        assert_upstream_snapshot!(data);
         Ok(())
     }
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_domain_search() -> Result<(), Error> {
         let name = "0xbillys";
         let edges = Farcaster::domain_search(name).await?;
-        println!("data: {:?}", edges);
+        assert_upstream_snapshot!(edges);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_following() -> Result<(), Error> {
+        let edges = fetch_following("follow-alice").await?;
+        assert_eq!(edges.len(), 2, "users without username should be left out");
+        store().batch_upsert(edges).await?;
+
+        let query = |name: &str, field: &str| {
+            format!(
+                r#"{{ identity(platform: "farcaster", identity: "{}") {{
+                    identityGraph {{ {}(limit: 10) {{ source from {{ identity }} to {{ identity }} }} }}
+                }} }}"#,
+                name, field
+            )
+        };
+        let resp = schema()
+            .execute(Request::new(query("follow-alice", "following")))
+            .await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let data = resp.data.into_json()?;
+        let following = data["identity"]["identityGraph"]["following"]
+            .as_array()
+            .expect("following should be listed");
+        assert_eq!(following.len(), 2);
+        assert!(following
+            .iter()
+            .all(|f| f["from"]["identity"] == json!("follow-alice")
+                && f["source"] == json!("farcaster")));
+
+        let resp = schema()
+            .execute(Request::new(query("follow-carol", "followers")))
+            .await;
+        assert!(resp.errors.is_empty(), "{:?}", resp.errors);
+        let data = resp.data.into_json()?;
+        assert_eq!(
+            data["identity"]["identityGraph"]["followers"][0]["to"]["identity"],
+            json!("follow-carol")
+        );
         Ok(())
     }
 }
@@ -424,6 +424,32 @@ pub struct Location {
     pub description: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowingResponse {
+    pub errors: Option<Vec<Message>>,
+    pub result: Option<FollowingResult>,
+    pub next: Option<NextCursor>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowingResult {
+    pub users: Vec<FollowingUser>,
+}
+
+/// Users in `v2/following` come with fewer fields than `User`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FollowingUser {
+    pub fid: i64,
+    pub username: Option<String>,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NextCursor {
+    pub cursor: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct VerificationResponse {
     pub errors: Option<Vec<Message>>,
@@ -445,7 +471,7 @@ pub struct Verification {
     pub protocol: String,
 }
 
-async fn user_by_username(username: &str) -> Result<Option<User>, Error> {
+pub(super) async fn user_by_username(username: &str) -> Result<Option<User>, Error> {
     let client = make_client().await.unwrap();
     let uri: http::Uri = format!(
         "{}/v2/user-by-username?username={}",
@@ -650,6 +676,70 @@ async fn get_verifications(fid: i64) -> Result<Option<Vec<Verification>>, Error>
     Ok(result)
 }
 
+/// One page of users `fid` follows, and the cursor of the next page if any.
+pub(super) async fn get_following(
+    fid: i64,
+    cursor: Option<&str>,
+) -> Result<(Vec<FollowingUser>, Option<String>), Error> {
+    let client = make_client().await?;
+    let mut url = format!(
+        "{}/v2/following?fid={}&limit=100",
+        C.upstream.warpcast_api.url, fid
+    );
+    if let Some(cursor) = cursor {
+        url = format!("{}&cursor={}", url, cursor);
+    }
+    let uri: http::Uri = url.parse().map_err(|err: InvalidUri| {
+        Error::ParamError(format!(
+            "v2/following?fid={} Uri format Error: {}",
+            fid, err
+        ))
+    })?;
+
+    let req = hyper::Request::builder()
+        .method(http::Method::GET)
+        .uri(uri)
+        .header(
+            "authorization",
+            format!("Bearer {}", C.upstream.warpcast_api.token),
+        )
+        .body(hyper::Body::empty())
+        .map_err(|err| {
+            Error::ParamError(format!(
+                "v2/following?fid={} Request build error: {}",
+                fid, err
+            ))
+        })?;
+
+    let mut resp = request_with_timeout(&client, req, None)
+        .await
+        .map_err(|err| {
+            Error::ManualHttpClientError(format!(
+                "Warpcast fetch error | failed to fetch following?fid={} | {:?}",
+                fid,
+                err.to_string()
+            ))
+        })?;
+
+    match parse_body::<FollowingResponse>(&mut resp).await {
+        Ok(r) => {
+            if let Some(errors) = r.errors {
+                warn!(
+                    "Warpcast fetch error| failed to fetch following?fid={}, message: {:?}",
+                    fid, errors
+                );
+                return Ok((vec![], None));
+            }
+            let users = r.result.map_or(vec![], |res| res.users);
+            Ok((users, r.next.and_then(|next| next.cursor)))
+        }
+        Err(err) => Err(Error::ManualHttpClientError(format!(
+            "Warpcast fetch error | parse_body error: {}",
+            err
+        ))),
+    }
+}
+
 pub async fn domain_search(name: &str) -> Result<EdgeList, Error> {
     if name == "".to_string() {
         warn!("Warpcast user_by_username(name='') is not a valid domain name");
@@ -6,9 +6,7 @@ use crate::tigergraph::edge::{HyperEdge, Proof, Wrapper};
 use crate::tigergraph::edge::{HYPER_EDGE, PROOF_EDGE, PROOF_REVERSE_EDGE};
 use crate::tigergraph::vertex::{IdentitiesGraph, Identity};
 use crate::tigergraph::{EdgeList, EdgeWrapperEnum};
-use crate::upstream::{
-    DataFetcher, DataSource, Fetcher, Platform, ProofLevel, TargetProcessedList,
-};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, TargetProcessedList};
 use crate::util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive};
 
 use async_trait::async_trait;
@@ -89,7 +87,7 @@ async fn batch_fetch_connections(
 
     for (from_idx, from_v) in records.iter().enumerate() {
         let mut data_source = DataSource::Firefly;
-        if from_v.data_source == String::from("admin") {
+        if from_v.data_source == "admin" {
             data_source = DataSource::ManuallyAdded;
         }
         let from_update_naive = timestamp_to_naive(from_v.update_time, 0);
@@ -104,7 +102,7 @@ async fn batch_fetch_connections(
         }
         let from = Identity {
             uuid: Some(Uuid::new_v4()),
-            platform: from_platform.clone(),
+            platform: from_platform,
             identity: from_v.identity.clone(),
             uid: from_v.uid.clone(),
             created_at: from_update_naive,
@@ -129,10 +127,10 @@ async fn batch_fetch_connections(
             }
             let to = Identity {
                 uuid: Some(Uuid::new_v4()),
-                platform: to_platform.clone(),
+                platform: to_platform,
                 identity: to_v.identity.clone(),
                 uid: to_v.uid.clone(),
-                created_at: to_update_naive.clone(),
+                created_at: to_update_naive,
                 display_name: None,
                 added_at: naive_now(),
                 avatar_url: None,
@@ -153,9 +151,9 @@ async fn batch_fetch_connections(
             let proof_forward = Proof {
                 uuid: Uuid::new_v4(),
                 source: data_source,
-                level: ProofLevel::VeryConfident,
+                level: data_source.proof_level(),
                 record_id: Some(from_v.account_id.clone()),
-                created_at: to_update_naive.clone(),
+                created_at: to_update_naive,
                 updated_at: naive_now(),
                 fetcher: DataFetcher::DataMgrService,
             };
@@ -163,9 +161,9 @@ async fn batch_fetch_connections(
             let proof_backward = Proof {
                 uuid: Uuid::new_v4(),
                 source: data_source,
-                level: ProofLevel::VeryConfident,
+                level: data_source.proof_level(),
                 record_id: Some(from_v.account_id.clone()),
-                created_at: to_update_naive.clone(),
+                created_at: to_update_naive,
                 updated_at: naive_now(),
                 fetcher: DataFetcher::DataMgrService,
             };
@@ -189,7 +187,7 @@ async fn search_records(
     let uri: http::Uri = format!(
         "{}/aggregation/search?platform={}&identity={}",
         C.upstream.aggregation_service.url.clone(),
-        platform.to_string(),
+        platform,
         identity
     )
     .parse()
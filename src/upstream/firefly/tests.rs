@@ -1,10 +1,12 @@
 #[cfg(test)]
 mod tests {
     use crate::error::Error;
+    use crate::tests::assert_upstream_snapshot;
     use crate::upstream::firefly::{search_records, Firefly};
     use crate::upstream::{Fetcher, Platform, Target};
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_search_records() -> Result<(), Error> {
         let _identity = "kins";
         let _platform = Platform::Farcaster;
@@ -16,18 +18,20 @@ mod tests {
         let _platform_2 = Platform::Ethereum;
 
         let records = search_records(&_platform_1, _identity_1).await?;
-        println!("records: {:?}", records);
+        assert_upstream_snapshot!(records);
         Ok(())
     }
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_fetch() -> Result<(), Error> {
         let target = Target::Identity(
             Platform::Ethereum,
             "0x61ae970ac67ff4164ebf2fd6f38f630df522e5ef".to_lowercase(),
         );
         // let target = Target::Identity(Platform::Farcaster, "kins".to_string());
-        let _ = Firefly::fetch(&target).await?;
+        let fetched = Firefly::batch_fetch(&target).await?;
+        assert_upstream_snapshot!(fetched);
         Ok(())
     }
 }
@@ -10,7 +10,7 @@ use crate::tigergraph::upsert::create_identity_to_identity_proof_two_way_binding
 use crate::tigergraph::vertex::{IdentitiesGraph, Identity, IdentityRecord};
 use crate::tigergraph::{BaseResponse, Graph};
 use crate::tigergraph::{EdgeList, EdgeWrapperEnum};
-use crate::upstream::{DataSource, Fetcher, Platform, ProofLevel, TargetProcessedList};
+use crate::upstream::{DataSource, Fetcher, Platform, TargetProcessedList};
 use crate::util::{
     make_client, make_http_client, naive_now, option_naive_datetime_from_string,
     option_naive_datetime_to_string, parse_body, request_with_timeout,
@@ -350,7 +350,7 @@ async fn stable_batch_fetch_connections(
         let proof_forward: Proof = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Keybase,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Keybase.proof_level(),
             record_id: p.record_id.clone(),
             created_at: p.created_time.clone(),
             updated_at: naive_now(),
@@ -360,7 +360,7 @@ async fn stable_batch_fetch_connections(
         let proof_backward: Proof = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Keybase,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Keybase.proof_level(),
             record_id: p.record_id.clone(),
             created_at: p.created_time.clone(),
             updated_at: naive_now(),
@@ -486,7 +486,7 @@ async fn stable_fetch_connections_by_platform_identity(
         let pf: Proof = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Keybase,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Keybase.proof_level(),
             record_id: p.record_id.clone(),
             created_at: p.created_time.clone(),
             updated_at: naive_now(),
@@ -496,7 +496,7 @@ async fn stable_fetch_connections_by_platform_identity(
         let pb: Proof = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Keybase,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Keybase.proof_level(),
             record_id: p.record_id.clone(),
             created_at: p.created_time.clone(),
             updated_at: naive_now(),
@@ -603,7 +603,7 @@ async fn fetch_connections_by_platform_identity(
         let pf: Proof = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Keybase,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Keybase.proof_level(),
             record_id: Some(p.proof_id.clone()),
             created_at: None,
             updated_at: naive_now(),
@@ -613,7 +613,7 @@ async fn fetch_connections_by_platform_identity(
         let pb: Proof = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::Keybase,
-            level: ProofLevel::VeryConfident,
+            level: DataSource::Keybase.proof_level(),
             record_id: Some(p.proof_id.clone()),
             created_at: None,
             updated_at: naive_now(),
@@ -1,21 +1,18 @@
 use crate::{
     error::Error,
-    tigergraph::vertex::Identity,
+    tests::{assert_upstream_snapshot, save_and_find},
     upstream::{keybase::Keybase, Target},
     upstream::{Fetcher, Platform},
-    util::make_http_client,
 };
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_smoke_keybase() -> Result<(), Error> {
     let target = Target::Identity(Platform::Keybase, "sujiyan".into());
-    Keybase::fetch(&target).await?;
-    let cli = make_http_client();
-    let _found =
-        Identity::find_by_platform_identity(&cli, &target.platform()?, &target.identity()?)
-            .await?
-            .expect("Record not found");
-
-    // assert!((found.updated_at.timestamp() - naive_now().timestamp()).abs() < 3);
+    let fetched = Keybase::batch_fetch(&target).await?;
+    assert_upstream_snapshot!(fetched);
+    let _found = save_and_find(fetched.1, &target.platform()?, &target.identity()?)
+        .await?
+        .expect("Record not found");
     Ok(())
 }
@@ -9,6 +9,7 @@ use crate::{
 };
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_knn3() -> Result<(), Error> {
     let target = Target::Identity(
         Platform::Ethereum,
@@ -0,0 +1,157 @@
+use super::{query_by_handle, schema, Profile, ProfileId};
+use crate::{
+    config::C,
+    error::Error,
+    tigergraph::{
+        edge::{Follow, Wrapper, FOLLOW},
+        vertex::Identity,
+        EdgeList, EdgeWrapperEnum,
+    },
+    upstream::{
+        as_followee, DataSource, Fetcher, Platform, Target, TargetProcessedList, FOLLOW_PAGES,
+    },
+    util::{naive_now, utc_to_naive},
+};
+use async_trait::async_trait;
+use cynic::{http::SurfExt, QueryBuilder};
+use tracing::warn;
+use uuid::Uuid;
+
+#[derive(cynic::QueryVariables, Debug)]
+pub struct FollowingVariables {
+    pub profile_id: ProfileId,
+    pub limit: LimitType,
+    pub cursor: Option<Cursor>,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    graphql_type = "Query",
+    schema_path = "src/upstream/lensv2/schema.graphql",
+    variables = "FollowingVariables"
+)]
+pub struct FollowingQuery {
+    #[arguments(request: { for: $profile_id, limit: $limit, cursor: $cursor })]
+    pub following: PaginatedFollowingResult,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(
+    graphql_type = "PaginatedProfileResult",
+    schema_path = "src/upstream/lensv2/schema.graphql"
+)]
+pub struct PaginatedFollowingResult {
+    pub items: Vec<Profile>,
+    pub page_info: PaginatedResultInfo,
+}
+
+#[derive(cynic::QueryFragment, Debug)]
+#[cynic(schema_path = "src/upstream/lensv2/schema.graphql")]
+pub struct PaginatedResultInfo {
+    pub next: Option<Cursor>,
+}
+
+#[derive(cynic::Enum, Debug, Clone, Copy)]
+#[cynic(
+    schema_path = "src/upstream/lensv2/schema.graphql",
+    rename_all = "None"
+)]
+pub enum LimitType {
+    Ten,
+    TwentyFive,
+    Fifty,
+}
+
+#[derive(cynic::Scalar, Debug, Clone)]
+pub struct Cursor(pub String);
+
+/// Whom a Lens profile follows.
+/// Followees are saved but not fetched further.
+pub struct LensV2Follow {}
+
+#[async_trait]
+impl Fetcher for LensV2Follow {
+    async fn fetch(target: &Target) -> Result<TargetProcessedList, Error> {
+        if !Self::can_fetch(target) {
+            return Ok(vec![]);
+        }
+        // Follows are only saved through `batch_fetch`, and lead to nothing to fetch next.
+        Ok(vec![])
+    }
+
+    async fn batch_fetch(target: &Target) -> Result<(TargetProcessedList, EdgeList), Error> {
+        if !Self::can_fetch(target) {
+            return Ok((vec![], vec![]));
+        }
+        Ok((vec![], fetch_following(&target.identity()?).await?))
+    }
+
+    fn can_fetch(target: &Target) -> bool {
+        target.in_platform_supported(vec![Platform::Lens])
+    }
+}
+
+/// `Identity` of a Lens profile, if it has a handle.
+fn lens(profile: &Profile) -> Option<Identity> {
+    let handle_info = profile.handle.as_ref()?;
+    Some(Identity {
+        uuid: Some(Uuid::new_v4()),
+        platform: Platform::Lens,
+        identity: format!("{}.{}", handle_info.local_name, handle_info.namespace),
+        uid: Some(profile.id.0.clone()),
+        created_at: utc_to_naive(profile.created_at.0.clone()).ok(),
+        display_name: profile
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.display_name.clone()),
+        added_at: naive_now(),
+        avatar_url: None,
+        profile_url: Some("https://hey.xyz/u/".to_owned() + &handle_info.local_name),
+        updated_at: naive_now(),
+        expired_at: None,
+        reverse: Some(false),
+    })
+}
+
+/// `Follow` edges from `handle` to whom it follows, at most `FOLLOW_PAGES` pages.
+pub(crate) async fn fetch_following(handle: &str) -> Result<EdgeList, Error> {
+    let full_handle = format!("lens/{}", handle.trim_end_matches(".lens"));
+    let follower = match query_by_handle(&full_handle).await?.first().and_then(lens) {
+        Some(follower) => follower,
+        None => return Ok(vec![]),
+    };
+    let profile_id = ProfileId(follower.uid.clone().unwrap_or_default());
+    let mut edges = EdgeList::new();
+    let mut cursor: Option<Cursor> = None;
+    for _ in 0..FOLLOW_PAGES {
+        let operation = FollowingQuery::build(FollowingVariables {
+            profile_id: profile_id.clone(),
+            limit: LimitType::Fifty,
+            cursor: cursor.clone(),
+        });
+        let response = match surf::post(C.upstream.lens_api.url.clone())
+            .run_graphql(operation)
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("LensV2 {} | Failed to fetch following: {}", handle, err);
+                break;
+            }
+        };
+        let Some(page) = response.data.map(|data| data.following) else {
+            break;
+        };
+        for followee in page.items.iter().filter_map(lens).map(as_followee) {
+            let follow = Follow::new(&follower, &followee, DataSource::Lens);
+            edges.push(EdgeWrapperEnum::new_follow(
+                follow.wrapper(&follower, &followee, FOLLOW),
+            ));
+        }
+        match page.page_info.next {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    Ok(edges)
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests;
 
+pub mod follow;
+
 use crate::config::C;
 use crate::error::Error;
 use crate::tigergraph::edge::{
@@ -2,22 +2,22 @@
 mod tests {
     use crate::{
         error::Error,
-        tigergraph::vertex::Identity,
+        tests::{assert_upstream_snapshot, save_and_find},
         upstream::lensv2::LensV2,
         upstream::Platform,
         upstream::{DomainSearch, Fetcher, Target},
-        util::make_http_client,
     };
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_fetch_by_wallet() -> Result<(), Error> {
         let target = Target::Identity(
             Platform::Ethereum,
             String::from("0x934B510D4C9103E6a87AEf13b816fb080286D649"),
         );
-        let _ = LensV2::fetch(&target).await?;
-        let client = make_http_client();
-        let found = Identity::find_by_platform_identity(&client, &Platform::Lens, "sujiyan.lens")
+        let fetched = LensV2::batch_fetch(&target).await?;
+        assert_upstream_snapshot!(fetched);
+        let found = save_and_find(fetched.1, &Platform::Lens, "sujiyan.lens")
             .await?
             .expect("Record not found");
         print!("found: {:?}", found);
@@ -25,12 +25,13 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_fetch_by_lens_handle() -> Result<(), Error> {
         let target = Target::Identity(Platform::Lens, String::from("xnownx.lens"));
-        let _ = LensV2::fetch(&target).await?;
-        let client = make_http_client();
-        let found = Identity::find_by_platform_identity(
-            &client,
+        let fetched = LensV2::batch_fetch(&target).await?;
+        assert_upstream_snapshot!(fetched);
+        let found = save_and_find(
+            fetched.1,
             &Platform::Ethereum,
             &String::from("0x88a4FebB4572CF01967e5Ff9B6109dEA57168c6d").to_lowercase(),
         )
@@ -41,10 +42,11 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "no fixture recorded yet, run `just record`"]
     async fn test_domain_search() -> Result<(), Error> {
         let name = "sujiyan";
         let edges = LensV2::domain_search(name).await?;
-        println!("data: {:?}", edges);
+        assert_upstream_snapshot!(edges);
         Ok(())
     }
 }
@@ -1,22 +1,27 @@
 // Upstreams
 mod aggregation;
+mod circuit_breaker;
 mod clusters;
 mod crossbell;
 mod dotbit;
 mod ens_reverse;
+mod evm_transfer;
 mod farcaster;
+mod firefly;
 mod genome;
 mod keybase;
 mod knn3;
 mod lensv2;
+mod opensea;
 mod proof_client;
+mod rate_limit;
+mod registry;
+mod report;
 mod rss3;
 mod solana;
 mod space_id;
 mod sybil_list;
 mod unstoppable;
-// mod firefly;
-// mod opensea;
 
 #[cfg(test)]
 mod tests;
@@ -24,32 +29,43 @@ mod the_graph;
 mod types;
 
 use crate::{
+    config::C,
     error::Error,
-    tigergraph::{batch_upsert, batch_upsert_domains, upsert_domain_collection, EdgeList},
-    upstream::{
-        clusters::Clusters, crossbell::Crossbell, dotbit::DotBit, ens_reverse::ENSReverseLookup,
-        farcaster::Farcaster, genome::Genome, keybase::Keybase, knn3::Knn3, lensv2::LensV2,
-        proof_client::ProofClient, rss3::Rss3, solana::Solana, space_id::v3::SpaceIdV3,
-        space_id::SpaceId, sybil_list::SybilList, the_graph::TheGraph,
-        unstoppable::UnstoppableDomains,
-    },
-    util::{hashset_append, make_http_client},
+    jobs::{queue, JobKind},
+    lease::{leases, LeaseGuard, LeaseState},
+    metrics::{fetching, observe_fetch_all},
+    storage::store,
+    tigergraph::{vertex::Identity, EdgeList},
+    upstream::rate_limit::limiter,
+    util::hashset_append,
 };
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use futures::{future::join_all, StreamExt};
-use std::{collections::HashSet, sync::Arc};
-use tokio::sync::Mutex;
+use std::{collections::HashSet, time::Duration};
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::{event, info, warn, Level};
 
+pub use circuit_breaker::{breakers, BreakerState, UpstreamStatus};
+pub use registry::{registry, DynDomainSearch, DynFetcher, Upstream, UpstreamRegistry};
+pub use report::{record, reported, FetchOutcome, TargetReport};
 pub(crate) use types::vec_string_to_vec_datasource;
+pub use types::{trim_name, Platform, Target};
 pub(crate) use types::{
-    trim_name, Chain, ContractCategory, DataFetcher, DataSource, DomainNameSystem, DomainStatus,
-    Platform, ProofLevel, Target, TargetProcessedList, EXT, EXTENSION,
+    Chain, ContractCategory, DataFetcher, DataSource, DomainNameSystem, DomainStatus, ProofLevel,
+    TargetProcessedList, EXT, EXTENSION,
 };
 
-lazy_static! {
-    /// Global processing queue to prevent duplicated query. i.e. multiple same request from frontend.
-    pub static ref FETCHING: Arc<Mutex<HashSet<Target>>> = Arc::new(Mutex::new(HashSet::new()));
+/// Pages of followees fetched for one identity by follow fetchers.
+pub(crate) const FOLLOW_PAGES: usize = 5;
+
+/// `identity` as met in a follow list. It has not been fetched itself,
+/// so it stays outdated until someone asks for it.
+pub(crate) fn as_followee(identity: Identity) -> Identity {
+    Identity {
+        updated_at: NaiveDateTime::default(),
+        ..identity
+    }
 }
 
 /// Fetcher defines how to fetch data from upstream.
@@ -79,26 +95,58 @@ pub trait DomainSearch {
 // #[tracing::instrument(name = "fetch_all", level = "trace")]
 #[async_recursion::async_recursion]
 pub async fn fetch_all(targets: TargetProcessedList, depth: Option<u16>) -> Result<(), Error> {
+    fetch_all_with_progress(targets, depth, None).await
+}
+
+/// Edges found by one `fetch_all` round. They are upserted after all rounds end.
+#[derive(Clone)]
+pub struct FetchRound {
+    pub round: u16,
+    pub edges: EdgeList,
+}
+
+/// `fetch_all`, sending what each round found to `progress`.
+/// A closed `progress` does not stop the fetch.
+pub async fn fetch_all_with_progress(
+    targets: TargetProcessedList,
+    depth: Option<u16>,
+    progress: Option<UnboundedSender<FetchRound>>,
+) -> Result<(), Error> {
     let mut round: u16 = 0;
     let mut up_next: HashSet<Target> = HashSet::new();
     let mut all_edges: EdgeList = EdgeList::new();
 
-    let mut fetching = FETCHING.lock().await;
-    up_next.extend(
-        targets
-            .clone()
-            .into_iter()
-            .filter(|target| !fetching.contains(target)),
-    );
-    up_next.iter().for_each(|target| {
-        fetching.insert(target.clone());
-    });
-    drop(fetching);
+    // Skip targets being fetched by other tasks or replicas.
+    let ttl = Duration::from_secs(C.lease.ttl_seconds);
+    let mut leased = LeaseGuard::new(leases());
+    for target in targets.iter() {
+        match leased.acquire(&target.to_string(), ttl).await? {
+            LeaseState::Acquired => {
+                up_next.insert(target.clone());
+            }
+            LeaseState::Held(lease) => event!(
+                Level::INFO,
+                %target,
+                holder = lease.holder,
+                expires_at = %lease.expires_at,
+                "Target is being fetched. Skipped."
+            ),
+        }
+    }
+    let _fetching = fetching(up_next.len());
+    // Expand jobs (`depth: None`) continue from targets met along the way, which are not roots.
+    if depth.is_some() {
+        defer_root_only(&up_next).await?;
+    }
 
     let mut processed: HashSet<Target> = HashSet::new();
 
     while !up_next.is_empty() {
         round += 1;
+        // A round may take a while; keep others out until the edges are saved.
+        if round > 1 {
+            leased.renew(ttl).await;
+        }
         let (next_targets, edges) = fetch_many(
             up_next
                 .clone()
@@ -112,27 +160,34 @@ pub async fn fetch_all(targets: TargetProcessedList, depth: Option<u16>) -> Resu
         hashset_append(&mut processed, up_next.into_iter().collect());
         up_next = HashSet::from_iter(next_targets.into_iter());
 
+        if let Some(progress) = &progress {
+            let _ = progress.send(FetchRound {
+                round,
+                edges: edges.clone(),
+            });
+        }
         all_edges.extend(edges);
 
         if depth.is_some() && depth.unwrap() <= round {
-            // Fork as background job to continue fetching.
-            tokio::spawn(fetch_all(up_next.into_iter().collect(), None));
+            // Continue fetching in background job queue.
+            let queue = queue()?;
+            for target in up_next.into_iter() {
+                queue
+                    .enqueue(JobKind::Expand(target), Duration::ZERO)
+                    .await?;
+            }
             break;
         }
     }
 
-    let mut fetching = FETCHING.lock().await;
-    targets.iter().for_each(|target| {
-        fetching.remove(&target);
-    });
-    drop(fetching);
-
     // Upsert all edges after fetching completes
     if !all_edges.is_empty() {
-        let cli = make_http_client();
-        batch_upsert(&cli, all_edges).await?;
+        leased.renew(ttl).await;
+        store().batch_upsert(all_edges).await?;
     }
+    leased.release().await;
 
+    observe_fetch_all(round, processed.len());
     event!(
         Level::INFO,
         round,
@@ -200,18 +255,18 @@ pub async fn fetch_all(targets: TargetProcessedList, depth: Option<u16>) -> Resu
     // Ok(())
 }
 
-/// Fetch targets in parallel of 5.
+/// Fetch targets in parallel of `[rate_limit] concurrent_targets`.
 /// `round` is only for log purpose.
 pub async fn fetch_many(
     targets: Vec<Target>,
     round: Option<u16>,
 ) -> Result<(TargetProcessedList, EdgeList), Error> {
-    const CONCURRENT: usize = 5;
     let futures: Vec<_> = targets
         .iter()
         .map(|target| batch_fetch_upstream(target))
         .collect();
-    let futures_stream = futures::stream::iter(futures).buffer_unordered(CONCURRENT);
+    let futures_stream =
+        futures::stream::iter(futures).buffer_unordered(C.rate_limit.concurrent_targets.max(1));
     let (mut all_targets, all_edges) = futures_stream
         .fold(
             (TargetProcessedList::new(), EdgeList::new()),
@@ -274,24 +329,11 @@ pub async fn fetch_many(
 /// Find one (platform, identity) pair in all upstreams.
 /// Returns amount of identities just fetched for next iter.
 pub async fn fetch_one(target: &Target) -> Result<Vec<Target>, Error> {
-    let mut up_next: TargetProcessedList = join_all(vec![
-        TheGraph::fetch(target),
-        ENSReverseLookup::fetch(target),
-        Farcaster::fetch(target),
-        LensV2::fetch(target),
-        ProofClient::fetch(target),
-        Keybase::fetch(target),
-        SybilList::fetch(target),
-        Rss3::fetch(target),
-        Knn3::fetch(target),
-        DotBit::fetch(target),
-        UnstoppableDomains::fetch(target),
-        SpaceId::fetch(target),
-        Genome::fetch(target),
-        Crossbell::fetch(target),
-        Solana::fetch(target),
-        Clusters::fetch(target),
-    ])
+    let mut up_next: TargetProcessedList = join_all(
+        registry()
+            .crawlers()
+            .map(|(upstream, fetcher)| upstream.guard(fetcher.fetch(target))),
+    )
     .await
     .into_iter()
     .flat_map(|res| {
@@ -321,99 +363,161 @@ pub async fn fetch_one(target: &Target) -> Result<Vec<Target>, Error> {
     Ok(up_next)
 }
 
+fn not_zero_address(target: &Target) -> bool {
+    match target {
+        Target::Identity(Platform::Ethereum, address) => {
+            // Filter zero address (without last 4 digits)
+            !address.starts_with("0x000000000000000000000000000000000000")
+        }
+        Target::Identity(_, _) => true,
+        Target::NFT(_, _, _, _) => true,
+    }
+}
+
+/// Ask root-only upstreams (e.g. follows) about `roots` in background.
+async fn defer_root_only(roots: &HashSet<Target>) -> Result<(), Error> {
+    let queue = queue()?;
+    for (upstream, fetcher) in registry().fetchers().filter(|(u, _)| u.root_only) {
+        for target in roots.iter().filter(|target| fetcher.can_fetch(target)) {
+            queue
+                .enqueue(
+                    JobKind::FetchUpstream {
+                        upstream: upstream.name.to_string(),
+                        target: target.clone(),
+                    },
+                    Duration::ZERO,
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn batch_fetch_upstream(
     target: &Target,
 ) -> Result<(TargetProcessedList, EdgeList), Error> {
     let mut up_next = TargetProcessedList::new();
     let mut all_edges = EdgeList::new();
-
-    let _ = join_all(vec![
-        TheGraph::batch_fetch(target),
-        ENSReverseLookup::batch_fetch(target),
-        Farcaster::batch_fetch(target),
-        LensV2::batch_fetch(target),
-        ProofClient::batch_fetch(target),
-        Keybase::batch_fetch(target),
-        Rss3::batch_fetch(target),
-        DotBit::batch_fetch(target),
-        UnstoppableDomains::batch_fetch(target),
-        SpaceId::batch_fetch(target),
-        Genome::batch_fetch(target),
-        Crossbell::batch_fetch(target),
-        Solana::batch_fetch(target),
-        Clusters::batch_fetch(target),
-        // SybilList::batch_fetch(target), // move this logic to `data_process` as a scheduled asynchronous fetch
-        // Knn3::batch_fetch(target), // Temporarily cancel
-        // Firefly::batch_fetch(target), // Temporarily cancel
-        // OpenSea::batch_fetch(target), // Temporarily cancel
-    ])
-    .await
-    .into_iter()
-    .for_each(|res| {
-        if let Ok((next_targets, edges)) = res {
-            up_next.extend(next_targets);
-            all_edges.extend(edges);
-        } else if let Err(err) = res {
-            warn!(
-                "Error happened when fetching and saving {}: {}",
-                target, err
-            );
-            // Don't break the procedure, continue with other results
-        }
-    });
-
-    up_next.dedup();
-    // Filter zero address
-    up_next = up_next
+    let mut deferred: Vec<&'static str> = vec![];
+
+    let (upstreams, calls): (Vec<_>, Vec<_>) = registry()
+        .crawlers()
+        .map(|(upstream, fetcher)| (upstream, upstream.guard(fetcher.batch_fetch(target))))
+        .unzip();
+    join_all(calls)
+        .await
         .into_iter()
-        .filter(|target| match target {
-            Target::Identity(Platform::Ethereum, address) => {
-                // Filter zero address (without last 4 digits)
-                return !address.starts_with("0x000000000000000000000000000000000000");
+        .zip(upstreams)
+        .for_each(|(res, upstream)| {
+            record(target, upstream, FetchOutcome::of(&res));
+            match res {
+                Ok((next_targets, edges)) => {
+                    up_next.extend(next_targets);
+                    all_edges.extend(edges);
+                }
+                Err(Error::RateLimited(_)) => {
+                    deferred.push(upstream.name);
+                }
+                Err(Error::CircuitOpen(_)) => {
+                    event!(Level::DEBUG, %target, upstream = upstream.name, "Circuit open. Skipped.");
+                }
+                Err(err) => {
+                    warn!(
+                        "Error happened when fetching and saving {}: {}",
+                        target, err
+                    );
+                    // Don't break the procedure, continue with other results
+                }
             }
-            Target::Identity(_, _) => true,
-            Target::NFT(_, _, _, _) => true,
-        })
-        .collect();
+        });
+
+    // Throttled upstreams are asked again in background instead of failing this round.
+    for upstream in deferred.into_iter() {
+        info!(%target, upstream, "Rate limited. Deferred to background job.");
+        queue()?
+            .enqueue(
+                JobKind::FetchUpstream {
+                    upstream: upstream.to_string(),
+                    target: target.clone(),
+                },
+                Duration::from_secs(C.rate_limit.defer_seconds),
+            )
+            .await?;
+    }
+
+    up_next.dedup();
+    up_next.retain(not_zero_address);
 
     // event!(Level::INFO, "fetch_one_and_save up_next {:?}", up_next);
     Ok((up_next, all_edges))
 }
 
+/// Fetch `target` from the upstream registered as `name` only, save the result,
+/// and continue with found targets in background.
+pub async fn fetch_upstream(name: &str, target: &Target) -> Result<(), Error> {
+    let (upstream, fetcher) = match registry().fetchers().find(|(u, _)| u.name == name) {
+        Some(found) => found,
+        None => {
+            info!(%target, upstream = name, "Upstream is disabled or unknown. Skipped.");
+            return Ok(());
+        }
+    };
+    let res = upstream.guard(fetcher.batch_fetch(target)).await;
+    record(target, upstream, FetchOutcome::of(&res));
+    let (mut up_next, edges) = res?;
+
+    if !edges.is_empty() {
+        store().batch_upsert(edges).await?;
+    }
+    up_next.dedup();
+    let queue = queue()?;
+    for next in up_next.into_iter().filter(not_zero_address) {
+        queue.enqueue(JobKind::Expand(next), Duration::ZERO).await?;
+    }
+    Ok(())
+}
+
 pub async fn fetch_domains(name: &str) -> Result<(), Error> {
-    let all_edges: EdgeList = join_all(vec![
-        TheGraph::domain_search(name),           // ens
-        Farcaster::domain_search(name),          // farcaster
-        LensV2::domain_search(name),             // lens
-        DotBit::domain_search(name),             // dotbit
-        UnstoppableDomains::domain_search(name), // unstoppabledomains
-        Genome::domain_search(name),             // gnosis
-        Crossbell::domain_search(name),          // crossbell
-        Solana::domain_search(name),             // sns
-        Clusters::domain_search(name),           // clusters
-        SpaceIdV3::domain_search(name),          // space_id
-    ])
-    .await
-    .into_iter()
-    .flat_map(|res| {
-        match res {
-            Ok(edges) => edges,
-            Err(err) => {
-                warn!("Error happened when fetching name({}): {}", name, err);
-                vec![] // Don't break the procedure
+    let mut incomplete = false;
+    let (upstreams, calls): (Vec<_>, Vec<_>) = registry()
+        .domain_searches()
+        .map(|(upstream, search)| (upstream, upstream.guard(search.domain_search(name))))
+        .unzip();
+    let target = format!("Domain/{}", name);
+    let all_edges: EdgeList = join_all(calls)
+        .await
+        .into_iter()
+        .zip(upstreams)
+        .flat_map(|(res, upstream)| {
+            record(&target, upstream, FetchOutcome::of(&res));
+            match res {
+                Ok(edges) => edges,
+                Err(err) => {
+                    incomplete |= matches!(err, Error::RateLimited(_) | Error::CircuitOpen(_));
+                    warn!("Error happened when fetching name({}): {}", name, err);
+                    vec![] // Don't break the procedure
+                }
             }
-        }
-    })
-    .collect();
+        })
+        .collect();
 
     // Upsert all edges after fetching completes
-    let gsql_cli = make_http_client();
     if !all_edges.is_empty() {
-        batch_upsert_domains(&gsql_cli, all_edges).await?;
-    } else {
+        store().batch_upsert_domains(all_edges).await?;
+    } else if !incomplete {
         // this name is available in all domain system
         // Record this information as a cache
-        upsert_domain_collection(&gsql_cli, name).await?;
+        store().upsert_domain_collection(name).await?;
+    }
+
+    if incomplete {
+        // Some upstreams were skipped. Search again in background later.
+        queue()?
+            .enqueue(
+                JobKind::RefetchDomains(name.to_string()),
+                Duration::from_secs(C.rate_limit.defer_seconds),
+            )
+            .await?;
     }
 
     event!(Level::INFO, "DomainSearch completed.");
@@ -6,9 +6,7 @@ use crate::tigergraph::edge::{HyperEdge, Proof, Wrapper};
 use crate::tigergraph::edge::{HYPER_EDGE, PROOF_EDGE, PROOF_REVERSE_EDGE};
 use crate::tigergraph::vertex::{IdentitiesGraph, Identity};
 use crate::tigergraph::{EdgeList, EdgeWrapperEnum};
-use crate::upstream::{
-    DataFetcher, DataSource, Fetcher, Platform, ProofLevel, TargetProcessedList,
-};
+use crate::upstream::{DataFetcher, DataSource, Fetcher, Platform, TargetProcessedList};
 use crate::util::{make_client, naive_now, parse_body, request_with_timeout};
 
 use async_trait::async_trait;
@@ -145,7 +143,7 @@ async fn batch_fetch_connections(
         let proof_forward = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::OpenSea,
-            level: ProofLevel::Confident,
+            level: DataSource::OpenSea.proof_level(),
             record_id: None,
             created_at: None,
             updated_at: naive_now(),
@@ -155,7 +153,7 @@ async fn batch_fetch_connections(
         let proof_backward = Proof {
             uuid: Uuid::new_v4(),
             source: DataSource::OpenSea,
-            level: ProofLevel::Confident,
+            level: DataSource::OpenSea.proof_level(),
             record_id: None,
             created_at: None,
             updated_at: naive_now(),
@@ -180,7 +178,7 @@ async fn search_opensea_account(
     let uri: http::Uri = format!(
         "{}/aggregation/opensea_account?platform={}&identity={}",
         C.upstream.aggregation_service.url.clone(),
-        platform.to_string(),
+        platform,
         identity
     )
     .parse()
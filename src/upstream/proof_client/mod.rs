@@ -10,7 +10,7 @@ use crate::tigergraph::edge::{
 use crate::tigergraph::upsert::create_identity_to_identity_proof_two_way_binding;
 use crate::tigergraph::vertex::{IdentitiesGraph, Identity};
 use crate::tigergraph::{EdgeList, EdgeWrapperEnum};
-use crate::upstream::{DataSource, Fetcher, Platform, ProofLevel, Target, TargetProcessedList};
+use crate::upstream::{DataSource, Fetcher, Platform, Target, TargetProcessedList};
 use crate::util::make_http_client;
 use crate::util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive};
 
@@ -214,7 +214,7 @@ async fn batch_fetch_connections(
             let proof_forward: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::NextID,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::NextID.proof_level(),
                 record_id: None,
                 created_at: timestamp_to_naive(p.created_at.to_string().parse().unwrap(), 0),
                 updated_at: naive_now(),
@@ -224,7 +224,7 @@ async fn batch_fetch_connections(
             let proof_backward: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::NextID,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::NextID.proof_level(),
                 record_id: None,
                 created_at: timestamp_to_naive(p.created_at.to_string().parse().unwrap(), 0),
                 updated_at: naive_now(),
@@ -365,7 +365,7 @@ async fn fetch_connections_by_platform_identity(
             let pf: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::NextID,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::NextID.proof_level(),
                 record_id: None,
                 created_at: timestamp_to_naive(p.created_at.to_string().parse().unwrap(), 0),
                 updated_at: naive_now(),
@@ -375,7 +375,7 @@ async fn fetch_connections_by_platform_identity(
             let pb: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::NextID,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::NextID.proof_level(),
                 record_id: None,
                 created_at: timestamp_to_naive(p.created_at.to_string().parse().unwrap(), 0),
                 updated_at: naive_now(),
@@ -1,42 +1,39 @@
 use crate::upstream::Target;
 use crate::{error::Error, upstream::proof_client::ProofClient, upstream::Fetcher};
 use crate::{
-    tigergraph::vertex::Identity,
+    tests::{assert_just_updated, assert_upstream_snapshot, save_and_find},
     upstream::Platform,
-    util::{make_http_client, naive_now},
 };
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_smoke() -> Result<(), Error> {
     let target = Target::Identity(
         Platform::Ethereum,
         "0x2467ee73bb0c5acdeedf4e6cc5aa685741126872".into(),
     );
-    ProofClient::fetch(&target).await?;
+    let fetched = ProofClient::batch_fetch(&target).await?;
+    assert_upstream_snapshot!(fetched);
 
-    let client = make_http_client();
-    let found =
-        Identity::find_by_platform_identity(&client, &target.platform()?, &target.identity()?)
-            .await?
-            .expect("Record not found");
-
-    assert_eq!(
-        found.updated_at.and_utc().timestamp(),
-        naive_now().and_utc().timestamp()
-    );
+    let found = save_and_find(fetched.1, &target.platform()?, &target.identity()?)
+        .await?
+        .expect("Record not found");
+    assert_just_updated(&found);
 
     Ok(())
 }
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_multiple_avatars() -> Result<(), Error> {
     let target = Target::Identity(
         Platform::Ethereum,
         "0x1cb1fa7d604e06cd8c596b5b7bcaaf5c5fdefd53".into(),
     );
-    ProofClient::fetch(&target).await?;
-    let client = make_http_client();
-    let found = Identity::find_by_platform_identity(&client, &Platform::Twitter, "lyria_shan0127")
+    let fetched = ProofClient::batch_fetch(&target).await?;
+    assert_upstream_snapshot!(fetched);
+
+    let found = save_and_find(fetched.1, &Platform::Twitter, "lyria_shan0127")
         .await?
         .expect("Record not found");
     assert_eq!(found.identity, "lyria_shan0127");
@@ -0,0 +1,115 @@
+mod tests;
+
+use crate::{
+    config::{ConfigSourceLimit, C},
+    upstream::DataSource,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{sleep, timeout_at, Duration, Instant};
+
+lazy_static! {
+    /// Limiters built from `[rate_limit.sources]`.
+    static ref LIMITERS: HashMap<DataSource, Limiter> = C
+        .rate_limit
+        .sources
+        .iter()
+        .map(|(source, limit)| (*source, Limiter::new(limit)))
+        .collect();
+}
+
+/// Limiter of `source`, if configured.
+pub fn limiter(source: DataSource) -> Option<&'static Limiter> {
+    LIMITERS.get(&source)
+}
+
+/// Classic token bucket: `capacity` tokens at most, refilled by `rate` per second.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, capacity: f64) -> Self {
+        Self {
+            capacity,
+            rate,
+            tokens: capacity,
+            updated_at: Instant::now(),
+        }
+    }
+
+    /// Take one token, or tell how long until the next one.
+    pub fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// Rate and in-flight budget of one upstream.
+#[derive(Debug)]
+pub struct Limiter {
+    bucket: Option<Mutex<TokenBucket>>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+/// Held while an upstream call is running.
+pub struct Permit {
+    _in_flight: Option<OwnedSemaphorePermit>,
+}
+
+impl Limiter {
+    pub fn new(limit: &ConfigSourceLimit) -> Self {
+        let bucket = limit
+            .rate_per_second
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| {
+                let burst = limit.burst.map(f64::from).unwrap_or(rate).max(1.0);
+                Mutex::new(TokenBucket::new(rate, burst))
+            });
+        let in_flight = limit
+            .max_in_flight
+            .map(|max| Arc::new(Semaphore::new(max.max(1))));
+        Self { bucket, in_flight }
+    }
+
+    /// Wait for an in-flight slot and a token.
+    /// Returns `None` if it takes longer than `max_wait`.
+    pub async fn acquire(&self, max_wait: Duration) -> Option<Permit> {
+        let deadline = Instant::now() + max_wait;
+        let in_flight = match &self.in_flight {
+            None => None,
+            Some(semaphore) => Some(
+                timeout_at(deadline, semaphore.clone().acquire_owned())
+                    .await
+                    .ok()?
+                    .ok()?,
+            ),
+        };
+        if let Some(bucket) = &self.bucket {
+            loop {
+                let now = Instant::now();
+                let taken = bucket.lock().unwrap_or_else(|e| e.into_inner()).take(now);
+                match taken {
+                    Ok(()) => break,
+                    Err(wait) if now + wait > deadline => return None,
+                    Err(wait) => sleep(wait).await,
+                }
+            }
+        }
+        Some(Permit {
+            _in_flight: in_flight,
+        })
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{ConfigRateLimit, ConfigSourceLimit},
+        upstream::{
+            rate_limit::{Limiter, TokenBucket},
+            DataSource,
+        },
+    };
+    use tokio::time::{Duration, Instant};
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 2.0);
+        assert!(bucket.take(start).is_ok());
+        assert!(bucket.take(start).is_ok());
+        let wait = bucket.take(start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert!(bucket.take(start + Duration::from_millis(500)).is_ok());
+        // Never refills over capacity.
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.take(later).is_ok());
+        assert!(bucket.take(later).is_ok());
+        assert!(bucket.take(later).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_limiter_max_in_flight() {
+        let limiter = Limiter::new(&ConfigSourceLimit {
+            max_in_flight: Some(1),
+            ..Default::default()
+        });
+        let permit = limiter.acquire(Duration::ZERO).await;
+        assert!(permit.is_some());
+        assert!(limiter.acquire(Duration::from_millis(20)).await.is_none());
+        drop(permit);
+        assert!(limiter.acquire(Duration::ZERO).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_limiter_rate() {
+        let limiter = Limiter::new(&ConfigSourceLimit {
+            rate_per_second: Some(20.0),
+            burst: Some(1),
+            ..Default::default()
+        });
+        assert!(limiter.acquire(Duration::ZERO).await.is_some());
+        // Next token comes in 50ms.
+        assert!(limiter.acquire(Duration::from_millis(10)).await.is_none());
+        assert!(limiter.acquire(Duration::from_millis(200)).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_by_default() {
+        let limiter = Limiter::new(&ConfigSourceLimit::default());
+        for _ in 0..100 {
+            assert!(limiter.acquire(Duration::ZERO).await.is_some());
+        }
+    }
+
+    #[test]
+    fn test_parse_sources() {
+        let toml = r#"
+            concurrent_targets = 3
+            [sources.farcaster]
+            rate_per_second = 5.0
+            [sources.the_graph]
+            max_in_flight = 2
+        "#;
+        let parsed: ConfigRateLimit = config::Config::builder()
+            .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(parsed.concurrent_targets, 3);
+        assert_eq!(parsed.max_wait_seconds, 5);
+        assert_eq!(
+            parsed.sources[&DataSource::Farcaster].rate_per_second,
+            Some(5.0)
+        );
+        assert_eq!(parsed.sources[&DataSource::TheGraph].max_in_flight, Some(2));
+    }
+}
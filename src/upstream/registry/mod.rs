@@ -0,0 +1,310 @@
+mod tests;
+
+use crate::{
+    config::{ConfigUpstreamRegistry, C},
+    error::Error,
+    metrics::observe_upstream,
+    tigergraph::EdgeList,
+    upstream::{
+        breakers, circuit_breaker::is_upstream_fault, clusters::Clusters,
+        crossbell::link::CrossbellLink, crossbell::Crossbell, dotbit::DotBit,
+        ens_reverse::ENSReverseLookup, evm_transfer::EvmTransfer,
+        farcaster::follow::FarcasterFollow, farcaster::Farcaster, firefly::Firefly, genome::Genome,
+        keybase::Keybase, knn3::Knn3, lensv2::follow::LensV2Follow, lensv2::LensV2, limiter,
+        opensea::OpenSea, proof_client::ProofClient, rss3::Rss3, solana::Solana,
+        space_id::v3::SpaceIdV3, space_id::SpaceId, sybil_list::SybilList, the_graph::TheGraph,
+        unstoppable::UnstoppableDomains, DataSource, DomainSearch, Fetcher, Target,
+        TargetProcessedList,
+    },
+};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use http::StatusCode;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Timeout of upstreams not configured otherwise.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    /// All built-in upstreams, adjusted by `[upstream_registry]`.
+    static ref REGISTRY: UpstreamRegistry = UpstreamRegistry::builtin(&C.upstream_registry);
+}
+
+/// Get the global upstream registry.
+pub fn registry() -> &'static UpstreamRegistry {
+    &REGISTRY
+}
+
+/// Object-safe `Fetcher`.
+#[async_trait]
+pub trait DynFetcher: Send + Sync {
+    async fn fetch(&self, target: &Target) -> Result<TargetProcessedList, Error>;
+
+    async fn batch_fetch(&self, target: &Target) -> Result<(TargetProcessedList, EdgeList), Error>;
+
+    fn can_fetch(&self, target: &Target) -> bool;
+}
+
+/// Object-safe `DomainSearch`.
+#[async_trait]
+pub trait DynDomainSearch: Send + Sync {
+    async fn domain_search(&self, name: &str) -> Result<EdgeList, Error>;
+}
+
+/// Trait object calling the associated functions of `U`.
+struct Static<U>(PhantomData<fn() -> U>);
+
+#[async_trait]
+impl<U: Fetcher + 'static> DynFetcher for Static<U> {
+    async fn fetch(&self, target: &Target) -> Result<TargetProcessedList, Error> {
+        U::fetch(target).await
+    }
+
+    async fn batch_fetch(&self, target: &Target) -> Result<(TargetProcessedList, EdgeList), Error> {
+        U::batch_fetch(target).await
+    }
+
+    fn can_fetch(&self, target: &Target) -> bool {
+        U::can_fetch(target)
+    }
+}
+
+#[async_trait]
+impl<U: DomainSearch + 'static> DynDomainSearch for Static<U> {
+    async fn domain_search(&self, name: &str) -> Result<EdgeList, Error> {
+        U::domain_search(name).await
+    }
+}
+
+/// `Fetcher` implementation `U` as a trait object.
+pub fn fetcher<U: Fetcher + 'static>() -> Box<dyn DynFetcher> {
+    Box::new(Static::<U>(PhantomData))
+}
+
+/// `DomainSearch` implementation `U` as a trait object.
+pub fn domain_search<U: DomainSearch + 'static>() -> Box<dyn DynDomainSearch> {
+    Box::new(Static::<U>(PhantomData))
+}
+
+/// A registered upstream.
+pub struct Upstream {
+    /// Name used in config, logs and circuit breaker.
+    pub name: &'static str,
+    /// `DataSource` it is rate limited as.
+    pub source: DataSource,
+    pub fetcher: Option<Box<dyn DynFetcher>>,
+    pub domain_search: Option<Box<dyn DynDomainSearch>>,
+    /// Disabled upstreams are never called.
+    pub enabled: bool,
+    /// Upstreams are called and their results merged in descending priority.
+    pub priority: i32,
+    /// A call is given up after this long.
+    pub timeout: Duration,
+    /// Only asked about the target a fetch starts from, in a background job,
+    /// instead of every target met while crawling.
+    pub root_only: bool,
+}
+
+impl Upstream {
+    pub fn new(name: &'static str, source: DataSource) -> Self {
+        Self {
+            name,
+            source,
+            fetcher: None,
+            domain_search: None,
+            enabled: true,
+            priority: 0,
+            timeout: DEFAULT_TIMEOUT,
+            root_only: false,
+        }
+    }
+
+    pub fn fetcher(mut self, fetcher: Box<dyn DynFetcher>) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    pub fn domain_search(mut self, domain_search: Box<dyn DynDomainSearch>) -> Self {
+        self.domain_search = Some(domain_search);
+        self
+    }
+
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    pub fn root_only(mut self) -> Self {
+        self.root_only = true;
+        self
+    }
+
+    /// Run `call` unless the circuit breaker is open, once `source` is allowed by limiter.
+    /// Gives `Error::CircuitOpen` or `Error::RateLimited` (after `[rate_limit] max_wait_seconds`)
+    /// without calling the upstream.
+    pub async fn guard<T>(&self, call: BoxFuture<'_, Result<T, Error>>) -> Result<T, Error> {
+        // Limiter goes first: a half-open probe handed out by `allow` must be
+        // followed by `success` or `failure`, or the breaker stays half-open.
+        let _permit = match limiter(self.source) {
+            None => None,
+            Some(limiter) => Some(
+                limiter
+                    .acquire(Duration::from_secs(C.rate_limit.max_wait_seconds))
+                    .await
+                    .ok_or_else(|| Error::RateLimited(self.source.to_string()))?,
+            ),
+        };
+        if !breakers().allow(self.name) {
+            return Err(Error::CircuitOpen(self.name.to_string()));
+        }
+        let started = Instant::now();
+        let result = match tokio::time::timeout(self.timeout, call).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::General(
+                format!("{} timed out after {:?}", self.name, self.timeout),
+                StatusCode::GATEWAY_TIMEOUT,
+            )),
+        };
+        let fault = matches!(&result, Err(err) if is_upstream_fault(err));
+        observe_upstream(self.source, started.elapsed(), fault);
+        match &result {
+            Err(err) if fault => breakers().failure(self.name, &err.to_string()),
+            _ => breakers().success(self.name),
+        }
+        result
+    }
+}
+
+/// Every upstream RelationService knows, ordered by priority.
+pub struct UpstreamRegistry {
+    upstreams: Vec<Upstream>,
+}
+
+impl UpstreamRegistry {
+    /// Apply `[upstream_registry]` onto `upstreams`.
+    pub fn new(mut upstreams: Vec<Upstream>, config: &ConfigUpstreamRegistry) -> Self {
+        if let Some(timeout) = config.timeout_seconds {
+            upstreams
+                .iter_mut()
+                .for_each(|u| u.timeout = Duration::from_secs(timeout));
+        }
+        for (name, entry) in config.upstreams.iter() {
+            let upstream = match upstreams.iter_mut().find(|u| u.name == name) {
+                Some(upstream) => upstream,
+                None => {
+                    warn!(name, "Unknown upstream in config. Ignored.");
+                    continue;
+                }
+            };
+            if let Some(enabled) = entry.enabled {
+                upstream.enabled = enabled;
+            }
+            if let Some(priority) = entry.priority {
+                upstream.priority = priority;
+            }
+            if let Some(timeout) = entry.timeout_seconds {
+                upstream.timeout = Duration::from_secs(timeout);
+            }
+        }
+        // Stable: same priority keeps registration order.
+        upstreams.sort_by_key(|u| std::cmp::Reverse(u.priority));
+        Self { upstreams }
+    }
+
+    /// Upstreams shipped with RelationService.
+    pub fn builtin(config: &ConfigUpstreamRegistry) -> Self {
+        Self::new(
+            vec![
+                Upstream::new("the_graph", DataSource::TheGraph)
+                    .fetcher(fetcher::<TheGraph>())
+                    .domain_search(domain_search::<TheGraph>()),
+                Upstream::new("ens_reverse", DataSource::TheGraph)
+                    .fetcher(fetcher::<ENSReverseLookup>()),
+                Upstream::new("farcaster", DataSource::Farcaster)
+                    .fetcher(fetcher::<Farcaster>())
+                    .domain_search(domain_search::<Farcaster>()),
+                Upstream::new("lens", DataSource::Lens)
+                    .fetcher(fetcher::<LensV2>())
+                    .domain_search(domain_search::<LensV2>()),
+                Upstream::new("proof_client", DataSource::NextID).fetcher(fetcher::<ProofClient>()),
+                Upstream::new("keybase", DataSource::Keybase).fetcher(fetcher::<Keybase>()),
+                Upstream::new("rss3", DataSource::Rss3).fetcher(fetcher::<Rss3>()),
+                Upstream::new("dotbit", DataSource::Dotbit)
+                    .fetcher(fetcher::<DotBit>())
+                    .domain_search(domain_search::<DotBit>()),
+                Upstream::new("unstoppable", DataSource::UnstoppableDomains)
+                    .fetcher(fetcher::<UnstoppableDomains>())
+                    .domain_search(domain_search::<UnstoppableDomains>()),
+                Upstream::new("space_id", DataSource::SpaceId)
+                    .fetcher(fetcher::<SpaceId>())
+                    .domain_search(domain_search::<SpaceIdV3>()),
+                Upstream::new("genome", DataSource::SpaceId)
+                    .fetcher(fetcher::<Genome>())
+                    .domain_search(domain_search::<Genome>()),
+                Upstream::new("crossbell", DataSource::Crossbell)
+                    .fetcher(fetcher::<Crossbell>())
+                    .domain_search(domain_search::<Crossbell>()),
+                Upstream::new("solana", DataSource::Solana)
+                    .fetcher(fetcher::<Solana>())
+                    .domain_search(domain_search::<Solana>()),
+                Upstream::new("clusters", DataSource::Clusters)
+                    .fetcher(fetcher::<Clusters>())
+                    .domain_search(domain_search::<Clusters>()),
+                // Fetches nothing unless `[upstream.evm_rpc] rpc_url` is set.
+                // Counterparties are saved without being fetched further.
+                Upstream::new("evm_transfer", DataSource::RPCServer)
+                    .fetcher(fetcher::<EvmTransfer>())
+                    .root_only(),
+                // Followees are saved without being fetched further.
+                Upstream::new("farcaster_follow", DataSource::Farcaster)
+                    .fetcher(fetcher::<FarcasterFollow>())
+                    .root_only(),
+                Upstream::new("lens_follow", DataSource::Lens)
+                    .fetcher(fetcher::<LensV2Follow>())
+                    .root_only(),
+                Upstream::new("crossbell_link", DataSource::Crossbell)
+                    .fetcher(fetcher::<CrossbellLink>())
+                    .root_only(),
+                // Fetched by `prefetch()` as a scheduled job instead.
+                Upstream::new("sybil_list", DataSource::SybilList)
+                    .fetcher(fetcher::<SybilList>())
+                    .disabled(),
+                // Temporarily cancelled.
+                Upstream::new("knn3", DataSource::Knn3)
+                    .fetcher(fetcher::<Knn3>())
+                    .disabled(),
+                Upstream::new("firefly", DataSource::Firefly)
+                    .fetcher(fetcher::<Firefly>())
+                    .disabled(),
+                Upstream::new("opensea", DataSource::OpenSea)
+                    .fetcher(fetcher::<OpenSea>())
+                    .disabled(),
+            ],
+            config,
+        )
+    }
+
+    /// Enabled upstreams, ordered by priority.
+    pub fn active(&self) -> impl Iterator<Item = &Upstream> {
+        self.upstreams.iter().filter(|u| u.enabled)
+    }
+
+    /// Enabled upstreams which implement `Fetcher`.
+    pub fn fetchers(&self) -> impl Iterator<Item = (&Upstream, &dyn DynFetcher)> {
+        self.active()
+            .filter_map(|u| u.fetcher.as_deref().map(|fetcher| (u, fetcher)))
+    }
+
+    /// Enabled upstreams which implement `Fetcher` and are asked while crawling.
+    pub fn crawlers(&self) -> impl Iterator<Item = (&Upstream, &dyn DynFetcher)> {
+        self.fetchers().filter(|(u, _)| !u.root_only)
+    }
+
+    /// Enabled upstreams which implement `DomainSearch`.
+    pub fn domain_searches(&self) -> impl Iterator<Item = (&Upstream, &dyn DynDomainSearch)> {
+        self.active()
+            .filter_map(|u| u.domain_search.as_deref().map(|search| (u, search)))
+    }
+}
@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::{ConfigUpstreamEntry, ConfigUpstreamRegistry},
+        error::Error,
+        tigergraph::EdgeList,
+        upstream::{
+            registry::{domain_search, fetcher},
+            DataSource, DomainSearch, Fetcher, Target, TargetProcessedList, Upstream,
+            UpstreamRegistry,
+        },
+    };
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    struct Dummy;
+
+    #[async_trait]
+    impl Fetcher for Dummy {
+        async fn fetch(_target: &Target) -> Result<TargetProcessedList, Error> {
+            Ok(vec![])
+        }
+
+        async fn batch_fetch(_target: &Target) -> Result<(TargetProcessedList, EdgeList), Error> {
+            Ok((vec![], vec![]))
+        }
+
+        fn can_fetch(_target: &Target) -> bool {
+            true
+        }
+    }
+
+    #[async_trait]
+    impl DomainSearch for Dummy {
+        async fn domain_search(_name: &str) -> Result<EdgeList, Error> {
+            Ok(vec![])
+        }
+    }
+
+    fn upstreams() -> Vec<Upstream> {
+        vec![
+            Upstream::new("a", DataSource::TheGraph).fetcher(fetcher::<Dummy>()),
+            Upstream::new("b", DataSource::Lens)
+                .fetcher(fetcher::<Dummy>())
+                .domain_search(domain_search::<Dummy>())
+                .root_only(),
+            Upstream::new("c", DataSource::Knn3)
+                .fetcher(fetcher::<Dummy>())
+                .disabled(),
+            Upstream::new("d", DataSource::SpaceId).domain_search(domain_search::<Dummy>()),
+        ]
+    }
+
+    fn names<'a>(upstreams: impl Iterator<Item = &'a Upstream>) -> Vec<&'static str> {
+        upstreams.map(|u| u.name).collect()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let registry = UpstreamRegistry::new(upstreams(), &ConfigUpstreamRegistry::default());
+        assert_eq!(names(registry.active()), vec!["a", "b", "d"]);
+        assert_eq!(names(registry.fetchers().map(|(u, _)| u)), vec!["a", "b"]);
+        assert_eq!(names(registry.crawlers().map(|(u, _)| u)), vec!["a"]);
+        assert_eq!(
+            names(registry.domain_searches().map(|(u, _)| u)),
+            vec!["b", "d"]
+        );
+    }
+
+    #[test]
+    fn test_config_overrides() {
+        let config = ConfigUpstreamRegistry {
+            timeout_seconds: Some(5),
+            upstreams: HashMap::from([
+                (
+                    "a".to_string(),
+                    ConfigUpstreamEntry {
+                        enabled: Some(false),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "c".to_string(),
+                    ConfigUpstreamEntry {
+                        enabled: Some(true),
+                        timeout_seconds: Some(1),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "d".to_string(),
+                    ConfigUpstreamEntry {
+                        priority: Some(10),
+                        ..Default::default()
+                    },
+                ),
+                ("unknown".to_string(), ConfigUpstreamEntry::default()),
+            ]),
+        };
+        let registry = UpstreamRegistry::new(upstreams(), &config);
+        assert_eq!(names(registry.active()), vec!["d", "b", "c"]);
+        let timeouts: Vec<Duration> = registry.active().map(|u| u.timeout).collect();
+        assert_eq!(
+            timeouts,
+            vec![
+                Duration::from_secs(5),
+                Duration::from_secs(5),
+                Duration::from_secs(1)
+            ]
+        );
+    }
+}
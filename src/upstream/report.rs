@@ -0,0 +1,90 @@
+use crate::{error::Error, upstream::Upstream};
+use http::StatusCode;
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+tokio::task_local! {
+    static REPORT: Arc<Mutex<Vec<TargetReport>>>;
+}
+
+/// What came of asking one upstream about one target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchOutcome {
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// Not called at all: circuit open, or rate limited and deferred to a background job.
+    Skipped,
+}
+
+impl FetchOutcome {
+    /// Outcome of a call made through `Upstream::guard`.
+    pub fn of<T>(result: &Result<T, Error>) -> Self {
+        match result {
+            Ok(_) => FetchOutcome::Succeeded,
+            Err(Error::CircuitOpen(_)) | Err(Error::RateLimited(_)) => FetchOutcome::Skipped,
+            Err(Error::General(_, status)) if *status == StatusCode::GATEWAY_TIMEOUT => {
+                FetchOutcome::TimedOut
+            }
+            Err(_) => FetchOutcome::Failed,
+        }
+    }
+}
+
+/// Names of the upstreams asked about `target`, by outcome.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetReport {
+    pub target: String,
+    pub succeeded: Vec<&'static str>,
+    pub failed: Vec<&'static str>,
+    pub timed_out: Vec<&'static str>,
+    pub skipped: Vec<&'static str>,
+}
+
+impl TargetReport {
+    fn add(&mut self, upstream: &'static str, outcome: FetchOutcome) {
+        let names = match outcome {
+            FetchOutcome::Succeeded => &mut self.succeeded,
+            FetchOutcome::Failed => &mut self.failed,
+            FetchOutcome::TimedOut => &mut self.timed_out,
+            FetchOutcome::Skipped => &mut self.skipped,
+        };
+        if !names.contains(&upstream) {
+            names.push(upstream);
+        }
+    }
+}
+
+/// Run `fut`, collecting what `record` is told within it, in order of targets first asked.
+/// Fetches spawned as other tasks are not included.
+pub async fn reported<F: Future>(fut: F) -> (F::Output, Vec<TargetReport>) {
+    let report = Arc::new(Mutex::new(vec![]));
+    let output = REPORT.scope(report.clone(), fut).await;
+    let report = std::mem::take(&mut *report.lock().unwrap());
+    (output, report)
+}
+
+/// Note the outcome of asking `upstream` about `target`.
+/// Does nothing outside of `reported`.
+pub fn record(target: &impl Display, upstream: &Upstream, outcome: FetchOutcome) {
+    let _ = REPORT.try_with(|report| {
+        let target = target.to_string();
+        let mut report = report.lock().unwrap();
+        let index = match report.iter().position(|r| r.target == target) {
+            Some(index) => index,
+            None => {
+                report.push(TargetReport {
+                    target,
+                    ..Default::default()
+                });
+                report.len() - 1
+            }
+        };
+        report[index].add(upstream.name, outcome);
+    });
+}
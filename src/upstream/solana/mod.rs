@@ -15,8 +15,7 @@ use crate::tigergraph::upsert::create_isolated_vertex;
 use crate::tigergraph::vertex::{Contract, DomainCollection, IdentitiesGraph, Identity};
 use crate::tigergraph::{EdgeList, EdgeWrapperEnum};
 use crate::upstream::{
-    Chain, ContractCategory, DataFetcher, DataSource, DomainNameSystem, DomainStatus, ProofLevel,
-    EXT,
+    Chain, ContractCategory, DataFetcher, DataSource, DomainNameSystem, DomainStatus, EXT,
 };
 use crate::util::{make_http_client, naive_now};
 use async_trait::async_trait;
@@ -179,7 +178,7 @@ async fn batch_fetch_by_wallet(target: &Target) -> Result<(TargetProcessedList,
             let proof_forward: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -189,7 +188,7 @@ async fn batch_fetch_by_wallet(target: &Target) -> Result<(TargetProcessedList,
             let proof_backward: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -435,7 +434,7 @@ async fn batch_fetch_by_twitter_handle(
             let proof_forward: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -445,7 +444,7 @@ async fn batch_fetch_by_twitter_handle(
             let proof_backward: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -575,7 +574,7 @@ async fn fetch_by_wallet(target: &Target) -> Result<TargetProcessedList, Error>
             let pf: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -585,7 +584,7 @@ async fn fetch_by_wallet(target: &Target) -> Result<TargetProcessedList, Error>
             let pb: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -800,7 +799,7 @@ async fn fetch_by_twitter_handle(target: &Target) -> Result<TargetProcessedList,
             let pf: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
@@ -810,7 +809,7 @@ async fn fetch_by_twitter_handle(target: &Target) -> Result<TargetProcessedList,
             let pb: Proof = Proof {
                 uuid: Uuid::new_v4(),
                 source: DataSource::SNS,
-                level: ProofLevel::VeryConfident,
+                level: DataSource::SNS.proof_level(),
                 record_id: None,
                 created_at: None,
                 updated_at: naive_now(),
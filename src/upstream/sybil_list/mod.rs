@@ -8,7 +8,7 @@ use crate::tigergraph::edge::Proof;
 use crate::tigergraph::upsert::create_identity_to_identity_proof_two_way_binding;
 use crate::tigergraph::vertex::Identity;
 use crate::tigergraph::EdgeList;
-use crate::upstream::{DataSource, Fetcher, Platform, ProofLevel, TargetProcessedList};
+use crate::upstream::{DataSource, Fetcher, Platform, TargetProcessedList};
 use crate::util::make_http_client;
 use crate::util::{make_client, naive_now, parse_body, request_with_timeout, timestamp_to_naive};
 use async_trait::async_trait;
@@ -94,7 +94,7 @@ async fn save_item(
     let pf: Proof = Proof {
         uuid: Uuid::new_v4(),
         source: DataSource::SybilList,
-        level: ProofLevel::VeryConfident,
+        level: DataSource::SybilList.proof_level(),
         record_id: Some(item.twitter.tweet_id.clone()),
         created_at: timestamp_to_naive(item.twitter.timestamp / 1000, create_ms_time),
         updated_at: naive_now(),
@@ -104,7 +104,7 @@ async fn save_item(
     let pb: Proof = Proof {
         uuid: Uuid::new_v4(),
         source: DataSource::SybilList,
-        level: ProofLevel::VeryConfident,
+        level: DataSource::SybilList.proof_level(),
         record_id: Some(item.twitter.tweet_id.clone()),
         created_at: timestamp_to_naive(item.twitter.timestamp / 1000, create_ms_time),
         updated_at: naive_now(),
@@ -9,6 +9,7 @@ use crate::{
 };
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_get_sybil_result() -> Result<(), Error> {
     // FIXME: too slow
     prefetch().await?;
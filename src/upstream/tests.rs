@@ -1,10 +1,12 @@
 use crate::error::Error;
 use crate::upstream::{
-    batch_fetch_upstream, fetch_all, fetch_domains, fetch_one, Chain, ContractCategory, Platform,
-    Target,
+    batch_fetch_upstream, fetch_all, fetch_domains, fetch_one, record, reported, Chain,
+    ContractCategory, DataSource, FetchOutcome, Platform, Target, Upstream,
 };
+use http::StatusCode;
 
 #[tokio::test]
+#[ignore = "no fixture recorded yet, run `just record`"]
 async fn test_fetch_one_result() -> Result<(), Error> {
     let result = fetch_one(&Target::Identity(Platform::Twitter, "yeiwb".into())).await?;
     assert_ne!(result.len(), 0);
@@ -131,3 +133,37 @@ async fn test_fetch_domains() -> Result<(), Error> {
     fetch_domains(name).await?;
     Ok(())
 }
+
+#[tokio::test]
+async fn test_fetch_report() {
+    let target = Target::Identity(Platform::Twitter, "report".into());
+    let keybase = Upstream::new("keybase", DataSource::Keybase);
+    let sybil = Upstream::new("sybil_list", DataSource::SybilList);
+
+    let timeout: Result<(), Error> = Err(Error::General(
+        "timed out".into(),
+        StatusCode::GATEWAY_TIMEOUT,
+    ));
+    assert_eq!(FetchOutcome::of(&timeout), FetchOutcome::TimedOut);
+    let skipped: Result<(), Error> = Err(Error::CircuitOpen("keybase".into()));
+    assert_eq!(FetchOutcome::of(&skipped), FetchOutcome::Skipped);
+    let failed: Result<(), Error> = Err(Error::NoResult);
+    assert_eq!(FetchOutcome::of(&failed), FetchOutcome::Failed);
+
+    // Not collected outside of `reported`.
+    record(&target, &keybase, FetchOutcome::Failed);
+    let ((), report) = reported(async {
+        record(&target, &keybase, FetchOutcome::Succeeded);
+        record(&target, &sybil, FetchOutcome::TimedOut);
+        record(&target, &keybase, FetchOutcome::Succeeded);
+        record(&"Domain/report", &keybase, FetchOutcome::Skipped);
+    })
+    .await;
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].target, target.to_string());
+    assert_eq!(report[0].succeeded, vec!["keybase"]);
+    assert_eq!(report[0].timed_out, vec!["sybil_list"]);
+    assert!(report[0].failed.is_empty());
+    assert_eq!(report[1].target, "Domain/report");
+    assert_eq!(report[1].skipped, vec!["keybase"]);
+}
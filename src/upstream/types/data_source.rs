@@ -1,4 +1,4 @@
-use crate::error::Error;
+use crate::{error::Error, upstream::ProofLevel};
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString};
 
@@ -12,6 +12,7 @@ use strum_macros::{Display, EnumIter, EnumString};
     EnumString,
     PartialEq,
     Eq,
+    Hash,
     EnumIter,
     Default,
     Copy,
@@ -211,6 +212,28 @@ pub enum DataSource {
     Unknown,
 }
 
+impl DataSource {
+    /// How far a `Proof` given by this source can be trusted, by how it is verified.
+    pub fn proof_level(&self) -> ProofLevel {
+        use DataSource::*;
+        match self {
+            // Signed by keys of both ends, or held on chain.
+            NextID | Keybase | Firefly | SNS | Clusters | TheGraph | RPCServer | Dotbit
+            | UnstoppableDomains | Lens | Farcaster | SpaceId | Crossbell | Solana | Basenames => {
+                ProofLevel::VeryConfident
+            }
+            // Verified by the upstream (OAuth / tweet), not by us.
+            SybilList | CyberConnect | Rss3 => ProofLevel::Confident,
+            // Only trust the team which adds them.
+            ManuallyAdded | Knn3 => ProofLevel::Neutral,
+            // Set by users without validation.
+            OpenSea | TwitterHexagon => ProofLevel::Cautious,
+            // Guessed from names or pictures.
+            EthLeaderboard | ENS | PFP | Unknown => ProofLevel::Insecure,
+        }
+    }
+}
+
 pub fn vec_string_to_vec_datasource(vec_string: Vec<String>) -> Result<Vec<DataSource>, Error> {
     let datasource_result: Result<Vec<DataSource>, _> = vec_string
         .into_iter()
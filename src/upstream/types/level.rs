@@ -10,6 +10,8 @@ use strum_macros::{Display, EnumIter, EnumString};
     EnumString,
     PartialEq,
     Eq,
+    PartialOrd,
+    Ord,
     EnumIter,
     async_graphql::Enum,
     Default,
@@ -1,4 +1,5 @@
 use http::StatusCode;
+use serde::{Deserialize, Serialize};
 
 use crate::error::Error;
 
@@ -10,7 +11,7 @@ use super::platform::Platform;
 pub type TargetProcessedList = Vec<Target>;
 
 /// Target to fetch.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Target {
     /// `Identity(platform, identity)`
     Identity(Platform, String),